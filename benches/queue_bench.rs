@@ -3,22 +3,24 @@
 //! Benchmarks cover:
 //! - Queue operations (enqueue/dequeue/priority sorting)
 //! - ResourcePool capacity management
-//! - Task execution and wake-up mechanism
+//! - Task execution and wake-up mechanism, with and without wake hysteresis
 //! - Mailbox delivery
 //! - End-to-end scheduling scenarios
+//! - WorkerPool result storage under concurrent fan-out
 //! - Parking lot primitives (Mutex, atomics, Condvar)
 
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use std::hint::black_box;
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use parking_lot::{Condvar, Mutex};
 
+use prometheus_parking_lot::config::WorkerPoolConfig;
 use prometheus_parking_lot::core::{
-    Mailbox, PoolLimits, ResourcePool, ScheduledTask, Spawn, TaskExecutor, TaskMetadata,
-    TaskQueue, TaskStatus,
+    Mailbox, PoolLimits, ResourcePool, ScheduledTask, SchedulerError, Spawn, TaskExecutor,
+    TaskMetadata, TaskQueue, TaskStatus, WorkerExecutor, WorkerPool,
 };
 use prometheus_parking_lot::infra::mailbox::memory::InMemoryMailbox;
 use prometheus_parking_lot::infra::queue::memory::InMemoryQueue;
@@ -49,6 +51,71 @@ impl TaskExecutor<BenchPayload, String> for BenchExecutor {
     }
 }
 
+#[async_trait]
+impl WorkerExecutor<u64, u64> for BenchExecutor {
+    async fn execute(&self, payload: u64, _meta: TaskMetadata) -> u64 {
+        payload
+    }
+}
+
+/// Executor that holds its units for a millisecond, so completions overlap
+/// submissions, and counts finished tasks.
+#[derive(Clone)]
+struct BriefExecutor {
+    done: Arc<AtomicU64>,
+}
+
+#[async_trait]
+impl TaskExecutor<BenchPayload, String> for BriefExecutor {
+    async fn execute(&self, payload: BenchPayload, _meta: TaskMetadata) -> String {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+        self.done.fetch_add(1, Ordering::Relaxed);
+        format!("result-{}", payload.id)
+    }
+}
+
+/// In-memory queue counting `dequeue` calls: every wake pass takes the queue
+/// lock to dequeue, so this counts wake-lock acquisitions.
+struct CountingQueue {
+    inner: InMemoryQueue<BenchPayload>,
+    dequeues: Arc<AtomicU64>,
+}
+
+impl TaskQueue<BenchPayload> for CountingQueue {
+    fn enqueue(&mut self, task: ScheduledTask<BenchPayload>) -> Result<(), SchedulerError> {
+        self.inner.enqueue(task)
+    }
+
+    fn dequeue(&mut self) -> Result<Option<ScheduledTask<BenchPayload>>, SchedulerError> {
+        self.dequeues.fetch_add(1, Ordering::Relaxed);
+        self.inner.dequeue()
+    }
+
+    fn peek(&self) -> Result<Option<TaskMetadata>, SchedulerError> {
+        self.inner.peek()
+    }
+
+    fn queued_units(&self) -> u64 {
+        self.inner.queued_units()
+    }
+
+    fn prune_expired(&mut self, now_ms: u128) -> Result<usize, SchedulerError> {
+        self.inner.prune_expired(now_ms)
+    }
+
+    fn drain(&mut self) -> Result<Vec<ScheduledTask<BenchPayload>>, SchedulerError> {
+        self.inner.drain()
+    }
+
+    fn max_depth(&self) -> usize {
+        self.inner.max_depth()
+    }
+
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
 #[derive(Clone)]
 struct NoOpSpawner;
 
@@ -81,6 +148,11 @@ fn build_task(id: u64, priority: Priority) -> ScheduledTask<BenchPayload> {
             },
             deadline_ms: None,
             created_at_ms: now_ms(),
+            trace_context: None,
+            degraded: false,
+            depends_on: Vec::new(),
+            idempotency_key: None,
+            retrieve_timeout_ms: None,
         },
         payload: BenchPayload {
             id,
@@ -109,6 +181,11 @@ fn build_string_task(id: u64) -> ScheduledTask<String> {
             },
             deadline_ms: None,
             created_at_ms: id as u128, // Use id for ordering
+            trace_context: None,
+            degraded: false,
+            depends_on: Vec::new(),
+            idempotency_key: None,
+            retrieve_timeout_ms: None,
         },
         payload: format!("payload-{}", id),
     }
@@ -356,6 +433,34 @@ fn bench_queue_prune_expired(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_queue_prune_none_expired(c: &mut Criterion) {
+    let mut group = c.benchmark_group("queue_prune_none_expired");
+
+    for size in [1_000, 10_000] {
+        let mut q = InMemoryQueue::<BenchPayload>::new(size as usize);
+        let now = now_ms();
+        for i in 0..size {
+            let mut task = build_task(i, Priority::Normal);
+            task.meta.deadline_ms = Some(now + 3_600_000);
+            q.enqueue(task).unwrap();
+        }
+
+        group.bench_with_input(BenchmarkId::new("prune_expired", size), &size, |b, _| {
+            b.iter(|| black_box(q.prune_expired(black_box(now)).unwrap()));
+        });
+        // Reference: what a pass cost when it rebuilt the heap every time
+        group.bench_with_input(BenchmarkId::new("full_rebuild", size), &size, |b, _| {
+            b.iter(|| {
+                for task in q.drain().unwrap() {
+                    q.enqueue(task).unwrap();
+                }
+                black_box(q.len())
+            });
+        });
+    }
+    group.finish();
+}
+
 // ============================================================================
 // Mailbox Benchmarks
 // ============================================================================
@@ -431,6 +536,7 @@ fn bench_pool_submit_immediate(c: &mut Criterion) {
                         max_units: capacity,
                         max_queue_depth: 1000,
                         default_timeout: Duration::from_secs(60),
+                        ..PoolLimits::default()
                     };
                     
                     let queue = InMemoryQueue::new(1000);
@@ -476,6 +582,7 @@ fn bench_pool_submit_with_queueing(c: &mut Criterion) {
                         max_units: 10, // Small capacity to force queueing
                         max_queue_depth: 1000,
                         default_timeout: Duration::from_secs(60),
+                        ..PoolLimits::default()
                     };
                     
                     let queue = InMemoryQueue::new(1000);
@@ -513,6 +620,7 @@ fn bench_pool_mixed_priorities(c: &mut Criterion) {
                 max_units: 20,
                 max_queue_depth: 500,
                 default_timeout: Duration::from_secs(60),
+                ..PoolLimits::default()
             };
             
             let queue = InMemoryQueue::new(500);
@@ -551,6 +659,7 @@ fn bench_pool_deadline_checking(c: &mut Criterion) {
                 max_units: 10,
                 max_queue_depth: 100,
                 default_timeout: Duration::from_secs(60),
+                ..PoolLimits::default()
             };
             
             let queue = InMemoryQueue::new(100);
@@ -574,6 +683,70 @@ fn bench_pool_deadline_checking(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_pool_wake_hysteresis(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pool_wake_hysteresis");
+
+    // A flood of cheap tasks holds the pool while 16-unit tasks wait; without
+    // hysteresis every cheap completion runs a wake pass that can't start one
+    for hysteresis in [false, true] {
+        let name = if hysteresis { "with_hysteresis" } else { "without_hysteresis" };
+        let dequeues = Arc::new(AtomicU64::new(0));
+        let busy_nanos = Arc::new(AtomicU64::new(0));
+
+        group.bench_function(name, |b| {
+            b.to_async(Runtime::new().unwrap()).iter(|| {
+                let dequeues = Arc::clone(&dequeues);
+                let busy_nanos = Arc::clone(&busy_nanos);
+                async move {
+                    let limits = PoolLimits {
+                        max_units: 64,
+                        max_queue_depth: 1000,
+                        default_timeout: Duration::from_secs(60),
+                        ..PoolLimits::default()
+                    };
+                    let done = Arc::new(AtomicU64::new(0));
+                    let queue = CountingQueue {
+                        inner: InMemoryQueue::new(1000),
+                        dequeues: Arc::clone(&dequeues),
+                    };
+                    let executor = BriefExecutor { done: Arc::clone(&done) };
+                    let pool = ResourcePool::new(
+                        limits,
+                        queue,
+                        InMemoryMailbox::new(),
+                        executor,
+                        NoOpSpawner,
+                    )
+                    .with_wake_hysteresis(hysteresis);
+
+                    let started = Instant::now();
+                    let submitted = 64 + 8;
+                    for i in 0..64u64 {
+                        let task = build_task(i, Priority::Normal);
+                        black_box(pool.submit(task, now_ms()).await.ok());
+                    }
+                    for i in 64..submitted {
+                        let mut task = build_task(i, Priority::Normal);
+                        task.meta.cost.units = 16;
+                        black_box(pool.submit(task, now_ms()).await.ok());
+                    }
+                    while done.load(Ordering::Relaxed) < submitted {
+                        tokio::time::sleep(Duration::from_micros(200)).await;
+                    }
+                    let elapsed = u64::try_from(started.elapsed().as_nanos()).unwrap_or(u64::MAX);
+                    busy_nanos.fetch_add(elapsed, Ordering::Relaxed);
+                }
+            });
+        });
+
+        #[allow(clippy::cast_precision_loss)] // Reporting only
+        let per_sec = dequeues.load(Ordering::Relaxed) as f64
+            / (busy_nanos.load(Ordering::Relaxed).max(1) as f64 / 1e9);
+        eprintln!("pool_wake_hysteresis/{name}: {per_sec:.0} wake-lock acquisitions/s");
+    }
+    group.finish();
+}
+
 // ============================================================================
 // End-to-End Scenario Benchmarks
 // ============================================================================
@@ -587,6 +760,7 @@ fn bench_end_to_end_scenario(c: &mut Criterion) {
                 max_units: 25,
                 max_queue_depth: 500,
                 default_timeout: Duration::from_secs(60),
+                ..PoolLimits::default()
             };
             
             let queue = InMemoryQueue::new(500);
@@ -626,6 +800,58 @@ fn bench_end_to_end_scenario(c: &mut Criterion) {
     group.finish();
 }
 
+// ============================================================================
+// WorkerPool Benchmarks
+// ============================================================================
+
+fn bench_worker_pool_result_storage(c: &mut Criterion) {
+    const SUBMITTERS: u64 = 16;
+    const TASKS_PER_SUBMITTER: u64 = 250;
+    
+    let mut group = c.benchmark_group("worker_pool_result_storage");
+    group.throughput(Throughput::Elements(SUBMITTERS * TASKS_PER_SUBMITTER));
+    
+    // A single shard reproduces the old single-RwLock map
+    for (name, shards) in [("single_map", Some(1)), ("sharded", None)] {
+        let mut config = WorkerPoolConfig::new()
+            .with_worker_count(8)
+            .with_max_units(1_000)
+            .with_max_queue_depth(10_000);
+        if let Some(shards) = shards {
+            config = config.with_result_shards(shards);
+        }
+        let pool = WorkerPool::new(config, BenchExecutor).unwrap();
+        
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                // Many threads submit and retrieve at once, so create/remove
+                // write locks on the result map contend
+                std::thread::scope(|scope| {
+                    for submitter in 0..SUBMITTERS {
+                        let pool = &pool;
+                        scope.spawn(move || {
+                            let keys: Vec<_> = (0..TASKS_PER_SUBMITTER)
+                                .map(|i| {
+                                    let id = submitter * TASKS_PER_SUBMITTER + i;
+                                    let meta = build_task(id, Priority::Normal).meta;
+                                    pool.submit(id, meta).unwrap()
+                                })
+                                .collect();
+                            for key in keys {
+                                black_box(pool.retrieve(&key, Duration::from_secs(5)).unwrap());
+                            }
+                        });
+                    }
+                });
+            });
+        });
+        
+        pool.shutdown();
+    }
+    
+    group.finish();
+}
+
 // ============================================================================
 // Benchmark Groups
 // ============================================================================
@@ -643,7 +869,8 @@ criterion_group!(
     bench_queue_enqueue_dequeue,
     bench_queue_priority_sorting,
     bench_queue_with_mutex,
-    bench_queue_prune_expired
+    bench_queue_prune_expired,
+    bench_queue_prune_none_expired
 );
 
 criterion_group!(
@@ -657,7 +884,8 @@ criterion_group!(
     bench_pool_submit_immediate,
     bench_pool_submit_with_queueing,
     bench_pool_mixed_priorities,
-    bench_pool_deadline_checking
+    bench_pool_deadline_checking,
+    bench_pool_wake_hysteresis
 );
 
 criterion_group!(
@@ -665,4 +893,16 @@ criterion_group!(
     bench_end_to_end_scenario
 );
 
-criterion_main!(primitives_benches, queue_benches, mailbox_benches, pool_benches, scenario_benches);
+criterion_group!(
+    worker_pool_benches,
+    bench_worker_pool_result_storage
+);
+
+criterion_main!(
+    primitives_benches,
+    queue_benches,
+    mailbox_benches,
+    pool_benches,
+    scenario_benches,
+    worker_pool_benches
+);
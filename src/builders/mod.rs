@@ -1,5 +1,7 @@
 //! Builders to construct scheduler components from configuration.
 
 pub mod pool_builder;
+pub mod worker_stack;
 
 pub use pool_builder::build_pools;
+pub use worker_stack::{build_worker_stack, WorkerStack};
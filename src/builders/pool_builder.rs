@@ -4,7 +4,7 @@ use std::collections::HashMap;
 use std::time::Duration;
 
 use crate::config::{PoolConfig, SchedulerConfig};
-use crate::core::{PoolLimits, ResourcePool, SchedulerError, TaskExecutor, TaskPayload};
+use crate::core::{PoolLimits, ResourcePool, SchedulerError, TaskExecutor, TaskPayload, TaskQueue};
 
 /// Build resource pools from scheduler configuration using provided factories.
 pub fn build_pools<P, T, Q, M, E, S, FQ, FM, FE>(
@@ -16,8 +16,9 @@ pub fn build_pools<P, T, Q, M, E, S, FQ, FM, FE>(
 ) -> Result<HashMap<String, ResourcePool<P, T, Q, M, E, S>>, SchedulerError>
 where
     P: TaskPayload,
-    T: Send + Sync + serde::Serialize + for<'de> serde::Deserialize<'de> + 'static,
-    E: TaskExecutor<P, T>,
+    T: Send + 'static,
+    Q: TaskQueue<P>,
+    E: TaskExecutor<P, T> + Clone,
     FQ: FnMut(&str, &PoolConfig) -> Result<Q, SchedulerError>,
     FM: FnMut(&str, &PoolConfig) -> Result<M, SchedulerError>,
     FE: FnMut(&str, &PoolConfig) -> Result<E, SchedulerError>,
@@ -32,12 +33,19 @@ where
             max_units: pool_cfg.max_units,
             max_queue_depth: pool_cfg.max_queue_depth,
             default_timeout: Duration::from_secs(pool_cfg.default_timeout_secs),
+            max_queue_wait: pool_cfg.max_queue_wait_ms.map(Duration::from_millis),
+            max_queued_units: pool_cfg.max_queued_units,
+            high_priority_reserve: pool_cfg.high_priority_reserve,
+            dispatch_mode: pool_cfg.dispatch_mode,
+            max_concurrent_tasks: pool_cfg.max_concurrent_tasks,
+            prefer_queued_on_contention: pool_cfg.prefer_queued_on_contention,
         };
 
         let queue = queue_factory(name, pool_cfg)?;
         let mailbox = mailbox_factory(name, pool_cfg)?;
         let executor = executor_factory(name, pool_cfg)?;
-        let pool = ResourcePool::<P, T, Q, M, E, S>::new(limits, queue, mailbox, executor, spawner.clone());
+        let pool = ResourcePool::<P, T, Q, M, E, S>::new(limits, queue, mailbox, executor, spawner.clone())
+            .with_kind_floors(&pool_cfg.kind_floors);
         pools.insert(name.clone(), pool);
     }
 
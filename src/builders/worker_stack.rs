@@ -0,0 +1,143 @@
+//! Builder assembling a `WorkerPool` with its mailbox and audit sink from one
+//! `PoolConfig`, so their limits and backends stay in sync.
+
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::config::{MailboxBackendConfig, PoolConfig, WorkerPoolConfig};
+use crate::core::{
+    AuditSink, FileAuditSink, InMemoryAuditSink, Mailbox, PostgresAuditSink, SchedulerError,
+    WorkerExecutor, WorkerPool,
+};
+use crate::infra::mailbox::{InMemoryMailbox, PostgresMailbox, YaqueMailbox};
+
+/// Events kept by the in-memory audit sink of an in-memory stack.
+const IN_MEMORY_AUDIT_EVENTS: usize = 1024;
+
+/// A configured `WorkerPool` together with the mailbox and audit sink built
+/// alongside it.
+pub struct WorkerStack<P, R, E, T>
+where
+    P: Send + 'static,
+    R: Send + 'static,
+    E: WorkerExecutor<P, R> + Clone,
+{
+    /// Worker pool sized from the config's `max_units`, `max_queue_depth`
+    /// and timeout.
+    pub pool: WorkerPool<P, R, E>,
+    /// Mailbox for the backend selected by `PoolConfig::mailbox`.
+    pub mailbox: Box<dyn Mailbox<T> + Send>,
+    /// Audit sink on the same backend as the mailbox.
+    pub audit: Box<dyn AuditSink>,
+}
+
+/// `WorkerPoolConfig` with the limits of `cfg`; other settings keep their defaults.
+#[must_use]
+pub fn worker_pool_config(cfg: &PoolConfig) -> WorkerPoolConfig {
+    let mut config = WorkerPoolConfig::new()
+        .with_max_units(cfg.max_units)
+        .with_max_queue_depth(cfg.max_queue_depth)
+        .with_timeout_ms(cfg.default_timeout_secs.saturating_mul(1000));
+    config.max_concurrent_tasks = cfg.max_concurrent_tasks;
+    config
+}
+
+/// Build the worker pool, mailbox and audit sink for pool `name`.
+///
+/// The mailbox follows `cfg.mailbox`, and the audit sink uses the same
+/// backend: in-memory, files under `data_dir` (streams named after the pool),
+/// or Postgres.
+///
+/// # Errors
+///
+/// Returns `SchedulerError::Backend` if `cfg` is invalid, if the file backend
+/// is selected without a `data_dir`, or if a file backend cannot be opened.
+pub fn build_worker_stack<P, R, E, T>(
+    name: &str,
+    cfg: &PoolConfig,
+    executor: E,
+    data_dir: Option<&Path>,
+) -> Result<WorkerStack<P, R, E, T>, SchedulerError>
+where
+    P: Send + 'static,
+    R: Send + 'static,
+    E: WorkerExecutor<P, R> + Clone,
+    T: Serialize + DeserializeOwned + Clone + Send + 'static,
+{
+    cfg.validate()
+        .map_err(|e| SchedulerError::Backend(format!("pool `{name}` invalid: {e}")))?;
+
+    let (mailbox, audit): (Box<dyn Mailbox<T> + Send>, Box<dyn AuditSink>) = match cfg.mailbox {
+        MailboxBackendConfig::InMemory => (
+            Box::new(InMemoryMailbox::new()),
+            Box::new(InMemoryAuditSink::new(IN_MEMORY_AUDIT_EVENTS)),
+        ),
+        MailboxBackendConfig::File => {
+            let dir = data_dir.ok_or_else(|| {
+                SchedulerError::Backend(format!("pool `{name}`: file mailbox requires a data directory"))
+            })?;
+            (
+                Box::new(YaqueMailbox::new(dir, name)?),
+                Box::new(FileAuditSink::new(dir.join(format!("{name}_audit.jsonl")))?),
+            )
+        }
+        MailboxBackendConfig::Postgres => (Box::new(PostgresMailbox::new()), Box::new(PostgresAuditSink)),
+    };
+
+    let pool = WorkerPool::new(worker_pool_config(cfg), executor)
+        .map_err(|e| SchedulerError::Backend(e.to_string()))?;
+
+    Ok(WorkerStack { pool, mailbox, audit })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::TaskMetadata;
+    use async_trait::async_trait;
+
+    #[derive(Clone)]
+    struct EchoExecutor;
+
+    #[async_trait]
+    impl WorkerExecutor<u32, u32> for EchoExecutor {
+        async fn execute(&self, payload: u32, _meta: TaskMetadata) -> u32 {
+            payload
+        }
+    }
+
+    fn parse(mailbox: &str) -> PoolConfig {
+        serde_json::from_str(&format!(
+            r#"{{
+                "max_units": 12,
+                "max_queue_depth": 34,
+                "default_timeout_secs": 5,
+                "queue": "in_memory",
+                "mailbox": "{mailbox}",
+                "runtime": "native"
+            }}"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_stack_limits_follow_config() {
+        let cfg = parse("in_memory");
+        let stack = build_worker_stack::<u32, u32, _, u32>("gpu", &cfg, EchoExecutor, None).unwrap();
+        assert_eq!(stack.pool.stats().total_units, cfg.max_units);
+
+        let worker_cfg = worker_pool_config(&cfg);
+        assert_eq!(worker_cfg.max_queue_depth, 34);
+        assert_eq!(worker_cfg.default_timeout_ms, 5_000);
+        stack.pool.shutdown();
+    }
+
+    #[test]
+    fn test_file_mailbox_requires_data_dir() {
+        let cfg = parse("file");
+        let result = build_worker_stack::<u32, u32, _, u32>("gpu", &cfg, EchoExecutor, None);
+        assert!(matches!(result, Err(SchedulerError::Backend(_))));
+    }
+}
@@ -2,4 +2,10 @@
 
 pub mod pool;
 
-pub use pool::{MailboxBackendConfig, PoolConfig, QueueBackendConfig, RuntimeConfig, SchedulerConfig, WorkerPoolConfig};
+pub use pool::{
+    CircuitBreakerConfig, DispatchMode, KindFloors, MailboxBackendConfig, PoolConfig, QueueBackendConfig,
+    RateLimitConfig, RetryPolicy, RuntimeConfig, SchedulerConfig, TokenBucketConfig,
+    WorkerPoolConfig,
+};
+#[cfg(not(target_arch = "wasm32"))]
+pub use pool::WorkerRuntimeKind;
@@ -5,6 +5,9 @@ use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
+use crate::util::backoff::{Backoff, Jitter};
+use crate::util::serde::ResourceKind;
+
 /// Runtime adapter configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -41,6 +44,21 @@ pub enum MailboxBackendConfig {
     Postgres,
 }
 
+/// How a `ResourcePool` starts tasks that fit in its free capacity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DispatchMode {
+    /// Start a task from `submit` when capacity allows, queueing only the
+    /// tasks that don't fit. Lowest submission-to-start latency.
+    #[default]
+    Immediate,
+    /// Queue every task and start tasks only from the wake pass, so all
+    /// execution goes through one scheduling point in strict queue order.
+    /// Each start waits for a spawned wake pass, adding its scheduling
+    /// latency to every task.
+    QueueAlways,
+}
+
 /// Pool configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PoolConfig {
@@ -56,6 +74,32 @@ pub struct PoolConfig {
     pub mailbox: MailboxBackendConfig,
     /// Runtime adapter selection.
     pub runtime: RuntimeConfig,
+    /// Minimum share of `max_units` guaranteed to each resource kind.
+    #[serde(default, skip_serializing_if = "KindFloors::is_empty")]
+    pub kind_floors: KindFloors,
+    /// Longest a task may wait in the queue before it is dropped, in
+    /// milliseconds. Unset lets tasks wait until their deadline, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_queue_wait_ms: Option<u64>,
+    /// Largest total `cost.units` the queue may hold. Unset bounds the queue
+    /// by `max_queue_depth` alone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_queued_units: Option<u64>,
+    /// Fraction of `max_units` held back for `High` and `Critical` tasks.
+    /// Unset lets every task use the full budget.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub high_priority_reserve: Option<f64>,
+    /// Whether tasks may start from `submit` or only from the wake pass.
+    #[serde(default)]
+    pub dispatch_mode: DispatchMode,
+    /// Most tasks running at once, whatever their units. Unset bounds
+    /// concurrency by `max_units` alone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_tasks: Option<usize>,
+    /// Queue a task that fits behind any queued task of equal or higher
+    /// priority, instead of starting it from `submit`.
+    #[serde(default)]
+    pub prefer_queued_on_contention: bool,
 }
 
 /// Root scheduler configuration.
@@ -65,6 +109,26 @@ pub struct SchedulerConfig {
     pub pools: HashMap<String, PoolConfig>,
 }
 
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_units: default_max_units(),
+            max_queue_depth: default_max_queue_depth(),
+            default_timeout_secs: default_timeout_ms() / 1000,
+            queue: QueueBackendConfig::InMemory,
+            mailbox: MailboxBackendConfig::InMemory,
+            runtime: RuntimeConfig::Native,
+            kind_floors: KindFloors::default(),
+            max_queue_wait_ms: None,
+            max_queued_units: None,
+            high_priority_reserve: None,
+            dispatch_mode: DispatchMode::Immediate,
+            max_concurrent_tasks: None,
+            prefer_queued_on_contention: false,
+        }
+    }
+}
+
 impl PoolConfig {
     /// Validate pool configuration values.
     pub fn validate(&self) -> Result<(), String> {
@@ -77,6 +141,85 @@ impl PoolConfig {
         if self.default_timeout_secs == 0 {
             return Err("default_timeout_secs must be greater than 0".into());
         }
+        if self.max_queue_wait_ms == Some(0) {
+            return Err("max_queue_wait_ms must be greater than 0".into());
+        }
+        if self.max_queued_units == Some(0) {
+            return Err("max_queued_units must be greater than 0".into());
+        }
+        if self.max_concurrent_tasks == Some(0) {
+            return Err("max_concurrent_tasks must be greater than 0".into());
+        }
+        if self
+            .high_priority_reserve
+            .is_some_and(|reserve| !(0.0..1.0).contains(&reserve))
+        {
+            return Err("high_priority_reserve must be in [0.0, 1.0)".into());
+        }
+        self.kind_floors.validate()
+    }
+}
+
+/// Per-`ResourceKind` capacity floors for a `ResourcePool`.
+///
+/// Each entry reserves a fraction of the pool's `max_units` for one kind: other
+/// kinds are only admitted while the unused part of every floor stays free, so
+/// a flood of cheap CPU tasks cannot starve GPU work. Kinds may still use more
+/// than their floor when capacity allows.
+///
+/// # Example
+///
+/// ```rust
+/// use prometheus_parking_lot::config::KindFloors;
+/// use prometheus_parking_lot::util::serde::ResourceKind;
+///
+/// let floors = KindFloors::default().with_floor(ResourceKind::GpuVram, 0.4);
+/// assert_eq!(floors.floor_units(ResourceKind::GpuVram, 10), 4);
+/// assert_eq!(floors.floor_units(ResourceKind::Cpu, 10), 0);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct KindFloors {
+    /// Fraction of `max_units` (`0.0..=1.0`) reserved for each kind.
+    pub floors: HashMap<ResourceKind, f64>,
+}
+
+impl KindFloors {
+    /// Reserve `fraction` of the pool's units for `kind`.
+    #[must_use]
+    pub fn with_floor(mut self, kind: ResourceKind, fraction: f64) -> Self {
+        self.floors.insert(kind, fraction);
+        self
+    }
+
+    /// Whether no floors are configured.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.floors.is_empty()
+    }
+
+    /// Units reserved for `kind` in a pool of `max_units`, rounded down.
+    #[must_use]
+    pub fn floor_units(&self, kind: ResourceKind, max_units: u32) -> u32 {
+        let fraction = self.floors.get(&kind).copied().unwrap_or(0.0);
+        // Validated fractions are within 0.0..=1.0, so this fits in u32
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let units = (f64::from(max_units) * fraction).floor() as u32;
+        units.min(max_units)
+    }
+
+    /// Validate the floor values.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the first invalid floor.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.floors.values().any(|f| !(0.0..=1.0).contains(f)) {
+            return Err("kind floors must be in [0.0, 1.0]".into());
+        }
+        if self.floors.values().sum::<f64>() > 1.0 {
+            return Err("kind floors must not add up to more than 1.0".into());
+        }
         Ok(())
     }
 }
@@ -136,6 +279,365 @@ fn default_timeout_ms() -> u64 {
     120_000
 }
 
+const fn default_overload_threshold() -> f32 {
+    1.0
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const fn default_shutdown_timeout_ms() -> u64 {
+    2_000
+}
+
+/// Default retry attempts: a single attempt (no retries).
+const fn default_retry_max_attempts() -> u32 {
+    1
+}
+
+/// Default delay before the first retry: 100ms.
+const fn default_retry_base_delay_ms() -> u64 {
+    100
+}
+
+/// Default upper bound on a single retry delay: 10 seconds.
+const fn default_retry_max_delay_ms() -> u64 {
+    10_000
+}
+
+/// Default multiplier applied to the delay after each failed attempt.
+const fn default_retry_backoff_factor() -> f64 {
+    2.0
+}
+
+/// Retry policy for tasks whose executor reports a retryable failure.
+///
+/// Retries are driven by `WorkerExecutor::classify` returning
+/// `ExecutionOutcome::Retryable`. The delay before retry `n` (1-based) is
+/// `base_delay_ms * backoff_factor^(n - 1)`, capped at `max_delay_ms`, then
+/// randomized by `jitter` so tasks that failed together don't retry in step.
+///
+/// # Example
+///
+/// ```rust
+/// use prometheus_parking_lot::config::RetryPolicy;
+/// use prometheus_parking_lot::util::backoff::Jitter;
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy {
+///     max_attempts: 3,
+///     base_delay_ms: 50,
+///     max_delay_ms: 1_000,
+///     backoff_factor: 2.0,
+///     jitter: Jitter::None,
+/// };
+/// assert_eq!(policy.delay_for_retry(1), Duration::from_millis(50));
+/// assert_eq!(policy.delay_for_retry(2), Duration::from_millis(100));
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Total attempts per task, including the first one. `1` disables retries.
+    #[serde(default = "default_retry_max_attempts")]
+    pub max_attempts: u32,
+
+    /// Delay before the first retry in milliseconds.
+    #[serde(default = "default_retry_base_delay_ms")]
+    pub base_delay_ms: u64,
+
+    /// Maximum delay between attempts in milliseconds.
+    #[serde(default = "default_retry_max_delay_ms")]
+    pub max_delay_ms: u64,
+
+    /// Multiplier applied to the delay after each failed attempt.
+    #[serde(default = "default_retry_backoff_factor")]
+    pub backoff_factor: f64,
+
+    /// Randomization of each retry delay. Default: none.
+    #[serde(default)]
+    pub jitter: Jitter,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_retry_max_attempts(),
+            base_delay_ms: default_retry_base_delay_ms(),
+            max_delay_ms: default_retry_max_delay_ms(),
+            backoff_factor: default_retry_backoff_factor(),
+            jitter: Jitter::None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Whether this policy allows more than one attempt.
+    #[must_use]
+    pub const fn is_enabled(&self) -> bool {
+        self.max_attempts > 1
+    }
+
+    /// Delay to wait before retry number `retry` (1-based), before jitter.
+    #[must_use]
+    pub fn delay_for_retry(&self, retry: u32) -> Duration {
+        let exponent = i32::try_from(retry.saturating_sub(1)).unwrap_or(i32::MAX);
+        let base = Duration::from_millis(self.base_delay_ms).as_secs_f64();
+        let scaled = base * self.backoff_factor.powi(exponent);
+        let max = Duration::from_millis(self.max_delay_ms);
+        if scaled.is_finite() && scaled < max.as_secs_f64() {
+            Duration::from_secs_f64(scaled)
+        } else {
+            max
+        }
+    }
+
+    /// Jittered retry delays, the first one for retry 1.
+    #[must_use]
+    pub fn backoff(&self) -> Backoff {
+        Backoff::new(
+            Duration::from_millis(self.base_delay_ms),
+            Duration::from_millis(self.max_delay_ms),
+            self.backoff_factor,
+        )
+        .with_jitter(self.jitter)
+    }
+
+    /// Validate the policy values.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the first invalid field.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_attempts == 0 {
+            return Err("retry max_attempts must be greater than 0".into());
+        }
+        if !self.backoff_factor.is_finite() || self.backoff_factor < 1.0 {
+            return Err("retry backoff_factor must be a finite value >= 1.0".into());
+        }
+        if self.base_delay_ms > self.max_delay_ms {
+            return Err("retry base_delay_ms must not exceed max_delay_ms".into());
+        }
+        Ok(())
+    }
+}
+
+/// Default failure rate that trips the circuit breaker: 50%.
+const fn default_circuit_failure_threshold() -> f64 {
+    0.5
+}
+
+/// Default time the circuit stays open before probing: 30 seconds.
+const fn default_circuit_cooldown_ms() -> u64 {
+    30_000
+}
+
+/// Circuit breaker that sheds load while the executor keeps failing.
+///
+/// The breaker tracks the outcomes of the last `window_size` tasks. Once the
+/// window is full and the share of failures reaches `failure_threshold`, the
+/// circuit opens and submissions fail fast with `PoolError::CircuitOpen`. After
+/// `cooldown_ms` the circuit is half-open: a single probe task is admitted, and
+/// its outcome either closes the circuit or re-opens it for another cooldown.
+///
+/// # Example
+///
+/// ```rust
+/// use prometheus_parking_lot::config::{CircuitBreakerConfig, WorkerPoolConfig};
+///
+/// let config = WorkerPoolConfig::new().with_circuit_breaker(CircuitBreakerConfig {
+///     window_size: 20,
+///     failure_threshold: 0.5,
+///     cooldown_ms: 5_000,
+/// });
+/// assert!(config.circuit_breaker.is_enabled());
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CircuitBreakerConfig {
+    /// Number of most recent task outcomes considered. `0` disables the breaker.
+    #[serde(default)]
+    pub window_size: usize,
+
+    /// Failure rate (`0.0..=1.0`) over the window at which the circuit opens.
+    #[serde(default = "default_circuit_failure_threshold")]
+    pub failure_threshold: f64,
+
+    /// Time the circuit stays open before admitting a probe, in milliseconds.
+    #[serde(default = "default_circuit_cooldown_ms")]
+    pub cooldown_ms: u64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            window_size: 0,
+            failure_threshold: default_circuit_failure_threshold(),
+            cooldown_ms: default_circuit_cooldown_ms(),
+        }
+    }
+}
+
+impl CircuitBreakerConfig {
+    /// Whether the breaker is active.
+    #[must_use]
+    pub const fn is_enabled(&self) -> bool {
+        self.window_size > 0
+    }
+
+    /// Validate the breaker values.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the first invalid field.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.failure_threshold > 0.0 && self.failure_threshold <= 1.0) {
+            return Err("circuit breaker failure_threshold must be in (0.0, 1.0]".into());
+        }
+        if self.is_enabled() && self.cooldown_ms == 0 {
+            return Err("circuit breaker cooldown_ms must be greater than 0".into());
+        }
+        Ok(())
+    }
+}
+
+/// Token bucket limiting how fast one tenant may submit tasks.
+///
+/// The bucket starts full; each submission takes one token and tokens refill
+/// continuously at `refill_per_sec`, so `capacity` is the largest burst a
+/// tenant can send at once.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TokenBucketConfig {
+    /// Maximum number of tokens the bucket holds.
+    pub capacity: u32,
+
+    /// Tokens added per second.
+    pub refill_per_sec: f64,
+}
+
+impl TokenBucketConfig {
+    /// Create a bucket allowing bursts of `capacity` and a sustained
+    /// `refill_per_sec` submissions per second.
+    #[must_use]
+    pub const fn new(capacity: u32, refill_per_sec: f64) -> Self {
+        Self {
+            capacity,
+            refill_per_sec,
+        }
+    }
+
+    /// Validate the bucket values.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the first invalid field.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.capacity == 0 {
+            return Err("rate limit capacity must be greater than 0".into());
+        }
+        if !(self.refill_per_sec.is_finite() && self.refill_per_sec > 0.0) {
+            return Err("rate limit refill_per_sec must be a positive number".into());
+        }
+        Ok(())
+    }
+}
+
+/// Per-tenant submission rate limits, keyed by `MailboxKey::tenant`.
+///
+/// Tenants listed in `tenants` get their own bucket; every other tenant
+/// (including tasks without a mailbox, counted as tenant `"unknown"`) uses
+/// `default`, or is unlimited when `default` is `None`.
+///
+/// # Example
+///
+/// ```rust
+/// use prometheus_parking_lot::config::{RateLimitConfig, TokenBucketConfig};
+///
+/// let config = RateLimitConfig::default()
+///     .with_default(TokenBucketConfig::new(10, 10.0))
+///     .with_tenant("batch", TokenBucketConfig::new(2, 0.5));
+/// assert!(config.validate().is_ok());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    /// Bucket for tenants without an entry in `tenants`.
+    #[serde(default)]
+    pub default: Option<TokenBucketConfig>,
+
+    /// Per-tenant buckets.
+    #[serde(default)]
+    pub tenants: HashMap<String, TokenBucketConfig>,
+}
+
+impl RateLimitConfig {
+    /// Set the bucket used by tenants without their own entry.
+    #[must_use]
+    pub const fn with_default(mut self, bucket: TokenBucketConfig) -> Self {
+        self.default = Some(bucket);
+        self
+    }
+
+    /// Give `tenant` its own bucket.
+    #[must_use]
+    pub fn with_tenant(mut self, tenant: impl Into<String>, bucket: TokenBucketConfig) -> Self {
+        self.tenants.insert(tenant.into(), bucket);
+        self
+    }
+
+    /// Bucket applying to `tenant`, or `None` if it is unlimited.
+    #[must_use]
+    pub fn bucket_for(&self, tenant: &str) -> Option<&TokenBucketConfig> {
+        self.tenants.get(tenant).or(self.default.as_ref())
+    }
+
+    /// Validate every configured bucket.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the first invalid bucket.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(bucket) = &self.default {
+            bucket.validate()?;
+        }
+        for (tenant, bucket) in &self.tenants {
+            bucket
+                .validate()
+                .map_err(|e| format!("tenant '{tenant}': {e}"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Tokio runtime flavor each native `WorkerPool` worker thread runs its
+/// executor on.
+///
+/// `CurrentThread` keeps every task isolated on its worker's OS thread: any
+/// sub-tasks the executor spawns share that one thread, so a task that blocks
+/// or burns CPU only slows itself. `MultiThread` gives each worker its own
+/// small thread pool so an executor that fans out concurrent async work (e.g.
+/// many calls to a remote model API) can make progress on several threads at
+/// once, at the cost of `worker_count * threads` extra OS threads and less
+/// isolation between a task's sub-tasks and the rest of the process.
+///
+/// # Example
+///
+/// ```rust
+/// use prometheus_parking_lot::config::{WorkerPoolConfig, WorkerRuntimeKind};
+///
+/// let config = WorkerPoolConfig::new()
+///     .with_worker_count(2)
+///     .with_runtime_kind(WorkerRuntimeKind::MultiThread { threads: 4 });
+/// assert!(config.validate().is_ok());
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkerRuntimeKind {
+    /// A single-threaded runtime per worker (the default).
+    #[default]
+    CurrentThread,
+    /// A multi-threaded runtime per worker with `threads` runtime threads.
+    MultiThread {
+        /// Number of runtime threads per worker.
+        threads: usize,
+    },
+}
+
 /// Configuration for the `WorkerPool`.
 /// 
 /// This configuration is used to create a worker pool with dedicated worker threads
@@ -169,6 +671,49 @@ pub struct WorkerPoolConfig {
     #[serde(default = "default_thread_stack_size")]
     pub thread_stack_size: usize,
     
+    /// Number of shards in the result storage map (native only).
+    /// 
+    /// Each shard has its own lock, so submissions and retrievals of different
+    /// tasks rarely contend. This field is ignored on WASM targets.
+    /// Default: `None`, i.e. `worker_count` rounded up to a power of two.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(default)]
+    pub result_shards: Option<usize>,
+    
+    /// Tokio runtime flavor of each worker thread (native only).
+    /// 
+    /// This field is ignored on WASM targets.
+    /// Default: `WorkerRuntimeKind::CurrentThread`.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(default)]
+    pub runtime_kind: WorkerRuntimeKind,
+    
+    /// Nice value applied to each worker thread, from -20 (highest
+    /// priority) to 19 (lowest) (native only).
+    /// 
+    /// Lets background work such as batch inference yield the CPU to the
+    /// rest of the process. Applied best-effort on Unix; elsewhere, or when
+    /// the OS refuses (raising priority usually needs privileges), the
+    /// workers log a warning and keep the default priority. This field is
+    /// ignored on WASM targets.
+    /// Default: `None` (inherit the spawning thread's priority).
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thread_priority: Option<i32>,
+    
+    /// Units each worker can run a task of, one entry per worker (native
+    /// only).
+    /// 
+    /// Models workers bound to devices of different sizes, e.g. `[24, 8]` for
+    /// a 24GB and an 8GB GPU: a task only runs on a worker whose units cover
+    /// its `cost.units`, preferring the smallest idle one that does, and a
+    /// task larger than every worker is rejected. Must have `worker_count`
+    /// entries. This field is ignored on WASM targets.
+    /// Default: empty (every worker runs any task).
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub worker_units: Vec<u32>,
+    
     /// Maximum resource units that can be active concurrently.
     /// 
     /// Tasks exceeding this limit are queued. Used for capacity-based
@@ -176,6 +721,16 @@ pub struct WorkerPoolConfig {
     #[serde(default = "default_max_units")]
     pub max_units: u32,
     
+    /// Maximum number of tasks running at once, independent of their units.
+    /// 
+    /// A task starts only when both its units fit and fewer than this many
+    /// tasks are running; the rest wait in the queue. On native this is
+    /// separate from `worker_count`, so a pool can keep threads warm while
+    /// limiting how many hit a shared backend. Default: `None` (bounded by
+    /// `worker_count` alone).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_tasks: Option<usize>,
+    
     /// Maximum number of tasks that can be queued before rejection.
     /// 
     /// When the queue is full, new submissions return `PoolError::QueueFull`.
@@ -187,6 +742,64 @@ pub struct WorkerPoolConfig {
     /// If a result is not available within this time, `PoolError::Timeout` is returned.
     #[serde(default = "default_timeout_ms")]
     pub default_timeout_ms: u64,
+    
+    /// Upper bound on a single task's execution in milliseconds.
+    /// 
+    /// A task still running when it elapses is cancelled: its executor future
+    /// is dropped, its units are released, and retrieving its result returns
+    /// `PoolError::Timeout`. Executors must therefore be cancel-safe.
+    /// Default: `None` (no bound).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub per_task_timeout_ms: Option<u64>,
+    
+    /// Execution time in milliseconds past which a task is logged as slow.
+    /// 
+    /// Each slow task emits a warning with its id, priority, cost and elapsed
+    /// time, and is counted in `PoolStats::slow_tasks`; the task itself keeps
+    /// running. Default: `None` (no slow-task logging).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slow_task_threshold_ms: Option<u64>,
+    
+    /// Load factor at which `WorkerPool::is_overloaded` reports the pool as
+    /// overloaded.
+    /// 
+    /// The load factor is running plus queued units over `max_units`, so the
+    /// default only trips once queued work exceeds the pool's capacity.
+    /// Default: 1.0.
+    #[serde(default = "default_overload_threshold")]
+    pub overload_threshold: f32,
+    
+    /// How long `shutdown` waits for each worker thread to exit, in
+    /// milliseconds (native only).
+    /// 
+    /// A worker still running its task after this long is detached. This
+    /// field is ignored on WASM targets.
+    /// Default: 2000 (2 seconds).
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(default = "default_shutdown_timeout_ms")]
+    pub shutdown_timeout_ms: u64,
+    
+    /// Retry policy applied when the executor classifies a result as retryable.
+    /// 
+    /// Default: a single attempt (no retries).
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    
+    /// Circuit breaker that rejects submissions while the executor is failing.
+    /// 
+    /// Default: disabled.
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+    
+    /// Reject submissions whose `TaskMetadata::id` is already in flight with
+    /// `PoolError::DuplicateTaskId`.
+    /// 
+    /// Status, dependency and idempotency tracking are keyed by task id, so
+    /// two live tasks sharing one confuse them. `WorkerPool::next_task_id`
+    /// hands out ids that never collide.
+    /// Default: `false`.
+    #[serde(default)]
+    pub enforce_unique_ids: bool,
 }
 
 impl Default for WorkerPoolConfig {
@@ -195,9 +808,26 @@ impl Default for WorkerPoolConfig {
             worker_count: default_worker_count(),
             #[cfg(not(target_arch = "wasm32"))]
             thread_stack_size: default_thread_stack_size(),
+            #[cfg(not(target_arch = "wasm32"))]
+            result_shards: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            runtime_kind: WorkerRuntimeKind::CurrentThread,
+            #[cfg(not(target_arch = "wasm32"))]
+            thread_priority: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            worker_units: Vec::new(),
             max_units: default_max_units(),
+            max_concurrent_tasks: None,
             max_queue_depth: default_max_queue_depth(),
             default_timeout_ms: default_timeout_ms(),
+            per_task_timeout_ms: None,
+            slow_task_threshold_ms: None,
+            overload_threshold: default_overload_threshold(),
+            #[cfg(not(target_arch = "wasm32"))]
+            shutdown_timeout_ms: default_shutdown_timeout_ms(),
+            retry: RetryPolicy::default(),
+            circuit_breaker: CircuitBreakerConfig::default(),
+            enforce_unique_ids: false,
         }
     }
 }
@@ -224,6 +854,48 @@ impl WorkerPoolConfig {
         self
     }
     
+    /// Set the number of result storage shards (native only, ignored on WASM).
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub const fn with_result_shards(mut self, shards: usize) -> Self {
+        self.result_shards = Some(shards);
+        self
+    }
+    
+    /// Set the Tokio runtime flavor of each worker thread (native only, ignored on WASM).
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub const fn with_runtime_kind(mut self, runtime_kind: WorkerRuntimeKind) -> Self {
+        self.runtime_kind = runtime_kind;
+        self
+    }
+    
+    /// Set the nice value of each worker thread (native only, ignored on WASM).
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub const fn with_thread_priority(mut self, nice: i32) -> Self {
+        self.thread_priority = Some(nice);
+        self
+    }
+    
+    /// Give each worker its own unit capacity, one worker per entry (native
+    /// only, ignored on WASM). Sets `worker_count` to the number of entries.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn with_worker_units(mut self, units: Vec<u32>) -> Self {
+        self.worker_count = units.len();
+        self.worker_units = units;
+        self
+    }
+    
+    /// Number of result storage shards to create (native only).
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn result_shard_count(&self) -> usize {
+        self.result_shards
+            .unwrap_or_else(|| self.worker_count.next_power_of_two())
+    }
+    
     /// Set the maximum resource units.
     #[must_use]
     pub fn with_max_units(mut self, units: u32) -> Self {
@@ -231,6 +903,13 @@ impl WorkerPoolConfig {
         self
     }
     
+    /// Cap the number of tasks running at once.
+    #[must_use]
+    pub const fn with_max_concurrent_tasks(mut self, max: usize) -> Self {
+        self.max_concurrent_tasks = Some(max);
+        self
+    }
+    
     /// Set the maximum queue depth.
     #[must_use]
     pub fn with_max_queue_depth(mut self, depth: usize) -> Self {
@@ -245,12 +924,81 @@ impl WorkerPoolConfig {
         self
     }
     
+    /// Bound each task's execution, cancelling it once `timeout_ms` elapses.
+    #[must_use]
+    pub const fn with_per_task_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.per_task_timeout_ms = Some(timeout_ms);
+        self
+    }
+    
+    /// Log and count tasks whose execution takes longer than `threshold_ms`.
+    #[must_use]
+    pub const fn with_slow_task_threshold_ms(mut self, threshold_ms: u64) -> Self {
+        self.slow_task_threshold_ms = Some(threshold_ms);
+        self
+    }
+    
+    /// Set the load factor above which the pool reports itself overloaded.
+    #[must_use]
+    pub const fn with_overload_threshold(mut self, threshold: f32) -> Self {
+        self.overload_threshold = threshold;
+        self
+    }
+    
+    /// Set how long shutdown waits for each worker (native only, ignored on WASM).
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub const fn with_shutdown_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.shutdown_timeout_ms = timeout_ms;
+        self
+    }
+    
+    /// Set the retry policy for retryable executor failures.
+    #[must_use]
+    pub const fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+    
+    /// Set the circuit breaker thresholds.
+    #[must_use]
+    pub const fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreakerConfig) -> Self {
+        self.circuit_breaker = circuit_breaker;
+        self
+    }
+    
+    /// Reject submissions reusing the id of a task still in flight.
+    #[must_use]
+    pub const fn with_enforce_unique_ids(mut self, enforce: bool) -> Self {
+        self.enforce_unique_ids = enforce;
+        self
+    }
+    
     /// Get the default timeout as a `Duration`.
     #[must_use]
     pub fn default_timeout(&self) -> Duration {
         Duration::from_millis(self.default_timeout_ms)
     }
     
+    /// Get the per-task execution timeout as a `Duration`, if one is set.
+    #[must_use]
+    pub fn per_task_timeout(&self) -> Option<Duration> {
+        self.per_task_timeout_ms.map(Duration::from_millis)
+    }
+    
+    /// Get the slow-task threshold as a `Duration`, if one is set.
+    #[must_use]
+    pub fn slow_task_threshold(&self) -> Option<Duration> {
+        self.slow_task_threshold_ms.map(Duration::from_millis)
+    }
+    
+    /// Get the per-worker shutdown join timeout as a `Duration` (native only).
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub const fn shutdown_timeout(&self) -> Duration {
+        Duration::from_millis(self.shutdown_timeout_ms)
+    }
+    
     /// Validate the configuration values.
     pub fn validate(&self) -> Result<(), String> {
         if self.worker_count == 0 {
@@ -259,16 +1007,50 @@ impl WorkerPoolConfig {
         if self.max_units == 0 {
             return Err("max_units must be greater than 0".into());
         }
+        if self.max_concurrent_tasks == Some(0) {
+            return Err("max_concurrent_tasks must be greater than 0".into());
+        }
         if self.max_queue_depth == 0 {
             return Err("max_queue_depth must be greater than 0".into());
         }
         if self.default_timeout_ms == 0 {
             return Err("default_timeout_ms must be greater than 0".into());
         }
+        if self.per_task_timeout_ms == Some(0) {
+            return Err("per_task_timeout_ms must be greater than 0".into());
+        }
+        if self.slow_task_threshold_ms == Some(0) {
+            return Err("slow_task_threshold_ms must be greater than 0".into());
+        }
+        if !(self.overload_threshold.is_finite() && self.overload_threshold > 0.0) {
+            return Err("overload_threshold must be a positive number".into());
+        }
         #[cfg(not(target_arch = "wasm32"))]
         if self.thread_stack_size < 64 * 1024 {
             return Err("thread_stack_size must be at least 64KB".into());
         }
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.result_shards == Some(0) {
+            return Err("result_shards must be greater than 0".into());
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.runtime_kind == (WorkerRuntimeKind::MultiThread { threads: 0 }) {
+            return Err("runtime_kind threads must be greater than 0".into());
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.thread_priority.is_some_and(|nice| !(-20..=19).contains(&nice)) {
+            return Err("thread_priority must be between -20 and 19".into());
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if !self.worker_units.is_empty() && self.worker_units.len() != self.worker_count {
+            return Err("worker_units must have one entry per worker".into());
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.worker_units.contains(&0) {
+            return Err("worker_units entries must be greater than 0".into());
+        }
+        self.retry.validate()?;
+        self.circuit_breaker.validate()?;
         Ok(())
     }
 }
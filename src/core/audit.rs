@@ -1,13 +1,20 @@
 //! Audit sink implementations.
 //!
-//! Provides in-memory logging and Postgres schema definitions for audit persistence.
+//! Provides in-memory logging, a JSONL file sink, and Postgres schema definitions
+//! for audit persistence.
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
 
+use serde::{Deserialize, Serialize};
+
+use crate::core::SchedulerError;
 use crate::util::clock::now_ms;
+use crate::util::serde::{append_json_line, read_json_lines};
 
 /// Audit event structure.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AuditEvent {
     /// Event identifier.
     pub event_id: String,
@@ -21,8 +28,8 @@ pub struct AuditEvent {
     pub action: String,
     /// Timestamp milliseconds.
     pub created_at_ms: u128,
-    /// Additional context.
-    pub payload: Option<String>,
+    /// Structured context (e.g. cost, priority, queue length at event time).
+    pub payload: Option<serde_json::Value>,
 }
 
 /// Audit sink abstraction.
@@ -50,6 +57,38 @@ impl InMemoryAuditSink {
     pub fn events(&self) -> Vec<AuditEvent> {
         self.events.iter().cloned().collect()
     }
+
+    /// Events recorded for the given task, oldest first.
+    #[must_use]
+    pub fn events_for_task(&self, task_id: &str) -> Vec<AuditEvent> {
+        self.filtered(|e| e.task_id == task_id)
+    }
+
+    /// Events recorded for the given tenant, oldest first.
+    #[must_use]
+    pub fn events_for_tenant(&self, tenant: &str) -> Vec<AuditEvent> {
+        self.filtered(|e| e.tenant == tenant)
+    }
+
+    /// Events created at or after `ms` (milliseconds since epoch), oldest first.
+    #[must_use]
+    pub fn events_since(&self, ms: u128) -> Vec<AuditEvent> {
+        self.filtered(|e| e.created_at_ms >= ms)
+    }
+
+    /// Number of stored events per action.
+    #[must_use]
+    pub fn count_by_action(&self) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for event in &self.events {
+            *counts.entry(event.action.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    fn filtered(&self, predicate: impl Fn(&AuditEvent) -> bool) -> Vec<AuditEvent> {
+        self.events.iter().filter(|e| predicate(e)).cloned().collect()
+    }
 }
 
 impl AuditSink for InMemoryAuditSink {
@@ -61,6 +100,167 @@ impl AuditSink for InMemoryAuditSink {
     }
 }
 
+/// File-backed audit sink appending one JSON line per event.
+///
+/// Suitable for single-node deployments that need an audit trail surviving
+/// restarts without a database.
+pub struct FileAuditSink {
+    path: PathBuf,
+}
+
+impl FileAuditSink {
+    /// Create a sink appending to `path`, creating parent directories if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SchedulerError::Backend` if the parent directory cannot be created.
+    pub fn new(path: impl AsRef<Path>) -> Result<Self, SchedulerError> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            create_dir_all(parent).map_err(|e| SchedulerError::Backend(e.to_string()))?;
+        }
+        Ok(Self { path })
+    }
+
+    /// Path of the JSONL file backing this sink.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read every audit event recorded in the file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SchedulerError::Backend` on I/O failure or a malformed line.
+    pub fn read_all(path: impl AsRef<Path>) -> Result<Vec<AuditEvent>, SchedulerError> {
+        read_json_lines(path.as_ref())
+    }
+}
+
+impl AuditSink for FileAuditSink {
+    fn record(&mut self, event: AuditEvent) {
+        if let Err(e) = append_json_line(&self.path, &event) {
+            tracing::error!("failed to write audit event {}: {}", event.event_id, e);
+        }
+    }
+}
+
+/// Audit sink that emits each event through `tracing`.
+///
+/// Zero-config: events go to whatever subscriber is installed, as structured
+/// `INFO` events with target `prometheus_parking_lot::audit`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TracingAuditSink;
+
+impl TracingAuditSink {
+    /// Create a new tracing-backed sink.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl AuditSink for TracingAuditSink {
+    fn record(&mut self, event: AuditEvent) {
+        tracing::info!(
+            target: "prometheus_parking_lot::audit",
+            event_id = %event.event_id,
+            task_id = %event.task_id,
+            pool = %event.pool,
+            tenant = %event.tenant,
+            action = %event.action,
+            created_at_ms = event.created_at_ms,
+            payload = ?event.payload,
+            "audit event"
+        );
+    }
+}
+
+/// Audit sink forwarding every event to several child sinks, in order.
+///
+/// Lets a pool keep e.g. an in-memory buffer for debugging while also writing
+/// to a file, through the single sink accepted by `with_audit`.
+#[derive(Default)]
+pub struct TeeAuditSink {
+    sinks: Vec<Box<dyn AuditSink>>,
+}
+
+impl TeeAuditSink {
+    /// Create a tee over `sinks`.
+    #[must_use]
+    pub fn new(sinks: Vec<Box<dyn AuditSink>>) -> Self {
+        Self { sinks }
+    }
+
+    /// Append another child sink.
+    #[must_use]
+    pub fn with_sink(mut self, sink: Box<dyn AuditSink>) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    /// Number of child sinks.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.sinks.len()
+    }
+
+    /// Whether the tee has no child sinks.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+}
+
+impl AuditSink for TeeAuditSink {
+    fn record(&mut self, event: AuditEvent) {
+        let Some((last, rest)) = self.sinks.split_last_mut() else {
+            return;
+        };
+        for sink in rest {
+            sink.record(event.clone());
+        }
+        last.record(event);
+    }
+}
+
+/// Audit sink forwarding only events whose action is in an allow list.
+///
+/// Keeps audit volume down on busy pools, e.g. recording only `reject` and
+/// `expire` events instead of one per submit/enqueue/start/complete.
+pub struct FilteringAuditSink {
+    inner: Box<dyn AuditSink>,
+    allowed_actions: HashSet<String>,
+}
+
+impl FilteringAuditSink {
+    /// Wrap `inner`, forwarding only events whose action is in `allowed_actions`.
+    pub fn new<A: Into<String>>(
+        inner: Box<dyn AuditSink>,
+        allowed_actions: impl IntoIterator<Item = A>,
+    ) -> Self {
+        Self {
+            inner,
+            allowed_actions: allowed_actions.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Whether events with `action` are forwarded.
+    #[must_use]
+    pub fn allows(&self, action: &str) -> bool {
+        self.allowed_actions.contains(action)
+    }
+}
+
+impl AuditSink for FilteringAuditSink {
+    fn record(&mut self, event: AuditEvent) {
+        if self.allows(&event.action) {
+            self.inner.record(event);
+        }
+    }
+}
+
 /// Postgres-backed audit sink (schema-only; DB I/O not wired).
 pub struct PostgresAuditSink;
 
@@ -99,7 +299,7 @@ pub fn build_audit_event(
     pool: impl Into<String>,
     tenant: impl Into<String>,
     action: impl Into<String>,
-    payload: Option<String>,
+    payload: Option<serde_json::Value>,
 ) -> AuditEvent {
     AuditEvent {
         event_id: event_id.into(),
@@ -0,0 +1,133 @@
+//! Dead-letter sinks for tasks that are dropped without producing a result.
+//!
+//! Tasks rejected because the queue is full, expired before they could run, or
+//! exhausted their retry budget are handed to a `DeadLetterSink` together with
+//! the reason, so expensive work is never lost silently.
+
+use std::collections::VecDeque;
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::{SchedulerError, TaskMetadata};
+use crate::util::clock::now_ms;
+use crate::util::serde::{append_json_line, read_json_lines};
+
+/// Reason recorded when a task is rejected because the queue is full.
+pub const REASON_QUEUE_FULL: &str = "queue full";
+/// Reason recorded when a task passes its deadline before it can run.
+pub const REASON_DEADLINE_EXPIRED: &str = "deadline expired";
+/// Reason recorded when a task still fails after its last retry.
+pub const REASON_RETRIES_EXHAUSTED: &str = "retries exhausted";
+/// Reason recorded when a task waits in the queue longer than
+/// `PoolLimits::max_queue_wait`.
+pub const REASON_QUEUE_WAIT_EXCEEDED: &str = "queue wait exceeded";
+/// Reason recorded when a task is dropped because one of its dependencies failed.
+pub const REASON_DEPENDENCY_FAILED: &str = "dependency failed";
+/// Reason recorded when a task runs past `WorkerPoolConfig::per_task_timeout_ms`.
+pub const REASON_EXECUTION_TIMEOUT: &str = "execution timed out";
+/// Reason recorded when lowering a pool's `max_units` leaves a queued task
+/// too large to ever start.
+pub const REASON_EXCEEDS_MAX_UNITS: &str = "exceeds max units";
+
+/// A dropped task together with the reason it was dropped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    /// Metadata of the dropped task.
+    pub meta: TaskMetadata,
+    /// Why the task was dropped.
+    pub reason: String,
+    /// Timestamp milliseconds when the task was dead-lettered.
+    pub recorded_at_ms: u128,
+}
+
+/// Dead-letter sink abstraction.
+pub trait DeadLetterSink: Send {
+    /// Record a task that was dropped without producing a result.
+    fn record(&mut self, task_meta: TaskMetadata, reason: String);
+}
+
+/// In-memory dead-letter sink for testing and dev.
+pub struct InMemoryDeadLetter {
+    entries: VecDeque<DeadLetter>,
+    max_entries: usize,
+}
+
+impl InMemoryDeadLetter {
+    /// Create a new in-memory sink with a bounded buffer.
+    #[must_use]
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(max_entries),
+            max_entries,
+        }
+    }
+
+    /// Retrieve a snapshot of stored dead letters.
+    #[must_use]
+    pub fn entries(&self) -> Vec<DeadLetter> {
+        self.entries.iter().cloned().collect()
+    }
+}
+
+impl DeadLetterSink for InMemoryDeadLetter {
+    fn record(&mut self, task_meta: TaskMetadata, reason: String) {
+        if self.entries.len() >= self.max_entries {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(DeadLetter {
+            meta: task_meta,
+            reason,
+            recorded_at_ms: now_ms(),
+        });
+    }
+}
+
+/// File-backed dead-letter sink appending JSON lines, in the Yaque file format.
+pub struct FileDeadLetter {
+    path: PathBuf,
+}
+
+impl FileDeadLetter {
+    /// Create a sink writing to `<dir>/<stream>_dead_letter.jsonl`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SchedulerError::Backend` if the directory cannot be created.
+    pub fn new(dir: impl AsRef<Path>, stream: impl AsRef<str>) -> Result<Self, SchedulerError> {
+        let dir = dir.as_ref();
+        create_dir_all(dir).map_err(|e| SchedulerError::Backend(e.to_string()))?;
+        Ok(Self {
+            path: dir.join(format!("{}_dead_letter.jsonl", stream.as_ref())),
+        })
+    }
+
+    /// Path of the JSONL file backing this sink.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read every dead letter recorded in the file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SchedulerError::Backend` on I/O failure or a malformed line.
+    pub fn read_all(path: impl AsRef<Path>) -> Result<Vec<DeadLetter>, SchedulerError> {
+        read_json_lines(path.as_ref())
+    }
+}
+
+impl DeadLetterSink for FileDeadLetter {
+    fn record(&mut self, task_meta: TaskMetadata, reason: String) {
+        let letter = DeadLetter {
+            meta: task_meta,
+            reason,
+            recorded_at_ms: now_ms(),
+        };
+        if let Err(e) = append_json_line(&self.path, &letter) {
+            tracing::error!("failed to write dead letter for task {}: {}", letter.meta.id, e);
+        }
+    }
+}
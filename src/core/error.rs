@@ -2,6 +2,9 @@
 
 use thiserror::Error;
 
+use crate::core::PoolError;
+use crate::util::serde::TaskId;
+
 /// Errors produced by scheduler components.
 #[derive(Debug, Error)]
 pub enum SchedulerError {
@@ -14,10 +17,133 @@ pub enum SchedulerError {
     /// Task deadline has passed.
     #[error("deadline expired")]
     DeadlineExpired,
+    /// The submitting tenant has used up its rate limit.
+    #[error("rate limited; retry after {retry_after_ms} ms")]
+    RateLimited {
+        /// Milliseconds until the tenant may submit again.
+        retry_after_ms: u64,
+    },
+    /// The task itself is malformed, e.g. it costs zero units.
+    #[error("invalid task: {0}")]
+    InvalidTask(String),
+    /// A task named in `depends_on` has already failed, so this one cannot run.
+    #[error("dependency {id} failed")]
+    DependencyFailed {
+        /// Id of the failed dependency.
+        id: TaskId,
+    },
     /// Backend-specific failure with context.
     #[error("backend error: {0}")]
     Backend(String),
+    /// A `WorkerPool` error with no direct `SchedulerError` equivalent.
+    #[error("worker pool error: {0}")]
+    Pool(#[source] Box<PoolError>),
+}
+
+/// Lets code driving both pool types surface `ResourcePool` errors as
+/// `PoolError`. Variants without an equivalent are wrapped in
+/// `PoolError::Scheduler`, which keeps them as the error's `source`.
+impl From<SchedulerError> for PoolError {
+    fn from(err: SchedulerError) -> Self {
+        match err {
+            SchedulerError::QueueFull(_) => Self::QueueFull {
+                task_id: None,
+                retry_after_ms: None,
+            },
+            SchedulerError::DeadlineExpired => Self::DeadlineExpired,
+            SchedulerError::RateLimited { retry_after_ms } => Self::RateLimited { retry_after_ms },
+            SchedulerError::InvalidTask(msg) => Self::InvalidTask(msg),
+            SchedulerError::DependencyFailed { id } => Self::DependencyFailed { id },
+            SchedulerError::Backend(msg) => Self::Internal(msg),
+            SchedulerError::Pool(err) => *err,
+            err @ SchedulerError::CapacityExceeded => Self::Scheduler(err),
+        }
+    }
+}
+
+/// Lets code driving both pool types surface `WorkerPool` errors as
+/// `SchedulerError`. Variants without an equivalent are wrapped in
+/// `SchedulerError::Pool`, which keeps them as the error's `source`.
+impl From<PoolError> for SchedulerError {
+    fn from(err: PoolError) -> Self {
+        match err {
+            PoolError::QueueFull { .. } => Self::QueueFull(err.to_string()),
+            PoolError::InsufficientCapacity { .. } => Self::CapacityExceeded,
+            PoolError::DeadlineExpired => Self::DeadlineExpired,
+            PoolError::RateLimited { retry_after_ms } => Self::RateLimited { retry_after_ms },
+            PoolError::InvalidTask(msg) => Self::InvalidTask(msg),
+            PoolError::DependencyFailed { id } => Self::DependencyFailed { id },
+            PoolError::Internal(msg) => Self::Backend(msg),
+            PoolError::Scheduler(err) => err,
+            err => Self::Pool(Box::new(err)),
+        }
+    }
+}
+
+/// Failure reported by a `TaskExecutor` through
+/// [`TaskExecutor::try_execute`](crate::core::TaskExecutor::try_execute).
+///
+/// The reason is delivered to the task's mailbox as `TaskStatus::Failed`.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{reason}")]
+pub struct ExecError {
+    reason: String,
+}
+
+impl ExecError {
+    /// Create an error with a human-readable `reason`.
+    pub fn new(reason: impl Into<String>) -> Self {
+        Self {
+            reason: reason.into(),
+        }
+    }
+
+    /// Why the task failed.
+    #[must_use]
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
 }
 
+/// Why both pools reject tasks with `cost.units == 0`.
+///
+/// A zero-cost task never counts against `max_units`, so any number of them
+/// could be admitted at once; requiring at least one unit keeps every task
+/// under the pool's concurrency bound.
+pub(crate) const ZERO_COST_TASK: &str = "task cost must be at least 1 unit";
+
 /// Application-facing result using anyhow for higher-level contexts.
 pub type AppResult<T> = Result<T, anyhow::Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn test_queue_full_round_trip() {
+        let err = PoolError::from(SchedulerError::QueueFull("max queue depth reached".into()));
+        assert!(matches!(err, PoolError::QueueFull { task_id: None, retry_after_ms: None }));
+
+        let back = SchedulerError::from(err);
+        assert!(matches!(&back, SchedulerError::QueueFull(msg) if msg == "task queue is full"));
+        assert_eq!(back.to_string(), "queue full: task queue is full");
+    }
+
+    #[test]
+    fn test_unmapped_variants_keep_cause() {
+        let err = PoolError::from(SchedulerError::CapacityExceeded);
+        let source = err.source().expect("wrapped scheduler error");
+        assert_eq!(source.to_string(), "capacity exceeded");
+        assert!(matches!(SchedulerError::from(err), SchedulerError::CapacityExceeded));
+
+        let err = SchedulerError::from(PoolError::CircuitOpen);
+        let source = err.source().expect("wrapped pool error");
+        assert_eq!(source.to_string(), PoolError::CircuitOpen.to_string());
+        assert!(matches!(PoolError::from(err), PoolError::CircuitOpen));
+
+        let err = SchedulerError::from(PoolError::Internal("worker panicked".into()));
+        assert!(matches!(&err, SchedulerError::Backend(msg) if msg == "worker panicked"));
+        assert!(matches!(PoolError::from(err), PoolError::Internal(msg) if msg == "worker panicked"));
+    }
+}
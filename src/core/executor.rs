@@ -1,9 +1,14 @@
 //! Task execution traits and payload abstraction.
 
+use std::any::Any;
+use std::future::Future;
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
-use super::TaskMetadata;
+use super::progress::ProgressReporter;
+use super::{ExecError, TaskMetadata};
 
 /// Marker trait for serializable task payloads.
 /// 
@@ -19,6 +24,15 @@ impl<T> TaskPayload for T where T: Send + Sync + Serialize + for<'de> Deserializ
 /// The executor is responsible for the actual business logic of running a task.
 /// It receives the payload `P` and metadata, then returns a result `T`.
 /// 
+/// `T` needs no serde bounds of its own: only a mailbox backend that persists
+/// results (such as `YaqueMailbox`) requires `T: Serialize`, so a pool with an
+/// in-memory mailbox can deliver channels and other non-serializable results.
+/// 
+/// `ResourcePool` clones its executor into every task it spawns. An executor
+/// that can't be cloned cheaply (or at all) can be passed as `Arc<E>`: all
+/// tasks then share the one instance, so any mutable state inside it must be
+/// synchronized.
+/// 
 /// # Example
 /// 
 /// ```rust,ignore
@@ -42,10 +56,10 @@ impl<T> TaskPayload for T where T: Send + Sync + Serialize + for<'de> Deserializ
 /// }
 /// ```
 #[async_trait]
-pub trait TaskExecutor<P, T>: Send + Sync + Clone + 'static
+pub trait TaskExecutor<P, T>: Send + Sync + 'static
 where
     P: TaskPayload,
-    T: Send + Sync + Serialize + for<'de> Deserialize<'de> + 'static,
+    T: Send + 'static,
 {
     /// Execute a task payload and return the result.
     /// 
@@ -59,16 +73,118 @@ where
     /// The result of task execution. This will be delivered to the mailbox
     /// if a mailbox key is present in the task metadata.
     async fn execute(&self, payload: P, meta: TaskMetadata) -> T;
+
+    /// Execute a task payload, reporting failure as an error.
+    ///
+    /// The pool always calls this method. The default delegates to
+    /// [`execute`](Self::execute) and never fails; executors that can fail
+    /// override it, and the pool delivers the error's reason to the mailbox
+    /// as `TaskStatus::Failed`.
+    async fn try_execute(&self, payload: P, meta: TaskMetadata) -> Result<T, ExecError> {
+        Ok(self.execute(payload, meta).await)
+    }
+}
+
+/// Shares one executor between all tasks of a `ResourcePool`.
+#[async_trait]
+impl<P, T, E> TaskExecutor<P, T> for Arc<E>
+where
+    P: TaskPayload,
+    T: Send + 'static,
+    E: TaskExecutor<P, T> + ?Sized,
+{
+    async fn execute(&self, payload: P, meta: TaskMetadata) -> T {
+        (**self).execute(payload, meta).await
+    }
+
+    async fn try_execute(&self, payload: P, meta: TaskMetadata) -> Result<T, ExecError> {
+        (**self).try_execute(payload, meta).await
+    }
+}
+
+/// Classification of an executor result used by the worker loop.
+///
+/// Executors that encode failure inside their result type (e.g. `Result<T, E>`)
+/// report it through [`WorkerExecutor::classify`] so the pool can retry or count it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionOutcome {
+    /// The task succeeded.
+    Success,
+    /// The task failed permanently; retrying will not help.
+    Failed,
+    /// The task failed transiently (rate limit, connection reset) and may be retried.
+    Retryable,
+}
+
+impl ExecutionOutcome {
+    /// Lowercase name of the outcome, for logs and telemetry attributes.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Failed => "failed",
+            Self::Retryable => "retryable",
+        }
+    }
+}
+
+/// State shared by every task a `WorkerPool` runs, set with `WorkerPool::with_context`.
+///
+/// The pool constructs nothing itself: it holds the value behind an `Arc` and
+/// hands the same reference to each [`WorkerExecutor::execute_with_context`]
+/// call, so a cache, connection pool or counter is shared without being
+/// threaded through every payload. Mutable state inside it must be synchronized.
+#[derive(Clone, Default)]
+pub struct ExecutorContext {
+    value: Option<Arc<dyn Any + Send + Sync>>,
+}
+
+impl ExecutorContext {
+    /// Wrap `value` as the shared context.
+    pub fn new<C: Send + Sync + 'static>(value: C) -> Self {
+        Self::from_arc(Arc::new(value))
+    }
+
+    /// Share an already reference-counted `value`.
+    pub fn from_arc<C: Send + Sync + 'static>(value: Arc<C>) -> Self {
+        Self { value: Some(value) }
+    }
+
+    /// The context as a `C`, or `None` if it is unset or of another type.
+    #[must_use]
+    pub fn get<C: 'static>(&self) -> Option<&C> {
+        self.value.as_deref()?.downcast_ref()
+    }
+
+    /// Whether a context value has been set.
+    #[must_use]
+    pub const fn is_set(&self) -> bool {
+        self.value.is_some()
+    }
+}
+
+impl std::fmt::Debug for ExecutorContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExecutorContext")
+            .field("set", &self.is_set())
+            .finish()
+    }
 }
 
 /// Executor trait for worker pools that does NOT require serialization on results.
 /// 
-/// This is the primary executor trait for `WorkerPool`. Unlike `TaskExecutor`,
+/// This is the primary executor trait for `WorkerPool`. Like `TaskExecutor`,
 /// this trait allows result types that cannot be serialized, such as:
 /// - Streaming channels (`flume::Receiver`, `tokio::sync::mpsc::Receiver`)
 /// - Complex types with non-serializable fields
 /// - Types containing file handles or network connections
 /// 
+/// `WorkerPool` clones its executor into every worker. An executor holding a
+/// resource that can't be cloned (a loaded model, a device handle) can be
+/// passed as `Arc<E>`: all workers then share the one instance, so any
+/// mutable state inside it must be synchronized, and a lock held across
+/// `execute` serializes the workers.
+/// 
 /// # Example
 /// 
 /// ```rust,ignore
@@ -102,7 +218,7 @@ where
 /// }
 /// ```
 #[async_trait]
-pub trait WorkerExecutor<P, R>: Send + Sync + Clone + 'static
+pub trait WorkerExecutor<P, R>: Send + Sync + 'static
 where
     P: Send + 'static,
     R: Send + 'static,  // NO Serialize requirement - supports channels, etc.
@@ -125,4 +241,144 @@ where
     /// with its own single-threaded tokio runtime. This ensures CPU/GPU-bound
     /// work does not block the main async runtime.
     async fn execute(&self, payload: P, meta: TaskMetadata) -> R;
+
+    /// Execute a task payload while reporting incremental progress.
+    ///
+    /// The default ignores `progress` and delegates to [`execute`](Self::execute);
+    /// override it to emit updates that callers observe through
+    /// `WorkerPool::progress_stream`.
+    async fn execute_with_progress(
+        &self,
+        payload: P,
+        meta: TaskMetadata,
+        _progress: ProgressReporter,
+    ) -> R {
+        self.execute(payload, meta).await
+    }
+
+    /// Execute a task payload with access to the pool's shared context.
+    ///
+    /// The pool always calls this method. The default ignores `context` and
+    /// delegates to [`execute_with_progress`](Self::execute_with_progress);
+    /// override it to read the value set with `WorkerPool::with_context`.
+    async fn execute_with_context(
+        &self,
+        payload: P,
+        meta: TaskMetadata,
+        progress: ProgressReporter,
+        _context: &ExecutorContext,
+    ) -> R {
+        self.execute_with_progress(payload, meta, progress).await
+    }
+
+    /// Classify a result produced by [`execute`](Self::execute).
+    ///
+    /// The default treats every result as a success. Override this to signal
+    /// failures: `Retryable` results are re-run according to the pool's
+    /// `RetryPolicy`, and `Failed` results are counted in `PoolStats::failed_tasks`.
+    fn classify(&self, _result: &R) -> ExecutionOutcome {
+        ExecutionOutcome::Success
+    }
+}
+
+/// Shares one executor between all workers of a `WorkerPool`.
+#[async_trait]
+impl<P, R, E> WorkerExecutor<P, R> for Arc<E>
+where
+    P: Send + 'static,
+    R: Send + 'static,
+    E: WorkerExecutor<P, R> + ?Sized,
+{
+    async fn execute(&self, payload: P, meta: TaskMetadata) -> R {
+        (**self).execute(payload, meta).await
+    }
+
+    async fn execute_with_progress(
+        &self,
+        payload: P,
+        meta: TaskMetadata,
+        progress: ProgressReporter,
+    ) -> R {
+        (**self).execute_with_progress(payload, meta, progress).await
+    }
+
+    async fn execute_with_context(
+        &self,
+        payload: P,
+        meta: TaskMetadata,
+        progress: ProgressReporter,
+        context: &ExecutorContext,
+    ) -> R {
+        (**self)
+            .execute_with_context(payload, meta, progress, context)
+            .await
+    }
+
+    fn classify(&self, result: &R) -> ExecutionOutcome {
+        (**self).classify(result)
+    }
+}
+
+/// Executor built from an async closure, for when a dedicated executor type
+/// would be boilerplate.
+///
+/// The closure is shared between clones through an `Arc`, so it only needs
+/// to be `Send + Sync`. Works as both a [`WorkerExecutor`] and a
+/// [`TaskExecutor`]; every result counts as a success.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use prometheus_parking_lot::core::{FnExecutor, WorkerPool};
+///
+/// let pool = WorkerPool::new(config, FnExecutor::new(|prompt: String, _meta| async move {
+///     prompt.to_uppercase()
+/// }))?;
+/// ```
+pub struct FnExecutor<F> {
+    f: Arc<F>,
+}
+
+impl<F> FnExecutor<F> {
+    /// Wrap `f`, called with each task's payload and metadata.
+    pub fn new<P, Fut>(f: F) -> Self
+    where
+        F: Fn(P, TaskMetadata) -> Fut,
+    {
+        Self { f: Arc::new(f) }
+    }
+}
+
+impl<F> Clone for FnExecutor<F> {
+    fn clone(&self) -> Self {
+        Self {
+            f: Arc::clone(&self.f),
+        }
+    }
+}
+
+#[async_trait]
+impl<P, R, F, Fut> WorkerExecutor<P, R> for FnExecutor<F>
+where
+    P: Send + 'static,
+    R: Send + 'static,
+    F: Fn(P, TaskMetadata) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = R> + Send + 'static,
+{
+    async fn execute(&self, payload: P, meta: TaskMetadata) -> R {
+        (self.f)(payload, meta).await
+    }
+}
+
+#[async_trait]
+impl<P, T, F, Fut> TaskExecutor<P, T> for FnExecutor<F>
+where
+    P: TaskPayload,
+    T: Send + 'static,
+    F: Fn(P, TaskMetadata) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = T> + Send + 'static,
+{
+    async fn execute(&self, payload: P, meta: TaskMetadata) -> T {
+        (self.f)(payload, meta).await
+    }
 }
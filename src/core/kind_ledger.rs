@@ -0,0 +1,68 @@
+//! Per-`ResourceKind` capacity accounting for `ResourcePool`.
+//!
+//! When kind floors are configured, every reservation and release goes through
+//! a single lock so the global budget and the per-kind floors are checked and
+//! updated together.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use parking_lot::Mutex;
+
+use crate::config::KindFloors;
+use crate::util::serde::{ResourceCost, ResourceKind};
+
+/// Units in use per kind, checked against the configured floors.
+pub struct KindLedger {
+    /// Units reserved for each kind with a floor.
+    floors: HashMap<ResourceKind, u32>,
+    /// Units currently held by each kind.
+    used: Mutex<HashMap<ResourceKind, u32>>,
+}
+
+impl KindLedger {
+    pub fn new(floors: &KindFloors, max_units: u32) -> Self {
+        Self {
+            floors: floors
+                .floors
+                .keys()
+                .map(|&kind| (kind, floors.floor_units(kind, max_units)))
+                .collect(),
+            used: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Reserve `cost` if it fits in `max_units` without eating into the unused
+    /// floor of any other kind, updating `active_units` on success.
+    pub fn try_reserve(&self, active_units: &AtomicU32, max_units: u32, cost: ResourceCost) -> bool {
+        let mut used = self.used.lock();
+        let held_back: u32 = self
+            .floors
+            .iter()
+            .filter(|(&kind, _)| kind != cost.kind)
+            .map(|(kind, &floor)| floor.saturating_sub(used.get(kind).copied().unwrap_or(0)))
+            .sum();
+        let current = active_units.load(Ordering::Acquire);
+        let fits = current
+            .checked_add(cost.units)
+            .and_then(|units| units.checked_add(held_back))
+            .is_some_and(|units| units <= max_units);
+        if !fits {
+            return false;
+        }
+        active_units.fetch_add(cost.units, Ordering::AcqRel);
+        *used.entry(cost.kind).or_insert(0) += cost.units;
+        true
+    }
+
+    /// Return `cost` to the pool, updating `active_units`.
+    pub fn release(&self, active_units: &AtomicU32, cost: ResourceCost) {
+        let mut used = self.used.lock();
+        if let Some(units) = used.get_mut(&cost.kind) {
+            *units = units.saturating_sub(cost.units);
+        }
+        // Releasing the kind before the global units only errs on the side of caution
+        drop(used);
+        active_units.fetch_sub(cost.units, Ordering::Release);
+    }
+}
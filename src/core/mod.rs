@@ -3,14 +3,35 @@
 pub mod error;
 pub mod resource_pool;
 pub mod audit;
+pub mod dead_letter;
 pub mod executor;
+mod kind_ledger;
+pub mod progress;
+pub mod rate_limit;
+pub mod routing;
+mod status_map;
 pub mod worker_pool;
 
-pub use error::{AppResult, SchedulerError};
+pub use error::{AppResult, ExecError, SchedulerError};
 pub use resource_pool::{
-    Mailbox, PoolLimits, ResourcePool, ScheduledTask, Spawn, TaskMetadata, TaskQueue, TaskStatus,
-    WakeState, sync_wake_worker_loop,
+    CapacityGuard, Mailbox, MailboxMessage, PoolLimits, PoolSnapshotState, ResourcePool, ScheduledTask, Spawn, TaskMetadata,
+    TaskMetadataBuilder, TaskQueue, TaskStatus, WakeState,
 };
-pub use audit::{AuditEvent, AuditSink, InMemoryAuditSink, PostgresAuditSink, build_audit_event};
-pub use executor::{TaskExecutor, TaskPayload, WorkerExecutor};
-pub use worker_pool::{PoolError, PoolStats, WorkerPool};
+pub use audit::{
+    AuditEvent, AuditSink, FileAuditSink, FilteringAuditSink, InMemoryAuditSink, PostgresAuditSink,
+    TeeAuditSink, TracingAuditSink, build_audit_event,
+};
+pub use dead_letter::{
+    DeadLetter, DeadLetterSink, FileDeadLetter, InMemoryDeadLetter, REASON_DEADLINE_EXPIRED,
+    REASON_DEPENDENCY_FAILED, REASON_EXCEEDS_MAX_UNITS, REASON_EXECUTION_TIMEOUT,
+    REASON_QUEUE_FULL, REASON_QUEUE_WAIT_EXCEEDED, REASON_RETRIES_EXHAUSTED,
+};
+pub use executor::{
+    ExecutionOutcome, ExecutorContext, FnExecutor, TaskExecutor, TaskPayload, WorkerExecutor,
+};
+pub use progress::{Progress, ProgressReporter};
+pub use rate_limit::RateLimiter;
+pub use routing::RoutingExecutor;
+pub use worker_pool::{CircuitState, PoolError, PoolStats, WorkerPool};
+#[cfg(not(target_arch = "wasm32"))]
+pub use worker_pool::{RuntimeBuilderFn, TaskHandle, WorkerState};
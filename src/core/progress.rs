@@ -0,0 +1,122 @@
+//! Incremental progress reporting for long-running worker tasks.
+//!
+//! Executors receive a `ProgressReporter` and may emit `Progress` updates while
+//! they run (e.g. tokens generated so far). The pool forwards the updates to a
+//! per-task channel obtained from `WorkerPool::progress_stream`. Every update
+//! also counts as a heartbeat, so a running task that stops reporting shows up
+//! in `WorkerPool::stale_tasks`.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::util::clock::now_ms;
+
+/// Updates buffered per task before the oldest are dropped to make room.
+pub(crate) const PROGRESS_BUFFER: usize = 64;
+
+/// A single progress update emitted by an executor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    /// Completed fraction of the task, in `0.0..=1.0`.
+    pub fraction: f32,
+    /// Optional human-readable status message.
+    pub message: Option<String>,
+}
+
+/// Cloneable handle an executor uses to emit progress updates.
+///
+/// The default reporter is a no-op, so executors that don't report progress
+/// pay nothing for it.
+#[derive(Debug, Clone, Default)]
+pub struct ProgressReporter {
+    tx: Option<flume::Sender<Progress>>,
+    /// Receiving end of the pool's buffer, used to drop the oldest update when
+    /// a slow subscriber lets it fill up.
+    overflow: Option<flume::Receiver<Progress>>,
+    /// Last heartbeat in ms since the epoch, shared with the pool; 0 while the
+    /// task is not running.
+    heartbeat: Option<Arc<AtomicU64>>,
+}
+
+impl ProgressReporter {
+    /// Create a reporter forwarding updates to `tx`.
+    #[must_use]
+    pub const fn new(tx: flume::Sender<Progress>) -> Self {
+        Self {
+            tx: Some(tx),
+            overflow: None,
+            heartbeat: None,
+        }
+    }
+
+    /// Create a reporter for the bounded channel `tx`/`rx` that keeps the
+    /// latest updates, dropping the oldest when the channel is full.
+    pub(crate) const fn latest(
+        tx: flume::Sender<Progress>,
+        rx: flume::Receiver<Progress>,
+    ) -> Self {
+        Self {
+            tx: Some(tx),
+            overflow: Some(rx),
+            heartbeat: None,
+        }
+    }
+
+    /// Also bump `heartbeat` on every update.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn with_heartbeat(mut self, heartbeat: Arc<AtomicU64>) -> Self {
+        self.heartbeat = Some(heartbeat);
+        self
+    }
+
+    /// Create a reporter that discards every update.
+    #[must_use]
+    pub const fn noop() -> Self {
+        Self {
+            tx: None,
+            overflow: None,
+            heartbeat: None,
+        }
+    }
+
+    /// Mark the task as running (starting its heartbeat) or finished.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn set_running(&self, running: bool) {
+        if let Some(heartbeat) = &self.heartbeat {
+            let at = if running { heartbeat_now() } else { 0 };
+            heartbeat.store(at, Ordering::Relaxed);
+        }
+    }
+
+    /// Emit a progress update. `fraction` is clamped to `0.0..=1.0`.
+    ///
+    /// Updates are dropped silently if nobody is listening. When the channel
+    /// is full, the pool's reporter drops the oldest buffered update to make
+    /// room; a reporter built with [`new`](Self::new) drops this one instead.
+    pub fn report(&self, fraction: f32, message: Option<String>) {
+        if let Some(heartbeat) = &self.heartbeat {
+            // Only a running task has a heartbeat; late updates don't revive it
+            let now = heartbeat_now();
+            let _ = heartbeat.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |last| {
+                (last != 0).then_some(now)
+            });
+        }
+        if let Some(tx) = &self.tx {
+            let update = Progress {
+                fraction: fraction.clamp(0.0, 1.0),
+                message,
+            };
+            if let (Err(flume::TrySendError::Full(update)), Some(rx)) =
+                (tx.try_send(update), &self.overflow)
+            {
+                let _ = rx.try_recv();
+                let _ = tx.try_send(update);
+            }
+        }
+    }
+}
+
+/// Current time as a heartbeat value.
+fn heartbeat_now() -> u64 {
+    u64::try_from(now_ms()).unwrap_or(u64::MAX)
+}
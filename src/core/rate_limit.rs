@@ -0,0 +1,170 @@
+//! Per-tenant submission rate limiting shared by `WorkerPool` and `ResourcePool`.
+//!
+//! Capacity accounting bounds how much work runs at once; the rate limiter
+//! bounds how fast each tenant may submit it. Every tenant gets a token bucket
+//! from `RateLimitConfig`, refilled according to an injectable [`Clock`] so
+//! tests can simulate time passing.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::config::RateLimitConfig;
+use crate::util::clock::{Clock, SystemClock};
+use crate::util::serde::MailboxKey;
+
+/// Tenant name used for tasks submitted without a mailbox key.
+pub const UNKNOWN_TENANT: &str = "unknown";
+
+/// Token bucket state for one tenant.
+struct Bucket {
+    tokens: f64,
+    last_refill_ms: u128,
+}
+
+/// Token-bucket rate limiter keyed by `MailboxKey::tenant`.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use prometheus_parking_lot::config::{RateLimitConfig, TokenBucketConfig};
+/// use prometheus_parking_lot::core::RateLimiter;
+/// use prometheus_parking_lot::util::clock::ManualClock;
+///
+/// let clock = ManualClock::new(0);
+/// let config = RateLimitConfig::default().with_default(TokenBucketConfig::new(1, 2.0));
+/// let limiter = RateLimiter::with_clock(config, Arc::new(clock.clone()));
+///
+/// assert!(limiter.try_acquire("acme").is_ok());
+/// assert_eq!(limiter.try_acquire("acme"), Err(500));
+///
+/// clock.advance(Duration::from_millis(500));
+/// assert!(limiter.try_acquire("acme").is_ok());
+/// ```
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    clock: Arc<dyn Clock>,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RateLimiter")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RateLimiter {
+    /// Create a limiter refilled from the system clock.
+    #[must_use]
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self::with_clock(config, Arc::new(SystemClock))
+    }
+
+    /// Create a limiter refilled from `clock`.
+    #[must_use]
+    pub fn with_clock(config: RateLimitConfig, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            config,
+            clock,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The configured limits.
+    #[must_use]
+    pub const fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    /// Take one token from `tenant`'s bucket.
+    ///
+    /// # Errors
+    ///
+    /// Returns the number of milliseconds until a token is available when the
+    /// bucket is empty.
+    pub fn try_acquire(&self, tenant: &str) -> Result<(), u64> {
+        let Some(limit) = self.config.bucket_for(tenant) else {
+            return Ok(());
+        };
+        let capacity = f64::from(limit.capacity);
+        let now = self.clock.now_ms();
+        let mut buckets = self.buckets.lock();
+        let bucket = buckets.entry(tenant.to_owned()).or_insert(Bucket {
+            tokens: capacity,
+            last_refill_ms: now,
+        });
+        #[allow(clippy::cast_precision_loss)]
+        let elapsed_secs = now.saturating_sub(bucket.last_refill_ms) as f64 / 1000.0;
+        bucket.tokens = elapsed_secs
+            .mul_add(limit.refill_per_sec, bucket.tokens)
+            .min(capacity);
+        bucket.last_refill_ms = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+        let missing = 1.0 - bucket.tokens;
+        drop(buckets);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let retry_after_ms = (missing / limit.refill_per_sec * 1000.0).ceil() as u64;
+        Err(retry_after_ms.max(1))
+    }
+
+    /// Take one token for the tenant of `mailbox`, see [`Self::try_acquire`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the number of milliseconds until a token is available.
+    pub fn try_acquire_for(&self, mailbox: Option<&MailboxKey>) -> Result<(), u64> {
+        self.try_acquire(mailbox.map_or(UNKNOWN_TENANT, |key| key.tenant.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TokenBucketConfig;
+    use crate::util::clock::ManualClock;
+    use std::time::Duration;
+
+    #[test]
+    fn test_tenants_have_separate_buckets() {
+        let clock = ManualClock::new(1_000);
+        let config = RateLimitConfig::default()
+            .with_default(TokenBucketConfig::new(2, 1.0))
+            .with_tenant("vip", TokenBucketConfig::new(5, 1.0));
+        let limiter = RateLimiter::with_clock(config, Arc::new(clock.clone()));
+
+        assert!(limiter.try_acquire("a").is_ok());
+        assert!(limiter.try_acquire("a").is_ok());
+        assert_eq!(limiter.try_acquire("a"), Err(1_000));
+
+        // Other tenants are unaffected by "a" running dry
+        assert!(limiter.try_acquire("b").is_ok());
+        for _ in 0..5 {
+            assert!(limiter.try_acquire("vip").is_ok());
+        }
+        assert!(limiter.try_acquire("vip").is_err());
+
+        // Refill never exceeds capacity
+        clock.advance(Duration::from_secs(60));
+        assert!(limiter.try_acquire("a").is_ok());
+        assert!(limiter.try_acquire("a").is_ok());
+        assert!(limiter.try_acquire("a").is_err());
+    }
+
+    #[test]
+    fn test_unlimited_without_default() {
+        let config = RateLimitConfig::default().with_tenant("slow", TokenBucketConfig::new(1, 1.0));
+        let limiter = RateLimiter::new(config);
+        for _ in 0..100 {
+            assert!(limiter.try_acquire_for(None).is_ok());
+        }
+    }
+}
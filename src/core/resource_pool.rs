@@ -1,15 +1,28 @@
 //! Resource pool skeleton and core scheduling traits.
 
+use std::collections::HashMap;
 use std::future::Future;
 use std::marker::PhantomData;
-use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
 use parking_lot::{Condvar, Mutex};
 
-use crate::core::{AuditSink, SchedulerError, TaskExecutor, TaskPayload};
-use crate::util::serde::{MailboxKey, Priority, ResourceCost, TaskId};
+use crate::core::dead_letter::{
+    REASON_DEADLINE_EXPIRED, REASON_DEPENDENCY_FAILED, REASON_EXCEEDS_MAX_UNITS,
+    REASON_QUEUE_FULL, REASON_QUEUE_WAIT_EXCEEDED,
+};
+use crate::core::error::ZERO_COST_TASK;
+use crate::core::kind_ledger::KindLedger;
+use crate::core::status_map::StatusMap;
+use crate::core::worker_pool::{DependencyTracker, Refusal};
+use crate::config::{DispatchMode, KindFloors};
+use crate::core::{
+    AuditSink, DeadLetterSink, ExecError, RateLimiter, SchedulerError, TaskExecutor, TaskPayload,
+};
+use crate::util::serde::{MailboxKey, Priority, ResourceCost, ResourceKind, TaskId};
+use crate::util::telemetry::{PoolKind, PoolSnapshotMetrics, SnapshotSource};
 
 /// Status of a task in the scheduler lifecycle.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -28,6 +41,17 @@ pub enum TaskStatus {
     Dropped(String),
 }
 
+impl TaskStatus {
+    /// Whether the task has finished and will not change status again.
+    #[must_use]
+    pub const fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            Self::Completed | Self::Failed(_) | Self::Expired | Self::Dropped(_)
+        )
+    }
+}
+
 /// Metadata describing a scheduled task.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct TaskMetadata {
@@ -43,6 +67,112 @@ pub struct TaskMetadata {
     pub deadline_ms: Option<u128>,
     /// Creation timestamp in milliseconds since epoch.
     pub created_at_ms: u128,
+    /// W3C trace-context carrier (`traceparent`/`tracestate`) linking the task
+    /// to the submitter's distributed trace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_context: Option<HashMap<String, String>>,
+    /// Set when the pool downgraded the task under load (see
+    /// `WorkerPool::with_degradation`); executors may serve it on a cheaper path.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub degraded: bool,
+    /// Tasks that must complete successfully before this one may run. Honored
+    /// by `WorkerPool` and `ResourcePool`; if any of them fails, this task is
+    /// dropped.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<TaskId>,
+    /// Client-chosen key deduplicating retried submissions of the same job.
+    /// Honored by the native `WorkerPool` (see `WorkerPool::with_idempotency`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
+    /// How long `WorkerPool::retrieve_default` and `retrieve_async_default`
+    /// wait for this task's result, in milliseconds, overriding the pool's
+    /// `default_timeout_ms`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retrieve_timeout_ms: Option<u64>,
+}
+
+impl TaskMetadata {
+    /// Whether the task's deadline has been reached at `now_ms`. A task whose
+    /// deadline equals `now_ms` counts as expired, on every queue and pool.
+    #[must_use]
+    pub const fn is_expired_at(&self, now_ms: u128) -> bool {
+        matches!(self.deadline_ms, Some(deadline) if deadline <= now_ms)
+    }
+
+    /// Start building metadata for task `id`, created now with `Normal`
+    /// priority and a cost of one CPU unit.
+    pub fn builder(id: TaskId) -> TaskMetadataBuilder {
+        TaskMetadataBuilder {
+            meta: Self {
+                id,
+                mailbox: None,
+                priority: Priority::Normal,
+                cost: ResourceCost {
+                    kind: ResourceKind::Cpu,
+                    units: 1,
+                },
+                deadline_ms: None,
+                created_at_ms: crate::util::clock::now_ms(),
+                trace_context: None,
+                degraded: false,
+                depends_on: Vec::new(),
+                idempotency_key: None,
+                retrieve_timeout_ms: None,
+            },
+        }
+    }
+}
+
+/// Builder for [`TaskMetadata`], created by [`TaskMetadata::builder`].
+#[derive(Debug, Clone)]
+#[must_use = "call `build` to get the metadata"]
+pub struct TaskMetadataBuilder {
+    meta: TaskMetadata,
+}
+
+impl TaskMetadataBuilder {
+    /// Set the queue priority.
+    pub const fn priority(mut self, priority: Priority) -> Self {
+        self.meta.priority = priority;
+        self
+    }
+
+    /// Set the resource cost.
+    pub const fn cost(mut self, kind: ResourceKind, units: u32) -> Self {
+        self.meta.cost = ResourceCost { kind, units };
+        self
+    }
+
+    /// Route the result to `key`.
+    pub fn mailbox(mut self, key: MailboxKey) -> Self {
+        self.meta.mailbox = Some(key);
+        self
+    }
+
+    /// Expire the task `timeout` after its creation time.
+    pub const fn deadline_in(mut self, timeout: Duration) -> Self {
+        self.meta.deadline_ms = Some(self.meta.created_at_ms + timeout.as_millis());
+        self
+    }
+
+    /// Hold the task until every task in `depends_on` has completed.
+    pub fn depends_on(mut self, depends_on: Vec<TaskId>) -> Self {
+        self.meta.depends_on = depends_on;
+        self
+    }
+
+    /// Wait up to `timeout` for the result when retrieving it with the
+    /// pool's default timeout.
+    pub fn retrieve_timeout(mut self, timeout: Duration) -> Self {
+        self.meta.retrieve_timeout_ms = Some(u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX));
+        self
+    }
+
+    /// Finish building.
+    #[must_use]
+    pub fn build(self) -> TaskMetadata {
+        self.meta
+    }
 }
 
 /// A schedulable task with metadata and payload.
@@ -62,12 +192,77 @@ pub trait TaskQueue<P> {
     fn enqueue(&mut self, task: ScheduledTask<P>) -> Result<(), SchedulerError>;
     /// Dequeue the next ready task, honoring priority and deadlines.
     fn dequeue(&mut self) -> Result<Option<ScheduledTask<P>>, SchedulerError>;
+    /// Metadata of the task `dequeue` would return next, without removing it.
+    ///
+    /// # Errors
+    ///
+    /// Returns the backend error if the queue cannot be read.
+    fn peek(&self) -> Result<Option<TaskMetadata>, SchedulerError>;
+    /// `created_at_ms` of the oldest queued task, or `None` when empty.
+    ///
+    /// The default reads the head's timestamp, which is exact for a queue
+    /// holding a single priority (FIFO within a priority); backends that can
+    /// cheaply find the minimum across priorities override it.
+    fn oldest_created_at_ms(&self) -> Option<u128> {
+        self.peek().ok().flatten().map(|meta| meta.created_at_ms)
+    }
+    /// Total `cost.units` of the queued tasks.
+    ///
+    /// The default sums nothing and returns 0, for backends that don't track
+    /// cost; a pool's `max_queued_units` limit is then never reached.
+    fn queued_units(&self) -> u64 {
+        0
+    }
     /// Remove expired tasks and return count.
     fn prune_expired(&mut self, now_ms: u128) -> Result<usize, SchedulerError>;
+    /// Remove expired tasks and return them, in no particular order.
+    ///
+    /// A task is expired once its deadline is at or before `now_ms`, as for
+    /// `prune_expired`. The default drains the queue and re-enqueues the tasks
+    /// still live; backends that can select expired tasks in place override it.
+    ///
+    /// # Errors
+    ///
+    /// Returns the backend error if the queue cannot be read or rewritten.
+    fn take_expired(&mut self, now_ms: u128) -> Result<Vec<ScheduledTask<P>>, SchedulerError> {
+        let (expired, live): (Vec<_>, Vec<_>) = self
+            .drain()?
+            .into_iter()
+            .partition(|task| task.meta.is_expired_at(now_ms));
+        for task in live {
+            self.enqueue(task)?;
+        }
+        Ok(expired)
+    }
+    /// Remove every queued task, returned in dequeue order.
+    ///
+    /// # Errors
+    ///
+    /// Returns the backend error if the queue cannot be emptied.
+    fn drain(&mut self) -> Result<Vec<ScheduledTask<P>>, SchedulerError>;
     /// Maximum depth allowed for this queue.
     fn max_depth(&self) -> usize;
     /// Current depth.
     fn len(&self) -> usize;
+    /// Whether no tasks are queued.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Whether the queue holds `max_depth` tasks and refuses more.
+    fn is_full(&self) -> bool {
+        self.len() >= self.max_depth()
+    }
+}
+
+/// Mailbox message container.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MailboxMessage<P> {
+    /// Task status.
+    pub status: TaskStatus,
+    /// Optional payload/result.
+    pub payload: Option<P>,
+    /// Timestamp milliseconds.
+    pub created_at_ms: u128,
 }
 
 /// Abstraction for mailbox backends.
@@ -79,6 +274,22 @@ pub trait Mailbox<T> {
         status: TaskStatus,
         payload: Option<T>,
     ) -> Result<(), SchedulerError>;
+
+    /// Fetch up to `limit` messages delivered to `key`, oldest first,
+    /// optionally only those created at or after `since_ms`.
+    ///
+    /// Write-only backends return nothing.
+    fn fetch(
+        &self,
+        _key: &MailboxKey,
+        _since_ms: Option<u128>,
+        _limit: usize,
+    ) -> Vec<MailboxMessage<T>>
+    where
+        T: Clone,
+    {
+        Vec::new()
+    }
 }
 
 /// Abstraction for spawning task execution on a runtime.
@@ -98,8 +309,130 @@ pub struct PoolLimits {
     pub max_queue_depth: usize,
     /// Default timeout for tasks (seconds).
     pub default_timeout: Duration,
+    /// Longest a task may wait in the queue for capacity. A task that has
+    /// waited longer when its turn comes is dropped instead of started,
+    /// whether or not it has a deadline. `None` lets tasks wait indefinitely.
+    pub max_queue_wait: Option<Duration>,
+    /// Largest total `cost.units` the queue may hold. A task that would push
+    /// the queued units past it is rejected like one arriving at a full queue.
+    /// `None` bounds the queue by `max_queue_depth` alone.
+    pub max_queued_units: Option<u64>,
+    /// Fraction of `max_units` (in `0.0..1.0`) held back for `High` and
+    /// `Critical` tasks. Lower-priority tasks are admitted only while the
+    /// units in use stay below `max_units` minus the reserve, so urgent work
+    /// always finds headroom. `None` lets every task use the full budget.
+    pub high_priority_reserve: Option<f64>,
+    /// Whether `submit` may start a task that fits right away
+    /// ([`DispatchMode::Immediate`]) or queues every task for the wake pass
+    /// ([`DispatchMode::QueueAlways`]). Queueing everything gives a single
+    /// scheduling point and strict priority order, at the cost of a wake
+    /// pass between submission and start.
+    pub dispatch_mode: DispatchMode,
+    /// Most tasks running at once, counted separately from units: a task
+    /// starts only when its units fit and fewer than this many are running.
+    /// `None` bounds concurrency by `max_units` alone.
+    pub max_concurrent_tasks: Option<usize>,
+    /// Whether `submit` queues a task that fits whenever anything queued has
+    /// equal or higher priority, so newcomers never jump older work.
+    ///
+    /// By default a newcomer that fits only queues behind a head of higher
+    /// priority, so a large queued task doesn't hold back smaller ones of its
+    /// own priority. A head whose kind floors keep it off the newcomer's units
+    /// doesn't hold it back either, and a newcomer can win capacity from a
+    /// wake pass that has the head out of the queue. Setting this queues the
+    /// newcomer in all those cases, and when the queue can't be read, at the
+    /// cost of head-of-line blocking.
+    pub prefer_queued_on_contention: bool,
+}
+
+impl Default for PoolLimits {
+    /// The same limits as a default `PoolConfig`.
+    fn default() -> Self {
+        Self {
+            max_units: 1000,
+            max_queue_depth: 1000,
+            default_timeout: Duration::from_mins(2),
+            max_queue_wait: None,
+            max_queued_units: None,
+            high_priority_reserve: None,
+            dispatch_mode: DispatchMode::Immediate,
+            max_concurrent_tasks: None,
+            prefer_queued_on_contention: false,
+        }
+    }
+}
+
+impl PoolLimits {
+    /// Units held back for high-priority tasks, rounded down.
+    #[must_use]
+    pub fn reserved_units(&self) -> u32 {
+        let fraction = self.high_priority_reserve.unwrap_or(0.0).clamp(0.0, 1.0);
+        // The clamped fraction keeps this within 0..=max_units
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let units = (f64::from(self.max_units) * fraction).floor() as u32;
+        units.min(self.max_units)
+    }
+
+    /// Units a task of `priority` may bring the pool's usage up to: the full
+    /// `max_units` for `High` and above, the unreserved part otherwise.
+    #[must_use]
+    pub fn unit_limit(&self, priority: Priority) -> u32 {
+        if priority.value() >= Priority::High.value() {
+            self.max_units
+        } else {
+            self.max_units - self.reserved_units()
+        }
+    }
+}
+
+/// A pool's `PoolLimits`, with a `max_units` that can change while it runs.
+#[derive(Debug)]
+struct LiveLimits {
+    limits: PoolLimits,
+    max_units: AtomicU32,
+}
+
+impl LiveLimits {
+    const fn new(limits: PoolLimits) -> Self {
+        Self {
+            max_units: AtomicU32::new(limits.max_units),
+            limits,
+        }
+    }
+
+    fn max_units(&self) -> u32 {
+        self.max_units.load(Ordering::Acquire)
+    }
+
+    /// The limits in force right now.
+    fn current(&self) -> PoolLimits {
+        PoolLimits {
+            max_units: self.max_units(),
+            ..self.limits.clone()
+        }
+    }
+}
+
+/// Serializable copy of a `ResourcePool`'s queue, taken with
+/// [`ResourcePool::snapshot`] and replayed with [`ResourcePool::restore`] to
+/// recover queued work across a restart, whatever the queue backend.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(bound(serialize = "P: serde::Serialize"))]
+#[serde(bound(deserialize = "P: serde::de::DeserializeOwned"))]
+pub struct PoolSnapshotState<P> {
+    /// Queued tasks, in dequeue order, then the tasks parked behind their
+    /// dependencies, oldest first.
+    pub queued: Vec<ScheduledTask<P>>,
+    /// Units held by running tasks when the snapshot was taken. Running tasks
+    /// are not captured, so `restore` does not reserve these units.
+    pub active_units: u32,
+    /// When the snapshot was taken, in milliseconds since epoch.
+    pub taken_at_ms: u128,
 }
 
+/// How long terminal task statuses stay queryable by default.
+const DEFAULT_STATUS_TTL: Duration = Duration::from_mins(5);
+
 /// Shared state for Condvar-based wake notifications.
 /// This allows efficient signaling when capacity becomes available.
 pub struct WakeState {
@@ -109,6 +442,115 @@ pub struct WakeState {
     pub shutdown: bool,
 }
 
+/// Units held outside any task, taken with [`ResourcePool::reserve`].
+///
+/// Dropping the guard returns the units and wakes queued tasks, as a task
+/// completion does.
+#[must_use = "dropping the guard releases the units immediately"]
+pub struct CapacityGuard {
+    units: u32,
+    release: Option<Box<dyn FnOnce() + Send>>,
+}
+
+impl CapacityGuard {
+    /// Units held by the guard.
+    #[must_use]
+    pub const fn units(&self) -> u32 {
+        self.units
+    }
+}
+
+impl std::fmt::Debug for CapacityGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CapacityGuard").field("units", &self.units).finish_non_exhaustive()
+    }
+}
+
+impl Drop for CapacityGuard {
+    fn drop(&mut self) {
+        if let Some(release) = self.release.take() {
+            release();
+        }
+    }
+}
+
+/// Serializes async wake passes so at most one `try_wake_next` loop runs at a time.
+///
+/// A wake requested while a pass is running is not dropped: the running pass
+/// notices it when it finishes and goes round again.
+///
+/// The gate also holds the wake hysteresis: a completion only requests a pass
+/// once the free units could start the cheapest queued task.
+struct WakeGate {
+    /// Set while a wake pass is running.
+    in_progress: AtomicBool,
+    /// Set when capacity was released since the running pass started.
+    requested: AtomicBool,
+    /// Whether completions skip passes that couldn't start anything.
+    hysteresis: AtomicBool,
+    /// At most the units of the cheapest queued task, or `u32::MAX` while the
+    /// queue is empty. Only written under the queue lock, so it never exceeds
+    /// the true minimum.
+    min_queued_units: AtomicU32,
+}
+
+impl WakeGate {
+    /// Gate for a pool whose queue starts out empty or not.
+    ///
+    /// Tasks already in a durable queue have unknown costs, so the bound
+    /// starts at 0 for them until the queue is next seen empty.
+    const fn new(queue_empty: bool) -> Self {
+        Self {
+            in_progress: AtomicBool::new(false),
+            requested: AtomicBool::new(false),
+            hysteresis: AtomicBool::new(true),
+            min_queued_units: AtomicU32::new(if queue_empty { u32::MAX } else { 0 }),
+        }
+    }
+
+    /// Record a task put in the queue; call with the queue lock held.
+    fn note_queued(&self, units: u32) {
+        self.min_queued_units.fetch_min(units, Ordering::AcqRel);
+    }
+
+    /// Record that the queue is empty; call with the queue lock held.
+    fn note_empty(&self) {
+        self.min_queued_units.store(u32::MAX, Ordering::Release);
+    }
+
+    /// Whether a completion leaving `free_units` unused should request a pass.
+    ///
+    /// Below the cheapest queued task's cost no queued task fits, so the pass
+    /// would only lock the queue to put its head back.
+    fn worth_waking(&self, free_units: u32) -> bool {
+        !self.hysteresis.load(Ordering::Acquire)
+            || free_units >= self.min_queued_units.load(Ordering::Acquire)
+    }
+
+    /// Whether a wake pass is running, possibly holding the queue's head.
+    fn in_pass(&self) -> bool {
+        self.in_progress.load(Ordering::Acquire)
+    }
+
+    /// Request a wake pass; returns true if the caller must run it.
+    fn request(&self) -> bool {
+        self.requested.store(true, Ordering::Release);
+        !self.in_progress.swap(true, Ordering::AcqRel)
+    }
+
+    /// Mark the start of a pass, consuming pending requests.
+    fn begin_pass(&self) {
+        self.requested.store(false, Ordering::Release);
+    }
+
+    /// Finish a pass; returns true if another pass was requested meanwhile and
+    /// the caller took ownership of running it.
+    fn finish_pass(&self) -> bool {
+        self.in_progress.store(false, Ordering::Release);
+        self.requested.load(Ordering::Acquire) && !self.in_progress.swap(true, Ordering::AcqRel)
+    }
+}
+
 /// Resource pool with capacity accounting and complete parking lot algorithm.
 ///
 /// Uses lock-free `AtomicU32` for capacity tracking (`active_units`),
@@ -117,11 +559,14 @@ pub struct WakeState {
 pub struct ResourcePool<P, T, Q, M, E, S>
 where
     P: TaskPayload,
-    T: Send + Sync + serde::Serialize + for<'de> serde::Deserialize<'de> + 'static,
+    T: Send + 'static,
 {
-    limits: PoolLimits,
+    /// Limits shared with wake passes, so `set_max_units` reaches them.
+    limits: Arc<LiveLimits>,
     /// Lock-free capacity tracking - number of active resource units in use.
     active_units: Arc<AtomicU32>,
+    /// Tasks currently running, checked against `max_concurrent_tasks`.
+    active_tasks: Arc<AtomicUsize>,
     /// Task queue protected by its own mutex for write-heavy operations.
     queue: Arc<Mutex<Q>>,
     /// Mailbox protected by its own mutex, separate from queue for better concurrency.
@@ -133,9 +578,22 @@ where
     wake_state: Arc<Mutex<WakeState>>,
     /// Flag indicating if async wake is enabled (vs sync wake worker).
     async_wake_enabled: Arc<AtomicBool>,
+    /// Ensures only one async wake loop dequeues at a time.
+    wake_gate: Arc<WakeGate>,
+    /// Latest status of each live or recently finished task.
+    status: Arc<StatusMap>,
+    /// In-flight task ids, and the tasks parked until their `depends_on`
+    /// complete.
+    dependencies: Arc<DependencyTracker<ScheduledTask<P>>>,
+    /// Per-kind usage, present when kind floors are configured.
+    kinds: Option<Arc<KindLedger>>,
     executor: E,
     spawner: S,
     audit: Option<Arc<Mutex<Box<dyn AuditSink>>>>,
+    dead_letter: Option<Arc<Mutex<Box<dyn DeadLetterSink>>>>,
+    rate_limiter: Option<RateLimiter>,
+    /// Next id handed out by `next_task_id`.
+    task_id_counter: AtomicU64,
     _payload_marker: PhantomData<P>,
     _result_marker: PhantomData<T>,
 }
@@ -143,13 +601,18 @@ where
 impl<P, T, Q, M, E, S> ResourcePool<P, T, Q, M, E, S>
 where
     P: TaskPayload,
-    T: Send + Sync + serde::Serialize + for<'de> serde::Deserialize<'de> + 'static,
+    T: Send + 'static,
 {
     /// Create a new pool from components.
-    pub fn new(limits: PoolLimits, queue: Q, mailbox: M, executor: E, spawner: S) -> Self {
+    pub fn new(limits: PoolLimits, queue: Q, mailbox: M, executor: E, spawner: S) -> Self
+    where
+        Q: TaskQueue<P>,
+    {
+        let wake_gate = WakeGate::new(queue.is_empty());
         Self {
-            limits,
+            limits: Arc::new(LiveLimits::new(limits)),
             active_units: Arc::new(AtomicU32::new(0)),
+            active_tasks: Arc::new(AtomicUsize::new(0)),
             queue: Arc::new(Mutex::new(queue)),
             mailbox: Arc::new(Mutex::new(mailbox)),
             wake_condvar: Arc::new(Condvar::new()),
@@ -158,9 +621,16 @@ where
                 shutdown: false,
             })),
             async_wake_enabled: Arc::new(AtomicBool::new(true)),
+            wake_gate: Arc::new(wake_gate),
+            status: Arc::new(StatusMap::new(DEFAULT_STATUS_TTL)),
+            dependencies: Arc::new(DependencyTracker::default()),
+            kinds: None,
             executor,
             spawner,
             audit: None,
+            dead_letter: None,
+            rate_limiter: None,
+            task_id_counter: AtomicU64::new(0),
             _payload_marker: PhantomData,
             _result_marker: PhantomData,
         }
@@ -172,30 +642,109 @@ where
         self
     }
 
-    /// Try to reserve capacity atomically using CAS loop.
+    /// Attach a dead-letter sink for tasks dropped without running.
+    #[must_use]
+    pub fn with_dead_letter(mut self, sink: Box<dyn DeadLetterSink>) -> Self {
+        self.dead_letter = Some(Arc::new(Mutex::new(sink)));
+        self
+    }
+
+    /// Guarantee each resource kind a minimum share of `max_units`.
+    ///
+    /// Reservations then take a short lock so the global budget and the
+    /// per-kind floors are checked together. An empty `floors` leaves the
+    /// lock-free path in place.
+    #[must_use]
+    pub fn with_kind_floors(mut self, floors: &KindFloors) -> Self {
+        self.kinds = (!floors.is_empty())
+            .then(|| Arc::new(KindLedger::new(floors, self.limits.max_units())));
+        self
+    }
+
+    /// Limit how fast each tenant (`MailboxKey::tenant`) may submit tasks.
+    ///
+    /// Submissions beyond a tenant's token bucket fail with
+    /// `SchedulerError::RateLimited` before they reserve capacity or queue.
+    #[must_use]
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Enable or disable wake hysteresis (enabled by default).
+    ///
+    /// With hysteresis, a finishing task only triggers a wake pass once the
+    /// free units could start the cheapest queued task, instead of on every
+    /// completion. This saves queue-lock round trips when many small tasks
+    /// finish while larger ones wait.
+    #[must_use]
+    pub fn with_wake_hysteresis(self, enabled: bool) -> Self {
+        self.wake_gate.hysteresis.store(enabled, Ordering::Release);
+        self
+    }
+
+    /// Keep terminal task statuses queryable for `ttl` (default five minutes).
+    #[must_use]
+    pub fn with_status_ttl(mut self, ttl: Duration) -> Self {
+        self.status = Arc::new(StatusMap::new(ttl));
+        self
+    }
+
+    /// Hand out a task id for [`submit_job`](ResourcePool::submit_job).
+    ///
+    /// Ids count up from 0, so they can collide with ids the caller picks
+    /// for tasks passed to `submit`; use one scheme or the other per pool.
+    pub fn next_task_id(&self) -> TaskId {
+        self.task_id_counter.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Current status of a task, or `None` if it is unknown or finished longer
+    /// ago than the status TTL.
+    pub fn status(&self, id: TaskId) -> Option<TaskStatus> {
+        self.status.get(id)
+    }
+
+    /// Fetch up to `limit` results delivered to `key`, oldest first,
+    /// optionally only those created at or after `since_ms`.
+    ///
+    /// Lets a reconnecting client read results delivered while it was away,
+    /// from mailboxes that keep them (see [`Mailbox::fetch`]).
+    pub fn fetch_results(
+        &self,
+        key: &MailboxKey,
+        since_ms: Option<u128>,
+        limit: usize,
+    ) -> Vec<MailboxMessage<T>>
+    where
+        M: Mailbox<T>,
+        T: Clone,
+    {
+        self.mailbox.lock().fetch(key, since_ms, limit)
+    }
+
+    /// Hand a dropped task to the dead-letter sink, if one is attached.
+    fn record_dead_letter(&self, meta: &TaskMetadata, reason: &str) {
+        record_dead_letter(self.dead_letter.as_deref(), meta, reason);
+    }
+
+    /// Try to reserve capacity atomically.
     /// Returns true if capacity was successfully reserved, false otherwise.
-    fn try_reserve_capacity(&self, cost: u32) -> bool {
-        let mut current = self.active_units.load(Ordering::Acquire);
-        loop {
-            if current + cost > self.limits.max_units {
-                return false;
-            }
-            match self.active_units.compare_exchange_weak(
-                current,
-                current + cost,
-                Ordering::AcqRel,
-                Ordering::Acquire,
-            ) {
-                Ok(_) => return true,
-                Err(actual) => current = actual,
-            }
-        }
+    fn try_reserve_capacity(&self, meta: &TaskMetadata) -> bool {
+        reserve_start(
+            &self.active_units,
+            &self.active_tasks,
+            self.kinds.as_deref(),
+            &self.limits.current(),
+            meta,
+        )
     }
 
     /// Check if task can start without acquiring any locks (lock-free read).
-    fn can_start_lockfree(&self, cost: u32) -> bool {
+    fn can_start_lockfree(&self, meta: &TaskMetadata) -> bool {
         let current = self.active_units.load(Ordering::Acquire);
-        current + cost <= self.limits.max_units
+        let limits = self.limits.current();
+        current + meta.cost.units <= limits.unit_limit(meta.priority)
+            && has_task_slot(&self.active_tasks, &limits)
     }
 
     /// Signal shutdown to any waiting wake workers.
@@ -211,366 +760,615 @@ where
 impl<P, T, Q, M, E, S> ResourcePool<P, T, Q, M, E, S>
 where
     P: TaskPayload,
-    T: Send + Sync + serde::Serialize + for<'de> serde::Deserialize<'de> + 'static,
+    T: Send + 'static,
     Q: TaskQueue<P> + Send + 'static,
     M: Mailbox<T> + Send + 'static,
-    E: TaskExecutor<P, T>,
+    E: TaskExecutor<P, T> + Clone,
     S: Spawn + Clone + Send + 'static,
 {
     /// Submit a task, enforcing capacity, deadlines, and queue depth.
     /// Executes immediately if capacity available, otherwise enqueues.
+    ///
+    /// A task whose `depends_on` names tasks still in flight is parked, with
+    /// status `Queued`, until they all complete, then joins the queue. If any
+    /// of them fails it is dropped instead, and so are its own dependents.
+    ///
+    /// A task that fits still queues behind a waiting task of higher priority
+    /// (of the same kind, when kind floors are set), so freed capacity goes to
+    /// the most important queued task instead of to whichever small task
+    /// arrives next. A queued task of equal priority doesn't hold it back
+    /// unless `prefer_queued_on_contention` is set.
     pub async fn submit(
         &self,
         task: ScheduledTask<P>,
         now_ms: u128,
     ) -> Result<TaskStatus, SchedulerError> {
+        // Admission never awaits; execution is handed to the spawner
+        self.submit_blocking(task, now_ms)
+    }
+
+    /// Submit `payload` as a new task whose result is delivered to `key`,
+    /// returning the key to pass to [`fetch_results`](ResourcePool::fetch_results).
+    ///
+    /// Builds the metadata for the caller, with an id from
+    /// [`next_task_id`](ResourcePool::next_task_id) and `now_ms` as its
+    /// creation time, then admits it like
+    /// [`submit_blocking`](Self::submit_blocking). Admission never waits, so
+    /// this serves async and blocking callers alike.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`submit_blocking`](Self::submit_blocking).
+    pub fn submit_job(
+        &self,
+        payload: P,
+        cost: ResourceCost,
+        priority: Priority,
+        key: MailboxKey,
+        now_ms: u128,
+    ) -> Result<MailboxKey, SchedulerError> {
+        let mut meta = TaskMetadata::builder(self.next_task_id())
+            .cost(cost.kind, cost.units)
+            .priority(priority)
+            .mailbox(key.clone())
+            .build();
+        meta.created_at_ms = now_ms;
+        self.submit_blocking(ScheduledTask { meta, payload }, now_ms)?;
+        Ok(key)
+    }
+
+    /// Submit a task from a non-async context (blocking API).
+    ///
+    /// Performs the same admission as [`submit`](Self::submit) and launches
+    /// execution through the pool's `Spawn` impl, so it can be called without
+    /// an async runtime on the calling thread.
+    ///
+    /// # Errors
+    ///
+    /// - `SchedulerError::InvalidTask` if the task costs zero units
+    /// - `SchedulerError::CapacityExceeded` if the task costs more units than
+    ///   its priority may use with the pool idle, under the current `max_units`
+    /// - `SchedulerError::DeadlineExpired` if the task's deadline already passed
+    /// - `SchedulerError::RateLimited` if the task's tenant exceeded its rate limit
+    /// - `SchedulerError::DependencyFailed` if a task in `depends_on` recently failed
+    /// - `SchedulerError::QueueFull` if the task cannot start and the queue is full
+    pub fn submit_blocking(
+        &self,
+        task: ScheduledTask<P>,
+        now_ms: u128,
+    ) -> Result<TaskStatus, SchedulerError> {
+        if task.meta.cost.units == 0 {
+            tracing::warn!("task {} rejected: zero cost", task.meta.id);
+            return Err(SchedulerError::InvalidTask(ZERO_COST_TASK.into()));
+        }
+        // A task larger than its priority may use under the current
+        // max_units would wait for it to be raised
+        let limits = self.limits.current();
+        if task.meta.cost.units > limits.unit_limit(task.meta.priority) {
+            tracing::warn!("task {} rejected: cost exceeds pool capacity", task.meta.id);
+            return Err(SchedulerError::CapacityExceeded);
+        }
+
         // Check deadline before any processing
-        if let Some(deadline) = task.meta.deadline_ms {
-            if now_ms > deadline {
-                tracing::warn!("task {} expired before enqueue", task.meta.id);
-                return Err(SchedulerError::DeadlineExpired);
-            }
+        if task.meta.is_expired_at(now_ms) {
+            tracing::warn!("task {} expired before enqueue", task.meta.id);
+            self.record_dead_letter(&task.meta, REASON_DEADLINE_EXPIRED);
+            return Err(SchedulerError::DeadlineExpired);
+        }
+
+        if let Some(limiter) = &self.rate_limiter {
+            limiter
+                .try_acquire_for(task.meta.mailbox.as_ref())
+                .map_err(|retry_after_ms| SchedulerError::RateLimited { retry_after_ms })?;
         }
 
-        // Lock-free capacity check and reservation using CAS
-        if self.can_start_lockfree(task.meta.cost.units)
-            && self.try_reserve_capacity(task.meta.cost.units)
-        {
+        // Register the task as in flight, holding it back while any of its
+        // dependencies still is; from here every rejection settles it
+        let (id, deadline_ms) = (task.meta.id, task.meta.deadline_ms);
+        let depends_on = task.meta.depends_on.clone();
+        let task = match self.dependencies.submit(id, &depends_on, task, false) {
+            Ok(Some(task)) => task,
+            Ok(None) => {
+                self.status.set(id, TaskStatus::Queued, deadline_ms);
+                tracing::info!("task {} parked until its dependencies complete", id);
+                return Ok(TaskStatus::Queued);
+            }
+            Err((Refusal::DependencyFailed(failed), task)) => {
+                tracing::warn!("task {} rejected: dependency {} failed", id, failed);
+                self.record_dead_letter(&task.meta, REASON_DEPENDENCY_FAILED);
+                return Err(SchedulerError::DependencyFailed { id: failed });
+            }
+            Err((Refusal::Duplicate, _)) => unreachable!("ids are not checked for uniqueness"),
+        };
+
+        // Lock-free capacity check and reservation using CAS; a task that fits
+        // doesn't overtake queued work of equal or higher priority
+        let fits = self.can_start_lockfree(&task.meta);
+        let deferred = fits
+            && (limits.dispatch_mode == DispatchMode::QueueAlways
+                || !self.may_bypass_queue(&task.meta));
+        if fits && !deferred && self.try_reserve_capacity(&task.meta) {
             // Record audit (sync operation with parking_lot mutex)
             self.record_audit(&task, "start");
+            self.status.set(task.meta.id, TaskStatus::Running, None);
             tracing::info!("task {} started immediately", task.meta.id);
 
             // Spawn execution
-            self.spawn_task(task).await;
+            self.spawn_task(task);
 
             return Ok(TaskStatus::Running);
         }
 
-        // Not enough capacity - try to enqueue
-        // Quick mutex for queue check and enqueue (parking_lot is fast here)
-        {
-            let queue = self.queue.lock();
-            if queue.len() >= self.limits.max_queue_depth {
-                tracing::warn!(
-                    "task {} rejected: queue full (depth={})",
-                    task.meta.id,
-                    queue.len()
-                );
-                return Err(SchedulerError::QueueFull("max queue depth reached".into()));
-            }
-        } // Lock released before audit
+        // Not enough capacity - try to enqueue. Reject a task that can't fit
+        // before auditing it (lock released before audit)
+        let room = check_queue_room(&*self.queue.lock(), &limits, &task.meta);
+        if let Err(e) = room {
+            tracing::warn!("task {} rejected: {}", task.meta.id, e);
+            self.record_dead_letter(&task.meta, REASON_QUEUE_FULL);
+            self.settle_dependents(id, false);
+            return Err(e);
+        }
 
         // Record audit
         self.record_audit(&task, "enqueue");
 
-        // Enqueue the task
-        {
-            let mut queue = self.queue.lock();
-            queue.enqueue(task)?;
+        // Enqueue the task; mark it queued first so a wake can't be overwritten.
+        // Concurrent submissions may have filled the queue since the check
+        // above, so check again under the enqueue's lock
+        let meta = task.meta.clone();
+        self.status.set(meta.id, TaskStatus::Queued, meta.deadline_ms);
+        let mut queue = self.queue.lock();
+        let enqueued =
+            check_queue_room(&*queue, &limits, &meta).and_then(|()| queue.enqueue(task));
+        if enqueued.is_ok() {
+            self.wake_gate.note_queued(meta.cost.units);
+        }
+        drop(queue);
+        if let Err(e) = enqueued {
+            tracing::warn!("task {} rejected: {}", meta.id, e);
+            self.status.remove(meta.id);
+            if matches!(e, SchedulerError::QueueFull(_)) {
+                self.record_dead_letter(&meta, REASON_QUEUE_FULL);
+            }
+            self.settle_dependents(id, false);
+            return Err(e);
         }
         tracing::info!("task enqueued");
+        if deferred {
+            // Capacity is free, so let the wake pass start the queue's head now
+            self.request_wake();
+        }
         Ok(TaskStatus::Queued)
     }
 
     /// Spawn a task execution asynchronously.
-    async fn spawn_task(&self, task: ScheduledTask<P>) {
-        let executor = self.executor.clone();
-        let queue = Arc::clone(&self.queue);
-        let mailbox = Arc::clone(&self.mailbox);
-        let active_units = Arc::clone(&self.active_units);
-        let wake_condvar = Arc::clone(&self.wake_condvar);
-        let wake_state = Arc::clone(&self.wake_state);
-        let async_wake_enabled = Arc::clone(&self.async_wake_enabled);
-        let limits = self.limits.clone();
-        let audit = self.audit.clone();
-        let spawner = self.spawner.clone();
-        let task_id = task.meta.id;
-        let task_cost = task.meta.cost.units;
-        let mailbox_key = task.meta.mailbox.clone();
-        let meta = task.meta.clone();
-        let payload = task.payload;
+    fn spawn_task(&self, task: ScheduledTask<P>) {
+        self.wake_handle().spawn_task(task);
+    }
 
-        self.spawner.spawn(async move {
-            tracing::debug!("executing task {}", task_id);
+    /// Clone the state that spawned tasks and wake passes share with the pool.
+    fn wake_handle(&self) -> WakeHandle<P, T, Q, M, E, S> {
+        WakeHandle {
+            queue: Arc::clone(&self.queue),
+            mailbox: Arc::clone(&self.mailbox),
+            active_units: Arc::clone(&self.active_units),
+            active_tasks: Arc::clone(&self.active_tasks),
+            wake_condvar: Arc::clone(&self.wake_condvar),
+            wake_state: Arc::clone(&self.wake_state),
+            async_wake_enabled: Arc::clone(&self.async_wake_enabled),
+            wake_gate: Arc::clone(&self.wake_gate),
+            status: Arc::clone(&self.status),
+            dependencies: Arc::clone(&self.dependencies),
+            kinds: self.kinds.clone(),
+            limits: Arc::clone(&self.limits),
+            audit: self.audit.clone(),
+            dead_letter: self.dead_letter.clone(),
+            spawner: self.spawner.clone(),
+            executor: self.executor.clone(),
+            _marker: PhantomData,
+        }
+    }
 
-            // Execute the task
-            let result = executor.execute(payload, meta).await;
+    /// Settle the dependents of a task that finished or left the pool, see
+    /// [`WakeHandle::settle_dependents`].
+    fn settle_dependents(&self, id: TaskId, succeeded: bool) {
+        self.wake_handle().settle_dependents(id, succeeded);
+    }
 
-            tracing::info!("task {} completed", task_id);
+    /// Start a dedicated thread that starts queued tasks in place of async
+    /// wake passes.
+    ///
+    /// Async wake is disabled from then on: each completion signals the
+    /// worker through the pool's condvar, and the worker dequeues and spawns
+    /// queued tasks on the pool's spawner exactly as a wake pass would. This
+    /// saves a spawned wake future per completion in high-throughput pools.
+    /// One signal is enough however many units a completion frees: the worker
+    /// keeps starting tasks until the queue's head doesn't fit, so a large
+    /// release starts every queued task it makes room for at once.
+    /// The worker exits once [`shutdown`](Self::shutdown) is called; join the
+    /// returned handle to wait for it.
+    ///
+    /// ```rust,ignore
+    /// let worker = pool.spawn_sync_wake_worker()?;
+    /// // ... submit tasks ...
+    /// pool.shutdown();
+    /// worker.join().unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the OS error if the thread cannot be spawned; async wake then
+    /// stays enabled.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn_sync_wake_worker(&self) -> std::io::Result<std::thread::JoinHandle<()>> {
+        let wake = self.wake_handle();
 
-            // Handle task completion
-            Self::on_task_finished_static(
-                queue,
-                mailbox,
-                active_units,
-                wake_condvar,
-                wake_state,
-                async_wake_enabled,
-                limits,
-                audit,
-                spawner,
-                executor,
-                task_id,
-                task_cost,
-                mailbox_key,
-                result,
-            )
-            .await;
-        });
+        let handle = std::thread::Builder::new()
+            .name("pl-wake".to_string())
+            .spawn(move || loop {
+                // Wait for capacity notification
+                let mut state = wake.wake_state.lock();
+                while !state.capacity_available && !state.shutdown {
+                    wake.wake_condvar.wait(&mut state);
+                }
+                if state.shutdown {
+                    tracing::info!("sync wake worker shutting down");
+                    break;
+                }
+                state.capacity_available = false;
+                drop(state);
+
+                wake.start_queued();
+            })?;
+
+        self.async_wake_enabled.store(false, Ordering::Release);
+        // Tasks queued before the switch would otherwise wait for a completion
+        self.request_wake();
+        Ok(handle)
     }
 
-    /// Static helper for task completion handling (callable from spawned task).
-    #[allow(clippy::too_many_arguments)]
-    fn on_task_finished_static(
-        queue: Arc<Mutex<Q>>,
-        mailbox: Arc<Mutex<M>>,
-        active_units: Arc<AtomicU32>,
-        wake_condvar: Arc<Condvar>,
-        wake_state: Arc<Mutex<WakeState>>,
-        async_wake_enabled: Arc<AtomicBool>,
-        limits: PoolLimits,
-        audit: Option<Arc<Mutex<Box<dyn AuditSink>>>>,
-        spawner: S,
-        executor: E,
-        task_id: TaskId,
-        task_cost: u32,
-        mailbox_key: Option<MailboxKey>,
-        result: T,
-    ) -> std::pin::Pin<Box<dyn Future<Output = ()> + Send + 'static>> {
-        Box::pin(async move {
-            // Release capacity atomically (lock-free)
-            active_units.fetch_sub(task_cost, Ordering::Release);
-            tracing::debug!(
-                "released {} units, active: {}",
-                task_cost,
-                active_units.load(Ordering::Acquire)
-            );
+    /// Current `max_units`, as set at construction or by
+    /// [`set_max_units`](Self::set_max_units).
+    pub fn max_units(&self) -> u32 {
+        self.limits.max_units()
+    }
+
+    /// Change the pool's `max_units` while it runs, e.g. after unloading a
+    /// model frees VRAM.
+    ///
+    /// Lowering it leaves running tasks alone; new and queued tasks wait
+    /// until usage drops under the new limit, and submissions larger than it
+    /// are rejected. Queued or parked tasks larger than it could never
+    /// start, so they are dropped (`TaskStatus::Dropped`) and dead-lettered,
+    /// along with their dependents, instead of holding back the tasks behind
+    /// them. Raising it starts the queued tasks that now fit right away. The
+    /// high-priority reserve follows the new limit; kind floors keep the
+    /// units they were given from the original one.
+    pub fn set_max_units(&self, new_max: u32) {
+        let old_max = self.limits.max_units.swap(new_max, Ordering::AcqRel);
+        tracing::info!("max_units changed from {} to {}", old_max, new_max);
+        if new_max > old_max {
+            self.request_wake();
+        } else if new_max < old_max {
+            self.drop_oversized_queued();
+        }
+    }
 
-            // Signal capacity available via Condvar (fast, non-blocking)
-            {
-                let mut state = wake_state.lock();
-                state.capacity_available = true;
+    /// Drop queued tasks that can't start even in an idle pool under the
+    /// current limits, keeping the rest in order.
+    fn drop_oversized_queued(&self) {
+        let limits = self.limits.current();
+        let mut queue = self.queue.lock();
+        let queued = match queue.drain() {
+            Ok(queued) => queued,
+            Err(e) => {
+                tracing::error!("failed to drain queue after lowering max_units: {}", e);
+                return;
             }
-            wake_condvar.notify_one();
-
-            // Deliver to mailbox if key present (separate mutex from queue)
-            if let Some(ref key) = mailbox_key {
-                let mut mailbox_guard = mailbox.lock();
-                if let Err(e) =
-                    mailbox_guard.deliver(key, TaskStatus::Completed, Some(result))
-                {
-                    tracing::error!("failed to deliver to mailbox: {}", e);
+        };
+        let too_large = |task: &ScheduledTask<P>| {
+            task.meta.cost.units > limits.unit_limit(task.meta.priority)
+        };
+        let mut oversized = Vec::new();
+        let mut lost = Vec::new();
+        for task in queued {
+            if too_large(&task) {
+                oversized.push(task);
+                continue;
+            }
+            let meta = task.meta.clone();
+            match queue.enqueue(task) {
+                Ok(()) => self.wake_gate.note_queued(meta.cost.units),
+                Err(e) => {
+                    tracing::error!("failed to re-enqueue task {}: {}", meta.id, e);
+                    self.status.remove(meta.id);
+                    self.record_dead_letter(&meta, REASON_QUEUE_FULL);
+                    lost.push(meta.id);
                 }
             }
+        }
+        drop(queue);
+        // Tasks parked behind their dependencies could never start either
+        while let Some(task) = self.dependencies.remove_parked(too_large) {
+            oversized.push(task);
+        }
+        let wake = self.wake_handle();
+        for task in oversized {
+            tracing::warn!("task {} no longer fits max_units, dropped", task.meta.id);
+            wake.drop_task(&task.meta, REASON_EXCEEDS_MAX_UNITS);
+            wake.settle_dependents(task.meta.id, false);
+        }
+        for id in lost {
+            wake.settle_dependents(id, false);
+        }
+    }
 
-            // Record audit (sync mutex)
-            if let Some(audit_sink) = audit.as_ref() {
-                let mut sink = audit_sink.lock();
-                let tenant = mailbox_key
-                    .as_ref()
-                    .map(|m| m.tenant.clone())
-                    .unwrap_or_else(|| "unknown".into());
-                sink.record(crate::core::build_audit_event(
-                    format!("{}-complete-{}", task_id, crate::util::clock::now_ms()),
-                    task_id.to_string(),
-                    "pool",
-                    tenant,
-                    "complete".to_string(),
-                    None,
-                ));
-            }
+    /// Reserve `units` for work done outside the pool, such as loading a
+    /// model, without submitting a task.
+    ///
+    /// The units count against the full `max_units` (the high-priority reserve
+    /// doesn't apply) and, with kind floors configured, as
+    /// `ResourceKind::Mixed`. They stay taken until the returned guard is
+    /// dropped, which releases them and wakes queued tasks like a task
+    /// completion. Returns `None` if the units don't fit right now, or if
+    /// `units` is zero or more than `max_units`.
+    pub fn reserve(&self, units: u32) -> Option<CapacityGuard> {
+        if units == 0 || units > self.limits.max_units() {
+            tracing::warn!("reservation of {} units rejected", units);
+            return None;
+        }
+        let cost = ResourceCost {
+            kind: ResourceKind::Mixed,
+            units,
+        };
+        let kinds = self.kinds.as_deref();
+        if !reserve_capacity(&self.active_units, kinds, self.limits.max_units(), cost) {
+            return None;
+        }
+        tracing::debug!("reserved {} units outside any task", units);
 
-            // Try to wake next task using async spawned task (default mode)
-            if async_wake_enabled.load(Ordering::Acquire) {
-                let spawner_clone = spawner.clone();
-                spawner.spawn(Self::try_wake_next_static(
-                    queue,
-                    mailbox,
-                    active_units,
-                    wake_condvar,
-                    wake_state,
-                    async_wake_enabled,
-                    limits,
-                    audit,
-                    spawner_clone,
-                    executor,
-                ));
-            }
-            // If async_wake_enabled is false, a dedicated sync wake worker
-            // is expected to be waiting on the condvar
+        let wake = self.wake_handle();
+        let release = move || {
+            release_capacity(&wake.active_units, wake.kinds.as_deref(), cost);
+            tracing::debug!("released {} reserved units", units);
+
+            // Wake the next task the same way a completion does
+            wake.wake();
+        };
+        Some(CapacityGuard {
+            units,
+            release: Some(Box::new(release)),
         })
     }
 
-    /// Try to wake and start the next queued task if capacity available.
-    #[allow(clippy::too_many_arguments)]
-    fn try_wake_next_static(
-        queue: Arc<Mutex<Q>>,
-        mailbox: Arc<Mutex<M>>,
-        active_units: Arc<AtomicU32>,
-        wake_condvar: Arc<Condvar>,
-        wake_state: Arc<Mutex<WakeState>>,
-        async_wake_enabled: Arc<AtomicBool>,
-        limits: PoolLimits,
-        audit: Option<Arc<Mutex<Box<dyn AuditSink>>>>,
-        spawner: S,
-        executor: E,
-    ) -> std::pin::Pin<Box<dyn Future<Output = ()> + Send + 'static>> {
-        Box::pin(async move {
-            loop {
-                // Try to dequeue a task (quick sync mutex on queue only)
-                let task_opt = {
-                    let mut queue_guard = queue.lock();
-                    match queue_guard.dequeue() {
-                        Ok(task) => task,
-                        Err(e) => {
-                            tracing::error!("failed to dequeue: {}", e);
-                            break;
-                        }
-                    }
-                };
-
-                let task = match task_opt {
-                    Some(t) => t,
-                    None => {
-                        tracing::debug!("queue empty, no tasks to wake");
-                        break;
-                    }
-                };
+    /// Prune expired tasks from the queue based on current time.
+    pub async fn prune_expired(&self, now_ms: u128) -> Result<usize, SchedulerError> {
+        self.prune_expired_collect(now_ms).map(|expired| expired.len())
+    }
 
-                // Check if we can start this task (lock-free)
-                let current = active_units.load(Ordering::Acquire);
-                let can_start = current + task.meta.cost.units <= limits.max_units;
+    /// Prune expired tasks from the queue like
+    /// [`prune_expired`](Self::prune_expired), returning them instead of a
+    /// count.
+    ///
+    /// Tasks parked behind their dependencies are pruned too. Each pruned task
+    /// is marked `Expired`, gets an `Expired` entry in its mailbox and an
+    /// `"expire"` audit event, and is handed to the dead-letter sink, and its
+    /// dependents are dropped; the returned tasks let the caller notify
+    /// clients.
+    ///
+    /// # Errors
+    ///
+    /// Returns the queue backend's error if the expired tasks cannot be
+    /// removed; nothing is marked expired then.
+    pub fn prune_expired_collect(
+        &self,
+        now_ms: u128,
+    ) -> Result<Vec<ScheduledTask<P>>, SchedulerError> {
+        let mut expired = self.queue.lock().take_expired(now_ms)?;
+        let parked_expired = |task: &ScheduledTask<P>| task.meta.is_expired_at(now_ms);
+        while let Some(task) = self.dependencies.remove_parked(parked_expired) {
+            expired.push(task);
+        }
+        if expired.is_empty() {
+            return Ok(expired);
+        }
 
-                if !can_start {
-                    // Re-enqueue the task and stop (quick sync mutex on queue only)
-                    let mut queue_guard = queue.lock();
-                    if let Err(e) = queue_guard.enqueue(task) {
-                        tracing::error!("failed to re-enqueue task: {}", e);
-                    }
-                    tracing::debug!("insufficient capacity to wake next task");
-                    break;
-                }
+        self.status.expire_queued(now_ms);
+        for task in &expired {
+            deliver_skipped(&task.meta, TaskStatus::Expired, &self.mailbox);
+            self.record_audit(task, "expire");
+            self.record_dead_letter(&task.meta, REASON_DEADLINE_EXPIRED);
+            self.settle_dependents(task.meta.id, false);
+        }
+        tracing::warn!("pruned {} expired tasks", expired.len());
+        Ok(expired)
+    }
 
-                // Try to reserve capacity atomically using CAS
-                let mut current = active_units.load(Ordering::Acquire);
-                let reserved = loop {
-                    if current + task.meta.cost.units > limits.max_units {
-                        break false;
-                    }
-                    match active_units.compare_exchange_weak(
-                        current,
-                        current + task.meta.cost.units,
-                        Ordering::AcqRel,
-                        Ordering::Acquire,
-                    ) {
-                        Ok(_) => break true,
-                        Err(actual) => current = actual,
-                    }
-                };
+    /// Metadata of the queued task that will start next, without dequeuing it.
+    ///
+    /// Lets a caller inspect the next task's cost, e.g. to decide whether to
+    /// free resources, before the pool admits it.
+    ///
+    /// # Errors
+    ///
+    /// Returns the queue backend's error if it cannot be read.
+    pub fn peek_next(&self) -> Result<Option<TaskMetadata>, SchedulerError> {
+        self.queue.lock().peek()
+    }
 
-                if !reserved {
-                    // Failed to reserve, re-enqueue and stop
-                    let mut queue_guard = queue.lock();
-                    if let Err(e) = queue_guard.enqueue(task) {
-                        tracing::error!("failed to re-enqueue task: {}", e);
-                    }
-                    tracing::debug!("failed to reserve capacity for wake");
-                    break;
-                }
+    /// Atomically empty the queue, returning its tasks in dequeue order.
+    ///
+    /// Use with [`enqueue_all`](Self::enqueue_all) to re-admit queued tasks
+    /// after the pool's limits change. Drained tasks are no longer tracked
+    /// by [`status`](Self::status) until they are submitted again, and tasks
+    /// depending on them stay parked until then. Parked tasks are not
+    /// drained.
+    ///
+    /// # Errors
+    ///
+    /// Returns the queue backend's error if it cannot be drained.
+    pub fn drain_queue(&self) -> Result<Vec<ScheduledTask<P>>, SchedulerError> {
+        let tasks = self.queue.lock().drain()?;
+        for task in &tasks {
+            self.status.remove(task.meta.id);
+            self.dependencies.withdraw(task.meta.id);
+        }
+        tracing::info!("drained {} queued tasks", tasks.len());
+        Ok(tasks)
+    }
 
-                tracing::info!("woke and started task {}", task.meta.id);
-
-                // Record audit (sync mutex)
-                if let Some(audit_sink) = audit.as_ref() {
-                    let mut sink = audit_sink.lock();
-                    let tenant = task
-                        .meta
-                        .mailbox
-                        .as_ref()
-                        .map(|m| m.tenant.clone())
-                        .unwrap_or_else(|| "unknown".into());
-                    sink.record(crate::core::build_audit_event(
-                        format!("{}-wake-{}", task.meta.id, crate::util::clock::now_ms()),
-                        task.meta.id.to_string(),
-                        "pool",
-                        tenant,
-                        "wake".to_string(),
-                        None,
-                    ));
-                }
+    /// Re-submit tasks (e.g. from [`drain_queue`](Self::drain_queue)) under
+    /// the pool's current limits, in order.
+    ///
+    /// Each task goes through the same admission as
+    /// [`submit_blocking`](Self::submit_blocking): it starts if capacity
+    /// allows and is queued otherwise. Returns one outcome per task.
+    pub fn enqueue_all(
+        &self,
+        tasks: Vec<ScheduledTask<P>>,
+        now_ms: u128,
+    ) -> Vec<Result<TaskStatus, SchedulerError>> {
+        tasks
+            .into_iter()
+            .map(|task| self.submit_blocking(task, now_ms))
+            .collect()
+    }
 
-                // Spawn the task
-                let executor_clone = executor.clone();
-                let queue_clone = Arc::clone(&queue);
-                let mailbox_clone = Arc::clone(&mailbox);
-                let active_units_clone = Arc::clone(&active_units);
-                let wake_condvar_clone = Arc::clone(&wake_condvar);
-                let wake_state_clone = Arc::clone(&wake_state);
-                let async_wake_enabled_clone = Arc::clone(&async_wake_enabled);
-                let limits_clone = limits.clone();
-                let audit_clone = audit.clone();
-                let spawner_clone = spawner.clone();
-                let task_id = task.meta.id;
-                let task_cost = task.meta.cost.units;
-                let mailbox_key = task.meta.mailbox.clone();
-                let meta = task.meta.clone();
-                let payload = task.payload;
-
-                spawner.spawn(async move {
-                    tracing::debug!("executing woken task {}", task_id);
-                    let result = executor_clone.execute(payload, meta).await;
-                    tracing::info!("woken task {} completed", task_id);
-
-                    Self::on_task_finished_static(
-                        queue_clone,
-                        mailbox_clone,
-                        active_units_clone,
-                        wake_condvar_clone,
-                        wake_state_clone,
-                        async_wake_enabled_clone,
-                        limits_clone,
-                        audit_clone,
-                        spawner_clone,
-                        executor_clone,
-                        task_id,
-                        task_cost,
-                        mailbox_key,
-                        result,
-                    )
-                    .await;
-                });
+    /// Copy the queued and parked tasks and current active-unit count.
+    ///
+    /// The queue is left as it was: its tasks are drained and put back under
+    /// one lock, so no task starts or is added while the copy is taken.
+    ///
+    /// # Errors
+    ///
+    /// Returns the queue backend's error if it cannot be drained, or the first
+    /// error putting a task back (those tasks are dead-lettered).
+    pub fn snapshot(&self) -> Result<PoolSnapshotState<P>, SchedulerError>
+    where
+        P: Clone,
+    {
+        let mut queue = self.queue.lock();
+        let mut queued = queue.drain()?;
+        let mut first_error = None;
+        let mut lost = Vec::new();
+        for task in &queued {
+            if let Err(e) = queue.enqueue(task.clone()) {
+                tracing::error!("failed to re-enqueue task {} after snapshot: {}", task.meta.id, e);
+                self.status.remove(task.meta.id);
+                self.record_dead_letter(&task.meta, REASON_QUEUE_FULL);
+                lost.push(task.meta.id);
+                first_error.get_or_insert(e);
             }
+        }
+        drop(queue);
+        for id in lost {
+            self.settle_dependents(id, false);
+        }
+        if let Some(e) = first_error {
+            return Err(e);
+        }
+        queued.extend(self.dependencies.parked(ScheduledTask::clone));
+        Ok(PoolSnapshotState {
+            queued,
+            active_units: self.active_units.load(Ordering::Acquire),
+            taken_at_ms: crate::util::clock::now_ms(),
         })
     }
 
-    /// Prune expired tasks from the queue based on current time.
-    pub async fn prune_expired(&self, now_ms: u128) -> Result<usize, SchedulerError> {
-        let removed = {
+    /// Put the queued tasks of a snapshot back into this pool's queue, in
+    /// order, and start as many as capacity allows.
+    ///
+    /// Tasks bypass admission so they keep their place in line, except that
+    /// a task depending on one restored before it is parked again. Tasks
+    /// whose deadline has passed, or that no longer fit in the queue's depth
+    /// or `max_queued_units`, are dead-lettered instead, and their dependents
+    /// dropped. Returns the number of tasks restored.
+    pub fn restore(&self, snapshot: PoolSnapshotState<P>, now_ms: u128) -> usize {
+        let mut restored = 0;
+        for task in snapshot.queued {
+            let meta = task.meta.clone();
+            if meta.is_expired_at(now_ms) {
+                tracing::warn!("task {} expired before restore", meta.id);
+                self.record_dead_letter(&meta, REASON_DEADLINE_EXPIRED);
+                self.settle_dependents(meta.id, false);
+                continue;
+            }
+            self.status.set(meta.id, TaskStatus::Queued, meta.deadline_ms);
+            let task = match self.dependencies.submit(meta.id, &meta.depends_on, task, false) {
+                Ok(Some(task)) => task,
+                Ok(None) => {
+                    restored += 1;
+                    continue;
+                }
+                Err((_, task)) => {
+                    tracing::warn!("task {} not restored: a dependency failed", meta.id);
+                    self.status.remove(meta.id);
+                    self.record_dead_letter(&task.meta, REASON_DEPENDENCY_FAILED);
+                    continue;
+                }
+            };
             let mut queue = self.queue.lock();
-            queue.prune_expired(now_ms)?
-        };
+            let enqueued = check_queue_room(&*queue, &self.limits.limits, &meta)
+                .and_then(|()| queue.enqueue(task));
+            if enqueued.is_ok() {
+                self.wake_gate.note_queued(meta.cost.units);
+            }
+            drop(queue);
+            if let Err(e) = enqueued {
+                tracing::warn!("task {} not restored: {}", meta.id, e);
+                self.status.remove(meta.id);
+                self.record_dead_letter(&meta, REASON_QUEUE_FULL);
+                self.settle_dependents(meta.id, false);
+                continue;
+            }
+            restored += 1;
+        }
+        tracing::info!("restored {} queued tasks", restored);
+        if restored > 0 {
+            self.request_wake();
+        }
+        restored
+    }
 
-        if removed > 0 {
-            // Audit generic expiration without specific task IDs (not available after prune).
-            if let Some(audit_sink) = &self.audit {
-                let mut sink = audit_sink.lock();
-                sink.record(crate::core::build_audit_event(
-                    format!("expire-batch-{now_ms}"),
-                    "batch",
-                    "unknown_pool",
-                    "unknown_tenant",
-                    "expire",
-                    None,
-                ));
+    /// Start queued tasks that fit, through the pool's configured wake path.
+    fn request_wake(&self) {
+        self.wake_handle().wake();
+    }
+
+    /// Whether `meta` may start ahead of the queue: nothing is queued, the
+    /// queue's head doesn't have higher priority, or kind floors keep the head
+    /// from competing for the same units.
+    ///
+    /// Under `prefer_queued_on_contention` only a lower-priority head lets
+    /// it through, and never while a wake pass is running.
+    fn may_bypass_queue(&self, meta: &TaskMetadata) -> bool {
+        let strict = self.limits.limits.prefer_queued_on_contention;
+        if strict && self.wake_gate.in_pass() {
+            return false;
+        }
+        let queue = self.queue.lock();
+        if queue.is_empty() {
+            return true;
+        }
+        if strict {
+            return queue
+                .peek()
+                .is_ok_and(|head| head.is_none_or(|head| meta.priority > head.priority));
+        }
+        // Backends that can't peek keep admitting tasks that fit
+        match queue.peek() {
+            Ok(Some(head)) => {
+                meta.priority >= head.priority
+                    || (self.kinds.is_some() && head.cost.kind != meta.cost.kind)
             }
-            tracing::warn!("pruned {} expired tasks", removed);
+            Ok(None) | Err(_) => true,
         }
-        Ok(removed)
     }
 
     /// Record an audit event (sync operation with parking_lot mutex).
     fn record_audit(&self, task: &ScheduledTask<P>, action: &str) {
         if let Some(audit_sink) = &self.audit {
+            let queue_len = self.queue.lock().len();
             let mut sink = audit_sink.lock();
             let tenant = task
                 .meta
@@ -584,72 +1382,120 @@ where
                 "pool", // pool name not tracked in metadata; set by caller if desired
                 tenant,
                 action.to_string(),
-                None,
+                Some(audit_payload(task.meta.cost.units, task.meta.priority, queue_len)),
             ));
         }
     }
 }
 
-/// Synchronous wake worker that can be run in a dedicated thread.
-///
-/// This worker waits on the `Condvar` for capacity release notifications and
-/// processes queued tasks. Use this instead of async wake tasks for reduced
-/// overhead in high-throughput scenarios.
-///
-/// # Example
-///
-/// ```ignore
-/// use std::thread;
-///
-/// // Disable async wake and start sync worker
-/// pool.async_wake_enabled.store(false, Ordering::Release);
-///
-/// let queue = Arc::clone(&pool.queue);
-/// let mailbox = Arc::clone(&pool.mailbox);
-/// let active_units = Arc::clone(&pool.active_units);
-/// let wake_condvar = Arc::clone(&pool.wake_condvar);
-/// let wake_state = Arc::clone(&pool.wake_state);
-/// let limits = pool.limits.clone();
-///
-/// thread::spawn(move || {
-///     sync_wake_worker(queue, mailbox, active_units, wake_condvar, wake_state, limits);
-/// });
-/// ```
-#[allow(dead_code)]
-pub fn sync_wake_worker_loop<P, Q>(
+/// The pool state a wake pass needs, cloned out of a `ResourcePool` so task
+/// completions and released reservations can start queued work.
+struct WakeHandle<P, T, Q, M, E, S> {
     queue: Arc<Mutex<Q>>,
+    mailbox: Arc<Mutex<M>>,
     active_units: Arc<AtomicU32>,
+    active_tasks: Arc<AtomicUsize>,
     wake_condvar: Arc<Condvar>,
     wake_state: Arc<Mutex<WakeState>>,
-    limits: PoolLimits,
-) where
-    P: TaskPayload,
-    Q: TaskQueue<P>,
+    async_wake_enabled: Arc<AtomicBool>,
+    wake_gate: Arc<WakeGate>,
+    status: Arc<StatusMap>,
+    dependencies: Arc<DependencyTracker<ScheduledTask<P>>>,
+    kinds: Option<Arc<KindLedger>>,
+    limits: Arc<LiveLimits>,
+    audit: Option<Arc<Mutex<Box<dyn AuditSink>>>>,
+    dead_letter: Option<Arc<Mutex<Box<dyn DeadLetterSink>>>>,
+    spawner: S,
+    executor: E,
+    _marker: PhantomData<fn(P) -> T>,
+}
+
+impl<P, T, Q, M, E, S> Clone for WakeHandle<P, T, Q, M, E, S>
+where
+    E: Clone,
+    S: Clone,
 {
-    loop {
-        // Wait for capacity notification
-        let mut state = wake_state.lock();
-        while !state.capacity_available && !state.shutdown {
-            wake_condvar.wait(&mut state);
+    fn clone(&self) -> Self {
+        Self {
+            queue: Arc::clone(&self.queue),
+            mailbox: Arc::clone(&self.mailbox),
+            active_units: Arc::clone(&self.active_units),
+            active_tasks: Arc::clone(&self.active_tasks),
+            wake_condvar: Arc::clone(&self.wake_condvar),
+            wake_state: Arc::clone(&self.wake_state),
+            async_wake_enabled: Arc::clone(&self.async_wake_enabled),
+            wake_gate: Arc::clone(&self.wake_gate),
+            status: Arc::clone(&self.status),
+            dependencies: Arc::clone(&self.dependencies),
+            kinds: self.kinds.clone(),
+            limits: Arc::clone(&self.limits),
+            audit: self.audit.clone(),
+            dead_letter: self.dead_letter.clone(),
+            spawner: self.spawner.clone(),
+            executor: self.executor.clone(),
+            _marker: PhantomData,
         }
+    }
+}
 
-        if state.shutdown {
-            tracing::info!("sync wake worker shutting down");
-            break;
+impl<P, T, Q, M, E, S> WakeHandle<P, T, Q, M, E, S>
+where
+    P: TaskPayload,
+    T: Send + 'static,
+    Q: TaskQueue<P> + Send + 'static,
+    M: Mailbox<T> + Send + 'static,
+    E: TaskExecutor<P, T> + Clone,
+    S: Spawn + Clone + Send + 'static,
+{
+    /// Start queued tasks that fit through exactly one mechanism: an async
+    /// wake pass (default mode, skipped if one is already running or if the
+    /// free units can't start any queued task), or the dedicated sync wake
+    /// worker waiting on the condvar.
+    fn wake(&self) {
+        if self.async_wake_enabled.load(Ordering::Acquire) {
+            let active = self.active_units.load(Ordering::Acquire);
+            let free_units = self.limits.max_units().saturating_sub(active);
+            if self.wake_gate.worth_waking(free_units) && self.wake_gate.request() {
+                self.spawner.spawn(self.clone().run_passes());
+            }
+        } else {
+            self.wake_state.lock().capacity_available = true;
+            self.wake_condvar.notify_one();
         }
+    }
 
-        // Reset the flag
-        state.capacity_available = false;
-        drop(state);
+    /// Run wake passes until no more wakes arrive.
+    fn run_passes(self) -> std::pin::Pin<Box<dyn Future<Output = ()> + Send + 'static>> {
+        Box::pin(async move {
+            // The caller owns the wake gate; keep passing while wakes arrive
+            loop {
+                self.wake_gate.begin_pass();
+                self.start_queued();
+                if !self.wake_gate.finish_pass() {
+                    break;
+                }
+            }
+        })
+    }
 
-        // Process queued tasks
+    /// Start queued tasks, in queue order, until the queue is empty or its
+    /// head doesn't fit. Expired and over-waited tasks are skipped on the way.
+    ///
+    /// Shared by the async wake pass and the sync wake worker.
+    fn start_queued(&self) {
         loop {
+            let limits = &self.limits.current();
+            // Try to dequeue a task (quick sync mutex on queue only)
             let task_opt = {
-                let mut queue_guard = queue.lock();
+                let mut queue_guard = self.queue.lock();
                 match queue_guard.dequeue() {
+                    Ok(None) => {
+                        self.wake_gate.note_empty();
+                        None
+                    }
                     Ok(task) => task,
                     Err(e) => {
-                        tracing::error!("sync wake worker failed to dequeue: {}", e);
+                        tracing::error!("failed to dequeue: {}", e);
                         break;
                     }
                 }
@@ -658,51 +1504,406 @@ pub fn sync_wake_worker_loop<P, Q>(
             let task = match task_opt {
                 Some(t) => t,
                 None => {
-                    tracing::debug!("sync wake worker: queue empty");
+                    tracing::debug!("queue empty, no tasks to wake");
                     break;
                 }
             };
 
-            // Try to reserve capacity
-            let current = active_units.load(Ordering::Acquire);
-            if current + task.meta.cost.units > limits.max_units {
-                // Re-enqueue and wait for more capacity
-                let mut queue_guard = queue.lock();
-                if let Err(e) = queue_guard.enqueue(task) {
-                    tracing::error!("sync wake worker failed to re-enqueue: {}", e);
+            // Skip tasks whose deadline passed while they were parked
+            let now = crate::util::clock::now_ms();
+            if task.meta.is_expired_at(now) {
+                self.status.set(task.meta.id, TaskStatus::Expired, None);
+                deliver_skipped(&task.meta, TaskStatus::Expired, &self.mailbox);
+                self.record_dead_letter(&task.meta, REASON_DEADLINE_EXPIRED);
+                self.settle_dependents(task.meta.id, false);
+                continue;
+            }
+
+            // Drop tasks that waited longer than the pool allows
+            if queue_wait_exceeded(&self.status, limits, task.meta.id, now) {
+                self.drop_task(&task.meta, REASON_QUEUE_WAIT_EXCEEDED);
+                self.settle_dependents(task.meta.id, false);
+                continue;
+            }
+
+            // Drop tasks that max_units was lowered under while the pass
+            // held them, which would otherwise block the queue for good
+            if task.meta.cost.units > limits.unit_limit(task.meta.priority) {
+                self.drop_task(&task.meta, REASON_EXCEEDS_MAX_UNITS);
+                self.settle_dependents(task.meta.id, false);
+                continue;
+            }
+
+            // Check if we can start this task (lock-free)
+            let current = self.active_units.load(Ordering::Acquire);
+            let can_start = current + task.meta.cost.units <= limits.unit_limit(task.meta.priority)
+                && has_task_slot(&self.active_tasks, limits);
+
+            if !can_start {
+                // Re-enqueue the task and stop (quick sync mutex on queue only)
+                let units = task.meta.cost.units;
+                let mut queue_guard = self.queue.lock();
+                match queue_guard.enqueue(task) {
+                    Ok(()) => self.wake_gate.note_queued(units),
+                    Err(e) => tracing::error!("failed to re-enqueue task: {}", e),
                 }
+                tracing::debug!("insufficient capacity to wake next task");
                 break;
             }
 
-            // Reserve capacity with CAS
-            let mut current = active_units.load(Ordering::Acquire);
-            let reserved = loop {
-                if current + task.meta.cost.units > limits.max_units {
-                    break false;
-                }
-                match active_units.compare_exchange_weak(
-                    current,
-                    current + task.meta.cost.units,
-                    Ordering::AcqRel,
-                    Ordering::Acquire,
-                ) {
-                    Ok(_) => break true,
-                    Err(actual) => current = actual,
-                }
-            };
+            // Try to reserve capacity atomically
+            let reserved = reserve_start(
+                &self.active_units,
+                &self.active_tasks,
+                self.kinds.as_deref(),
+                limits,
+                &task.meta,
+            );
 
             if !reserved {
-                let mut queue_guard = queue.lock();
-                if let Err(e) = queue_guard.enqueue(task) {
-                    tracing::error!("sync wake worker failed to re-enqueue: {}", e);
+                // Failed to reserve, re-enqueue and stop
+                let units = task.meta.cost.units;
+                let mut queue_guard = self.queue.lock();
+                match queue_guard.enqueue(task) {
+                    Ok(()) => self.wake_gate.note_queued(units),
+                    Err(e) => tracing::error!("failed to re-enqueue task: {}", e),
                 }
+                tracing::debug!("failed to reserve capacity for wake");
                 break;
             }
 
-            tracing::info!("sync wake worker: ready to start task {}", task.meta.id);
-            // Note: Actual task execution would be handled by passing to executor
-            // This worker just reserves capacity and prepares tasks
-            // The caller would need to handle the actual execution
+            tracing::info!("woke and started task {}", task.meta.id);
+            self.status.set(task.meta.id, TaskStatus::Running, None);
+
+            // Record audit (sync mutex)
+            if let Some(audit_sink) = &self.audit {
+                let queue_len = self.queue.lock().len();
+                let mut sink = audit_sink.lock();
+                let tenant = task
+                    .meta
+                    .mailbox
+                    .as_ref()
+                    .map(|m| m.tenant.clone())
+                    .unwrap_or_else(|| "unknown".into());
+                sink.record(crate::core::build_audit_event(
+                    format!("{}-wake-{}", task.meta.id, crate::util::clock::now_ms()),
+                    task.meta.id.to_string(),
+                    "pool",
+                    tenant,
+                    "wake".to_string(),
+                    Some(audit_payload(
+                        task.meta.cost.units,
+                        task.meta.priority,
+                        queue_len,
+                    )),
+                ));
+            }
+
+            self.spawn_task(task);
         }
     }
+
+    /// Hand a task skipped by a wake pass to the dead-letter sink.
+    fn record_dead_letter(&self, meta: &TaskMetadata, reason: &str) {
+        record_dead_letter(self.dead_letter.as_deref(), meta, reason);
+    }
+
+    /// Drop a task that will never run: mark it `Dropped` with `reason`,
+    /// notify its mailbox and dead-letter it.
+    fn drop_task(&self, meta: &TaskMetadata, reason: &str) {
+        let dropped = TaskStatus::Dropped(reason.into());
+        self.status.set(meta.id, dropped.clone(), None);
+        deliver_skipped(meta, dropped, &self.mailbox);
+        self.record_dead_letter(meta, reason);
+    }
+
+    /// Settle the dependents of a task that finished or left the pool: queue
+    /// those whose dependencies have all succeeded and drop those whose
+    /// dependency failed. A released task that cannot be queued is dropped
+    /// too, and its own dependents with it.
+    fn settle_dependents(&self, id: TaskId, succeeded: bool) {
+        let limits = self.limits.current();
+        let mut finished = vec![(id, succeeded)];
+        let mut queued = false;
+        while let Some((id, succeeded)) = finished.pop() {
+            let released = self.dependencies.finish(id, succeeded);
+            for task in released.dropped {
+                self.drop_task(&task.meta, REASON_DEPENDENCY_FAILED);
+            }
+            for task in released.ready {
+                // Time spent parked doesn't count toward max_queue_wait
+                let meta = task.meta.clone();
+                self.status.remove(meta.id);
+                self.status.set(meta.id, TaskStatus::Queued, meta.deadline_ms);
+                let mut queue = self.queue.lock();
+                let enqueued =
+                    check_queue_room(&*queue, &limits, &meta).and_then(|()| queue.enqueue(task));
+                if enqueued.is_ok() {
+                    self.wake_gate.note_queued(meta.cost.units);
+                }
+                drop(queue);
+                match enqueued {
+                    Ok(()) => queued = true,
+                    Err(e) => {
+                        tracing::warn!("released task {} dropped: {}", meta.id, e);
+                        self.drop_task(&meta, REASON_QUEUE_FULL);
+                        finished.push((meta.id, false));
+                    }
+                }
+            }
+        }
+        if queued {
+            self.wake();
+        }
+    }
+
+    /// Spawn a task whose capacity is already reserved; its completion
+    /// releases the capacity and wakes the next queued task.
+    fn spawn_task(&self, task: ScheduledTask<P>) {
+        let wake = self.clone();
+        let task_id = task.meta.id;
+        let cost = task.meta.cost;
+        let priority = task.meta.priority;
+        let mailbox_key = task.meta.mailbox.clone();
+        let meta = task.meta.clone();
+        let payload = task.payload;
+
+        self.spawner.spawn(async move {
+            tracing::debug!("executing task {}", task_id);
+
+            // Execute the task
+            let result = wake.executor.try_execute(payload, meta).await;
+
+            tracing::info!("task {} finished", task_id);
+
+            // Handle task completion
+            wake.on_task_finished(task_id, cost, priority, mailbox_key.as_ref(), result);
+        });
+    }
+
+    /// Release a finished task's capacity, record its outcome, and wake the
+    /// next queued task.
+    fn on_task_finished(
+        &self,
+        task_id: TaskId,
+        cost: ResourceCost,
+        priority: Priority,
+        mailbox_key: Option<&MailboxKey>,
+        result: Result<T, ExecError>,
+    ) {
+        // Release capacity atomically (lock-free unless kind floors are set)
+        release_capacity(&self.active_units, self.kinds.as_deref(), cost);
+        self.active_tasks.fetch_sub(1, Ordering::Release);
+        tracing::debug!(
+            "released {} units, active: {}",
+            cost.units,
+            self.active_units.load(Ordering::Acquire)
+        );
+
+        // An executor error marks the task failed, which also bumps the
+        // status map's failure count
+        let (final_status, result, action) = match result {
+            Ok(result) => (TaskStatus::Completed, Some(result), "complete"),
+            Err(err) => {
+                tracing::warn!("task {} failed: {}", task_id, err);
+                (TaskStatus::Failed(err.reason().to_string()), None, "fail")
+            }
+        };
+        let succeeded = matches!(final_status, TaskStatus::Completed);
+        self.status.set(task_id, final_status.clone(), None);
+
+        // Deliver to mailbox if key present (separate mutex from queue)
+        if let Some(key) = mailbox_key {
+            let mut mailbox_guard = self.mailbox.lock();
+            if let Err(e) = mailbox_guard.deliver(key, final_status, result) {
+                tracing::error!("failed to deliver to mailbox: {}", e);
+            }
+        }
+
+        // Record audit (sync mutex)
+        if let Some(audit_sink) = &self.audit {
+            let queue_len = self.queue.lock().len();
+            let mut sink = audit_sink.lock();
+            let tenant = mailbox_key.map_or_else(|| "unknown".into(), |m| m.tenant.clone());
+            sink.record(crate::core::build_audit_event(
+                format!("{}-{}-{}", task_id, action, crate::util::clock::now_ms()),
+                task_id.to_string(),
+                "pool",
+                tenant,
+                action.to_string(),
+                Some(audit_payload(cost.units, priority, queue_len)),
+            ));
+        }
+
+        // Queue (or drop) the tasks waiting on this one, then wake the next
+        self.settle_dependents(task_id, succeeded);
+        self.wake();
+    }
+}
+
+impl<P, T, Q, M, E, S> SnapshotSource for ResourcePool<P, T, Q, M, E, S>
+where
+    P: TaskPayload,
+    T: Send + 'static,
+    Q: TaskQueue<P>,
+{
+    fn snapshot_metrics(&self, name: &str) -> PoolSnapshotMetrics {
+        let (completed_tasks, failed_tasks) = self.status.outcome_counts();
+        let queue = self.queue.lock();
+        PoolSnapshotMetrics {
+            name: name.to_string(),
+            kind: PoolKind::Resource,
+            used_units: self.active_units.load(Ordering::Acquire),
+            total_units: self.limits.max_units(),
+            active_tasks: self.status.running(),
+            queue_depth: u64::try_from(queue.len()).unwrap_or(u64::MAX),
+            queue_oldest_age_ms: queue.oldest_created_at_ms().map(crate::util::clock::age_ms),
+            completed_tasks,
+            failed_tasks,
+        }
+    }
+}
+
+/// Reserve `cost` against the global budget and, when configured, the per-kind
+/// floors. Without floors this is a lock-free CAS loop.
+fn reserve_capacity(
+    active_units: &AtomicU32,
+    kinds: Option<&KindLedger>,
+    max_units: u32,
+    cost: ResourceCost,
+) -> bool {
+    if let Some(kinds) = kinds {
+        return kinds.try_reserve(active_units, max_units, cost);
+    }
+    let mut current = active_units.load(Ordering::Acquire);
+    loop {
+        let Some(next) = current.checked_add(cost.units).filter(|&next| next <= max_units) else {
+            return false;
+        };
+        match active_units.compare_exchange_weak(
+            current,
+            next,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            Ok(_) => return true,
+            Err(actual) => current = actual,
+        }
+    }
+}
+
+/// Whether fewer tasks are running than `limits.max_concurrent_tasks`.
+fn has_task_slot(active_tasks: &AtomicUsize, limits: &PoolLimits) -> bool {
+    limits
+        .max_concurrent_tasks
+        .is_none_or(|max| active_tasks.load(Ordering::Acquire) < max)
+}
+
+/// Reserve a task slot and the units of a task about to start, taking
+/// neither unless both are available.
+fn reserve_start(
+    active_units: &AtomicU32,
+    active_tasks: &AtomicUsize,
+    kinds: Option<&KindLedger>,
+    limits: &PoolLimits,
+    meta: &TaskMetadata,
+) -> bool {
+    let slot = active_tasks.fetch_update(Ordering::AcqRel, Ordering::Acquire, |running| {
+        limits
+            .max_concurrent_tasks
+            .is_none_or(|max| running < max)
+            .then_some(running + 1)
+    });
+    if slot.is_err() {
+        return false;
+    }
+    let reserved = reserve_capacity(
+        active_units,
+        kinds,
+        limits.unit_limit(meta.priority),
+        meta.cost,
+    );
+    if !reserved {
+        active_tasks.fetch_sub(1, Ordering::Release);
+    }
+    reserved
+}
+
+/// Release capacity taken by [`reserve_capacity`].
+fn release_capacity(active_units: &AtomicU32, kinds: Option<&KindLedger>, cost: ResourceCost) {
+    match kinds {
+        Some(kinds) => kinds.release(active_units, cost),
+        None => {
+            active_units.fetch_sub(cost.units, Ordering::Release);
+        }
+    }
+}
+
+/// Whether a queued task has waited longer than `limits.max_queue_wait`.
+fn queue_wait_exceeded(status: &StatusMap, limits: &PoolLimits, id: TaskId, now_ms: u128) -> bool {
+    let Some(max_wait) = limits.max_queue_wait else {
+        return false;
+    };
+    status
+        .queued_at(id)
+        .is_some_and(|queued_at| now_ms.saturating_sub(queued_at) > max_wait.as_millis())
+}
+
+/// Whether `queue` has room for `meta` under `max_queue_depth` and
+/// `max_queued_units`. Run it under the same lock as the `enqueue` it guards,
+/// or concurrent submissions can pass it together and overshoot.
+fn check_queue_room<P, Q>(
+    queue: &Q,
+    limits: &PoolLimits,
+    meta: &TaskMetadata,
+) -> Result<(), SchedulerError>
+where
+    Q: TaskQueue<P>,
+{
+    if queue.len() >= limits.max_queue_depth {
+        return Err(SchedulerError::QueueFull("max queue depth reached".into()));
+    }
+    if let Some(max_units) = limits.max_queued_units {
+        if queue.queued_units() + u64::from(meta.cost.units) > max_units {
+            return Err(SchedulerError::QueueFull(format!(
+                "queued units would exceed max_queued_units ({max_units})"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Notify the task's mailbox that it left the queue without running, with
+/// `status` saying why (expired or dropped).
+fn deliver_skipped<T, M>(meta: &TaskMetadata, status: TaskStatus, mailbox: &Mutex<M>)
+where
+    M: Mailbox<T>,
+{
+    tracing::warn!("task {} skipped on wake: {:?}", meta.id, status);
+    if let Some(key) = &meta.mailbox {
+        let delivered = mailbox.lock().deliver(key, status, None);
+        if let Err(e) = delivered {
+            tracing::error!("failed to deliver skipped task to mailbox: {}", e);
+        }
+    }
+}
+
+/// Hand a dropped task to `sink`, if the pool has one.
+fn record_dead_letter(
+    sink: Option<&Mutex<Box<dyn DeadLetterSink>>>,
+    meta: &TaskMetadata,
+    reason: &str,
+) {
+    if let Some(sink) = sink {
+        sink.lock().record(meta.clone(), reason.to_string());
+    }
+}
+
+/// Structured audit context describing a task at the time of the event.
+fn audit_payload(cost: u32, priority: Priority, queue_len: usize) -> serde_json::Value {
+    serde_json::json!({
+        "cost": cost,
+        "priority": priority,
+        "queue_len": queue_len,
+    })
 }
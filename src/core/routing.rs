@@ -0,0 +1,167 @@
+//! Executor that dispatches each task to one of several registered executors.
+//!
+//! A pool runs a single executor type, but a multi-model server wants each
+//! task handled by the backend of its model. `RoutingExecutor` is that single
+//! executor: it reads a route key from the payload and forwards the task to
+//! the executor registered under the key.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::executor::{ExecutionOutcome, ExecutorContext, WorkerExecutor};
+use super::progress::ProgressReporter;
+use super::TaskMetadata;
+
+/// Shared handle to a registered executor.
+type SharedExecutor<P, R> = Arc<dyn WorkerExecutor<P, R>>;
+
+/// Classifies results on behalf of the sub-executors.
+type Classifier<R> = Arc<dyn Fn(&R) -> ExecutionOutcome + Send + Sync>;
+
+/// Routes each task to the executor registered for a key read from its payload.
+///
+/// Sub-executors are shared between the pool's workers (the pool clones the
+/// router, not the executors), so they need not be `Clone`; as with an
+/// `Arc<E>` executor, any mutable state inside them must be synchronized.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use prometheus_parking_lot::core::RoutingExecutor;
+///
+/// let router = RoutingExecutor::new(|job: &InferenceJob| job.model.clone())
+///     .register("llama".to_string(), LlamaExecutor::load()?)
+///     .register("mistral".to_string(), MistralExecutor::load()?);
+/// let pool = WorkerPool::new(config, router)?;
+/// ```
+pub struct RoutingExecutor<P, R, K = String> {
+    route: Arc<dyn Fn(&P) -> K + Send + Sync>,
+    routes: HashMap<K, SharedExecutor<P, R>>,
+    fallback: Option<SharedExecutor<P, R>>,
+    classify: Option<Classifier<R>>,
+}
+
+impl<P, R, K> Clone for RoutingExecutor<P, R, K>
+where
+    K: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            route: Arc::clone(&self.route),
+            routes: self.routes.clone(),
+            fallback: self.fallback.clone(),
+            classify: self.classify.clone(),
+        }
+    }
+}
+
+impl<P, R, K> RoutingExecutor<P, R, K>
+where
+    P: Send + 'static,
+    R: Send + 'static,
+    K: Hash + Eq,
+{
+    /// Create a router that picks a task's executor by `route(&payload)`.
+    pub fn new(route: impl Fn(&P) -> K + Send + Sync + 'static) -> Self {
+        Self {
+            route: Arc::new(route),
+            routes: HashMap::new(),
+            fallback: None,
+            classify: None,
+        }
+    }
+
+    /// Register `executor` for tasks routed to `key`, replacing any executor
+    /// registered for it before.
+    #[must_use]
+    pub fn register(mut self, key: K, executor: impl WorkerExecutor<P, R>) -> Self {
+        self.routes.insert(key, Arc::new(executor));
+        self
+    }
+
+    /// Run tasks whose key has no registered executor on `executor`.
+    ///
+    /// Without a fallback such a task panics its worker.
+    #[must_use]
+    pub fn with_fallback(mut self, executor: impl WorkerExecutor<P, R>) -> Self {
+        self.fallback = Some(Arc::new(executor));
+        self
+    }
+
+    /// Classify results with `classify`.
+    ///
+    /// A result doesn't record which executor produced it, so the router
+    /// can't ask the sub-executors; without a classifier every result counts
+    /// as a success.
+    #[must_use]
+    pub fn with_classifier(
+        mut self,
+        classify: impl Fn(&R) -> ExecutionOutcome + Send + Sync + 'static,
+    ) -> Self {
+        self.classify = Some(Arc::new(classify));
+        self
+    }
+
+    /// Whether an executor is registered for `key`.
+    #[must_use]
+    pub fn routes_to(&self, key: &K) -> bool {
+        self.routes.contains_key(key)
+    }
+
+    /// Executor for a payload: the one registered for its key, else the fallback.
+    fn select(&self, payload: &P) -> &SharedExecutor<P, R>
+    where
+        K: Debug,
+    {
+        let key = (self.route)(payload);
+        self.routes
+            .get(&key)
+            .or(self.fallback.as_ref())
+            .unwrap_or_else(|| panic!("no executor registered for route {key:?}"))
+    }
+}
+
+#[async_trait]
+impl<P, R, K> WorkerExecutor<P, R> for RoutingExecutor<P, R, K>
+where
+    P: Send + 'static,
+    R: Send + 'static,
+    K: Hash + Eq + Debug + Send + Sync + 'static,
+{
+    async fn execute(&self, payload: P, meta: TaskMetadata) -> R {
+        self.select(&payload).execute(payload, meta).await
+    }
+
+    async fn execute_with_progress(
+        &self,
+        payload: P,
+        meta: TaskMetadata,
+        progress: ProgressReporter,
+    ) -> R {
+        self.select(&payload)
+            .execute_with_progress(payload, meta, progress)
+            .await
+    }
+
+    async fn execute_with_context(
+        &self,
+        payload: P,
+        meta: TaskMetadata,
+        progress: ProgressReporter,
+        context: &ExecutorContext,
+    ) -> R {
+        self.select(&payload)
+            .execute_with_context(payload, meta, progress, context)
+            .await
+    }
+
+    fn classify(&self, result: &R) -> ExecutionOutcome {
+        self.classify
+            .as_ref()
+            .map_or(ExecutionOutcome::Success, |classify| classify(result))
+    }
+}
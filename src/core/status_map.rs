@@ -0,0 +1,231 @@
+//! Per-task status tracking for `ResourcePool`.
+//!
+//! The pool records each task's status as it is enqueued, started, completed,
+//! or expired so callers can look it up by `TaskId`. Terminal statuses are
+//! evicted once they are older than the configured TTL, bounding the map to
+//! live tasks plus recently finished ones.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use parking_lot::Mutex;
+
+use crate::core::TaskStatus;
+use crate::util::clock;
+use crate::util::serde::TaskId;
+
+/// Tracked status of a single task.
+struct StatusEntry {
+    status: TaskStatus,
+    /// Deadline of a queued task, used to mark it expired when pruned.
+    deadline_ms: Option<u128>,
+    /// When a queued task entered the queue.
+    queued_at_ms: Option<u128>,
+    /// When the task reached a terminal status.
+    finished_at_ms: Option<u128>,
+}
+
+/// Mutable map state, guarded by a single lock.
+#[derive(Default)]
+struct StatusInner {
+    statuses: HashMap<TaskId, StatusEntry>,
+    /// Terminal entries in the order they finished, oldest first.
+    finished: VecDeque<(u128, TaskId)>,
+}
+
+/// Status lookup table with TTL eviction of terminal entries.
+///
+/// Also counts the tasks that reached a terminal status, which outlive the
+/// entries themselves, and the tasks running now.
+pub struct StatusMap {
+    ttl_ms: u128,
+    inner: Mutex<StatusInner>,
+    completed: AtomicU64,
+    failed: AtomicU64,
+    running: AtomicU64,
+}
+
+impl StatusMap {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl_ms: ttl.as_millis(),
+            inner: Mutex::new(StatusInner::default()),
+            completed: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            running: AtomicU64::new(0),
+        }
+    }
+
+    /// Current status of a task, if it is live or finished within the TTL.
+    pub fn get(&self, id: TaskId) -> Option<TaskStatus> {
+        let mut inner = self.inner.lock();
+        self.evict(&mut inner, clock::now_ms());
+        inner.statuses.get(&id).map(|entry| entry.status.clone())
+    }
+
+    /// Number of tasks that completed, and that failed, expired or were dropped.
+    pub fn outcome_counts(&self) -> (u64, u64) {
+        (
+            self.completed.load(Ordering::Relaxed),
+            self.failed.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Number of tasks whose current status is `Running`.
+    pub fn running(&self) -> u64 {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    /// Count a task reaching `status`, if it is terminal.
+    fn count_outcome(&self, status: &TaskStatus) {
+        match status {
+            TaskStatus::Completed => self.completed.fetch_add(1, Ordering::Relaxed),
+            TaskStatus::Failed(_) | TaskStatus::Expired | TaskStatus::Dropped(_) => {
+                self.failed.fetch_add(1, Ordering::Relaxed)
+            }
+            TaskStatus::Queued | TaskStatus::Running => return,
+        };
+    }
+
+    /// Record a status transition; `deadline_ms` matters only for queued tasks.
+    pub fn set(&self, id: TaskId, status: TaskStatus, deadline_ms: Option<u128>) {
+        self.count_outcome(&status);
+        let now = clock::now_ms();
+        let finished_at_ms = status.is_terminal().then_some(now);
+        let mut inner = self.inner.lock();
+        self.evict(&mut inner, now);
+        if finished_at_ms.is_some() {
+            inner.finished.push_back((now, id));
+        }
+        // A task put back in the queue keeps its original enqueue time
+        let queued_at_ms = matches!(status, TaskStatus::Queued).then(|| {
+            inner
+                .statuses
+                .get(&id)
+                .and_then(|entry| entry.queued_at_ms)
+                .unwrap_or(now)
+        });
+        let now_running = matches!(status, TaskStatus::Running);
+        let previous = inner.statuses.insert(
+            id,
+            StatusEntry {
+                status,
+                deadline_ms,
+                queued_at_ms,
+                finished_at_ms,
+            },
+        );
+        drop(inner);
+        let was_running = previous.is_some_and(|entry| matches!(entry.status, TaskStatus::Running));
+        match (was_running, now_running) {
+            (false, true) => self.running.fetch_add(1, Ordering::Relaxed),
+            (true, false) => self.running.fetch_sub(1, Ordering::Relaxed),
+            _ => return,
+        };
+    }
+
+    /// When a queued task entered the queue, or `None` if it is not queued.
+    pub fn queued_at(&self, id: TaskId) -> Option<u128> {
+        self.inner
+            .lock()
+            .statuses
+            .get(&id)
+            .and_then(|entry| entry.queued_at_ms)
+    }
+
+    /// Forget a task that was never accepted.
+    pub fn remove(&self, id: TaskId) {
+        self.inner.lock().statuses.remove(&id);
+    }
+
+    /// Mark queued tasks with a deadline at or before `now_ms` as expired,
+    /// matching what `TaskQueue::prune_expired` removes.
+    pub fn expire_queued(&self, now_ms: u128) {
+        let finished_at = clock::now_ms();
+        let mut inner = self.inner.lock();
+        let expired: Vec<TaskId> = inner
+            .statuses
+            .iter()
+            .filter(|(_, entry)| {
+                matches!(entry.status, TaskStatus::Queued)
+                    && entry.deadline_ms.is_some_and(|deadline| deadline <= now_ms)
+            })
+            .map(|(&id, _)| id)
+            .collect();
+        self.failed.fetch_add(expired.len() as u64, Ordering::Relaxed);
+        for id in expired {
+            if let Some(entry) = inner.statuses.get_mut(&id) {
+                entry.status = TaskStatus::Expired;
+                entry.finished_at_ms = Some(finished_at);
+            }
+            inner.finished.push_back((finished_at, id));
+        }
+    }
+
+    /// Drop terminal entries older than the TTL.
+    fn evict(&self, inner: &mut StatusInner, now: u128) {
+        while let Some(&(finished_at, id)) = inner.finished.front() {
+            if now.saturating_sub(finished_at) < self.ttl_ms {
+                break;
+            }
+            inner.finished.pop_front();
+            // The id may have been reused since; only evict the entry we queued
+            if inner
+                .statuses
+                .get(&id)
+                .is_some_and(|entry| entry.finished_at_ms == Some(finished_at))
+            {
+                inner.statuses.remove(&id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terminal_status_evicted_after_ttl() {
+        let map = StatusMap::new(Duration::from_millis(20));
+        map.set(1, TaskStatus::Running, None);
+        map.set(2, TaskStatus::Completed, None);
+        assert!(matches!(map.get(2), Some(TaskStatus::Completed)));
+
+        std::thread::sleep(Duration::from_millis(40));
+        assert!(map.get(2).is_none());
+        // Live tasks are never evicted
+        assert!(matches!(map.get(1), Some(TaskStatus::Running)));
+    }
+
+    #[test]
+    fn test_expire_queued_respects_deadline() {
+        let map = StatusMap::new(Duration::from_secs(60));
+        map.set(1, TaskStatus::Queued, Some(100));
+        map.set(2, TaskStatus::Queued, Some(300));
+        map.set(3, TaskStatus::Queued, None);
+
+        map.expire_queued(200);
+        assert_eq!(map.outcome_counts(), (0, 1));
+        assert!(matches!(map.get(1), Some(TaskStatus::Expired)));
+        assert!(matches!(map.get(2), Some(TaskStatus::Queued)));
+        assert!(matches!(map.get(3), Some(TaskStatus::Queued)));
+    }
+
+    #[test]
+    fn test_running_count_follows_transitions() {
+        let map = StatusMap::new(Duration::from_secs(60));
+        map.set(1, TaskStatus::Queued, None);
+        map.set(2, TaskStatus::Running, None);
+        assert_eq!(map.running(), 1);
+
+        map.set(1, TaskStatus::Running, None);
+        map.set(1, TaskStatus::Running, None);
+        assert_eq!(map.running(), 2);
+
+        map.set(2, TaskStatus::Completed, None);
+        map.set(1, TaskStatus::Failed("boom".into()), None);
+        assert_eq!(map.running(), 0);
+    }
+}
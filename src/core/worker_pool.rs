@@ -36,17 +36,52 @@ mod native;
 #[cfg(target_arch = "wasm32")]
 mod wasm;
 
+mod circuit;
+mod dependencies;
+#[cfg(not(target_arch = "wasm32"))]
+mod handle;
+#[cfg(not(target_arch = "wasm32"))]
+mod work_queue;
+
+pub use circuit::CircuitState;
+pub(crate) use circuit::CircuitBreaker;
+pub(crate) use dependencies::{DependencyTracker, Refusal};
+#[cfg(not(target_arch = "wasm32"))]
+pub use handle::TaskHandle;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use work_queue::{PushError, WorkQueue};
+
+use std::collections::{HashMap, VecDeque};
 use std::fmt;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
 
-use crate::core::TaskMetadata;
-use crate::util::serde::MailboxKey;
+use crate::config::RetryPolicy;
+use crate::core::dead_letter::REASON_RETRIES_EXHAUSTED;
+use crate::core::executor::{ExecutionOutcome, ExecutorContext, WorkerExecutor};
+use crate::core::progress::{Progress, ProgressReporter, PROGRESS_BUFFER};
+use crate::core::{DeadLetterSink, SchedulerError, TaskMetadata};
+use crate::util::clock::now_ms;
+use crate::util::serde::{MailboxKey, ResourceKind, TaskId};
 
 /// Errors that can occur when using a `WorkerPool`.
 #[derive(Debug)]
 pub enum PoolError {
     /// The task queue is full; no more tasks can be accepted.
-    QueueFull,
+    QueueFull {
+        /// Id of the rejected task, when known (`None` for errors converted
+        /// from `SchedulerError`).
+        task_id: Option<TaskId>,
+        /// Estimated milliseconds until the queue has room again, from the
+        /// pool's recent completion rate (`None` for errors converted from
+        /// `SchedulerError`).
+        retry_after_ms: Option<u64>,
+    },
     
     /// Insufficient resource capacity to run the task.
     InsufficientCapacity {
@@ -56,18 +91,62 @@ pub enum PoolError {
         available: u32,
     },
     
-    /// The operation timed out.
-    Timeout,
+    /// No result arrived within the retrieval timeout.
+    Timeout {
+        /// Mailbox key being retrieved.
+        key: MailboxKey,
+    },
     
     /// The requested result was not found in the mailbox.
-    ResultNotFound,
+    ResultNotFound {
+        /// Mailbox key being retrieved.
+        key: MailboxKey,
+    },
     
     /// The pool has been shut down.
     PoolShutdown,
     
+    /// The circuit breaker is open because the executor keeps failing.
+    CircuitOpen,
+    
+    /// The submitting tenant has used up its rate limit.
+    RateLimited {
+        /// Milliseconds until the tenant may submit again.
+        retry_after_ms: u64,
+    },
+    
+    /// The task's deadline passed before it could run.
+    DeadlineExpired,
+    
+    /// A `ResourcePool` error with no direct `PoolError` equivalent.
+    Scheduler(SchedulerError),
+    
+    /// A task named in `depends_on` has already failed, so this one cannot run.
+    DependencyFailed {
+        /// Id of the failed dependency.
+        id: TaskId,
+    },
+    
+    /// Another task with the same id is still in flight and the pool
+    /// enforces unique ids (`WorkerPoolConfig::enforce_unique_ids`).
+    DuplicateTaskId {
+        /// The id already in use.
+        id: TaskId,
+    },
+    
+    /// A result under the requested mailbox key is still pending or has not
+    /// been retrieved yet.
+    KeyInUse {
+        /// The mailbox key already in use.
+        key: MailboxKey,
+    },
+    
     /// Configuration validation failed.
     InvalidConfig(String),
     
+    /// The task itself is malformed, e.g. it costs zero units.
+    InvalidTask(String),
+    
     /// Internal error (worker thread panic, channel closed, etc.).
     Internal(String),
 }
@@ -75,23 +154,69 @@ pub enum PoolError {
 impl fmt::Display for PoolError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::QueueFull => write!(f, "task queue is full"),
+            Self::QueueFull { task_id: Some(id), .. } => {
+                write!(f, "task queue is full (task {id})")
+            }
+            Self::QueueFull { task_id: None, .. } => write!(f, "task queue is full"),
             Self::InsufficientCapacity { requested, available } => {
                 write!(f, "insufficient capacity: requested {requested}, available {available}")
             }
-            Self::Timeout => write!(f, "operation timed out"),
-            Self::ResultNotFound => write!(f, "result not found in mailbox"),
+            Self::Timeout { key } => {
+                write!(f, "timed out waiting for result {}", mailbox_key_to_string(key))
+            }
+            Self::ResultNotFound { key } => {
+                write!(f, "result {} not found in mailbox", mailbox_key_to_string(key))
+            }
             Self::PoolShutdown => write!(f, "pool has been shut down"),
+            Self::CircuitOpen => write!(f, "circuit breaker is open; executor is failing"),
+            Self::RateLimited { retry_after_ms } => {
+                write!(f, "rate limited; retry after {retry_after_ms} ms")
+            }
+            Self::DeadlineExpired => write!(f, "task deadline expired"),
+            Self::Scheduler(err) => write!(f, "scheduler error: {err}"),
+            Self::DependencyFailed { id } => write!(f, "dependency {id} failed"),
+            Self::DuplicateTaskId { id } => write!(f, "task id {id} is already in flight"),
+            Self::KeyInUse { key } => {
+                write!(f, "mailbox key {} is already in use", mailbox_key_to_string(key))
+            }
             Self::InvalidConfig(msg) => write!(f, "invalid configuration: {msg}"),
+            Self::InvalidTask(msg) => write!(f, "invalid task: {msg}"),
             Self::Internal(msg) => write!(f, "internal error: {msg}"),
         }
     }
 }
 
-impl std::error::Error for PoolError {}
+impl PoolError {
+    /// How long to wait before submitting again, for `QueueFull` and
+    /// `RateLimited` errors that carry an estimate.
+    ///
+    /// Meant for a gateway's `Retry-After` header, so clients back off
+    /// instead of retrying in a busy loop.
+    #[must_use]
+    pub const fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::QueueFull { retry_after_ms: Some(ms), .. }
+            | Self::RateLimited { retry_after_ms: ms } => Some(Duration::from_millis(*ms)),
+            _ => None,
+        }
+    }
+}
+
+impl std::error::Error for PoolError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Scheduler(err) => Some(err),
+            _ => None,
+        }
+    }
+}
 
 /// Statistics about pool utilization and performance.
-#[derive(Debug, Clone, Default)]
+///
+/// Serializes with the field names below, so a `/stats` endpoint can return
+/// it as JSON directly; `used_units_by_kind` becomes an object keyed by the
+/// kind names (`"cpu"`, `"gpu_vram"`, ...).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PoolStats {
     /// Number of worker threads/tasks.
     pub worker_count: usize,
@@ -102,9 +227,18 @@ pub struct PoolStats {
     /// Tasks waiting in the queue.
     pub queued_tasks: u64,
     
+    /// Age of the oldest waiting task by its `created_at_ms`, or `None` when
+    /// nothing is waiting.
+    pub queue_oldest_age_ms: Option<u64>,
+    
     /// Resource units currently in use.
     pub used_units: u32,
     
+    /// Resource units currently in use, broken down by `ResourceKind`.
+    /// 
+    /// Every kind has an entry, zero when no running task uses it.
+    pub used_units_by_kind: HashMap<ResourceKind, u32>,
+    
     /// Total resource units available.
     pub total_units: u32,
     
@@ -116,6 +250,156 @@ pub struct PoolStats {
     
     /// Total tasks submitted.
     pub submitted_tasks: u64,
+    
+    /// Total executor invocations, including retries.
+    pub total_attempts: u64,
+    
+    /// Tasks that needed more than one attempt.
+    pub retried_tasks: u64,
+    
+    /// Tasks whose execution took longer than
+    /// `WorkerPoolConfig::slow_task_threshold_ms`.
+    pub slow_tasks: u64,
+    
+    /// Whether the queue is at or above the degradation high-watermark, so new
+    /// submissions are being downgraded.
+    pub degradation_active: bool,
+    
+    /// Total tasks downgraded at submission.
+    pub degraded_tasks: u64,
+    
+    /// Whether the pool is paused: submissions still queue, but no queued
+    /// task starts until it is resumed.
+    pub paused: bool,
+    
+    /// Average time the recent tasks waited between submission and the start
+    /// of execution, in milliseconds. High waits call for more capacity.
+    pub avg_wait_ms: f64,
+    
+    /// 99th percentile of the recent tasks' wait, in milliseconds.
+    pub p99_wait_ms: u64,
+    
+    /// Average execution time of the recent tasks, retries included, in
+    /// milliseconds. High execution times call for a faster executor.
+    pub avg_exec_ms: f64,
+    
+    /// 99th percentile of the recent tasks' execution time, in milliseconds.
+    pub p99_exec_ms: u64,
+}
+
+/// Number of most recent tasks the `PoolStats` latency figures cover.
+const LATENCY_WINDOW: usize = 1024;
+
+/// Durations recorded for the most recent tasks.
+#[derive(Debug, Default)]
+pub(crate) struct LatencyWindow {
+    samples: Mutex<VecDeque<u64>>,
+}
+
+impl LatencyWindow {
+    /// Record the time elapsed since `since_ms`, returning it.
+    pub fn record_since(&self, since_ms: u128) -> u64 {
+        let elapsed = u64::try_from(now_ms().saturating_sub(since_ms)).unwrap_or(u64::MAX);
+        let mut samples = self.samples.lock();
+        if samples.len() == LATENCY_WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(elapsed);
+        elapsed
+    }
+    
+    /// Average and 99th percentile (nearest rank) of the window; zero when empty.
+    #[allow(clippy::cast_precision_loss)] // A gauge; sums past 2^52 ms are irrelevant
+    pub fn summary(&self) -> (f64, u64) {
+        let mut sorted: Vec<u64> = self.samples.lock().iter().copied().collect();
+        if sorted.is_empty() {
+            return (0.0, 0);
+        }
+        sorted.sort_unstable();
+        let total: u64 = sorted.iter().sum();
+        let avg = total as f64 / sorted.len() as f64;
+        (avg, sorted[(sorted.len() * 99).div_ceil(100) - 1])
+    }
+}
+
+/// Span of recent completions the queue-full retry estimate is based on.
+const THROUGHPUT_WINDOW_MS: u64 = 10_000;
+
+/// Most completions kept for the retry estimate.
+const THROUGHPUT_SAMPLES: usize = 1024;
+
+/// Completion times of the most recent tasks, for estimating how soon a
+/// full queue has room again.
+#[derive(Debug)]
+pub(crate) struct ThroughputWindow {
+    started_at_ms: u128,
+    finished_at_ms: Mutex<VecDeque<u128>>,
+}
+
+impl Default for ThroughputWindow {
+    fn default() -> Self {
+        Self {
+            started_at_ms: now_ms(),
+            finished_at_ms: Mutex::new(VecDeque::new()),
+        }
+    }
+}
+
+impl ThroughputWindow {
+    /// Record a task finishing now.
+    pub fn record(&self) {
+        let mut finished = self.finished_at_ms.lock();
+        if finished.len() == THROUGHPUT_SAMPLES {
+            finished.pop_front();
+        }
+        finished.push_back(now_ms());
+    }
+    
+    /// Average milliseconds between completions over the recent window, at
+    /// least 1 ms; the whole window when nothing finished within it.
+    ///
+    /// A queued task starts each time a running one finishes, so this is
+    /// about how long a rejected submission has to wait for a free slot.
+    pub fn retry_after_ms(&self) -> u64 {
+        let now = now_ms();
+        let window_start = now
+            .saturating_sub(u128::from(THROUGHPUT_WINDOW_MS))
+            .max(self.started_at_ms);
+        let finished = self
+            .finished_at_ms
+            .lock()
+            .iter()
+            .rev()
+            .take_while(|&&at| at >= window_start)
+            .count();
+        if finished == 0 {
+            return THROUGHPUT_WINDOW_MS;
+        }
+        let span = u64::try_from(now - window_start).unwrap_or(THROUGHPUT_WINDOW_MS);
+        (span / finished as u64).clamp(1, THROUGHPUT_WINDOW_MS)
+    }
+}
+
+/// Upper bound of `WorkerPool::load_factor`; beyond ten times capacity the
+/// pool is simply saturated.
+const MAX_LOAD_FACTOR: f32 = 10.0;
+
+/// Every `ResourceKind`, in `kind_index` order.
+const RESOURCE_KINDS: [ResourceKind; 4] = [
+    ResourceKind::Cpu,
+    ResourceKind::GpuVram,
+    ResourceKind::Io,
+    ResourceKind::Mixed,
+];
+
+/// Position of `kind` in `RESOURCE_KINDS`.
+const fn kind_index(kind: ResourceKind) -> usize {
+    match kind {
+        ResourceKind::Cpu => 0,
+        ResourceKind::GpuVram => 1,
+        ResourceKind::Io => 2,
+        ResourceKind::Mixed => 3,
+    }
 }
 
 /// Internal counters for pool statistics (thread-safe).
@@ -123,10 +407,24 @@ pub struct PoolStats {
 pub(crate) struct PoolCounters {
     pub active_tasks: AtomicU64,
     pub queued_tasks: AtomicU64,
-    pub used_units: std::sync::atomic::AtomicU32,
+    /// Total cost of the tasks counted in `queued_tasks`.
+    pub queued_units: AtomicU64,
+    pub used_units: AtomicU32,
+    /// Units held by running tasks, indexed by `kind_index`.
+    pub units_by_kind: [AtomicU32; RESOURCE_KINDS.len()],
     pub completed_tasks: AtomicU64,
     pub failed_tasks: AtomicU64,
     pub submitted_tasks: AtomicU64,
+    pub total_attempts: AtomicU64,
+    pub retried_tasks: AtomicU64,
+    pub slow_tasks: AtomicU64,
+    pub degraded_tasks: AtomicU64,
+    /// Time from submission to execution start of recent tasks.
+    pub wait_ms: LatencyWindow,
+    /// Execution time of recent tasks.
+    pub exec_ms: LatencyWindow,
+    /// When recent tasks finished, successfully or not.
+    pub throughput: ThroughputWindow,
 }
 
 impl Default for PoolCounters {
@@ -134,10 +432,19 @@ impl Default for PoolCounters {
         Self {
             active_tasks: AtomicU64::new(0),
             queued_tasks: AtomicU64::new(0),
-            used_units: std::sync::atomic::AtomicU32::new(0),
+            queued_units: AtomicU64::new(0),
+            used_units: AtomicU32::new(0),
+            units_by_kind: Default::default(),
             completed_tasks: AtomicU64::new(0),
             failed_tasks: AtomicU64::new(0),
             submitted_tasks: AtomicU64::new(0),
+            total_attempts: AtomicU64::new(0),
+            retried_tasks: AtomicU64::new(0),
+            slow_tasks: AtomicU64::new(0),
+            degraded_tasks: AtomicU64::new(0),
+            wait_ms: LatencyWindow::default(),
+            exec_ms: LatencyWindow::default(),
+            throughput: ThroughputWindow::default(),
         }
     }
 }
@@ -145,16 +452,106 @@ impl Default for PoolCounters {
 impl PoolCounters {
     /// Get a snapshot of current statistics.
     pub fn snapshot(&self, worker_count: usize, total_units: u32) -> PoolStats {
+        let (avg_wait_ms, p99_wait_ms) = self.wait_ms.summary();
+        let (avg_exec_ms, p99_exec_ms) = self.exec_ms.summary();
         PoolStats {
             worker_count,
             active_tasks: self.active_tasks.load(Ordering::Relaxed),
             queued_tasks: self.queued_tasks.load(Ordering::Relaxed),
+            queue_oldest_age_ms: None,
             used_units: self.used_units.load(Ordering::Relaxed),
+            used_units_by_kind: RESOURCE_KINDS
+                .iter()
+                .map(|&kind| (kind, self.units_by_kind[kind_index(kind)].load(Ordering::Relaxed)))
+                .collect(),
             total_units,
             completed_tasks: self.completed_tasks.load(Ordering::Relaxed),
             failed_tasks: self.failed_tasks.load(Ordering::Relaxed),
             submitted_tasks: self.submitted_tasks.load(Ordering::Relaxed),
+            total_attempts: self.total_attempts.load(Ordering::Relaxed),
+            retried_tasks: self.retried_tasks.load(Ordering::Relaxed),
+            slow_tasks: self.slow_tasks.load(Ordering::Relaxed),
+            degradation_active: false,
+            degraded_tasks: self.degraded_tasks.load(Ordering::Relaxed),
+            paused: false,
+            avg_wait_ms,
+            p99_wait_ms,
+            avg_exec_ms,
+            p99_exec_ms,
+        }
+    }
+    
+    /// Record the execution time of the task `meta` started at
+    /// `started_at_ms`, warning about it and counting it as slow when it ran
+    /// past `slow_threshold`.
+    pub fn record_execution(
+        &self,
+        meta: &TaskMetadata,
+        started_at_ms: u128,
+        slow_threshold: Option<Duration>,
+    ) {
+        let elapsed_ms = self.exec_ms.record_since(started_at_ms);
+        let Some(threshold) = slow_threshold else {
+            return;
+        };
+        if u128::from(elapsed_ms) > threshold.as_millis() {
+            self.slow_tasks.fetch_add(1, Ordering::Relaxed);
+            tracing::warn!(
+                task_id = meta.id,
+                priority = ?meta.priority,
+                cost = meta.cost.units,
+                elapsed_ms,
+                threshold_ms = u64::try_from(threshold.as_millis()).unwrap_or(u64::MAX),
+                "Slow task"
+            );
+        }
+    }
+    
+    /// Count a task costing `units` as waiting to run.
+    pub fn queue_task(&self, units: u32) {
+        self.queued_tasks.fetch_add(1, Ordering::Relaxed);
+        self.queued_units.fetch_add(u64::from(units), Ordering::Relaxed);
+    }
+    
+    /// Stop counting a task costing `units` as waiting, once it starts or is dropped.
+    pub fn unqueue_task(&self, units: u32) {
+        self.queued_tasks.fetch_sub(1, Ordering::Relaxed);
+        self.queued_units.fetch_sub(u64::from(units), Ordering::Relaxed);
+    }
+    
+    /// Running plus queued units relative to `max_units`, capped at `MAX_LOAD_FACTOR`.
+    ///
+    /// Running units come from the per-kind counters, which a task enters at
+    /// the moment it leaves `queued_units`, so no task is counted twice.
+    #[allow(clippy::cast_precision_loss)] // A gauge; precision past 2^24 units is irrelevant
+    pub fn load_factor(&self, max_units: u32) -> f32 {
+        let running: u64 = self
+            .units_by_kind
+            .iter()
+            .map(|units| u64::from(units.load(Ordering::Relaxed)))
+            .sum();
+        let demand = running + self.queued_units.load(Ordering::Relaxed);
+        (demand as f32 / max_units.max(1) as f32).min(MAX_LOAD_FACTOR)
+    }
+    
+    /// Count `units` of `kind` as held by a running task.
+    pub fn acquire_kind_units(&self, kind: ResourceKind, units: u32) {
+        self.units_by_kind[kind_index(kind)].fetch_add(units, Ordering::Relaxed);
+    }
+    
+    /// Release `units` of `kind` held by a finished task.
+    pub fn release_kind_units(&self, kind: ResourceKind, units: u32) {
+        self.units_by_kind[kind_index(kind)].fetch_sub(units, Ordering::Relaxed);
+    }
+    
+    /// Record the final outcome of a task.
+    pub fn record_outcome(&self, outcome: ExecutionOutcome) {
+        if outcome == ExecutionOutcome::Success {
+            self.completed_tasks.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.failed_tasks.fetch_add(1, Ordering::Relaxed);
         }
+        self.throughput.record();
     }
 }
 
@@ -167,6 +564,15 @@ pub(crate) struct WorkerTask<P> {
     pub meta: TaskMetadata,
     /// Mailbox key for result storage.
     pub mailbox_key: MailboxKey,
+    /// Sending half of the task's progress channel.
+    pub progress: ProgressReporter,
+    /// Whether the circuit breaker admitted this task as its half-open probe.
+    pub probe: bool,
+    /// When the task was submitted, in ms since the epoch.
+    pub enqueued_at_ms: u128,
+    /// Task-lifetime span, started at submission.
+    #[cfg(feature = "otel")]
+    pub span: opentelemetry::Context,
 }
 
 /// Generate a unique mailbox key for a task.
@@ -183,9 +589,232 @@ pub(crate) fn mailbox_key_to_string(key: &MailboxKey) -> String {
     format!("{}:{}", key.tenant, key.session_id.as_deref().unwrap_or("unknown"))
 }
 
+/// Per-task progress channels, keyed by mailbox key.
+///
+/// Only the receiving half is kept here; the sending half travels with the task,
+/// so the stream disconnects once the task finishes.
+#[derive(Default)]
+pub(crate) struct ProgressChannels {
+    receivers: Mutex<HashMap<String, ProgressChannel>>,
+}
+
+/// Receiving half of a task's progress channel, with its heartbeat.
+struct ProgressChannel {
+    #[cfg(not(target_arch = "wasm32"))]
+    key: MailboxKey,
+    rx: flume::Receiver<Progress>,
+    /// Last heartbeat in ms since the epoch; 0 while the task is not running.
+    #[cfg(not(target_arch = "wasm32"))]
+    heartbeat: Arc<AtomicU64>,
+}
+
+impl ProgressChannels {
+    /// Open a channel for a task and return the reporter handed to its executor.
+    pub(crate) fn open(&self, key: &MailboxKey) -> ProgressReporter {
+        let (tx, rx) = flume::bounded(PROGRESS_BUFFER);
+        // Heartbeats only feed `stale`, which the WASM pool doesn't offer
+        #[cfg(not(target_arch = "wasm32"))]
+        let heartbeat = Arc::new(AtomicU64::new(0));
+        let reporter = ProgressReporter::latest(tx, rx.clone());
+        #[cfg(not(target_arch = "wasm32"))]
+        let reporter = reporter.with_heartbeat(Arc::clone(&heartbeat));
+        let channel = ProgressChannel {
+            #[cfg(not(target_arch = "wasm32"))]
+            key: key.clone(),
+            rx,
+            #[cfg(not(target_arch = "wasm32"))]
+            heartbeat,
+        };
+        self.receivers.lock().insert(mailbox_key_to_string(key), channel);
+        reporter
+    }
+    
+    /// Running tasks whose last heartbeat is older than `older_than`, stalest first.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn stale(&self, older_than: std::time::Duration) -> Vec<MailboxKey> {
+        let now = u64::try_from(now_ms()).unwrap_or(u64::MAX);
+        let window = u64::try_from(older_than.as_millis()).unwrap_or(u64::MAX);
+        let mut stale: Vec<(u64, MailboxKey)> = self
+            .receivers
+            .lock()
+            .values()
+            .filter_map(|channel| {
+                let last = channel.heartbeat.load(Ordering::Relaxed);
+                (last != 0 && now.saturating_sub(last) > window)
+                    .then(|| (last, channel.key.clone()))
+            })
+            .collect();
+        stale.sort_unstable_by_key(|(last, _)| *last);
+        stale.into_iter().map(|(_, key)| key).collect()
+    }
+    
+    /// Receiver for a task's updates; already disconnected if the key is unknown.
+    pub(crate) fn subscribe(&self, key: &MailboxKey) -> flume::Receiver<Progress> {
+        self.receivers
+            .lock()
+            .get(&mailbox_key_to_string(key))
+            .map_or_else(|| flume::bounded(0).1, |channel| channel.rx.clone())
+    }
+    
+    /// Forget a task's channel once its result has been retrieved or dropped.
+    pub(crate) fn close(&self, key: &MailboxKey) {
+        self.receivers.lock().remove(&mailbox_key_to_string(key));
+    }
+    
+    /// Forget the channels of results evicted unretrieved, by key string.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn close_evicted(&self, key_strs: &[String]) {
+        let mut receivers = self.receivers.lock();
+        for key_str in key_strs {
+            receivers.remove(key_str);
+        }
+    }
+}
+
+/// Callback that downgrades a task's metadata, e.g. lowering `cost.units`.
+pub(crate) type DegradeFn = Box<dyn Fn(&mut TaskMetadata) + Send + Sync>;
+
+/// Opt-in graceful degradation applied to submissions while the queue is deep.
+pub(crate) struct Degradation {
+    /// Queue depth at which submissions start being downgraded.
+    pub high_watermark: usize,
+    /// Rewrites the metadata of a downgraded task.
+    pub degrade: DegradeFn,
+}
+
+impl Degradation {
+    /// Whether a queue holding `queued` tasks is under pressure.
+    pub(crate) const fn is_active(&self, queued: u64) -> bool {
+        queued >= self.high_watermark as u64
+    }
+    
+    /// Downgrade `meta` if the queue is under pressure, tagging it as degraded.
+    pub(crate) fn apply(&self, meta: &mut TaskMetadata, counters: &PoolCounters) {
+        if !self.is_active(counters.queued_tasks.load(Ordering::Relaxed)) {
+            return;
+        }
+        (self.degrade)(meta);
+        meta.degraded = true;
+        counters.degraded_tasks.fetch_add(1, Ordering::Relaxed);
+        debug!(task_id = meta.id, units = meta.cost.units, "Task degraded under load");
+    }
+}
+
+/// Dead-letter sink shared with workers; attached after the workers are spawned.
+pub(crate) type DeadLetterSlot = Arc<Mutex<Option<Box<dyn DeadLetterSink>>>>;
+
+/// Callback run as each task completes, with its result key and metadata.
+pub(crate) type CompletionHook = Arc<dyn Fn(&MailboxKey, &TaskMetadata) + Send + Sync>;
+
+/// Completion hook shared with workers; attached after the workers are spawned.
+pub(crate) type CompletionSlot = Arc<Mutex<Option<CompletionHook>>>;
+
+/// Run the pool's completion hook, if one is attached.
+pub(crate) fn run_completion_hook(slot: &CompletionSlot, key: &MailboxKey, meta: &TaskMetadata) {
+    // Clone the hook out so it runs without holding the slot's lock
+    let hook = slot.lock().clone();
+    if let Some(hook) = hook {
+        hook(key, meta);
+    }
+}
+
+/// Hand a dropped task to the pool's dead-letter sink, if one is attached.
+pub(crate) fn record_dead_letter(slot: &DeadLetterSlot, meta: TaskMetadata, reason: &str) {
+    if let Some(sink) = slot.lock().as_mut() {
+        sink.record(meta, reason.to_string());
+    }
+}
+
+/// Whether a task has passed its deadline and should not be started.
+pub(crate) fn is_expired(meta: &TaskMetadata) -> bool {
+    meta.is_expired_at(now_ms())
+}
+
+/// Time left until `deadline_ms`, an absolute time in milliseconds since the
+/// epoch; zero once it has passed.
+pub(crate) fn time_until(deadline_ms: u128) -> Duration {
+    let remaining = deadline_ms.saturating_sub(now_ms());
+    Duration::from_millis(u64::try_from(remaining).unwrap_or(u64::MAX))
+}
+
+/// Record a task's final outcome, dead-lettering it if it ran out of retries.
+pub(crate) fn finish_task(
+    counters: &PoolCounters,
+    dead_letter: &DeadLetterSlot,
+    retry: &RetryPolicy,
+    circuit: &CircuitBreaker,
+    meta: TaskMetadata,
+    outcome: ExecutionOutcome,
+    probe: bool,
+) {
+    counters.record_outcome(outcome);
+    circuit.record(outcome, probe);
+    if outcome == ExecutionOutcome::Retryable && retry.is_enabled() {
+        warn!(task_id = meta.id, "Task exhausted its retries");
+        record_dead_letter(dead_letter, meta, REASON_RETRIES_EXHAUSTED);
+    }
+}
+
+/// Execute a task, re-running it while the executor reports a retryable failure.
+///
+/// Retries need a fresh copy of the payload, so they only happen when
+/// `clone_payload` is available. Between attempts the task sleeps on the
+/// runtime driving this future (the worker's own runtime on native).
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn execute_with_retry<P, R, E>(
+    executor: &E,
+    payload: P,
+    meta: &TaskMetadata,
+    retry: &RetryPolicy,
+    clone_payload: Option<fn(&P) -> P>,
+    counters: &PoolCounters,
+    progress: &ProgressReporter,
+    context: &ExecutorContext,
+) -> (R, ExecutionOutcome)
+where
+    P: Send + 'static,
+    R: Send + 'static,
+    E: WorkerExecutor<P, R>,
+{
+    let mut payload = payload;
+    let mut attempt = 1;
+    let mut backoff = retry.backoff();
+    loop {
+        let next_payload = match clone_payload {
+            Some(clone) if attempt < retry.max_attempts => Some(clone(&payload)),
+            _ => None,
+        };
+        
+        counters.total_attempts.fetch_add(1, Ordering::Relaxed);
+        let result = executor
+            .execute_with_context(payload, meta.clone(), progress.clone(), context)
+            .await;
+        let outcome = executor.classify(&result);
+        
+        let (ExecutionOutcome::Retryable, Some(next)) = (outcome, next_payload) else {
+            return (result, outcome);
+        };
+        
+        if attempt == 1 {
+            counters.retried_tasks.fetch_add(1, Ordering::Relaxed);
+        }
+        let delay = backoff.next().unwrap_or_default();
+        warn!(
+            task_id = meta.id,
+            attempt = attempt,
+            delay_ms = u64::try_from(delay.as_millis()).unwrap_or(u64::MAX),
+            "Retryable task failure, retrying after backoff"
+        );
+        drop(result);
+        tokio::time::sleep(delay).await;
+        payload = next;
+        attempt += 1;
+    }
+}
+
 // Re-export the platform-specific WorkerPool implementation
 #[cfg(not(target_arch = "wasm32"))]
-pub use native::WorkerPool;
+pub use native::{RuntimeBuilderFn, WorkerPool, WorkerState};
 
 #[cfg(target_arch = "wasm32")]
 pub use wasm::WorkerPool;
@@ -196,14 +825,17 @@ mod tests {
     
     #[test]
     fn test_pool_error_display() {
-        let err = PoolError::QueueFull;
-        assert_eq!(format!("{}", err), "task queue is full");
+        let err = PoolError::QueueFull { task_id: Some(7), retry_after_ms: Some(40) };
+        assert_eq!(format!("{}", err), "task queue is full (task 7)");
         
         let err = PoolError::InsufficientCapacity { requested: 100, available: 50 };
         assert_eq!(format!("{}", err), "insufficient capacity: requested 100, available 50");
         
-        let err = PoolError::Timeout;
-        assert_eq!(format!("{}", err), "operation timed out");
+        let err = PoolError::Timeout { key: generate_mailbox_key(3) };
+        assert_eq!(format!("{}", err), "timed out waiting for result worker_pool:3");
+        
+        let err = PoolError::ResultNotFound { key: generate_mailbox_key(3) };
+        assert_eq!(format!("{}", err), "result worker_pool:3 not found in mailbox");
     }
     
     #[test]
@@ -228,4 +860,49 @@ mod tests {
         assert_eq!(stats.used_units, 100);
         assert_eq!(stats.total_units, 1000);
     }
+    
+    #[test]
+    fn test_pool_stats_json_round_trip() {
+        let mut stats = PoolCounters::default().snapshot(2, 100);
+        stats.used_units_by_kind = HashMap::from([(ResourceKind::GpuVram, 40)]);
+        stats.queue_oldest_age_ms = Some(250);
+        stats.paused = true;
+        
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["worker_count"], 2);
+        assert_eq!(json["total_units"], 100);
+        assert_eq!(json["queue_oldest_age_ms"], 250);
+        assert_eq!(json["used_units_by_kind"]["gpu_vram"], 40);
+        assert_eq!(json["paused"], true);
+        
+        let back: PoolStats = serde_json::from_value(json).unwrap();
+        assert_eq!(back.used_units_by_kind.get(&ResourceKind::GpuVram), Some(&40));
+        assert_eq!(back.queue_oldest_age_ms, Some(250));
+        assert!(back.paused);
+    }
+    
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_progress_channel_keeps_latest_updates() {
+        let channels = ProgressChannels::default();
+        let key = generate_mailbox_key(1);
+        let reporter = channels.open(&key);
+        let stream = channels.subscribe(&key);
+        
+        // Nobody reads while the task overflows its buffer
+        let total = PROGRESS_BUFFER + 10;
+        for step in 0..total {
+            reporter.report(0.0, Some(step.to_string()));
+        }
+        drop(reporter);
+        
+        let messages: Vec<String> = stream.drain().filter_map(|update| update.message).collect();
+        assert_eq!(messages.len(), PROGRESS_BUFFER);
+        assert_eq!(messages.first().map(String::as_str), Some("10"));
+        assert_eq!(messages.last(), Some(&(total - 1).to_string()));
+        
+        // Evicting the result forgets the channel
+        channels.close_evicted(&[mailbox_key_to_string(&key)]);
+        assert!(channels.subscribe(&key).recv().is_err());
+    }
 }
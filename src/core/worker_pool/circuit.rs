@@ -0,0 +1,143 @@
+//! Circuit breaker shared by the native and WASM `WorkerPool` implementations.
+//!
+//! The breaker is fed the final outcome of every executed task and decides
+//! whether new submissions are admitted. See `CircuitBreakerConfig` for the
+//! open → half-open → closed cycle.
+
+use std::collections::VecDeque;
+
+use parking_lot::Mutex;
+use tracing::{info, warn};
+
+use crate::config::CircuitBreakerConfig;
+use crate::core::executor::ExecutionOutcome;
+use crate::util::clock::now_ms;
+
+use super::PoolError;
+
+/// State of a pool's circuit breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Tasks are admitted normally.
+    Closed,
+    /// The executor is failing; submissions are rejected until the cooldown ends.
+    Open,
+    /// The cooldown ended; a single probe task is admitted to test recovery.
+    HalfOpen,
+}
+
+/// Mutable breaker state, guarded by a single lock.
+struct CircuitInner {
+    /// Current state.
+    state: CircuitState,
+    /// Recent outcomes while closed (`true` = failure), oldest first.
+    window: VecDeque<bool>,
+    /// When the circuit last opened (Open) or admitted its probe (`HalfOpen`).
+    since_ms: u128,
+}
+
+/// Rolling failure-rate circuit breaker.
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    inner: Mutex<CircuitInner>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            inner: Mutex::new(CircuitInner {
+                state: CircuitState::Closed,
+                window: VecDeque::with_capacity(config.window_size),
+                since_ms: 0,
+            }),
+            config,
+        }
+    }
+
+    fn cooldown_elapsed(&self, inner: &CircuitInner, now: u128) -> bool {
+        now.saturating_sub(inner.since_ms) >= u128::from(self.config.cooldown_ms)
+    }
+
+    /// Current state, reporting an open circuit whose cooldown ended as half-open.
+    pub fn state(&self) -> CircuitState {
+        let inner = self.inner.lock();
+        if inner.state == CircuitState::Open && self.cooldown_elapsed(&inner, now_ms()) {
+            CircuitState::HalfOpen
+        } else {
+            inner.state
+        }
+    }
+
+    /// Decide whether a new submission is admitted, returning `true` when it
+    /// is admitted as the half-open probe.
+    ///
+    /// A half-open probe that never reports back (e.g. it was rejected by a full
+    /// queue) is replaced by a new one after another cooldown.
+    pub fn admit(&self) -> Result<bool, PoolError> {
+        if !self.config.is_enabled() {
+            return Ok(false);
+        }
+        let now = now_ms();
+        let mut inner = self.inner.lock();
+        if inner.state == CircuitState::Closed {
+            return Ok(false);
+        }
+        if !self.cooldown_elapsed(&inner, now) {
+            return Err(PoolError::CircuitOpen);
+        }
+        let was_open = inner.state == CircuitState::Open;
+        inner.state = CircuitState::HalfOpen;
+        inner.since_ms = now;
+        drop(inner);
+        if was_open {
+            info!("Circuit breaker half-open, admitting a probe task");
+        }
+        Ok(true)
+    }
+
+    /// Feed the final outcome of an executed task; `probe` is the flag returned
+    /// by [`admit`](Self::admit) for it.
+    pub fn record(&self, outcome: ExecutionOutcome, probe: bool) {
+        if !self.config.is_enabled() {
+            return;
+        }
+        let failed = outcome != ExecutionOutcome::Success;
+        let mut inner = self.inner.lock();
+        match inner.state {
+            CircuitState::Closed => {
+                if inner.window.len() == self.config.window_size {
+                    inner.window.pop_front();
+                }
+                inner.window.push_back(failed);
+                if inner.window.len() < self.config.window_size {
+                    return;
+                }
+                let failures = inner.window.iter().filter(|&&f| f).count();
+                #[allow(clippy::cast_precision_loss)]
+                let rate = failures as f64 / inner.window.len() as f64;
+                if rate >= self.config.failure_threshold {
+                    warn!(failure_rate = rate, "Circuit breaker opened");
+                    Self::open(&mut inner);
+                }
+            }
+            // Only the probe decides; other tasks were admitted before the circuit opened
+            CircuitState::HalfOpen if !probe => {}
+            CircuitState::HalfOpen if failed => {
+                warn!("Circuit breaker probe failed, re-opening");
+                Self::open(&mut inner);
+            }
+            CircuitState::HalfOpen => {
+                info!("Circuit breaker probe succeeded, closing");
+                inner.state = CircuitState::Closed;
+                inner.window.clear();
+            }
+            CircuitState::Open => {}
+        }
+    }
+
+    fn open(inner: &mut CircuitInner) {
+        inner.state = CircuitState::Open;
+        inner.since_ms = now_ms();
+        inner.window.clear();
+    }
+}
@@ -0,0 +1,280 @@
+//! Task dependency tracking shared by the native and WASM `WorkerPool`
+//! implementations and by `ResourcePool`.
+//!
+//! Every submitted task is registered by `TaskId` while it is in flight. A task
+//! whose `depends_on` names in-flight tasks is parked here and released once all
+//! of them succeed; if any of them fails, it is dropped instead, and the failure
+//! cascades to its own dependents. Dependencies that are not in flight are
+//! treated as already complete, unless they recently failed. The same
+//! registry backs `enforce_unique_ids`, which refuses an id already in flight.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use parking_lot::Mutex;
+
+use crate::util::serde::TaskId;
+
+/// Number of failed task ids remembered for dependents submitted afterwards.
+const FAILED_HISTORY: usize = 4096;
+
+/// Tasks unblocked or dropped by a finished dependency.
+pub struct Released<T> {
+    /// Parked tasks whose dependencies have all succeeded.
+    pub ready: Vec<T>,
+    /// Parked tasks dropped because a dependency failed, including cascades.
+    pub dropped: Vec<T>,
+}
+
+/// Why [`DependencyTracker::submit`] refused a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Refusal {
+    /// A dependency with this id recently failed.
+    DependencyFailed(TaskId),
+    /// Another task with the same id is in flight.
+    Duplicate,
+}
+
+/// A task waiting on its dependencies.
+struct Parked<T> {
+    id: TaskId,
+    waiting_on: HashSet<TaskId>,
+    item: T,
+}
+
+/// Mutable tracker state, guarded by a single lock.
+struct DependencyInner<T> {
+    /// In-flight task ids (submitted, not yet finished) and how many share each id.
+    active: HashMap<TaskId, usize>,
+    /// Recently failed ids, oldest first in `failed_order`.
+    failed: HashSet<TaskId>,
+    failed_order: VecDeque<TaskId>,
+    /// Parked tasks by slot number.
+    parked: HashMap<u64, Parked<T>>,
+    next_slot: u64,
+    /// Slots of the parked tasks waiting on each id.
+    dependents: HashMap<TaskId, Vec<u64>>,
+}
+
+impl<T> DependencyInner<T> {
+    fn deactivate(&mut self, id: TaskId) {
+        if let Some(count) = self.active.get_mut(&id) {
+            *count -= 1;
+            if *count == 0 {
+                self.active.remove(&id);
+            }
+        }
+    }
+
+    fn mark_failed(&mut self, id: TaskId) {
+        if self.failed.insert(id) {
+            self.failed_order.push_back(id);
+            if self.failed_order.len() > FAILED_HISTORY {
+                if let Some(oldest) = self.failed_order.pop_front() {
+                    self.failed.remove(&oldest);
+                }
+            }
+        }
+    }
+}
+
+/// Holds dependent tasks until their dependencies finish.
+pub struct DependencyTracker<T> {
+    inner: Mutex<DependencyInner<T>>,
+}
+
+impl<T> Default for DependencyTracker<T> {
+    fn default() -> Self {
+        Self {
+            inner: Mutex::new(DependencyInner {
+                active: HashMap::new(),
+                failed: HashSet::new(),
+                failed_order: VecDeque::new(),
+                parked: HashMap::new(),
+                next_slot: 0,
+                dependents: HashMap::new(),
+            }),
+        }
+    }
+}
+
+impl<T> DependencyTracker<T> {
+    /// Register a submitted task as in flight.
+    ///
+    /// Returns `Ok(Some(item))` if it may run now and `Ok(None)` if it was
+    /// parked. If a dependency already failed, or `unique` is set and the id
+    /// is already in flight, the task is not registered and `Err` hands the
+    /// item back with the reason.
+    pub fn submit(
+        &self,
+        id: TaskId,
+        depends_on: &[TaskId],
+        item: T,
+        unique: bool,
+    ) -> Result<Option<T>, (Refusal, T)> {
+        let mut inner = self.inner.lock();
+        if unique && inner.active.contains_key(&id) {
+            return Err((Refusal::Duplicate, item));
+        }
+        if let Some(&failed) = depends_on.iter().find(|dep| inner.failed.contains(dep)) {
+            inner.mark_failed(id);
+            return Err((Refusal::DependencyFailed(failed), item));
+        }
+        let waiting_on: HashSet<TaskId> = depends_on
+            .iter()
+            .copied()
+            .filter(|dep| *dep != id && inner.active.contains_key(dep))
+            .collect();
+        *inner.active.entry(id).or_insert(0) += 1;
+        if waiting_on.is_empty() {
+            return Ok(Some(item));
+        }
+        let slot = inner.next_slot;
+        inner.next_slot += 1;
+        for dep in &waiting_on {
+            inner.dependents.entry(*dep).or_default().push(slot);
+        }
+        inner.parked.insert(slot, Parked { id, waiting_on, item });
+        drop(inner);
+        Ok(None)
+    }
+
+    /// Map every parked item with `f`, oldest first.
+    pub fn parked<K>(&self, f: impl Fn(&T) -> K) -> Vec<K> {
+        let inner = self.inner.lock();
+        let mut parked: Vec<(u64, K)> = inner
+            .parked
+            .iter()
+            .map(|(slot, parked)| (*slot, f(&parked.item)))
+            .collect();
+        drop(inner);
+        parked.sort_unstable_by_key(|(slot, _)| *slot);
+        parked.into_iter().map(|(_, key)| key).collect()
+    }
+
+    /// Number of parked items.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn parked_len(&self) -> usize {
+        self.inner.lock().parked.len()
+    }
+
+    /// Remove every parked item, oldest first, deregistering their tasks.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn drain_parked(&self) -> Vec<T> {
+        let mut inner = self.inner.lock();
+        let mut parked: Vec<(u64, Parked<T>)> = inner.parked.drain().collect();
+        for (_, task) in &parked {
+            inner.deactivate(task.id);
+        }
+        inner.dependents.clear();
+        drop(inner);
+        parked.sort_unstable_by_key(|(slot, _)| *slot);
+        parked.into_iter().map(|(_, task)| task.item).collect()
+    }
+    
+    /// Deregister a task that left the pool without finishing, e.g. drained
+    /// to be submitted again. Its dependents stay parked.
+    pub fn withdraw(&self, id: TaskId) {
+        self.inner.lock().deactivate(id);
+    }
+    
+    /// Remove the first parked item matching `matches`, if any.
+    ///
+    /// The task stays registered; settle it with [`finish`](Self::finish).
+    pub fn remove_parked(&self, matches: impl Fn(&T) -> bool) -> Option<T> {
+        let mut inner = self.inner.lock();
+        let slot = inner
+            .parked
+            .iter()
+            .find(|(_, parked)| matches(&parked.item))
+            .map(|(slot, _)| *slot)?;
+        // Stale slots left in `dependents` are skipped by `finish`
+        inner.parked.remove(&slot).map(|parked| parked.item)
+    }
+    
+    /// Record that a registered task finished, returning the parked tasks it
+    /// unblocks (on success) or drops (on failure).
+    pub fn finish(&self, id: TaskId, succeeded: bool) -> Released<T> {
+        let mut inner = self.inner.lock();
+        let mut released = Released {
+            ready: Vec::new(),
+            dropped: Vec::new(),
+        };
+        let mut finished = vec![(id, succeeded)];
+        while let Some((id, succeeded)) = finished.pop() {
+            inner.deactivate(id);
+            if !succeeded {
+                inner.mark_failed(id);
+            } else if inner.active.contains_key(&id) {
+                // Another task with the same id is still running
+                continue;
+            }
+            let Some(slots) = inner.dependents.remove(&id) else {
+                continue;
+            };
+            for slot in slots {
+                if !succeeded {
+                    if let Some(parked) = inner.parked.remove(&slot) {
+                        finished.push((parked.id, false));
+                        released.dropped.push(parked.item);
+                    }
+                    continue;
+                }
+                // Slots of tasks already dropped through another dependency are gone
+                let Some(parked) = inner.parked.get_mut(&slot) else {
+                    continue;
+                };
+                parked.waiting_on.remove(&id);
+                if parked.waiting_on.is_empty() {
+                    if let Some(parked) = inner.parked.remove(&slot) {
+                        released.ready.push(parked.item);
+                    }
+                }
+            }
+        }
+        released
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failure_cascades_to_dependents() {
+        let tracker = DependencyTracker::default();
+        assert_eq!(tracker.submit(1, &[], "a", false), Ok(Some("a")));
+        assert_eq!(tracker.submit(2, &[1], "b", false), Ok(None));
+        assert_eq!(tracker.submit(3, &[2], "c", false), Ok(None));
+
+        let released = tracker.finish(1, false);
+        assert!(released.ready.is_empty());
+        assert_eq!(released.dropped, vec!["b", "c"]);
+
+        // Later dependents of a failed task are rejected outright
+        assert_eq!(tracker.submit(4, &[3], "d", false), Err((Refusal::DependencyFailed(3), "d")));
+    }
+
+    #[test]
+    fn test_released_after_all_dependencies_succeed() {
+        let tracker = DependencyTracker::default();
+        assert_eq!(tracker.submit(1, &[], "a", false), Ok(Some("a")));
+        assert_eq!(tracker.submit(2, &[], "b", false), Ok(Some("b")));
+        assert_eq!(tracker.submit(3, &[1, 2, 99], "c", false), Ok(None));
+
+        assert!(tracker.finish(1, true).ready.is_empty());
+        assert_eq!(tracker.finish(2, true).ready, vec!["c"]);
+    }
+
+    #[test]
+    fn test_unique_ids_refuse_active_duplicates() {
+        let tracker = DependencyTracker::default();
+        assert_eq!(tracker.submit(1, &[], "a", true), Ok(Some("a")));
+        assert_eq!(tracker.submit(1, &[], "b", true), Err((Refusal::Duplicate, "b")));
+        // Without the check the duplicate is registered alongside
+        assert_eq!(tracker.submit(1, &[], "c", false), Ok(Some("c")));
+
+        tracker.finish(1, true);
+        tracker.finish(1, true);
+        assert_eq!(tracker.submit(1, &[], "d", true), Ok(Some("d")));
+    }
+}
@@ -0,0 +1,73 @@
+//! Typed handle to a task submitted with [`WorkerPool::submit_handle`].
+
+use std::time::Duration;
+
+use crate::core::executor::WorkerExecutor;
+use crate::util::serde::MailboxKey;
+
+use super::{PoolError, WorkerPool};
+
+/// A submitted task's mailbox key together with the pool it was submitted to.
+///
+/// The handle borrows the pool, so the result type and the pool to retrieve
+/// from can't be mixed up. Consuming methods release the handle once the
+/// result has been taken or the task cancelled.
+#[must_use = "the result can only be retrieved through the handle or its key"]
+pub struct TaskHandle<'a, P, R, E>
+where
+    P: Send + 'static,
+    R: Send + 'static,
+    E: WorkerExecutor<P, R> + Clone,
+{
+    pool: &'a WorkerPool<P, R, E>,
+    key: MailboxKey,
+}
+
+impl<'a, P, R, E> TaskHandle<'a, P, R, E>
+where
+    P: Send + 'static,
+    R: Send + 'static,
+    E: WorkerExecutor<P, R> + Clone,
+{
+    pub(super) const fn new(pool: &'a WorkerPool<P, R, E>, key: MailboxKey) -> Self {
+        Self { pool, key }
+    }
+
+    /// Mailbox key of the task.
+    #[must_use]
+    pub const fn key(&self) -> &MailboxKey {
+        &self.key
+    }
+
+    /// Give up the handle, keeping the key for a later `retrieve`.
+    #[must_use]
+    pub fn into_key(self) -> MailboxKey {
+        self.key
+    }
+
+    /// Wait up to `timeout` for the result.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`WorkerPool::retrieve_async`].
+    pub async fn await_result(self, timeout: Duration) -> Result<R, PoolError> {
+        self.pool.retrieve_async(&self.key, timeout).await
+    }
+
+    /// Take the result if it is ready, without waiting.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`WorkerPool::try_retrieve`].
+    pub fn try_result(&self) -> Result<Option<R>, PoolError> {
+        self.pool.try_retrieve(&self.key)
+    }
+
+    /// Cancel the task if it has not started running.
+    ///
+    /// See [`WorkerPool::cancel`].
+    #[must_use]
+    pub fn cancel(self) -> bool {
+        self.pool.cancel(&self.key)
+    }
+}
@@ -10,22 +10,45 @@
 //! - **Lock-free fast path**: Result storage uses RwLock with brief critical sections
 //! - **Clean shutdown**: Dropping the sender unblocks workers naturally
 
+use std::any::Any;
 use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, OnceLock};
+use std::panic::{self, AssertUnwindSafe};
 use std::thread::{self, JoinHandle};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use crossbeam_channel::{bounded, Receiver, Sender};
 use parking_lot::{Condvar, Mutex, RwLock};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use tracing::{debug, error, info, warn};
 
-use crate::config::WorkerPoolConfig;
-use crate::core::executor::WorkerExecutor;
-use crate::core::TaskMetadata;
-use crate::util::serde::MailboxKey;
+use crate::config::{RetryPolicy, WorkerPoolConfig, WorkerRuntimeKind};
+use crate::core::error::ZERO_COST_TASK;
+use crate::core::executor::{ExecutionOutcome, ExecutorContext, WorkerExecutor};
+use crate::core::{
+    build_audit_event, AuditSink, Progress, RateLimiter, ScheduledTask, SchedulerError, TaskMetadata,
+};
+use crate::util::serde::{append_json_line, read_json_lines, write_json_lines, MailboxKey, TaskId};
+use crate::util::telemetry::{PoolKind, PoolSnapshotMetrics, SnapshotSource};
+use crate::util::clock::{age_ms, now_ms};
 
-use super::{generate_mailbox_key, mailbox_key_to_string, PoolCounters, PoolError, PoolStats, WorkerTask};
+use crate::core::dead_letter::{
+    REASON_DEADLINE_EXPIRED, REASON_DEPENDENCY_FAILED, REASON_EXCEEDS_MAX_UNITS,
+    REASON_EXECUTION_TIMEOUT, REASON_QUEUE_FULL,
+};
+use crate::core::DeadLetterSink;
+#[cfg(feature = "otel")]
+use crate::util::telemetry::otel;
+
+use super::{
+    execute_with_retry, finish_task, CircuitBreaker, CircuitState, generate_mailbox_key, is_expired, mailbox_key_to_string,
+    record_dead_letter, DeadLetterSlot, Degradation, DependencyTracker, PushError, WorkQueue, PoolCounters, PoolError, PoolStats, ProgressChannels,
+    Refusal, TaskHandle, WorkerTask, time_until, run_completion_hook, CompletionSlot,
+};
 
 /// Result entry state.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -34,6 +57,10 @@ enum ResultState {
     Pending,
     /// Result is ready.
     Ready,
+    /// The task will never produce a result.
+    Discarded,
+    /// The task was cancelled after running past its per-task timeout.
+    TimedOut,
 }
 
 /// Result storage entry with Condvar-based notification.
@@ -42,38 +69,211 @@ struct ResultEntry<R> {
     result: Option<R>,
     /// State of this entry.
     state: ResultState,
+    /// Set for idempotent submissions and pools retaining results: every
+    /// retrieval gets a copy and the entry stays until its idempotency key
+    /// or retention expires.
+    retain: Option<fn(&R) -> R>,
+    /// How long a retained result is kept once stored.
+    keep_for: Option<Duration>,
+    /// When a result kept for `keep_for` expires.
+    expires_at_ms: Option<u128>,
+    /// How long `retrieve_default` waits, from `TaskMetadata::retrieve_timeout_ms`.
+    retrieve_timeout: Option<Duration>,
+}
+
+impl<R> ResultEntry<R> {
+    /// Hand out the result, keeping a retained entry's copy in place.
+    fn read(&mut self) -> Option<R> {
+        if self.expires_at_ms.is_some_and(|at| now_ms() >= at) {
+            self.result = None;
+            return None;
+        }
+        match self.retain {
+            Some(clone) => self.result.as_ref().map(clone),
+            None => self.result.take(),
+        }
+    }
+    
+    /// Read the result after waking from a wait on `condvar`.
+    ///
+    /// A take-once result wakes a single waiter when stored; that waiter wakes
+    /// the next one, which finds the result gone, and so on, so concurrent
+    /// waiters on the key fail promptly instead of sleeping out their timeout.
+    fn read_woken(&mut self, condvar: &Condvar) -> Option<R> {
+        let result = self.read();
+        if self.retain.is_none() {
+            condvar.notify_one();
+        }
+        result
+    }
 }
 
+/// Idempotency keys remembered by [`WorkerPool::with_idempotency`].
+struct Idempotency<R> {
+    /// How long a key (and its task's result) is kept.
+    ttl: Duration,
+    /// Copies a retained result for each retrieval.
+    clone_result: fn(&R) -> R,
+    /// Idempotency key to the original task's mailbox key and expiry time.
+    keys: Mutex<HashMap<String, (MailboxKey, u128)>>,
+}
+
+/// Results kept for every retrieval, see [`WorkerPool::with_result_retention`].
+struct Retention<R> {
+    /// How long a result is kept after it is stored.
+    ttl: Duration,
+    /// Copies a retained result for each retrieval.
+    clone_result: fn(&R) -> R,
+    /// Earliest time the next sweep of expired results runs.
+    next_sweep_ms: Mutex<u128>,
+}
+
+impl<R> Retention<R> {
+    /// Drop expired results and their progress channels, at most once per
+    /// `ttl`.
+    fn sweep(&self, results: &ResultStorage<R>, progress: &ProgressChannels) {
+        let now = now_ms();
+        let mut next_sweep_ms = self.next_sweep_ms.lock();
+        if now < *next_sweep_ms {
+            return;
+        }
+        *next_sweep_ms = now + self.ttl.as_millis();
+        drop(next_sweep_ms);
+        progress.close_evicted(&results.evict_expired(now));
+    }
+}
+
+/// Where [`WorkerPool::shutdown_drain`] writes the tasks still queued.
+struct Persistence<P> {
+    /// JSON-lines file of `ScheduledTask` records.
+    path: PathBuf,
+    /// Writes the tasks to `path`, replacing its contents.
+    write: fn(&Path, &[ScheduledTask<P>]) -> Result<(), SchedulerError>,
+}
+
+/// Where [`WorkerPool::with_result_persistence`] records finished results.
+///
+/// The file is a JSON-lines log of `(key, result)` records keyed by the
+/// mailbox key string: a stored result, or `null` once it has been handed out.
+struct ResultLog<R> {
+    /// JSON-lines file the records are appended to.
+    path: PathBuf,
+    /// Appends one record to `path`.
+    append: fn(&Path, &str, Option<&R>) -> Result<(), SchedulerError>,
+    /// Keeps records from different workers on separate lines.
+    lock: Mutex<()>,
+}
+
+impl<R> ResultLog<R> {
+    /// Append a record, logging instead of failing the task on error.
+    fn record(&self, key_str: &str, result: Option<&R>) {
+        let _guard = self.lock.lock();
+        if let Err(e) = (self.append)(&self.path, key_str, result) {
+            warn!(path = %self.path.display(), error = %e, "Could not persist result record");
+        }
+    }
+}
+
+/// Audit sink shared with workers; attached after the workers are spawned.
+type AuditSlot = Arc<Mutex<Option<Box<dyn AuditSink>>>>;
+
+/// Executor context shared with workers; set after the workers are spawned.
+type ContextSlot = Arc<Mutex<ExecutorContext>>;
+
+/// Task each worker is executing and when it started, indexed by worker id.
+type WorkerSlots = Arc<[Mutex<Option<(TaskId, u128)>>]>;
+
+/// What one worker thread is doing, as reported by [`WorkerPool::worker_states`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkerState {
+    /// Worker id, as in the `pl-worker-{id}` thread name.
+    pub id: usize,
+    /// Id of the task the worker is executing, `None` while idle.
+    pub current_task: Option<TaskId>,
+    /// When the current task started executing, in milliseconds since epoch.
+    pub started_at_ms: Option<u128>,
+    /// Largest task the worker runs, from `WorkerPoolConfig::worker_units`;
+    /// `None` when workers aren't weighted.
+    pub capacity_units: Option<u32>,
+}
+
+/// How long `WorkerPool` construction waits for its workers to be ready.
+const WORKER_STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Readiness a worker thread reports once its runtime is built, or why it
+/// could not build one.
+type WorkerReady = Result<(), String>;
+
+/// Factory for the Tokio runtime builder each worker thread starts from.
+///
+/// See [`WorkerPool::with_runtime_builder`].
+pub type RuntimeBuilderFn = Arc<dyn Fn() -> tokio::runtime::Builder + Send + Sync>;
+
+/// Shared handle to a result entry and the Condvar paired with its mutex.
+type EntryPair<R> = Arc<(Mutex<ResultEntry<R>>, Condvar)>;
+
 /// Result storage for the worker pool using Condvar for efficient waiting.
 /// 
 /// Design:
-/// - RwLock for the entry map (read-heavy, write on create/remove)
+/// - Entry map sharded by key hash, one RwLock per shard, so create/remove on
+///   different keys don't contend under high submission fan-out
 /// - Per-entry Mutex + Condvar for waiting (lock only when blocking wait needed)
 /// - Lock-free check via state atomic would be ideal but Condvar needs Mutex
 struct ResultStorage<R> {
-    /// Map from mailbox key to (entry, condvar) pair.
+    /// Shards mapping mailbox key to (entry, condvar) pair.
     /// The Condvar is used for blocking wait, paired with entry's mutex.
-    entries: RwLock<HashMap<String, Arc<(Mutex<ResultEntry<R>>, Condvar)>>>,
+    shards: Box<[RwLock<HashMap<String, EntryPair<R>>>]>,
+    /// Durable record of stored results, set by `with_result_persistence`.
+    log: OnceLock<ResultLog<R>>,
 }
 
 impl<R> ResultStorage<R> {
-    fn new() -> Self {
+    fn new(shard_count: usize) -> Self {
         Self {
-            entries: RwLock::new(HashMap::new()),
+            shards: (0..shard_count.max(1)).map(|_| RwLock::new(HashMap::new())).collect(),
+            log: OnceLock::new(),
         }
     }
     
-    /// Create a slot for a result.
-    fn create_slot(&self, key: &MailboxKey) {
+    /// Shard owning a key string.
+    fn shard(&self, key_str: &str) -> &RwLock<HashMap<String, EntryPair<R>>> {
+        let mut hasher = DefaultHasher::new();
+        key_str.hash(&mut hasher);
+        // Truncating the hash is fine; it only picks a shard
+        #[allow(clippy::cast_possible_truncation)]
+        let index = hasher.finish() as usize % self.shards.len();
+        &self.shards[index]
+    }
+    
+    /// Create a slot for a result, retained across retrievals if `retain` is
+    /// set, for `keep_for` after it is stored if that is set too.
+    ///
+    /// Returns `false`, leaving the store unchanged, if a result is already
+    /// pending or stored under `key`.
+    fn create_slot(
+        &self,
+        key: &MailboxKey,
+        retain: Option<fn(&R) -> R>,
+        keep_for: Option<Duration>,
+        retrieve_timeout: Option<Duration>,
+    ) -> bool {
         let key_str = mailbox_key_to_string(key);
         
         let entry = ResultEntry {
             result: None,
             state: ResultState::Pending,
+            retain,
+            keep_for,
+            expires_at_ms: None,
+            retrieve_timeout,
         };
         
-        let mut entries = self.entries.write();
+        let mut entries = self.shard(&key_str).write();
+        if entries.contains_key(&key_str) {
+            return false;
+        }
         entries.insert(key_str, Arc::new((Mutex::new(entry), Condvar::new())));
+        true
     }
     
     /// Store a result and notify any waiters.
@@ -81,32 +281,48 @@ impl<R> ResultStorage<R> {
     fn store(&self, key: &MailboxKey, result: R) {
         let key_str = mailbox_key_to_string(key);
         
+        // Persist before publishing, so a retrieved result is always on disk
+        if let Some(log) = self.log.get() {
+            log.record(&key_str, Some(&result));
+        }
+        
         // Read lock on map (fast, concurrent reads allowed)
-        let entries = self.entries.read();
+        let entries = self.shard(&key_str).read();
         if let Some(entry_pair) = entries.get(&key_str) {
             let (entry_mutex, condvar) = entry_pair.as_ref();
             // Brief lock on entry
             let mut entry = entry_mutex.lock();
             entry.result = Some(result);
             entry.state = ResultState::Ready;
-            // Notify ALL waiters (there should only be one, but be safe)
-            condvar.notify_all();
+            entry.expires_at_ms = entry.keep_for.map(|ttl| now_ms() + ttl.as_millis());
+            // A retained result is read by every waiter; a take-once result by
+            // exactly one, which passes the wakeup on (see `read_woken`).
+            // Waiters check the state under this lock before waiting, so none
+            // can miss the notification.
+            if entry.retain.is_some() {
+                condvar.notify_all();
+            } else {
+                condvar.notify_one();
+            }
         }
     }
     
     /// Try to retrieve a result immediately (non-blocking).
-    fn try_retrieve(&self, key: &MailboxKey) -> Option<R> {
+    ///
+    /// Returns `Ok(None)` while the task is still pending.
+    fn try_retrieve(&self, key: &MailboxKey) -> Result<Option<R>, PoolError> {
         let key_str = mailbox_key_to_string(key);
         
-        let entries = self.entries.read();
-        if let Some(entry_pair) = entries.get(&key_str) {
-            let (entry_mutex, _) = entry_pair.as_ref();
-            let mut entry = entry_mutex.lock();
-            if entry.state == ResultState::Ready {
-                return entry.result.take();
-            }
+        let not_found = || PoolError::ResultNotFound { key: key.clone() };
+        let entries = self.shard(&key_str).read();
+        let entry_pair = entries.get(&key_str).ok_or_else(not_found)?;
+        let mut entry = entry_pair.0.lock();
+        match entry.state {
+            ResultState::Pending => Ok(None),
+            ResultState::Ready => entry.read().map(Some).ok_or_else(not_found),
+            ResultState::Discarded => Err(not_found()),
+            ResultState::TimedOut => Err(PoolError::Timeout { key: key.clone() }),
         }
-        None
     }
     
     /// Wait for a result with timeout (blocking).
@@ -116,12 +332,13 @@ impl<R> ResultStorage<R> {
         
         // Get the entry pair (need to hold Arc while waiting)
         let entry_pair = {
-            let entries = self.entries.read();
+            let entries = self.shard(&key_str).read();
             entries.get(&key_str).cloned()
         };
         
+        let not_found = || PoolError::ResultNotFound { key: key.clone() };
         let Some(entry_pair) = entry_pair else {
-            return Err(PoolError::ResultNotFound);
+            return Err(not_found());
         };
         
         let (entry_mutex, condvar) = entry_pair.as_ref();
@@ -129,20 +346,52 @@ impl<R> ResultStorage<R> {
         
         // Fast path: result already ready
         if entry.state == ResultState::Ready {
-            return entry.result.take().ok_or(PoolError::ResultNotFound);
+            return entry.read().ok_or_else(not_found);
+        }
+        if entry.state == ResultState::Discarded {
+            return Err(not_found());
+        }
+        if entry.state == ResultState::TimedOut {
+            return Err(PoolError::Timeout { key: key.clone() });
         }
         
         // Wait with timeout using Condvar (NO POLLING)
         let wait_result = condvar.wait_for(&mut entry, timeout);
         
         if wait_result.timed_out() {
-            return Err(PoolError::Timeout);
+            return Err(PoolError::Timeout { key: key.clone() });
         }
         
-        if entry.state == ResultState::Ready {
-            entry.result.take().ok_or(PoolError::ResultNotFound)
-        } else {
-            Err(PoolError::Timeout)
+        match entry.state {
+            ResultState::Ready => entry.read_woken(condvar).ok_or_else(not_found),
+            ResultState::Discarded => Err(not_found()),
+            ResultState::Pending | ResultState::TimedOut => Err(PoolError::Timeout { key: key.clone() }),
+        }
+    }
+    
+    /// Mark an entry whose task was cancelled by its execution timeout, waking any waiters.
+    fn time_out(&self, key: &MailboxKey) {
+        let key_str = mailbox_key_to_string(key);
+        
+        let entries = self.shard(&key_str).read();
+        if let Some(entry_pair) = entries.get(&key_str) {
+            let (entry_mutex, condvar) = entry_pair.as_ref();
+            entry_mutex.lock().state = ResultState::TimedOut;
+            // Every waiter fails the same way, so wake them all
+            condvar.notify_all();
+        }
+    }
+    
+    /// Remove an entry whose task will never produce a result, waking any waiters.
+    fn discard(&self, key: &MailboxKey) {
+        let key_str = mailbox_key_to_string(key);
+        
+        let removed = self.shard(&key_str).write().remove(&key_str);
+        if let Some(entry_pair) = removed {
+            let (entry_mutex, condvar) = entry_pair.as_ref();
+            // Waiters that fetched the entry but have not started waiting see the state
+            entry_mutex.lock().state = ResultState::Discarded;
+            condvar.notify_all();
         }
     }
     
@@ -150,7 +399,7 @@ impl<R> ResultStorage<R> {
     fn remove(&self, key: &MailboxKey) -> Option<R> {
         let key_str = mailbox_key_to_string(key);
         
-        let mut entries = self.entries.write();
+        let mut entries = self.shard(&key_str).write();
         if let Some(entry_pair) = entries.remove(&key_str) {
             let (entry_mutex, _) = entry_pair.as_ref();
             let mut entry = entry_mutex.lock();
@@ -160,12 +409,69 @@ impl<R> ResultStorage<R> {
         }
     }
     
+    /// Remove an entry after it was retrieved, unless it is retained.
+    fn release(&self, key: &MailboxKey) {
+        let key_str = mailbox_key_to_string(key);
+        
+        let mut entries = self.shard(&key_str).write();
+        let retained = entries
+            .get(&key_str)
+            .is_some_and(|entry_pair| entry_pair.0.lock().retain.is_some());
+        if !retained && entries.remove(&key_str).is_some() {
+            drop(entries);
+            if let Some(log) = self.log.get() {
+                log.record(&key_str, None);
+            }
+        }
+    }
+    
+    /// Remove retained results that expired by `now`, returning their key
+    /// strings.
+    fn evict_expired(&self, now: u128) -> Vec<String> {
+        let mut evicted = Vec::new();
+        for shard in &self.shards {
+            shard.write().retain(|key_str, entry_pair| {
+                let keep = entry_pair.0.lock().expires_at_ms.is_none_or(|at| at > now);
+                if !keep {
+                    evicted.push(key_str.clone());
+                }
+                keep
+            });
+        }
+        if let Some(log) = self.log.get() {
+            for key_str in &evicted {
+                log.record(key_str, None);
+            }
+        }
+        evicted
+    }
+    
+    /// Add an entry holding an already finished result.
+    fn insert_ready(&self, key_str: String, result: R) {
+        let entry = ResultEntry {
+            result: Some(result),
+            state: ResultState::Ready,
+            retain: None,
+            keep_for: None,
+            expires_at_ms: None,
+            retrieve_timeout: None,
+        };
+        
+        let mut entries = self.shard(&key_str).write();
+        entries.insert(key_str, Arc::new((Mutex::new(entry), Condvar::new())));
+    }
+    
     /// Get entry for async waiting (returns clone of Arc).
-    fn get_entry(&self, key: &MailboxKey) -> Option<Arc<(Mutex<ResultEntry<R>>, Condvar)>> {
+    fn get_entry(&self, key: &MailboxKey) -> Option<EntryPair<R>> {
         let key_str = mailbox_key_to_string(key);
-        let entries = self.entries.read();
+        let entries = self.shard(&key_str).read();
         entries.get(&key_str).cloned()
     }
+    
+    /// Retrieval timeout the task behind `key` asked for, if any.
+    fn retrieve_timeout(&self, key: &MailboxKey) -> Option<Duration> {
+        self.get_entry(key)?.0.lock().retrieve_timeout
+    }
 }
 
 /// Worker pool with dedicated OS threads for CPU/GPU-bound work.
@@ -175,20 +481,21 @@ impl<R> ResultStorage<R> {
 ///
 /// # Design
 ///
-/// - **No polling**: Workers block on channel recv; results use Condvar
-/// - **Clean shutdown**: Dropping sender naturally unblocks all workers
+/// - **No polling**: Workers block on the task queue's Condvar; results use Condvar
+/// - **Priority order**: Queued tasks run highest priority first, FIFO within a priority
+/// - **Clean shutdown**: Closing the task queue naturally unblocks all workers
 /// - **Lock-free fast path**: Atomic counters, RwLock for read-heavy maps
 pub struct WorkerPool<P, R, E>
 where
     P: Send + 'static,
     R: Send + 'static,
-    E: WorkerExecutor<P, R>,
+    E: WorkerExecutor<P, R> + Clone,
 {
     /// Pool configuration.
     config: WorkerPoolConfig,
     
-    /// Task sender (to workers). Option allows clean shutdown by dropping.
-    task_tx: Mutex<Option<Sender<WorkerTask<P>>>>,
+    /// Priority queue of tasks waiting for a worker; closed on shutdown.
+    queue: Arc<WorkQueue<WorkerTask<P>>>,
     
     /// Result storage with Condvar-based notification.
     results: Arc<ResultStorage<R>>,
@@ -199,15 +506,57 @@ where
     /// Active resource units (lock-free atomic).
     active_units: Arc<AtomicU32>,
     
+    /// Current `max_units`, changed by `set_max_units`.
+    max_units: Arc<AtomicU32>,
+    
     /// Shutdown flag (lock-free atomic).
     shutdown: Arc<AtomicBool>,
     
     /// Worker thread handles.
     workers: Mutex<Vec<JoinHandle<()>>>,
     
+    /// Task each worker is executing (shared with workers).
+    worker_slots: WorkerSlots,
+    
     /// Task ID counter (lock-free atomic).
     task_id_counter: AtomicU64,
     
+    /// Dead-letter sink for dropped tasks (shared with workers).
+    dead_letter: DeadLetterSlot,
+    
+    /// Audit sink for executor panics (shared with workers).
+    audit: AuditSlot,
+    
+    /// Hook run as each task completes (shared with workers).
+    on_complete: CompletionSlot,
+    
+    /// Context handed to every executor invocation (shared with workers).
+    executor_context: ContextSlot,
+    
+    /// Per-task progress channels.
+    progress: ProgressChannels,
+    
+    /// Circuit breaker fed by task outcomes (shared with workers).
+    circuit: Arc<CircuitBreaker>,
+    
+    /// Graceful degradation applied to submissions under load.
+    degradation: Option<Degradation>,
+    
+    /// Per-tenant submission rate limits.
+    rate_limiter: Option<RateLimiter>,
+    
+    /// Deduplication of submissions carrying `TaskMetadata::idempotency_key`.
+    idempotency: Option<Idempotency<R>>,
+    
+    /// Results kept for every retrieval until they expire.
+    retention: Option<Retention<R>>,
+    
+    /// Tasks held back until their dependencies finish (shared with workers).
+    dependencies: Arc<DependencyTracker<WorkerTask<P>>>,
+    
+    /// File queued tasks are saved to by `shutdown_drain`.
+    persistence: Option<Persistence<P>>,
+    
     /// Phantom data for executor type.
     _executor: std::marker::PhantomData<E>,
 }
@@ -216,41 +565,151 @@ impl<P, R, E> WorkerPool<P, R, E>
 where
     P: Send + 'static,
     R: Send + 'static,
-    E: WorkerExecutor<P, R>,
+    E: WorkerExecutor<P, R> + Clone,
 {
     /// Create a new worker pool with the given configuration and executor.
     ///
     /// This spawns `config.worker_count` OS threads, each with its own
-    /// single-threaded tokio runtime for executing tasks.
+    /// tokio runtime (single-threaded unless `config.runtime_kind` says
+    /// otherwise) for executing tasks. It returns once every worker has
+    /// built its runtime, so the first task doesn't wait for one.
     ///
     /// # Errors
     ///
-    /// Returns `PoolError::InvalidConfig` if the configuration is invalid.
+    /// Returns `PoolError::InvalidConfig` if the configuration is invalid, or if
+    /// it enables retries (retrying needs a cloneable payload; use
+    /// [`new_retryable`](Self::new_retryable) instead), and
+    /// `PoolError::Internal` if a worker fails to build its runtime or isn't
+    /// ready within 30 seconds.
     pub fn new(config: WorkerPoolConfig, executor: E) -> Result<Self, PoolError> {
+        if config.retry.is_enabled() {
+            return Err(PoolError::InvalidConfig(
+                "retry policy requires a cloneable payload; use WorkerPool::new_retryable".into(),
+            ));
+        }
+        Self::build(config, executor, None, None)
+    }
+    
+    /// Create a worker pool whose worker runtimes come from `builder_fn`.
+    ///
+    /// Each worker thread calls `builder_fn` once, enables all drivers on the
+    /// returned builder and runs its tasks on the resulting runtime, in place
+    /// of the one `config.runtime_kind` would build. Use this to install
+    /// runtime hooks such as `on_thread_start` (e.g. to bind a GPU device).
+    /// The executor future itself is driven from the worker thread; hooks
+    /// apply to the runtime's own threads, where spawned sub-tasks run.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolError::InvalidConfig` under the same conditions as
+    /// [`new`](Self::new).
+    pub fn with_runtime_builder(
+        config: WorkerPoolConfig,
+        executor: E,
+        builder_fn: RuntimeBuilderFn,
+    ) -> Result<Self, PoolError> {
+        if config.retry.is_enabled() {
+            return Err(PoolError::InvalidConfig(
+                "retry policy requires a cloneable payload; use WorkerPool::new_retryable".into(),
+            ));
+        }
+        Self::build(config, executor, None, Some(builder_fn))
+    }
+    
+    /// Create a worker pool whose tasks are retried according to `config.retry`.
+    ///
+    /// Each attempt consumes a clone of the payload, so `P` must be `Clone`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolError::InvalidConfig` if the configuration is invalid.
+    pub fn new_retryable(config: WorkerPoolConfig, executor: E) -> Result<Self, PoolError>
+    where
+        P: Clone,
+    {
+        Self::build(config, executor, Some(P::clone), None)
+    }
+    
+    fn build(
+        config: WorkerPoolConfig,
+        executor: E,
+        clone_payload: Option<fn(&P) -> P>,
+        runtime_builder: Option<RuntimeBuilderFn>,
+    ) -> Result<Self, PoolError> {
         config.validate().map_err(PoolError::InvalidConfig)?;
         
-        let (task_tx, task_rx) = bounded::<WorkerTask<P>>(config.max_queue_depth);
-        let results = Arc::new(ResultStorage::new());
-        let counters = Arc::new(PoolCounters::default());
         let active_units = Arc::new(AtomicU32::new(0));
+        let max_units = Arc::new(AtomicU32::new(config.max_units));
+        let mut queue = WorkQueue::new(config.max_queue_depth).with_unit_budget(
+            Arc::clone(&active_units),
+            Arc::clone(&max_units),
+            |task: &WorkerTask<P>| task.meta.cost.units,
+        );
+        if let Some(max) = config.max_concurrent_tasks {
+            queue = queue.with_max_running(max);
+        }
+        if !config.worker_units.is_empty() {
+            queue = queue.with_weighted_workers();
+        }
+        let queue = Arc::new(queue);
+        let results = Arc::new(ResultStorage::new(config.result_shard_count()));
+        let counters = Arc::new(PoolCounters::default());
         let shutdown = Arc::new(AtomicBool::new(false));
+        let dead_letter: DeadLetterSlot = Arc::new(Mutex::new(None));
+        let audit: AuditSlot = Arc::new(Mutex::new(None));
+        let on_complete: CompletionSlot = Arc::new(Mutex::new(None));
+        let executor_context: ContextSlot = Arc::new(Mutex::new(ExecutorContext::default()));
+        let circuit = Arc::new(CircuitBreaker::new(config.circuit_breaker.clone()));
+        let dependencies = Arc::new(DependencyTracker::default());
+        let worker_slots: WorkerSlots =
+            (0..config.worker_count).map(|_| Mutex::new(None)).collect();
+        
+        let context = WorkerContext {
+            results: Arc::clone(&results),
+            counters: Arc::clone(&counters),
+            shutdown: Arc::clone(&shutdown),
+            dead_letter: Arc::clone(&dead_letter),
+            audit: Arc::clone(&audit),
+            on_complete: Arc::clone(&on_complete),
+            executor_context: Arc::clone(&executor_context),
+            circuit: Arc::clone(&circuit),
+            dependencies: Arc::clone(&dependencies),
+            queue: Arc::clone(&queue),
+            executor,
+            retry: config.retry.clone(),
+            clone_payload,
+            per_task_timeout: config.per_task_timeout(),
+            slow_task_threshold: config.slow_task_threshold(),
+            worker_slots: Arc::clone(&worker_slots),
+            worker_units: config.worker_units.clone().into(),
+        };
         
         // Spawn worker threads
         let mut workers = Vec::with_capacity(config.worker_count);
+        let (ready_tx, ready_rx) = mpsc::channel();
         
         for worker_id in 0..config.worker_count {
             let worker = spawn_worker(
                 worker_id,
-                task_rx.clone(),
-                Arc::clone(&results),
-                Arc::clone(&counters),
-                Arc::clone(&active_units),
-                Arc::clone(&shutdown),
-                executor.clone(),
+                context.clone(),
                 config.thread_stack_size,
+                config.runtime_kind,
+                config.thread_priority,
+                runtime_builder.clone(),
+                ready_tx.clone(),
             );
             workers.push(worker);
         }
+        drop(ready_tx);
+        
+        // Wait for every runtime, so the first tasks don't pay for building one
+        if let Err(reason) = await_workers(&ready_rx, config.worker_count, WORKER_STARTUP_TIMEOUT) {
+            error!(reason = %reason, "Worker pool failed to start");
+            // Release the workers that did start; they exit once the queue closes
+            shutdown.store(true, Ordering::Release);
+            queue.close();
+            return Err(PoolError::Internal(reason));
+        }
         
         info!(
             worker_count = config.worker_count,
@@ -261,17 +720,300 @@ where
         
         Ok(Self {
             config,
-            task_tx: Mutex::new(Some(task_tx)),
+            queue,
             results,
             counters,
             active_units,
+            max_units,
             shutdown,
             workers: Mutex::new(workers),
+            worker_slots,
             task_id_counter: AtomicU64::new(0),
+            dead_letter,
+            audit,
+            on_complete,
+            executor_context,
+            progress: ProgressChannels::default(),
+            circuit,
+            degradation: None,
+            rate_limiter: None,
+            idempotency: None,
+            retention: None,
+            dependencies,
+            persistence: None,
             _executor: std::marker::PhantomData,
         })
     }
     
+    /// Attach a dead-letter sink for tasks that are rejected, expire before
+    /// running, or exhaust their retries.
+    #[must_use]
+    pub fn with_dead_letter(self, sink: Box<dyn DeadLetterSink>) -> Self {
+        *self.dead_letter.lock() = Some(sink);
+        self
+    }
+    
+    /// Attach an audit sink recording executor panics.
+    ///
+    /// A panic is recorded as an `AuditEvent` with action `"panic"`, the
+    /// task's id, and the panic message in its payload. The worker still
+    /// exits afterwards.
+    #[must_use]
+    pub fn with_audit(self, sink: Box<dyn AuditSink>) -> Self {
+        *self.audit.lock() = Some(sink);
+        self
+    }
+    
+    /// Run `hook` as each task completes, with its result key and metadata.
+    ///
+    /// The worker calls it once per task that produces a result, successful
+    /// or not, just before publishing the result, so a caller woken by
+    /// `retrieve` sees the hook's side effects. Tasks that time out, expire or
+    /// are dropped never complete and skip it. The hook runs on the worker
+    /// and delays the result until it returns: keep it cheap and hand heavy
+    /// work such as database writes to another task or thread.
+    #[must_use]
+    pub fn with_on_complete<F>(self, hook: F) -> Self
+    where
+        F: Fn(&MailboxKey, &TaskMetadata) + Send + Sync + 'static,
+    {
+        *self.on_complete.lock() = Some(Arc::new(hook));
+        self
+    }
+    
+    /// Share `context` with every task: the executor receives it in
+    /// [`WorkerExecutor::execute_with_context`] and reads it with
+    /// [`ExecutorContext::get`].
+    ///
+    /// The value is constructed once and shared behind an `Arc`, so any
+    /// mutable state inside it must be synchronized.
+    #[must_use]
+    pub fn with_context<C: Send + Sync + 'static>(self, context: C) -> Self {
+        *self.executor_context.lock() = ExecutorContext::new(context);
+        self
+    }
+    
+    /// Enable graceful degradation: once at least `high_watermark` tasks are
+    /// queued, `degrade` rewrites the metadata of each new submission (e.g.
+    /// lowering `cost.units`) and the task is tagged `TaskMetadata::degraded`
+    /// so the executor can serve it on a cheaper path.
+    #[must_use]
+    pub fn with_degradation<F>(mut self, high_watermark: usize, degrade: F) -> Self
+    where
+        F: Fn(&mut TaskMetadata) + Send + Sync + 'static,
+    {
+        self.degradation = Some(Degradation {
+            high_watermark,
+            degrade: Box::new(degrade),
+        });
+        self
+    }
+    
+    /// Limit how fast each tenant (`MailboxKey::tenant`) may submit tasks.
+    ///
+    /// Submissions beyond a tenant's token bucket fail with
+    /// `PoolError::RateLimited` before they are queued.
+    #[must_use]
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+    
+    /// Deduplicate submissions by `TaskMetadata::idempotency_key`.
+    ///
+    /// A submission whose key was seen within `ttl` is not enqueued again;
+    /// it returns the original task's `MailboxKey` instead. The result of a
+    /// keyed task stays in the pool until the key expires, and every
+    /// retrieval receives a copy of it.
+    #[must_use]
+    pub fn with_idempotency(mut self, ttl: Duration) -> Self
+    where
+        R: Clone,
+    {
+        self.idempotency = Some(Idempotency {
+            ttl,
+            clone_result: R::clone,
+            keys: Mutex::new(HashMap::new()),
+        });
+        self
+    }
+    
+    /// Keep each result for `ttl` after its task finishes instead of handing
+    /// it to the first retrieval.
+    ///
+    /// Every `retrieve`, `retrieve_async` or `try_retrieve` of the key within
+    /// `ttl` receives a copy, so several consumers can share one task's
+    /// result. Afterwards the result is dropped and retrieving it returns
+    /// `PoolError::ResultNotFound`.
+    #[must_use]
+    pub fn with_result_retention(mut self, ttl: Duration) -> Self
+    where
+        R: Clone,
+    {
+        self.retention = Some(Retention {
+            ttl,
+            clone_result: R::clone,
+            next_sweep_ms: Mutex::new(0),
+        });
+        self
+    }
+    
+    /// Save the tasks still queued at [`shutdown_drain`](Self::shutdown_drain)
+    /// to `path`, a JSON-lines file of `ScheduledTask` records, so that
+    /// [`load_pending`](Self::load_pending) can resubmit them on the next boot.
+    #[must_use]
+    pub fn with_persistence(mut self, path: impl Into<PathBuf>) -> Self
+    where
+        P: Serialize,
+    {
+        self.persistence = Some(Persistence {
+            path: path.into(),
+            write: |path, tasks| write_json_lines(path, tasks),
+        });
+        self
+    }
+    
+    /// Resubmit the tasks a previous pool saved to `path` at
+    /// [`shutdown_drain`](Self::shutdown_drain).
+    ///
+    /// Returns the mailbox keys of the resubmitted tasks, in their saved
+    /// order. Tasks the pool rejects (for example because its queue is full)
+    /// are logged and written back to `path`, so a later call can retry
+    /// them; once every saved task has been resubmitted the file is deleted.
+    /// A missing file means there is nothing to load.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolError::Internal` if the file cannot be read, parsed,
+    /// rewritten or removed.
+    pub fn load_pending(&self, path: impl AsRef<Path>) -> Result<Vec<MailboxKey>, PoolError>
+    where
+        P: DeserializeOwned,
+    {
+        let path = path.as_ref();
+        let records: Vec<serde_json::Value> = read_json_lines(path)?;
+        if records.is_empty() {
+            return Ok(Vec::new());
+        }
+        // Parse every record up front so a malformed file resubmits nothing
+        let tasks = records
+            .iter()
+            .map(ScheduledTask::<P>::deserialize)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PoolError::Internal(e.to_string()))?;
+        
+        // Keep the raw record of each rejected task to write back
+        let mut keys = Vec::with_capacity(tasks.len());
+        let mut rejected = Vec::new();
+        for (task, record) in tasks.into_iter().zip(&records) {
+            let task_id = task.meta.id;
+            match self.submit(task.payload, task.meta) {
+                Ok(key) => keys.push(key),
+                Err(e) => {
+                    warn!(task_id = task_id, error = %e, "Could not resubmit saved task");
+                    rejected.push(record);
+                }
+            }
+        }
+        info!(count = keys.len(), kept = rejected.len(), "Resubmitted saved tasks");
+        if rejected.is_empty() {
+            std::fs::remove_file(path).map_err(|e| PoolError::Internal(e.to_string()))?;
+        } else {
+            write_json_lines(path, rejected)?;
+        }
+        Ok(keys)
+    }
+    
+    /// Record every finished result in `path`, a JSON-lines file keyed by
+    /// mailbox key, until it is retrieved, so that
+    /// [`load_results`](Self::load_results) can restore the results a
+    /// previous pool completed but never handed out.
+    ///
+    /// Each result is written by the worker that produced it before it
+    /// becomes retrievable. Pools with non-serializable results can't use
+    /// this and keep results in memory only.
+    #[must_use]
+    pub fn with_result_persistence(self, path: impl Into<PathBuf>) -> Self
+    where
+        R: Serialize,
+    {
+        let log = ResultLog {
+            path: path.into(),
+            append: |path, key_str, result| append_json_line(path, &(key_str, result)),
+            lock: Mutex::new(()),
+        };
+        if self.results.log.set(log).is_err() {
+            warn!("Result persistence is already configured; keeping the first file");
+        }
+        self
+    }
+    
+    /// Restore the results a previous pool recorded in `path` with
+    /// [`with_result_persistence`](Self::with_result_persistence) and never
+    /// handed out, so clients can still retrieve them by their mailbox keys.
+    ///
+    /// The file is rewritten to hold just the restored results, ready for
+    /// this pool to keep recording in it. Call this before submitting: new
+    /// tasks get mailbox keys past the restored ones. Restored results are
+    /// handed out once, whatever the pool's retention. Returns the number of
+    /// results restored; a missing file means there is nothing to load.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolError::Internal` if the file cannot be read, parsed or
+    /// rewritten.
+    pub fn load_results(&self, path: impl AsRef<Path>) -> Result<usize, PoolError>
+    where
+        R: Serialize + DeserializeOwned,
+    {
+        let path = path.as_ref();
+        let records: Vec<(String, Option<R>)> = read_json_lines(path)?;
+        if records.is_empty() {
+            return Ok(0);
+        }
+        
+        // Replay the log: a later record for a key replaces the earlier one
+        let mut order = Vec::new();
+        let mut pending: HashMap<String, R> = HashMap::new();
+        for (key_str, result) in records {
+            match result {
+                Some(result) => {
+                    if pending.insert(key_str.clone(), result).is_none() {
+                        order.push(key_str);
+                    }
+                }
+                None => {
+                    pending.remove(&key_str);
+                }
+            }
+        }
+        let restored: Vec<(String, R)> = order
+            .into_iter()
+            .filter_map(|key_str| pending.remove(&key_str).map(|result| (key_str, result)))
+            .collect();
+        write_json_lines(path, &restored)?;
+        
+        let count = restored.len();
+        for (key_str, result) in restored {
+            if let Some(task_id) = task_id_of_key(&key_str) {
+                self.task_id_counter.fetch_max(task_id + 1, Ordering::Relaxed);
+            }
+            self.results.insert_ready(key_str, result);
+        }
+        info!(count = count, "Restored persisted results");
+        Ok(count)
+    }
+    
+    /// A task id no other call returns, for callers that let the pool
+    /// number their tasks.
+    ///
+    /// Ids count up from 0 and are drawn from the same counter as the pool's
+    /// mailbox keys.
+    #[must_use]
+    pub fn next_task_id(&self) -> TaskId {
+        self.task_id_counter.fetch_add(1, Ordering::Relaxed)
+    }
+    
     /// Submit a task asynchronously.
     ///
     /// This method can be called from an async context and will not block.
@@ -283,6 +1025,14 @@ where
     /// # Errors
     ///
     /// - `PoolError::QueueFull` if the task queue is full
+    /// - `PoolError::CircuitOpen` if the circuit breaker is shedding load
+    /// - `PoolError::RateLimited` if the task's tenant exceeded its rate limit
+    /// - `PoolError::DependencyFailed` if a task in `meta.depends_on` failed
+    /// - `PoolError::DuplicateTaskId` if `enforce_unique_ids` is set and a
+    ///   task with `meta.id` is still in flight
+    /// - `PoolError::InvalidTask` if `meta.cost.units` is zero
+    /// - `PoolError::InsufficientCapacity` if `meta.cost.units` exceeds
+    ///   `max_units` or every worker's `worker_units`
     /// - `PoolError::PoolShutdown` if the pool has been shut down
     pub async fn submit_async(
         &self,
@@ -297,6 +1047,14 @@ where
     ///
     /// This method can be called from any context. The enqueue operation
     /// itself is non-blocking; it only fails immediately if the queue is full.
+    /// A task whose `meta.depends_on` names tasks still in flight is held back
+    /// until they all succeed; if one fails it is dropped and dead-lettered.
+    /// A task whose `meta.idempotency_key` was already submitted returns the
+    /// original task's key (see [`with_idempotency`](Self::with_idempotency)).
+    ///
+    /// The result is stored under `meta.mailbox` when that names a session,
+    /// as with [`submit_with_key`](Self::submit_with_key); otherwise the pool
+    /// generates a key, and `meta.mailbox` only tags the task with its tenant.
     ///
     /// # Returns
     ///
@@ -305,59 +1063,248 @@ where
     /// # Errors
     ///
     /// - `PoolError::QueueFull` if the task queue is full
+    /// - `PoolError::CircuitOpen` if the circuit breaker is shedding load
+    /// - `PoolError::RateLimited` if the task's tenant exceeded its rate limit
+    /// - `PoolError::DependencyFailed` if a task in `meta.depends_on` failed
+    /// - `PoolError::DuplicateTaskId` if `enforce_unique_ids` is set and a
+    ///   task with `meta.id` is still in flight
+    /// - `PoolError::InvalidConfig` if `meta.idempotency_key` is set but the
+    ///   pool was not built with `with_idempotency`
+    /// - `PoolError::InvalidTask` if `meta.cost.units` is zero
+    /// - `PoolError::InsufficientCapacity` if `meta.cost.units` exceeds
+    ///   `max_units` or every worker's `worker_units`
+    /// - `PoolError::KeyInUse` if `meta.mailbox` names a session whose result
+    ///   is still pending or unretrieved
     /// - `PoolError::PoolShutdown` if the pool has been shut down
     pub fn submit(&self, payload: P, meta: TaskMetadata) -> Result<MailboxKey, PoolError> {
+        let key = meta.mailbox.clone().filter(|key| key.session_id.is_some());
+        self.submit_keyed(payload, meta, key)
+    }
+    
+    /// Submit a task whose result is stored under a caller-chosen `key`.
+    ///
+    /// The result is retrieved by exactly that key, which lets a server
+    /// address results by tenant, user and session instead of handing out
+    /// pool-generated keys. Results are told apart by tenant and session, so
+    /// a key's `user_id` does not make it distinct. Otherwise behaves like
+    /// [`submit`](Self::submit).
+    ///
+    /// # Errors
+    ///
+    /// Same as [`submit`](Self::submit), plus `PoolError::KeyInUse` if a
+    /// result under `key` is still pending or unretrieved.
+    pub fn submit_with_key(
+        &self,
+        payload: P,
+        meta: TaskMetadata,
+        key: MailboxKey,
+    ) -> Result<MailboxKey, PoolError> {
+        self.submit_keyed(payload, meta, Some(key))
+    }
+    
+    /// Submit a task under `key`, or a generated key if `None`.
+    fn submit_keyed(
+        &self,
+        payload: P,
+        meta: TaskMetadata,
+        key: Option<MailboxKey>,
+    ) -> Result<MailboxKey, PoolError> {
         if self.shutdown.load(Ordering::Acquire) {
             return Err(PoolError::PoolShutdown);
         }
+        if meta.cost.units == 0 {
+            return Err(PoolError::InvalidTask(ZERO_COST_TASK.into()));
+        }
+        // A task larger than the pool, or with weighted workers than every
+        // worker, would never start
+        let max_units = self.max_units();
+        let capacity = self
+            .config
+            .worker_units
+            .iter()
+            .max()
+            .map_or(max_units, |&largest| largest.min(max_units));
+        if meta.cost.units > capacity {
+            warn!(
+                task_id = meta.id,
+                cost = meta.cost.units,
+                "Task rejected: cost exceeds pool capacity"
+            );
+            return Err(PoolError::InsufficientCapacity {
+                requested: meta.cost.units,
+                available: capacity,
+            });
+        }
+        
+        let Some(idempotency_key) = meta.idempotency_key.clone() else {
+            return self.submit_task(payload, meta, key, None);
+        };
+        let Some(idempotency) = &self.idempotency else {
+            return Err(PoolError::InvalidConfig(
+                "idempotency_key requires WorkerPool::with_idempotency".into(),
+            ));
+        };
+        
+        // Hold the key map across the submission so concurrent duplicates wait
+        let mut keys = idempotency.keys.lock();
+        let now = now_ms();
+        keys.retain(|_, (key, expires_at_ms)| {
+            let live = *expires_at_ms > now;
+            if !live {
+                self.results.discard(key);
+            }
+            live
+        });
+        if let Some((key, _)) = keys.get(&idempotency_key) {
+            debug!(idempotency_key = %idempotency_key, "Duplicate submission deduplicated");
+            return Ok(key.clone());
+        }
+        let key = self.submit_task(payload, meta, key, Some(idempotency.clone_result))?;
+        keys.insert(idempotency_key, (key.clone(), now + idempotency.ttl.as_millis()));
+        drop(keys);
+        Ok(key)
+    }
+    
+    /// Admit and enqueue a task, creating its result slot under `key` (or a
+    /// generated key) with `retain`.
+    fn submit_task(
+        &self,
+        payload: P,
+        meta: TaskMetadata,
+        key: Option<MailboxKey>,
+        retain: Option<fn(&R) -> R>,
+    ) -> Result<MailboxKey, PoolError> {
+        if let Some(limiter) = &self.rate_limiter {
+            limiter
+                .try_acquire_for(meta.mailbox.as_ref())
+                .map_err(|retry_after_ms| PoolError::RateLimited { retry_after_ms })?;
+        }
         
-        // Generate unique task ID and mailbox key
+        // Fail fast while the executor is unhealthy
+        let probe = self.circuit.admit()?;
+        
+        // Downgrade the task while the queue is under pressure
+        let mut meta = meta;
+        if let Some(degradation) = &self.degradation {
+            degradation.apply(&mut meta, &self.counters);
+        }
+        
+        // Generate unique task ID and mailbox key, unless the caller chose one
         let task_id = self.task_id_counter.fetch_add(1, Ordering::Relaxed);
-        let mailbox_key = generate_mailbox_key(task_id);
+        let mailbox_key = key.unwrap_or_else(|| generate_mailbox_key(task_id));
         
-        // Create result slot
-        self.results.create_slot(&mailbox_key);
+        // Create result slot and progress channel; idempotent results follow
+        // their key's lifetime instead of the retention TTL
+        let (retain, keep_for) = match (retain, &self.retention) {
+            (Some(clone), _) => (Some(clone), None),
+            (None, Some(retention)) => {
+                retention.sweep(&self.results, &self.progress);
+                (Some(retention.clone_result), Some(retention.ttl))
+            }
+            (None, None) => (None, None),
+        };
+        let retrieve_timeout = meta.retrieve_timeout_ms.map(Duration::from_millis);
+        if !self.results.create_slot(&mailbox_key, retain, keep_for, retrieve_timeout) {
+            warn!(task_id = meta.id, "Task rejected: its mailbox key is already in use");
+            return Err(PoolError::KeyInUse { key: mailbox_key });
+        }
+        let progress = self.progress.open(&mailbox_key);
         
         // Create the worker task
         let task = WorkerTask {
+            #[cfg(feature = "otel")]
+            span: otel::start_task_span(&meta),
             payload,
             meta,
             mailbox_key: mailbox_key.clone(),
+            progress,
+            probe,
+            enqueued_at_ms: now_ms(),
         };
         
-        // Get sender (brief lock)
-        let task_tx_guard = self.task_tx.lock();
-        let Some(task_tx) = task_tx_guard.as_ref() else {
-            // Pool is shutting down
-            self.results.remove(&mailbox_key);
-            return Err(PoolError::PoolShutdown);
+        // Hold the task back while any of its dependencies is still in flight
+        let meta_id = task.meta.id;
+        let task_cost = task.meta.cost.units;
+        let depends_on = task.meta.depends_on.clone();
+        let unique = self.config.enforce_unique_ids;
+        let task = match self.dependencies.submit(meta_id, &depends_on, task, unique) {
+            Ok(Some(task)) => task,
+            Ok(None) => {
+                self.counters.submitted_tasks.fetch_add(1, Ordering::Relaxed);
+                self.counters.queue_task(task_cost);
+                debug!(task_id = task_id, "Task parked until its dependencies complete");
+                return Ok(mailbox_key);
+            }
+            Err((Refusal::Duplicate, task)) => {
+                self.results.remove(&mailbox_key);
+                self.progress.close(&mailbox_key);
+                warn!(task_id = meta_id, "Task rejected: its id is already in flight");
+                #[cfg(feature = "otel")]
+                otel::end_span(&task.span, "rejected");
+                drop(task);
+                return Err(PoolError::DuplicateTaskId { id: meta_id });
+            }
+            Err((Refusal::DependencyFailed(failed), task)) => {
+                self.results.remove(&mailbox_key);
+                self.progress.close(&mailbox_key);
+                warn!(task_id = meta_id, dependency = failed, "Task rejected: a dependency failed");
+                #[cfg(feature = "otel")]
+                otel::end_span(&task.span, "rejected");
+                record_dead_letter(&self.dead_letter, task.meta, REASON_DEPENDENCY_FAILED);
+                return Err(PoolError::DependencyFailed { id: failed });
+            }
         };
         
         // Try to enqueue (non-blocking)
-        match task_tx.try_send(task) {
+        let priority = task.meta.priority;
+        match self.queue.try_push(task, priority) {
             Ok(()) => {
                 self.counters.submitted_tasks.fetch_add(1, Ordering::Relaxed);
-                self.counters.queued_tasks.fetch_add(1, Ordering::Relaxed);
+                self.counters.queue_task(task_cost);
                 debug!(task_id = task_id, "Task submitted to worker pool");
                 Ok(mailbox_key)
             }
-            Err(crossbeam_channel::TrySendError::Full(_)) => {
-                // Remove the result slot we created
+            Err(PushError::Full(task)) => {
+                // Remove the result slot and progress channel we created
                 self.results.remove(&mailbox_key);
+                self.progress.close(&mailbox_key);
                 warn!("Worker pool queue is full");
-                Err(PoolError::QueueFull)
+                #[cfg(feature = "otel")]
+                otel::end_span(&task.span, "rejected");
+                record_dead_letter(&self.dead_letter, task.meta, REASON_QUEUE_FULL);
+                self.settle_dependents(meta_id, false);
+                Err(PoolError::QueueFull {
+                    task_id: Some(meta_id),
+                    retry_after_ms: Some(self.counters.throughput.retry_after_ms()),
+                })
             }
-            Err(crossbeam_channel::TrySendError::Disconnected(_)) => {
+            Err(PushError::Closed(_)) => {
+                // Pool is shutting down
                 self.results.remove(&mailbox_key);
+                self.progress.close(&mailbox_key);
+                self.settle_dependents(meta_id, false);
                 Err(PoolError::PoolShutdown)
             }
         }
     }
     
+    /// Release or drop the dependents of a task that will not run after all.
+    fn settle_dependents(&self, id: TaskId, succeeded: bool) {
+        settle_dependents(
+            id,
+            succeeded,
+            &self.dependencies,
+            &self.queue,
+            &self.results,
+            &self.counters,
+            &self.dead_letter,
+        );
+    }
+    
     /// Retrieve a result asynchronously with timeout.
     ///
     /// This method waits for the result to become available or times out.
-    /// Uses tokio's async timing - no polling.
+    /// The wait runs on Tokio's blocking thread pool - no polling.
     ///
     /// # Errors
     ///
@@ -369,51 +1316,62 @@ where
         timeout: Duration,
     ) -> Result<R, PoolError> {
         // First, try immediate retrieval (fast path)
-        if let Some(result) = self.results.try_retrieve(key) {
-            self.results.remove(key);
+        if let Ok(Some(result)) = self.results.try_retrieve(key) {
+            self.results.release(key);
+            self.progress.close(key);
             return Ok(result);
         }
         
         // Get entry for waiting
         let entry_pair = self.results.get_entry(key)
-            .ok_or(PoolError::ResultNotFound)?;
+            .ok_or_else(|| PoolError::ResultNotFound { key: key.clone() })?;
         
         // Use tokio::task::spawn_blocking to wait on the parking_lot Condvar
         // This moves the blocking wait to tokio's blocking thread pool
-        // parking_lot's Condvar is significantly faster than std's
-        let key_clone = key.clone();
-        
-        let result = tokio::time::timeout(timeout, async move {
-            // Use spawn_blocking for the Condvar wait
-            tokio::task::spawn_blocking(move || {
-                let (entry_mutex, condvar) = entry_pair.as_ref();
-                let mut entry = entry_mutex.lock();
-                
-                // Check if already ready (fast path, no wait needed)
-                if entry.state == ResultState::Ready {
-                    return entry.result.take();
-                }
-                
-                // Wait on parking_lot Condvar (blocking, but in spawn_blocking thread)
-                // parking_lot's wait is more efficient than std::sync::Condvar
-                condvar.wait(&mut entry);
-                
-                if entry.state == ResultState::Ready {
-                    entry.result.take()
-                } else {
-                    None
-                }
-            }).await.ok().flatten()
+        // parking_lot's Condvar is significantly faster than std's.
+        // The blocking thread alone enforces the timeout: an outer async
+        // timeout could give up while the thread goes on to take the result,
+        // losing it. The deadline is fixed now so a late start doesn't
+        // extend the wait.
+        let waiter_key = key.clone();
+        let deadline = Instant::now() + timeout;
+        
+        let result = tokio::task::spawn_blocking(move || {
+            let not_found = || PoolError::ResultNotFound { key: waiter_key.clone() };
+            let (entry_mutex, condvar) = entry_pair.as_ref();
+            let mut entry = entry_mutex.lock();
+            
+            // Check if already ready (fast path, no wait needed)
+            if entry.state == ResultState::Ready {
+                return entry.read().ok_or_else(not_found);
+            }
+            if entry.state == ResultState::Discarded {
+                return Err(not_found());
+            }
+            if entry.state == ResultState::TimedOut {
+                return Err(PoolError::Timeout { key: waiter_key.clone() });
+            }
+            
+            // Wait on parking_lot Condvar (blocking, but in spawn_blocking thread)
+            // parking_lot's wait is more efficient than std::sync::Condvar.
+            // The wait is bounded so this thread exits even if the entry is
+            // removed and no result is ever stored.
+            if condvar.wait_until(&mut entry, deadline).timed_out() {
+                return Err(PoolError::Timeout { key: waiter_key.clone() });
+            }
+            
+            match entry.state {
+                ResultState::Ready => entry.read_woken(condvar).ok_or_else(not_found),
+                ResultState::TimedOut => Err(PoolError::Timeout { key: waiter_key.clone() }),
+                ResultState::Pending | ResultState::Discarded => Err(not_found()),
+            }
         }).await;
         
         // Clean up the entry
-        self.results.remove(&key_clone);
+        self.results.release(key);
+        self.progress.close(key);
         
-        match result {
-            Ok(Some(r)) => Ok(r),
-            Ok(None) => Err(PoolError::ResultNotFound),
-            Err(_) => Err(PoolError::Timeout),
-        }
+        result.unwrap_or_else(|_| Err(PoolError::ResultNotFound { key: key.clone() }))
     }
     
     /// Retrieve a result (blocking API) with timeout.
@@ -428,38 +1386,465 @@ where
     pub fn retrieve(&self, key: &MailboxKey, timeout: Duration) -> Result<R, PoolError> {
         let result = self.results.wait_for_result(key, timeout);
         // Clean up entry on any outcome
-        self.results.remove(key);
+        self.results.release(key);
+        self.progress.close(key);
+        result
+    }
+    
+    /// [`retrieve_async`](Self::retrieve_async) with the task's
+    /// `TaskMetadata::retrieve_timeout_ms`, else the pool's `default_timeout_ms`.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`retrieve_async`](Self::retrieve_async).
+    pub async fn retrieve_async_default(&self, key: &MailboxKey) -> Result<R, PoolError> {
+        self.retrieve_async(key, self.default_wait(key)).await
+    }
+    
+    /// [`retrieve`](Self::retrieve) with the task's
+    /// `TaskMetadata::retrieve_timeout_ms`, else the pool's `default_timeout_ms`.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`retrieve`](Self::retrieve).
+    pub fn retrieve_default(&self, key: &MailboxKey) -> Result<R, PoolError> {
+        self.retrieve(key, self.default_wait(key))
+    }
+    
+    /// [`retrieve_async`](Self::retrieve_async) waiting until `deadline_ms`,
+    /// in milliseconds since the epoch, instead of for a duration.
+    ///
+    /// Also available on WASM, so code that waits up to a request's deadline
+    /// compiles unchanged on both targets. A deadline already passed still
+    /// returns a result that is ready.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`retrieve_async`](Self::retrieve_async).
+    pub async fn retrieve_with_deadline(
+        &self,
+        key: &MailboxKey,
+        deadline_ms: u128,
+    ) -> Result<R, PoolError> {
+        self.retrieve_async(key, time_until(deadline_ms)).await
+    }
+    
+    /// How long the `*_default` retrievals wait for `key`.
+    fn default_wait(&self, key: &MailboxKey) -> Duration {
+        self.results
+            .retrieve_timeout(key)
+            .unwrap_or_else(|| self.config.default_timeout())
+    }
+    
+    /// Retrieve a result if it is ready, without waiting.
+    ///
+    /// Returns `Ok(None)` while the task is queued or running; the result can
+    /// then still be retrieved later. Otherwise the entry is released as with
+    /// [`retrieve`](Self::retrieve).
+    ///
+    /// # Errors
+    ///
+    /// - `PoolError::Timeout` if the task was cancelled by its execution timeout
+    /// - `PoolError::ResultNotFound` if the mailbox key is invalid, the result
+    ///   was already retrieved, or the task was cancelled
+    pub fn try_retrieve(&self, key: &MailboxKey) -> Result<Option<R>, PoolError> {
+        let result = self.results.try_retrieve(key);
+        if !matches!(result, Ok(None)) {
+            self.results.release(key);
+            self.progress.close(key);
+        }
         result
     }
     
+    /// Cancel a task that has not started running yet.
+    ///
+    /// The task is removed from the queue (or from waiting on its
+    /// dependencies) and its result slot is discarded, so retrieving it
+    /// returns `PoolError::ResultNotFound`. Its dependents are dropped as if
+    /// it had failed. Returns `false` if the task is already running or
+    /// finished, or the key is unknown.
+    pub fn cancel(&self, key: &MailboxKey) -> bool {
+        let matches = |task: &WorkerTask<P>| task.mailbox_key == *key;
+        let Some(task) = self
+            .queue
+            .remove(matches)
+            .or_else(|| self.dependencies.remove_parked(matches))
+        else {
+            return false;
+        };
+        
+        self.counters.unqueue_task(task.meta.cost.units);
+        self.results.discard(key);
+        self.progress.close(key);
+        if let Some(idempotency) = &self.idempotency {
+            idempotency.keys.lock().retain(|_, (original, _)| original != key);
+        }
+        #[cfg(feature = "otel")]
+        otel::end_span(&task.span, "cancelled");
+        debug!(task_id = task.meta.id, "Task cancelled");
+        self.settle_dependents(task.meta.id, false);
+        true
+    }
+    
+    /// Cancel every task of `tenant` that has not started running yet.
+    ///
+    /// A task belongs to the tenant named by its `meta.mailbox`; tasks
+    /// submitted without one belong to no tenant. Each match is cancelled as
+    /// with [`cancel`](Self::cancel), and tasks already running are left to
+    /// finish. Held-back dependents go before the tasks they wait on, so none
+    /// is dropped as a failed dependent instead. Returns the number of tasks
+    /// cancelled.
+    pub fn cancel_tenant(&self, tenant: &str) -> usize {
+        let of_tenant = |task: &WorkerTask<P>| {
+            task.meta
+                .mailbox
+                .as_ref()
+                .filter(|mailbox| mailbox.tenant == tenant)
+                .map(|_| task.mailbox_key.clone())
+        };
+        let mut keys: Vec<MailboxKey> =
+            self.queue.snapshot(of_tenant).into_iter().flatten().collect();
+        keys.extend(self.dependencies.parked(of_tenant).into_iter().flatten());
+        let cancelled = keys.iter().rev().filter(|key| self.cancel(key)).count();
+        debug!(tenant, cancelled, "Tenant tasks cancelled");
+        cancelled
+    }
+    
+    /// Submit a task and return a [`TaskHandle`] for its result.
+    ///
+    /// Equivalent to [`submit`](Self::submit), with the key wrapped together
+    /// with a reference to this pool.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`submit`](Self::submit).
+    pub fn submit_handle(&self, payload: P, meta: TaskMetadata) -> Result<TaskHandle<'_, P, R, E>, PoolError> {
+        let key = self.submit(payload, meta)?;
+        Ok(TaskHandle::new(self, key))
+    }
+    
+    /// What each worker thread is executing right now, by worker id.
+    ///
+    /// Meant for diagnosing a wedged pool: a worker holding the same task for
+    /// far longer than usual points at the stuck executor call.
+    #[must_use]
+    pub fn worker_states(&self) -> Vec<WorkerState> {
+        self.worker_slots
+            .iter()
+            .enumerate()
+            .map(|(id, slot)| {
+                let current = *slot.lock();
+                WorkerState {
+                    id,
+                    current_task: current.map(|(task_id, _)| task_id),
+                    started_at_ms: current.map(|(_, started_at_ms)| started_at_ms),
+                    capacity_units: self.config.worker_units.get(id).copied(),
+                }
+            })
+            .collect()
+    }
+    
+    /// Current state of the pool's circuit breaker.
+    ///
+    /// Always `Closed` when no breaker is configured.
+    #[must_use]
+    pub fn circuit_state(&self) -> CircuitState {
+        self.circuit.state()
+    }
+    
+    /// Subscribe to the progress updates emitted while a task runs.
+    ///
+    /// Updates arrive in the order the executor reported them, and the stream
+    /// disconnects once the task finishes. Subscribe before retrieving the
+    /// result: the channel is released on retrieval, after which (or for an
+    /// unknown key) the returned receiver is already disconnected.
+    #[must_use]
+    pub fn progress_stream(&self, key: &MailboxKey) -> flume::Receiver<Progress> {
+        self.progress.subscribe(key)
+    }
+    
+    /// Mailbox keys of running tasks that sent no heartbeat within `older_than`.
+    ///
+    /// A task's heartbeat starts when a worker picks it up and is refreshed by
+    /// every progress update its executor reports, so a task listed here has
+    /// been silent for at least the window and may be hung. Stalest first.
+    /// Tasks that never report progress are listed once they have run for
+    /// longer than the window. Nothing is cancelled; acting on the list is up
+    /// to the caller.
+    #[must_use]
+    pub fn stale_tasks(&self, older_than: Duration) -> Vec<MailboxKey> {
+        self.progress.stale(older_than)
+    }
+    
+    /// Mailbox keys of the tasks waiting for a worker, next to run first.
+    ///
+    /// Tasks held back by `depends_on` follow, oldest first.
+    #[must_use]
+    pub fn queued_keys(&self) -> Vec<MailboxKey> {
+        let mut keys = self.queue.snapshot(|task| task.mailbox_key.clone());
+        keys.extend(self.dependencies.parked(|task| task.mailbox_key.clone()));
+        keys
+    }
+    
+    /// `created_at_ms` of the oldest task waiting to run, queued or parked.
+    fn oldest_queued_at_ms(&self) -> Option<u128> {
+        let created_at = |task: &WorkerTask<P>| task.meta.created_at_ms;
+        let parked = self.dependencies.parked(created_at).into_iter().min();
+        self.queue.min_by_key(created_at).into_iter().chain(parked).min()
+    }
+    
+    /// Demand on the pool relative to its capacity: running plus queued
+    /// units over `max_units`.
+    ///
+    /// Read from atomics without locking, so it is cheap enough to check on
+    /// every request. Values above 1.0 mean work is waiting; the result is
+    /// capped at 10.0.
+    #[must_use]
+    pub fn load_factor(&self) -> f32 {
+        self.counters.load_factor(self.max_units())
+    }
+    
+    /// Current `max_units`, as configured or set by
+    /// [`set_max_units`](Self::set_max_units).
+    #[must_use]
+    pub fn max_units(&self) -> u32 {
+        self.max_units.load(Ordering::Acquire)
+    }
+    
+    /// Change `max_units` while the pool runs, e.g. after unloading a model
+    /// frees VRAM.
+    ///
+    /// Lowering it leaves running tasks alone; workers hold queued tasks back
+    /// until usage drops under the new limit, and new tasks larger than it
+    /// are rejected with `PoolError::InsufficientCapacity`. Waiting tasks
+    /// larger than the new limit, queued or parked behind their
+    /// dependencies, are dropped and dead-lettered, since they could never
+    /// start. Raising it lets workers start the queued tasks that now fit
+    /// right away.
+    pub fn set_max_units(&self, new_max: u32) {
+        let old_max = self.max_units.swap(new_max, Ordering::AcqRel);
+        info!(old_max, new_max, "WorkerPool max_units changed");
+        if new_max > old_max {
+            self.queue.budget_changed();
+        }
+        let oversized = |task: &WorkerTask<P>| task.meta.cost.units > new_max;
+        while let Some(task) = self
+            .queue
+            .remove(oversized)
+            .or_else(|| self.dependencies.remove_parked(oversized))
+        {
+            warn!(
+                task_id = task.meta.id,
+                cost = task.meta.cost.units,
+                max_units = new_max,
+                "Queued task no longer fits max_units, dropped"
+            );
+            let task_id = task.meta.id;
+            self.progress.close(&task.mailbox_key);
+            drop_parked_task(
+                task,
+                Some(REASON_EXCEEDS_MAX_UNITS),
+                &self.results,
+                &self.counters,
+                &self.dead_letter,
+            );
+            self.settle_dependents(task_id, false);
+        }
+    }
+    
+    /// Whether [`load_factor`](Self::load_factor) exceeds the configured
+    /// `overload_threshold`, e.g. to reject requests before submitting.
+    #[must_use]
+    pub fn is_overloaded(&self) -> bool {
+        self.load_factor() > self.config.overload_threshold
+    }
+    
     /// Get current pool statistics.
+    ///
+    /// `queued_tasks` and `queue_oldest_age_ms` are read from the task queue
+    /// itself rather than the submission counters.
     #[must_use]
     pub fn stats(&self) -> PoolStats {
-        let mut stats = self.counters.snapshot(self.config.worker_count, self.config.max_units);
+        let mut stats = self.counters.snapshot(self.config.worker_count, self.max_units());
+        stats.queued_tasks = queued_len(&self.queue, &self.dependencies);
+        stats.queue_oldest_age_ms = self.oldest_queued_at_ms().map(age_ms);
         stats.used_units = self.active_units.load(Ordering::Relaxed);
+        stats.paused = self.queue.is_paused();
+        stats.degradation_active = self
+            .degradation
+            .as_ref()
+            .is_some_and(|d| d.is_active(stats.queued_tasks));
         stats
     }
     
+    /// Register this pool's statistics with a Prometheus registry.
+    ///
+    /// Values are read from the pool's counters at scrape time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the metrics are already registered in `registry`.
+    #[cfg(feature = "metrics")]
+    pub fn register_metrics(&self, registry: &prometheus::Registry) -> Result<(), prometheus::Error> {
+        let counters = Arc::clone(&self.counters);
+        let active_units = Arc::clone(&self.active_units);
+        let queue = Arc::clone(&self.queue);
+        let dependencies = Arc::clone(&self.dependencies);
+        let worker_count = self.config.worker_count;
+        let max_units = Arc::clone(&self.max_units);
+        let exporter = crate::util::telemetry::PrometheusExporter::new(move || {
+            let mut stats = counters.snapshot(worker_count, max_units.load(Ordering::Relaxed));
+            stats.queued_tasks = queued_len(&queue, &dependencies);
+            stats.used_units = active_units.load(Ordering::Relaxed);
+            stats
+        })?;
+        registry.register(Box::new(exporter))
+    }
+    
+    /// Stop starting queued tasks, e.g. while a model is reloaded.
+    ///
+    /// Running tasks finish, and submissions keep succeeding up to
+    /// `max_queue_depth`; they wait in the queue until
+    /// [`resume`](Self::resume). `stats().paused` reports the state.
+    /// Shutting down a paused pool doesn't run the queued tasks.
+    pub fn pause(&self) {
+        self.queue.set_paused(true);
+        info!("Worker pool paused");
+    }
+    
+    /// Start queued tasks again after [`pause`](Self::pause).
+    pub fn resume(&self) {
+        self.queue.set_paused(false);
+        info!("Worker pool resumed");
+    }
+    
     /// Shut down the pool gracefully with timeout.
     ///
     /// This drops the task sender to unblock idle workers, then attempts to join
-    /// all workers with a reasonable timeout (2 seconds per worker).
+    /// each worker for up to `config.shutdown_timeout_ms` (2 seconds by default).
     /// 
     /// Workers that don't exit within the timeout are detached to prevent hangs.
     pub fn shutdown(&self) {
+        self.shutdown_with_timeout(self.config.shutdown_timeout());
+    }
+    
+    /// Shut down the pool like [`shutdown`](Self::shutdown), waiting up to
+    /// `per_worker` for each worker instead of the configured timeout.
+    pub fn shutdown_with_timeout(&self, per_worker: Duration) {
         // Check if already shut down
         if self.shutdown.swap(true, Ordering::AcqRel) {
             return; // Already shut down
         }
         
         info!("Shutting down worker pool");
+        self.stop_workers(per_worker);
+    }
+    
+    /// Shut down the pool from async code, awaiting the workers for up to
+    /// `timeout` in total without blocking the runtime.
+    ///
+    /// Like [`shutdown`](Self::shutdown), running tasks finish, queued ones
+    /// don't start, and workers that outlast the timeout are detached. Returns whether every worker
+    /// exited in time; a pool already shut down returns `true` at once.
+    /// Must be called within a Tokio runtime.
+    pub async fn close(&self, timeout: Duration) -> bool {
+        if self.shutdown.swap(true, Ordering::AcqRel) {
+            return true;
+        }
         
-        // Drop the sender to unblock all workers waiting on recv()
-        {
-            let mut task_tx = self.task_tx.lock();
-            *task_tx = None;
+        info!("Closing worker pool");
+        self.queue.close();
+        
+        // Join each worker on a helper thread, all against one deadline. Not
+        // `spawn_blocking`: a detached join would hold up the runtime's shutdown
+        let deadline = tokio::time::Instant::now() + timeout;
+        let joins: Vec<_> = self
+            .workers
+            .lock()
+            .drain(..)
+            .map(|worker| {
+                let (tx, rx) = tokio::sync::oneshot::channel();
+                thread::spawn(move || {
+                    let _ = tx.send(worker.join().is_ok());
+                });
+                rx
+            })
+            .collect();
+        let worker_count = joins.len();
+        let mut all_exited = true;
+        for (idx, join) in joins.into_iter().enumerate() {
+            match tokio::time::timeout_at(deadline, join).await {
+                Ok(Ok(true)) => debug!(worker_id = idx, "Worker joined successfully"),
+                Ok(_) => warn!(worker_id = idx, "Worker panicked"),
+                Err(_) => {
+                    warn!(worker_id = idx, "Worker did not exit within timeout - detaching");
+                    all_exited = false;
+                }
+            }
+        }
+        
+        info!(worker_count = worker_count, "Worker pool closed");
+        all_exited
+    }
+    
+    /// Shut down the pool, saving the tasks that have not started yet instead
+    /// of running them.
+    ///
+    /// Queued tasks, and tasks still waiting on their dependencies, are
+    /// written to the file given to [`with_persistence`](Self::with_persistence)
+    /// and their results are discarded. Tasks already running complete as
+    /// with [`shutdown`](Self::shutdown). Returns the number of tasks saved.
+    ///
+    /// # Errors
+    ///
+    /// - `PoolError::InvalidConfig` if the pool was not built with `with_persistence`
+    /// - `PoolError::PoolShutdown` if the pool has already been shut down
+    /// - `PoolError::Internal` if the tasks cannot be written; the pool is
+    ///   shut down regardless
+    pub fn shutdown_drain(&self) -> Result<usize, PoolError> {
+        let Some(persistence) = &self.persistence else {
+            return Err(PoolError::InvalidConfig(
+                "shutdown_drain requires WorkerPool::with_persistence".into(),
+            ));
+        };
+        if self.shutdown.swap(true, Ordering::AcqRel) {
+            return Err(PoolError::PoolShutdown);
         }
         
+        info!("Shutting down worker pool, saving queued tasks");
+        
+        // Take the waiting tasks before closing the queue, so workers exit
+        // instead of running them
+        let mut drained = self.queue.drain();
+        drained.extend(self.dependencies.drain_parked());
+        let count = drained.len();
+        let tasks: Vec<ScheduledTask<P>> = drained
+            .into_iter()
+            .map(|task| {
+                self.counters.unqueue_task(task.meta.cost.units);
+                self.results.discard(&task.mailbox_key);
+                self.progress.close(&task.mailbox_key);
+                #[cfg(feature = "otel")]
+                otel::end_span(&task.span, "persisted");
+                ScheduledTask {
+                    meta: task.meta,
+                    payload: task.payload,
+                }
+            })
+            .collect();
+        let written = (persistence.write)(&persistence.path, &tasks);
+        
+        self.stop_workers(self.config.shutdown_timeout());
+        written?;
+        info!(count = count, "Saved queued tasks");
+        Ok(count)
+    }
+    
+    /// Close the queue and join the workers.
+    fn stop_workers(&self, per_worker: Duration) {
+        // Close the queue to unblock all idle workers
+        self.queue.close();
+        
         // Join workers with timeout
         let mut workers = self.workers.lock();
         let worker_count = workers.len();
@@ -472,41 +1857,63 @@ where
                 let _ = tx.send(result.is_ok());
             });
             
-            // Wait up to 2 seconds for this worker to exit
-            match rx.recv_timeout(Duration::from_secs(2)) {
-                Ok(true) => {
-                    debug!(worker_id = idx, "Worker joined successfully");
-                }
-                Ok(false) => {
-                    warn!(worker_id = idx, "Worker panicked");
+            // Wait up to `per_worker` for this worker to exit
+            match rx.recv_timeout(per_worker) {
+                Ok(joined) => {
+                    if joined {
+                        debug!(worker_id = idx, "Worker joined successfully");
+                    } else {
+                        warn!(worker_id = idx, "Worker panicked");
+                    }
+                    // Clean up join thread
+                    let _ = join_thread.join();
                 }
                 Err(_) => {
                     warn!(worker_id = idx, "Worker did not exit within timeout - detaching");
                     // Detach the join thread - worker will eventually exit
+                    drop(join_thread);
                 }
             }
-            
-            // Clean up join thread
-            let _ = join_thread.join();
         }
         
         info!(worker_count = worker_count, "Worker pool shut down complete");
     }
 }
 
+impl<P, R, E> SnapshotSource for WorkerPool<P, R, E>
+where
+    P: Send + 'static,
+    R: Send + 'static,
+    E: WorkerExecutor<P, R> + Clone,
+{
+    fn snapshot_metrics(&self, name: &str) -> PoolSnapshotMetrics {
+        let stats = self.stats();
+        PoolSnapshotMetrics {
+            name: name.to_string(),
+            kind: PoolKind::Worker,
+            used_units: stats.used_units,
+            total_units: stats.total_units,
+            active_tasks: stats.active_tasks,
+            queue_depth: stats.queued_tasks,
+            queue_oldest_age_ms: stats.queue_oldest_age_ms,
+            completed_tasks: stats.completed_tasks,
+            failed_tasks: stats.failed_tasks,
+        }
+    }
+}
+
 impl<P, R, E> Drop for WorkerPool<P, R, E>
 where
     P: Send + 'static,
     R: Send + 'static,
-    E: WorkerExecutor<P, R>,
+    E: WorkerExecutor<P, R> + Clone,
 {
     fn drop(&mut self) {
         // Signal shutdown but DON'T join workers in Drop
         // This prevents test hangs when pools are dropped with tasks still running
         if !self.shutdown.swap(true, Ordering::AcqRel) {
-            // Drop the sender to unblock waiting workers
-            let mut task_tx = self.task_tx.lock();
-            *task_tx = None;
+            // Close the queue to unblock waiting workers
+            self.queue.close();
             
             // DON'T join workers here - let OS clean up threads
             // Explicit shutdown() is required for graceful cleanup
@@ -515,33 +1922,182 @@ where
     }
 }
 
-/// Spawn a worker thread.
-fn spawn_worker<P, R, E>(
-    worker_id: usize,
-    task_rx: Receiver<WorkerTask<P>>,
+/// State shared by every worker thread of a pool.
+struct WorkerContext<P, R, E> {
+    /// Result storage with Condvar-based notification.
     results: Arc<ResultStorage<R>>,
+    /// Pool statistics counters.
     counters: Arc<PoolCounters>,
-    active_units: Arc<AtomicU32>,
+    /// Shutdown flag.
     shutdown: Arc<AtomicBool>,
+    /// Dead-letter sink for dropped tasks.
+    dead_letter: DeadLetterSlot,
+    /// Audit sink for executor panics.
+    audit: AuditSlot,
+    /// Hook run as each task completes.
+    on_complete: CompletionSlot,
+    /// Context handed to every executor invocation.
+    executor_context: ContextSlot,
+    /// Circuit breaker fed by task outcomes.
+    circuit: Arc<CircuitBreaker>,
+    /// Tasks held back until their dependencies finish.
+    dependencies: Arc<DependencyTracker<WorkerTask<P>>>,
+    /// Task queue workers pop from and released dependents are pushed to.
+    queue: Arc<WorkQueue<WorkerTask<P>>>,
+    /// Executor cloned into each worker.
     executor: E,
+    /// Retry policy for retryable failures.
+    retry: RetryPolicy,
+    /// Payload cloner; `None` when the payload type cannot be retried.
+    clone_payload: Option<fn(&P) -> P>,
+    /// Bound on each task's execution, including its retries.
+    per_task_timeout: Option<Duration>,
+    /// Execution time past which a task is logged as slow.
+    slow_task_threshold: Option<Duration>,
+    /// Task each worker is executing.
+    worker_slots: WorkerSlots,
+    /// Unit capacity of each worker; empty when workers aren't weighted.
+    worker_units: Arc<[u32]>,
+}
+
+impl<P, R, E: Clone> Clone for WorkerContext<P, R, E> {
+    fn clone(&self) -> Self {
+        Self {
+            results: Arc::clone(&self.results),
+            counters: Arc::clone(&self.counters),
+            shutdown: Arc::clone(&self.shutdown),
+            dead_letter: Arc::clone(&self.dead_letter),
+            audit: Arc::clone(&self.audit),
+            on_complete: Arc::clone(&self.on_complete),
+            executor_context: Arc::clone(&self.executor_context),
+            circuit: Arc::clone(&self.circuit),
+            dependencies: Arc::clone(&self.dependencies),
+            queue: Arc::clone(&self.queue),
+            executor: self.executor.clone(),
+            retry: self.retry.clone(),
+            clone_payload: self.clone_payload,
+            per_task_timeout: self.per_task_timeout,
+            slow_task_threshold: self.slow_task_threshold,
+            worker_slots: Arc::clone(&self.worker_slots),
+            worker_units: Arc::clone(&self.worker_units),
+        }
+    }
+}
+
+/// Set the calling worker thread's nice value, warning if the OS refuses.
+#[cfg(unix)]
+fn set_thread_priority(worker_id: usize, nice: i32) {
+    use thread_priority::{set_current_thread_priority, ThreadPriority, ThreadPriorityValue};
+    
+    // Map -20..=19 onto the crate's 0..=99 scale (99 highest); the `- 1`
+    // keeps its conversion back to a nice value from landing a step lower
+    let scaled = (99 * (19 - nice.clamp(-20, 19)) - 1).max(0) / 39;
+    let Some(value) = u8::try_from(scaled)
+        .ok()
+        .and_then(|scaled| ThreadPriorityValue::try_from(scaled).ok())
+    else {
+        return;
+    };
+    if let Err(error) = set_current_thread_priority(ThreadPriority::Crossplatform(value)) {
+        warn!(
+            worker_id = worker_id,
+            nice = nice,
+            error = %error,
+            "Failed to set worker thread priority"
+        );
+    }
+}
+
+/// Thread priorities are only applied on Unix.
+#[cfg(not(unix))]
+fn set_thread_priority(worker_id: usize, nice: i32) {
+    warn!(
+        worker_id = worker_id,
+        nice = nice,
+        "Worker thread priority is not supported on this platform"
+    );
+}
+
+/// Wait up to `timeout` for `count` workers to report their runtime built.
+///
+/// A worker thread that exits without reporting (e.g. a panicking runtime
+/// builder) drops its sender, which ends the wait early.
+fn await_workers(ready: &mpsc::Receiver<WorkerReady>, count: usize, timeout: Duration) -> WorkerReady {
+    let deadline = Instant::now() + timeout;
+    for _ in 0..count {
+        match ready.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(Ok(())) => {}
+            Ok(Err(reason)) => return Err(reason),
+            Err(RecvTimeoutError::Timeout) => {
+                return Err(format!("workers not ready within {timeout:?}"));
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err("a worker thread exited during startup".into());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Spawn a worker thread.
+#[allow(clippy::too_many_lines)]
+fn spawn_worker<P, R, E>(
+    worker_id: usize,
+    context: WorkerContext<P, R, E>,
     stack_size: usize,
+    runtime_kind: WorkerRuntimeKind,
+    thread_priority: Option<i32>,
+    runtime_builder: Option<RuntimeBuilderFn>,
+    ready: mpsc::Sender<WorkerReady>,
 ) -> JoinHandle<()>
 where
     P: Send + 'static,
     R: Send + 'static,
-    E: WorkerExecutor<P, R>,
+    E: WorkerExecutor<P, R> + Clone,
 {
     thread::Builder::new()
         .name(format!("pl-worker-{worker_id}"))
         .stack_size(stack_size)
         .spawn(move || {
             debug!(worker_id = worker_id, "Worker thread started");
+            if let Some(nice) = thread_priority {
+                set_thread_priority(worker_id, nice);
+            }
+            let WorkerContext {
+                results,
+                counters,
+                shutdown,
+                dead_letter,
+                audit,
+                on_complete,
+                executor_context,
+                circuit,
+                dependencies,
+                queue,
+                executor,
+                retry,
+                clone_payload,
+                per_task_timeout,
+                slow_task_threshold,
+                worker_slots,
+                worker_units,
+            } = context;
+            let slot = &worker_slots[worker_id];
+            let capacity = worker_units.get(worker_id).copied();
             
-            // Each worker has its own single-threaded tokio runtime
-            let rt = match tokio::runtime::Builder::new_current_thread()
-                .enable_all()
-                .build()
-            {
+            // Each worker has its own tokio runtime, single-threaded unless configured otherwise
+            let mut builder = match (runtime_builder, runtime_kind) {
+                (Some(builder_fn), _) => builder_fn(),
+                (None, WorkerRuntimeKind::CurrentThread) => tokio::runtime::Builder::new_current_thread(),
+                (None, WorkerRuntimeKind::MultiThread { threads }) => {
+                    let mut builder = tokio::runtime::Builder::new_multi_thread();
+                    builder
+                        .worker_threads(threads)
+                        .thread_name(format!("pl-worker-{worker_id}-rt"));
+                    builder
+                }
+            };
+            let rt = match builder.enable_all().build() {
                 Ok(rt) => rt,
                 Err(e) => {
                     error!(
@@ -549,38 +2105,72 @@ where
                         error = %e,
                         "Failed to create worker runtime"
                     );
+                    let _ = ready.send(Err(format!("worker {worker_id} failed to create its runtime: {e}")));
                     return;
                 }
             };
+            let _ = ready.send(Ok(()));
+            drop(ready);
             
-            // Worker loop - blocking recv, NO POLLING
-            // When sender is dropped, recv() returns Err and worker exits
+            // Worker loop - blocking pop, NO POLLING
+            // Once the queue is closed and empty, pop() returns None and worker exits
             loop {
                 // Block waiting for a task
                 // This is efficient - thread sleeps until work arrives
-                // When sender is dropped (shutdown), recv returns Err
-                let task = match task_rx.recv() {
-                    Ok(task) => task,
-                    Err(_) => {
-                        // Channel closed (sender dropped) - clean exit
-                        debug!(worker_id = worker_id, "Worker channel closed, exiting");
-                        break;
-                    }
+                let popped = capacity.map_or_else(
+                    || queue.pop(),
+                    |units| queue.pop_fitting(units, |task| task.meta.cost.units),
+                );
+                let Some(task) = popped else {
+                    // Queue closed (shutdown) - clean exit
+                    debug!(worker_id = worker_id, "Worker queue closed, exiting");
+                    break;
                 };
+                let _running = FinishOnDrop(&queue);
                 
                 // Check shutdown flag (in case of shutdown during task processing)
                 if shutdown.load(Ordering::Acquire) {
+                    queue.release_units(task.meta.cost.units);
                     debug!(worker_id = worker_id, "Worker shutdown during task, exiting");
                     break;
                 }
                 
+                // Drop tasks whose deadline passed while they were queued
+                if is_expired(&task.meta) {
+                    queue.release_units(task.meta.cost.units);
+                    counters.unqueue_task(task.meta.cost.units);
+                    counters.failed_tasks.fetch_add(1, Ordering::Relaxed);
+                    warn!(
+                        worker_id = worker_id,
+                        task_id = task.meta.id,
+                        "Task deadline expired before execution"
+                    );
+                    #[cfg(feature = "otel")]
+                    otel::end_span(&task.span, "expired");
+                    let task_id = task.meta.id;
+                    record_dead_letter(&dead_letter, task.meta, REASON_DEADLINE_EXPIRED);
+                    results.discard(&task.mailbox_key);
+                    settle_dependents(
+                        task_id,
+                        false,
+                        &dependencies,
+                        &queue,
+                        &results,
+                        &counters,
+                        &dead_letter,
+                    );
+                    continue;
+                }
+                
                 // Update counters (lock-free atomics)
-                counters.queued_tasks.fetch_sub(1, Ordering::Relaxed);
+                counters.unqueue_task(task.meta.cost.units);
+                counters.wait_ms.record_since(task.enqueued_at_ms);
                 counters.active_tasks.fetch_add(1, Ordering::Relaxed);
-                active_units.fetch_add(task.meta.cost.units, Ordering::Relaxed);
+                counters.acquire_kind_units(task.meta.cost.kind, task.meta.cost.units);
                 
                 let task_id = task.meta.id;
                 let task_cost = task.meta.cost.units;
+                let task_kind = task.meta.cost.kind;
                 let mailbox_key = task.mailbox_key.clone();
                 
                 debug!(
@@ -590,10 +2180,101 @@ where
                     "Worker executing task"
                 );
                 
-                // Execute the task in this worker's runtime
-                let result = rt.block_on(async {
-                    executor.execute(task.payload, task.meta).await
-                });
+                #[cfg(feature = "otel")]
+                let execute_cx = otel::start_execute_span(&task.span, &task.meta);
+                #[cfg(feature = "otel")]
+                let execute_guard = execute_cx.clone().attach();
+                
+                // Execute the task in this worker's runtime, retrying transient
+                // failures; a task past its timeout is cancelled by dropping it
+                task.progress.set_running(true);
+                let started_at_ms = now_ms();
+                *slot.lock() = Some((task_id, started_at_ms));
+                let shared_context = executor_context.lock().clone();
+                let execution = execute_with_retry(
+                    &executor,
+                    task.payload,
+                    &task.meta,
+                    &retry,
+                    clone_payload,
+                    &counters,
+                    &task.progress,
+                    &shared_context,
+                );
+                // A panicking executor is audited and its task failed like a
+                // timed-out one, then the panic still takes its worker down
+                let executed = match panic::catch_unwind(AssertUnwindSafe(|| {
+                    rt.block_on(async {
+                        match per_task_timeout {
+                            Some(limit) => tokio::time::timeout(limit, execution).await.ok(),
+                            None => Some(execution.await),
+                        }
+                    })
+                })) {
+                    Ok(executed) => executed,
+                    Err(panic) => {
+                        *slot.lock() = None;
+                        task.progress.set_running(false);
+                        results.discard(&mailbox_key);
+                        counters.active_tasks.fetch_sub(1, Ordering::Relaxed);
+                        queue.release_units(task_cost);
+                        counters.release_kind_units(task_kind, task_cost);
+                        counters.record_outcome(ExecutionOutcome::Failed);
+                        circuit.record(ExecutionOutcome::Failed, task.probe);
+                        settle_dependents(
+                            task_id,
+                            false,
+                            &dependencies,
+                            &queue,
+                            &results,
+                            &counters,
+                            &dead_letter,
+                        );
+                        record_panic(&audit, worker_id, &task.meta, panic.as_ref());
+                        panic::resume_unwind(panic);
+                    }
+                };
+                *slot.lock() = None;
+                task.progress.set_running(false);
+                counters.record_execution(&task.meta, started_at_ms, slow_task_threshold);
+                
+                let Some((result, outcome)) = executed else {
+                    #[cfg(feature = "otel")]
+                    {
+                        drop(execute_guard);
+                        otel::end_span(&execute_cx, "timed_out");
+                        otel::end_span(&task.span, "timed_out");
+                    }
+                    warn!(
+                        worker_id = worker_id,
+                        task_id = task_id,
+                        "Task exceeded its execution timeout, cancelled"
+                    );
+                    results.time_out(&mailbox_key);
+                    counters.active_tasks.fetch_sub(1, Ordering::Relaxed);
+                    queue.release_units(task_cost);
+                    counters.release_kind_units(task_kind, task_cost);
+                    counters.record_outcome(ExecutionOutcome::Failed);
+                    circuit.record(ExecutionOutcome::Failed, task.probe);
+                    record_dead_letter(&dead_letter, task.meta, REASON_EXECUTION_TIMEOUT);
+                    settle_dependents(
+                        task_id,
+                        false,
+                        &dependencies,
+                        &queue,
+                        &results,
+                        &counters,
+                        &dead_letter,
+                    );
+                    continue;
+                };
+                
+                #[cfg(feature = "otel")]
+                {
+                    drop(execute_guard);
+                    otel::end_span(&execute_cx, outcome.as_str());
+                    otel::end_span(&task.span, outcome.as_str());
+                }
                 
                 debug!(
                     worker_id = worker_id,
@@ -601,13 +2282,35 @@ where
                     "Worker completed task"
                 );
                 
-                // Store result and notify waiters (via Condvar)
+                // Run the completion hook, then store the result and notify
+                // waiters (via Condvar)
+                run_completion_hook(&on_complete, &mailbox_key, &task.meta);
                 results.store(&mailbox_key, result);
                 
                 // Update counters (lock-free atomics)
                 counters.active_tasks.fetch_sub(1, Ordering::Relaxed);
-                active_units.fetch_sub(task_cost, Ordering::Relaxed);
-                counters.completed_tasks.fetch_add(1, Ordering::Relaxed);
+                queue.release_units(task_cost);
+                counters.release_kind_units(task_kind, task_cost);
+                finish_task(
+                    &counters,
+                    &dead_letter,
+                    &retry,
+                    &circuit,
+                    task.meta,
+                    outcome,
+                    task.probe,
+                );
+                
+                // Release (or drop) tasks waiting on this one
+                settle_dependents(
+                    task_id,
+                    outcome == ExecutionOutcome::Success,
+                    &dependencies,
+                    &queue,
+                    &results,
+                    &counters,
+                    &dead_letter,
+                );
             }
             
             debug!(worker_id = worker_id, "Worker thread exiting");
@@ -615,6 +2318,111 @@ where
         .expect("Failed to spawn worker thread")
 }
 
+/// Calls [`WorkQueue::finish`] when dropped, so a popped task gives back its
+/// running slot however the worker leaves it, panics included.
+struct FinishOnDrop<'a, T>(&'a WorkQueue<T>);
+
+impl<T> Drop for FinishOnDrop<'_, T> {
+    fn drop(&mut self) {
+        self.0.finish();
+    }
+}
+
+/// Settle the dependents of a finished task: enqueue those whose dependencies
+/// have all succeeded and drop those whose dependency failed. A released task
+/// that cannot be enqueued is dropped too, and its own dependents with it.
+fn settle_dependents<P, R>(
+    id: TaskId,
+    succeeded: bool,
+    dependencies: &DependencyTracker<WorkerTask<P>>,
+    queue: &WorkQueue<WorkerTask<P>>,
+    results: &ResultStorage<R>,
+    counters: &PoolCounters,
+    dead_letter: &DeadLetterSlot,
+) {
+    let mut finished = vec![(id, succeeded)];
+    while let Some((id, succeeded)) = finished.pop() {
+        let released = dependencies.finish(id, succeeded);
+        for task in released.dropped {
+            warn!(task_id = task.meta.id, "Dropping task: a dependency failed");
+            drop_parked_task(task, Some(REASON_DEPENDENCY_FAILED), results, counters, dead_letter);
+        }
+        for task in released.ready {
+            let priority = task.meta.priority;
+            match queue.try_push(task, priority) {
+                Ok(()) => {}
+                Err(PushError::Full(task)) => {
+                    warn!(task_id = task.meta.id, "Queue full; dropping released dependent");
+                    finished.push((task.meta.id, false));
+                    drop_parked_task(task, Some(REASON_QUEUE_FULL), results, counters, dead_letter);
+                }
+                Err(PushError::Closed(task)) => {
+                    finished.push((task.meta.id, false));
+                    drop_parked_task(task, None, results, counters, dead_letter);
+                }
+            }
+        }
+    }
+}
+
+/// Task id a pool-generated mailbox key string was made from, see
+/// `generate_mailbox_key`.
+fn task_id_of_key(key_str: &str) -> Option<TaskId> {
+    key_str.strip_prefix("worker_pool:")?.parse().ok()
+}
+
+/// Record an executor panic, with the attached audit sink if there is one.
+fn record_panic(audit: &AuditSlot, worker_id: usize, meta: &TaskMetadata, panic: &(dyn Any + Send)) {
+    let message = panic_message(panic);
+    error!(worker_id = worker_id, task_id = meta.id, message = %message, "Executor panicked");
+    if let Some(sink) = audit.lock().as_mut() {
+        let tenant = meta
+            .mailbox
+            .as_ref()
+            .map_or_else(|| "unknown".to_string(), |key| key.tenant.clone());
+        sink.record(build_audit_event(
+            format!("{}-panic-{}", meta.id, now_ms()),
+            meta.id.to_string(),
+            "worker_pool",
+            tenant,
+            "panic",
+            Some(serde_json::json!({ "message": message, "worker_id": worker_id })),
+        ));
+    }
+}
+
+/// Text of a panic payload; `panic!` produces a `&str` or a `String`.
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|message| (*message).to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string panic payload".to_string())
+}
+
+/// Number of tasks waiting to run: queued for a worker or parked on dependencies.
+fn queued_len<P>(queue: &WorkQueue<WorkerTask<P>>, dependencies: &DependencyTracker<WorkerTask<P>>) -> u64 {
+    u64::try_from(queue.len() + dependencies.parked_len()).unwrap_or(u64::MAX)
+}
+
+/// Drop a parked task that will never run, dead-lettering it under `reason`.
+fn drop_parked_task<P, R>(
+    task: WorkerTask<P>,
+    reason: Option<&str>,
+    results: &ResultStorage<R>,
+    counters: &PoolCounters,
+    dead_letter: &DeadLetterSlot,
+) {
+    counters.unqueue_task(task.meta.cost.units);
+    counters.failed_tasks.fetch_add(1, Ordering::Relaxed);
+    #[cfg(feature = "otel")]
+    otel::end_span(&task.span, "dropped");
+    results.discard(&task.mailbox_key);
+    if let Some(reason) = reason {
+        record_dead_letter(dead_letter, task.meta, reason);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -649,9 +2457,81 @@ mod tests {
             },
             deadline_ms: None,
             created_at_ms: 0,
+            trace_context: None,
+            degraded: false,
+            depends_on: Vec::new(),
+            idempotency_key: None,
+            retrieve_timeout_ms: None,
+        }
+    }
+    
+    fn session_key(session: usize) -> MailboxKey {
+        MailboxKey {
+            tenant: "test".to_string(),
+            user_id: None,
+            session_id: Some(session.to_string()),
+        }
+    }
+    
+    #[test]
+    fn test_take_once_results_wake_their_waiters() {
+        let storage = Arc::new(ResultStorage::<usize>::new(4));
+        let keys: Vec<MailboxKey> = (0..64).map(session_key).collect();
+        for key in &keys {
+            storage.create_slot(key, None, None, None);
+        }
+        
+        // One waiter per key, all parked before any result is stored
+        let waiters: Vec<_> = keys
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(i, key)| {
+                let storage = Arc::clone(&storage);
+                thread::spawn(move || (i, storage.wait_for_result(&key, Duration::from_secs(10))))
+            })
+            .collect();
+        thread::sleep(Duration::from_millis(50));
+        for (i, key) in keys.iter().enumerate().rev() {
+            storage.store(key, i);
+        }
+        
+        for waiter in waiters {
+            let (i, result) = waiter.join().unwrap();
+            assert_eq!(result.unwrap(), i);
         }
     }
     
+    #[test]
+    fn test_take_once_wakeup_passed_to_other_waiters() {
+        let storage = Arc::new(ResultStorage::<usize>::new(1));
+        let key = session_key(0);
+        storage.create_slot(&key, None, None, None);
+        
+        let waiters: Vec<_> = (0..3)
+            .map(|_| {
+                let storage = Arc::clone(&storage);
+                let key = key.clone();
+                thread::spawn(move || storage.wait_for_result(&key, Duration::from_secs(10)))
+            })
+            .collect();
+        thread::sleep(Duration::from_millis(50));
+        let started = Instant::now();
+        storage.store(&key, 7);
+        
+        // Exactly one waiter gets the result; the others give up well before their timeout
+        let results: Vec<_> = waiters.into_iter().map(|w| w.join().unwrap()).collect();
+        assert_eq!(results.iter().filter(|r| matches!(r, Ok(7))).count(), 1);
+        assert_eq!(
+            results
+                .iter()
+                .filter(|r| matches!(r, Err(PoolError::ResultNotFound { .. })))
+                .count(),
+            2
+        );
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+    
     #[tokio::test]
     async fn test_worker_pool_basic() {
         let executor = TestExecutor {
@@ -728,4 +2608,52 @@ mod tests {
         let result = pool.retrieve(&key, Duration::from_secs(5)).unwrap();
         assert_eq!(result, "Result: blocking");
     }
+    
+    #[test]
+    fn test_result_storage_shards_keys() {
+        let storage = ResultStorage::new(4);
+        let keys: Vec<_> = (0..64).map(generate_mailbox_key).collect();
+        for (i, key) in keys.iter().enumerate() {
+            storage.create_slot(key, None, None, None);
+            storage.store(key, i);
+        }
+        
+        // Keys spread over every shard and each resolves to its own entry
+        assert!(storage.shards.iter().all(|shard| !shard.read().is_empty()));
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(storage.try_retrieve(key).unwrap(), Some(i));
+            storage.remove(key);
+        }
+        assert!(storage.shards.iter().all(|shard| shard.read().is_empty()));
+    }
+    
+    #[test]
+    fn test_result_shards_config() {
+        assert_eq!(WorkerPoolConfig::new().with_worker_count(6).result_shard_count(), 8);
+        assert_eq!(WorkerPoolConfig::new().with_result_shards(1).result_shard_count(), 1);
+        assert!(WorkerPoolConfig::new().with_result_shards(0).validate().is_err());
+    }
+    
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_set_thread_priority_applies_nice() {
+        fn current_nice() -> i32 {
+            let stat = std::fs::read_to_string("/proc/thread-self/stat").unwrap();
+            // Field 19 is the nice value; fields from 3 on follow the `)`
+            let fields = stat.rsplit_once(')').unwrap().1;
+            fields.split_whitespace().nth(16).unwrap().parse().unwrap()
+        }
+        
+        // Threads inherit the test's nice value, and lowering priority from
+        // there needs no privileges, so every value from it up applies
+        for nice in current_nice()..=19 {
+            let applied = std::thread::spawn(move || {
+                set_thread_priority(0, nice);
+                current_nice()
+            })
+            .join()
+            .unwrap();
+            assert_eq!(applied, nice);
+        }
+    }
 }
@@ -8,22 +8,156 @@
 //! - **No polling**: Uses oneshot channels for result notification
 //! - **Async-native**: All operations are async, no blocking
 //! - **Semaphore-based concurrency**: Efficient permit-based limiting
+//! - **Unit-based admission**: Tasks wait until their cost fits under `max_units`
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
 use parking_lot::{Mutex, RwLock};
-use tokio::sync::{oneshot, Semaphore};
+use tokio::sync::{oneshot, Notify, Semaphore};
+use tokio::task::AbortHandle;
 use tracing::{debug, error, info, warn};
 
 use crate::config::WorkerPoolConfig;
-use crate::core::executor::WorkerExecutor;
-use crate::core::TaskMetadata;
-use crate::util::serde::MailboxKey;
+use crate::core::error::ZERO_COST_TASK;
+use crate::core::executor::{ExecutionOutcome, ExecutorContext, WorkerExecutor};
+use crate::core::{Progress, RateLimiter, TaskMetadata};
+use crate::util::clock::{age_ms, now_ms};
+use crate::util::serde::{MailboxKey, TaskId};
+use crate::util::telemetry::{PoolKind, PoolSnapshotMetrics, SnapshotSource};
 
-use super::{generate_mailbox_key, mailbox_key_to_string, PoolCounters, PoolError, PoolStats};
+use crate::core::dead_letter::{
+    REASON_DEADLINE_EXPIRED, REASON_DEPENDENCY_FAILED, REASON_EXCEEDS_MAX_UNITS,
+    REASON_EXECUTION_TIMEOUT, REASON_QUEUE_FULL,
+};
+use crate::core::DeadLetterSink;
+#[cfg(feature = "otel")]
+use crate::util::telemetry::otel;
+
+use super::{
+    execute_with_retry, finish_task, CircuitBreaker, CircuitState, generate_mailbox_key, is_expired, mailbox_key_to_string,
+    record_dead_letter, DeadLetterSlot, Degradation, DependencyTracker, PoolCounters, PoolError, PoolStats, ProgressChannels,
+    Refusal, time_until, run_completion_hook, CompletionSlot,
+};
+
+/// The dependency gate of a parked task.
+struct ParkedGate {
+    /// Id of the parked task.
+    id: TaskId,
+    /// Units the task costs, checked when `max_units` is lowered.
+    cost: u32,
+    /// Receives `Ok(())` once the task may run, or the dead-letter reason it
+    /// is dropped for.
+    open: oneshot::Sender<Result<(), &'static str>>,
+}
+
+/// Dependency gates of parked tasks.
+type DependencyGates = DependencyTracker<ParkedGate>;
+
+/// Open or fail the gates of the tasks waiting on a finished task.
+fn settle_dependents(dependencies: &DependencyGates, id: TaskId, succeeded: bool) {
+    let released = dependencies.finish(id, succeeded);
+    for parked in released.ready {
+        let _ = parked.open.send(Ok(()));
+    }
+    for parked in released.dropped {
+        let _ = parked.open.send(Err(REASON_DEPENDENCY_FAILED));
+    }
+}
+
+/// Why [`reserve_units`] gave up on a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unreserved {
+    /// The pool shut down while the task waited.
+    Shutdown,
+    /// `max_units` was lowered below the task's cost, so it can never run.
+    ExceedsMaxUnits,
+}
+
+/// Reserve `units` against the current `max_units`, waiting for running
+/// tasks to release theirs (or the limit to be raised) while the cost does
+/// not fit.
+async fn reserve_units(
+    active_units: &AtomicU32,
+    released: &Notify,
+    shutdown: &AtomicBool,
+    units: u32,
+    max_units: &AtomicU32,
+) -> Result<(), Unreserved> {
+    loop {
+        // Register before checking so a release in between is not missed
+        let notified = released.notified();
+        if shutdown.load(Ordering::Acquire) {
+            return Err(Unreserved::Shutdown);
+        }
+        let max_units = max_units.load(Ordering::Acquire);
+        if units > max_units {
+            return Err(Unreserved::ExceedsMaxUnits);
+        }
+        let reserved = active_units.fetch_update(Ordering::AcqRel, Ordering::Acquire, |used| {
+            (used.saturating_add(units) <= max_units).then(|| used.saturating_add(units))
+        });
+        if reserved.is_ok() {
+            return Ok(());
+        }
+        notified.await;
+    }
+}
+
+/// Return `units` reserved by [`reserve_units`] and wake the tasks waiting for them.
+fn release_units(active_units: &AtomicU32, released: &Notify, units: u32) {
+    active_units.fetch_sub(units, Ordering::AcqRel);
+    released.notify_waiters();
+}
+
+/// Abort handles of spawned tasks that have not finished, by task id.
+type TaskHandles = Arc<Mutex<HashMap<TaskId, AbortHandle>>>;
+
+/// Removes a spawned task's abort handle once the task ends, however it ends.
+struct TrackedTask {
+    handles: TaskHandles,
+    task_id: TaskId,
+}
+
+impl Drop for TrackedTask {
+    fn drop(&mut self) {
+        self.handles.lock().remove(&self.task_id);
+    }
+}
+
+/// Pause switch checked by every task before it claims capacity.
+#[derive(Default)]
+struct PauseGate {
+    paused: AtomicBool,
+    resumed: Notify,
+}
+
+impl PauseGate {
+    fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Acquire)
+    }
+    
+    fn set(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Release);
+        if !paused {
+            self.resumed.notify_waiters();
+        }
+    }
+    
+    /// Wait until the pool is not paused.
+    async fn wait(&self) {
+        loop {
+            // Register before checking, so a resume in between isn't missed
+            let resumed = self.resumed.notified();
+            if !self.is_paused() {
+                return;
+            }
+            resumed.await;
+        }
+    }
+}
 
 /// Result entry state.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -32,6 +166,10 @@ enum ResultState {
     Pending,
     /// Result is ready.
     Ready,
+    /// The task was cancelled after running past its per-task timeout.
+    TimedOut,
+    /// The pool shut down before the task produced a result.
+    Shutdown,
 }
 
 /// Result storage entry with oneshot notification.
@@ -42,6 +180,8 @@ struct ResultEntry<R> {
     state: ResultState,
     /// Oneshot sender for async notification.
     notify_tx: Option<oneshot::Sender<()>>,
+    /// How long `retrieve_async_default` waits, from `TaskMetadata::retrieve_timeout_ms`.
+    retrieve_timeout: Option<Duration>,
 }
 
 /// Result storage for the worker pool.
@@ -57,21 +197,25 @@ impl<R> ResultStorage<R> {
         }
     }
     
-    /// Create a slot for a result and return a oneshot receiver for notification.
-    fn create_slot(&self, key: &MailboxKey) -> oneshot::Receiver<()> {
-        let (tx, rx) = oneshot::channel();
+    /// Whether a result is pending or stored under `key`.
+    fn contains(&self, key: &MailboxKey) -> bool {
+        self.entries.read().contains_key(&mailbox_key_to_string(key))
+    }
+    
+    /// Create a slot for a result; the first waiter registers its own
+    /// notification channel through `get_notify_rx`.
+    fn create_slot(&self, key: &MailboxKey, retrieve_timeout: Option<Duration>) {
         let key_str = mailbox_key_to_string(key);
         
         let entry = ResultEntry {
             result: None,
             state: ResultState::Pending,
-            notify_tx: Some(tx),
+            notify_tx: None,
+            retrieve_timeout,
         };
         
         let mut entries = self.entries.write();
         entries.insert(key_str, Mutex::new(entry));
-        
-        rx
     }
     
     /// Store a result and notify any waiters.
@@ -90,6 +234,61 @@ impl<R> ResultStorage<R> {
         }
     }
     
+    /// Mark an entry whose task was cancelled by its execution timeout and
+    /// notify any waiters.
+    fn time_out(&self, key: &MailboxKey) {
+        let key_str = mailbox_key_to_string(key);
+        
+        let entries = self.entries.read();
+        if let Some(entry_mutex) = entries.get(&key_str) {
+            let mut entry = entry_mutex.lock();
+            entry.state = ResultState::TimedOut;
+            if let Some(tx) = entry.notify_tx.take() {
+                let _ = tx.send(());
+            }
+        }
+    }
+    
+    /// Mark every entry still waiting for a result as abandoned by shutdown
+    /// and notify its waiters.
+    fn shut_down_pending(&self) {
+        let entries = self.entries.read();
+        for entry_mutex in entries.values() {
+            let mut entry = entry_mutex.lock();
+            if entry.state == ResultState::Pending {
+                entry.state = ResultState::Shutdown;
+                if let Some(tx) = entry.notify_tx.take() {
+                    let _ = tx.send(());
+                }
+            }
+        }
+    }
+    
+    /// Remove an entry and hand out its result, or the reason there is none.
+    fn take(&self, key: &MailboxKey) -> Result<R, PoolError> {
+        let key_str = mailbox_key_to_string(key);
+        
+        let removed = self.entries.write().remove(&key_str);
+        let Some(entry_mutex) = removed else {
+            return Err(PoolError::ResultNotFound { key: key.clone() });
+        };
+        let mut entry = entry_mutex.into_inner();
+        match entry.state {
+            ResultState::TimedOut => Err(PoolError::Timeout { key: key.clone() }),
+            ResultState::Shutdown => Err(PoolError::PoolShutdown),
+            _ => entry.result.take().ok_or_else(|| PoolError::ResultNotFound { key: key.clone() }),
+        }
+    }
+    
+    /// Whether `key` is still waiting for its task to finish.
+    fn is_pending(&self, key: &MailboxKey) -> bool {
+        let key_str = mailbox_key_to_string(key);
+        self.entries
+            .read()
+            .get(&key_str)
+            .is_some_and(|entry| entry.lock().state == ResultState::Pending)
+    }
+    
     /// Try to retrieve a result immediately.
     fn try_retrieve(&self, key: &MailboxKey) -> Option<R> {
         let key_str = mailbox_key_to_string(key);
@@ -133,6 +332,15 @@ impl<R> ResultStorage<R> {
         }
         None
     }
+    
+    /// Retrieval timeout the task behind `key` asked for, if any.
+    fn retrieve_timeout(&self, key: &MailboxKey) -> Option<Duration> {
+        let key_str = mailbox_key_to_string(key);
+        self.entries
+            .read()
+            .get(&key_str)
+            .and_then(|entry| entry.lock().retrieve_timeout)
+    }
 }
 
 /// Worker pool using async tasks for WASM environments.
@@ -140,11 +348,17 @@ impl<R> ResultStorage<R> {
 /// This implementation uses tokio async tasks with a semaphore for concurrency
 /// control. Unlike the native implementation, there are no blocking APIs since
 /// WASM cannot block.
+///
+/// In particular there is no `retrieve` or `retrieve_default`: wait with
+/// [`retrieve_async`](Self::retrieve_async), poll with
+/// [`try_retrieve`](Self::try_retrieve), or use
+/// [`retrieve_with_deadline`](Self::retrieve_with_deadline), which has the
+/// same signature on native.
 pub struct WorkerPool<P, R, E>
 where
     P: Send + 'static,
     R: Send + 'static,
-    E: WorkerExecutor<P, R>,
+    E: WorkerExecutor<P, R> + Clone,
 {
     /// Pool configuration.
     config: WorkerPoolConfig,
@@ -161,15 +375,59 @@ where
     /// Pool statistics counters (lock-free).
     counters: Arc<PoolCounters>,
     
-    /// Active resource units (lock-free).
+    /// Active resource units (lock-free), reserved before a task takes a permit.
     active_units: Arc<AtomicU32>,
     
+    /// Notified whenever units are released, waking tasks waiting to reserve.
+    units_released: Arc<Notify>,
+    
+    /// Current `max_units`, changed by `set_max_units`.
+    max_units: Arc<AtomicU32>,
+    
+    /// Holds tasks back from claiming capacity while the pool is paused.
+    pause: Arc<PauseGate>,
+    
     /// Shutdown flag (lock-free).
     shutdown: Arc<AtomicBool>,
     
+    /// Abort handles of spawned tasks, aborted on shutdown so hung executors
+    /// don't outlive the pool.
+    tasks: TaskHandles,
+    
     /// Task ID counter (lock-free).
     task_id_counter: AtomicU64,
     
+    /// Payload cloner; `None` when the payload type cannot be retried.
+    clone_payload: Option<fn(&P) -> P>,
+    
+    /// Dead-letter sink for dropped tasks (shared with spawned tasks).
+    dead_letter: DeadLetterSlot,
+    
+    /// Hook run as each task completes (shared with spawned tasks).
+    on_complete: CompletionSlot,
+    
+    /// Context handed to every executor invocation.
+    executor_context: ExecutorContext,
+    
+    /// Per-task progress channels.
+    progress: ProgressChannels,
+    
+    /// Circuit breaker fed by task outcomes (shared with spawned tasks).
+    circuit: Arc<CircuitBreaker>,
+    
+    /// Graceful degradation applied to submissions under load.
+    degradation: Option<Degradation>,
+    
+    /// Per-tenant submission rate limits.
+    rate_limiter: Option<RateLimiter>,
+    
+    /// Gates of tasks held back until their dependencies finish (shared with spawned tasks).
+    dependencies: Arc<DependencyGates>,
+    
+    /// Mailbox keys and `created_at_ms` of tasks that have not started yet,
+    /// by submission order (shared with spawned tasks).
+    queued: Arc<Mutex<BTreeMap<u64, (MailboxKey, u128)>>>,
+    
     /// Phantom data for payload type.
     _payload: std::marker::PhantomData<P>,
 }
@@ -178,7 +436,7 @@ impl<P, R, E> WorkerPool<P, R, E>
 where
     P: Send + 'static,
     R: Send + 'static,
-    E: WorkerExecutor<P, R>,
+    E: WorkerExecutor<P, R> + Clone,
 {
     /// Create a new worker pool with the given configuration and executor.
     ///
@@ -186,14 +444,48 @@ where
     ///
     /// # Errors
     ///
-    /// Returns `PoolError::InvalidConfig` if the configuration is invalid.
+    /// Returns `PoolError::InvalidConfig` if the configuration is invalid, or if
+    /// it enables retries (retrying needs a cloneable payload; use
+    /// [`new_retryable`](Self::new_retryable) instead).
     pub fn new(config: WorkerPoolConfig, executor: E) -> Result<Self, PoolError> {
+        if config.retry.is_enabled() {
+            return Err(PoolError::InvalidConfig(
+                "retry policy requires a cloneable payload; use WorkerPool::new_retryable".into(),
+            ));
+        }
+        Self::build(config, executor, None)
+    }
+    
+    /// Create a worker pool whose tasks are retried according to `config.retry`.
+    ///
+    /// Each attempt consumes a clone of the payload, so `P` must be `Clone`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoolError::InvalidConfig` if the configuration is invalid.
+    pub fn new_retryable(config: WorkerPoolConfig, executor: E) -> Result<Self, PoolError>
+    where
+        P: Clone,
+    {
+        Self::build(config, executor, Some(P::clone))
+    }
+    
+    fn build(
+        config: WorkerPoolConfig,
+        executor: E,
+        clone_payload: Option<fn(&P) -> P>,
+    ) -> Result<Self, PoolError> {
         config.validate().map_err(PoolError::InvalidConfig)?;
         
-        let semaphore = Arc::new(Semaphore::new(config.worker_count));
+        // Both limits bound concurrent tasks here, so one semaphore enforces them
+        let permits = config
+            .max_concurrent_tasks
+            .map_or(config.worker_count, |max| max.min(config.worker_count));
+        let semaphore = Arc::new(Semaphore::new(permits));
         let results = Arc::new(ResultStorage::new());
         let counters = Arc::new(PoolCounters::default());
         let active_units = Arc::new(AtomicU32::new(0));
+        let units_released = Arc::new(Notify::new());
         let shutdown = Arc::new(AtomicBool::new(false));
         
         info!(
@@ -203,6 +495,9 @@ where
             "WorkerPool (WASM) initialized with async tasks"
         );
         
+        let circuit = Arc::new(CircuitBreaker::new(config.circuit_breaker.clone()));
+        
+        let max_units = Arc::new(AtomicU32::new(config.max_units));
         Ok(Self {
             config,
             executor,
@@ -210,14 +505,105 @@ where
             results,
             counters,
             active_units,
+            units_released,
+            max_units,
+            pause: Arc::new(PauseGate::default()),
             shutdown,
+            tasks: Arc::new(Mutex::new(HashMap::new())),
             task_id_counter: AtomicU64::new(0),
+            clone_payload,
+            dead_letter: Arc::new(Mutex::new(None)),
+            on_complete: Arc::new(Mutex::new(None)),
+            executor_context: ExecutorContext::default(),
+            progress: ProgressChannels::default(),
+            circuit,
+            degradation: None,
+            rate_limiter: None,
+            dependencies: Arc::new(DependencyTracker::default()),
+            queued: Arc::new(Mutex::new(BTreeMap::new())),
             _payload: std::marker::PhantomData,
         })
     }
     
+    /// Attach a dead-letter sink for tasks that are rejected, expire before
+    /// running, or exhaust their retries.
+    #[must_use]
+    pub fn with_dead_letter(self, sink: Box<dyn DeadLetterSink>) -> Self {
+        *self.dead_letter.lock() = Some(sink);
+        self
+    }
+    
+    /// Run `hook` as each task completes, with its result key and metadata.
+    ///
+    /// It is called once per task that produces a result, successful or not,
+    /// just before the result is published, so a caller woken by
+    /// `retrieve_async` sees the hook's side effects. Tasks that time out,
+    /// expire or are dropped never complete and skip it. The hook runs inside
+    /// the task and delays the result until it returns: keep it cheap and
+    /// spawn heavy work such as database writes separately.
+    #[must_use]
+    pub fn with_on_complete<F>(self, hook: F) -> Self
+    where
+        F: Fn(&MailboxKey, &TaskMetadata) + Send + Sync + 'static,
+    {
+        *self.on_complete.lock() = Some(Arc::new(hook));
+        self
+    }
+    
+    /// Share `context` with every task: the executor receives it in
+    /// [`WorkerExecutor::execute_with_context`] and reads it with
+    /// [`ExecutorContext::get`].
+    ///
+    /// The value is constructed once and shared behind an `Arc`, so any
+    /// mutable state inside it must be synchronized.
+    #[must_use]
+    pub fn with_context<C: Send + Sync + 'static>(mut self, context: C) -> Self {
+        self.executor_context = ExecutorContext::new(context);
+        self
+    }
+    
+    /// Enable graceful degradation: once at least `high_watermark` tasks are
+    /// queued, `degrade` rewrites the metadata of each new submission (e.g.
+    /// lowering `cost.units`) and the task is tagged `TaskMetadata::degraded`
+    /// so the executor can serve it on a cheaper path.
+    #[must_use]
+    pub fn with_degradation<F>(mut self, high_watermark: usize, degrade: F) -> Self
+    where
+        F: Fn(&mut TaskMetadata) + Send + Sync + 'static,
+    {
+        self.degradation = Some(Degradation {
+            high_watermark,
+            degrade: Box::new(degrade),
+        });
+        self
+    }
+    
+    /// Limit how fast each tenant (`MailboxKey::tenant`) may submit tasks.
+    ///
+    /// Submissions beyond a tenant's token bucket fail with
+    /// `PoolError::RateLimited` before they are queued.
+    #[must_use]
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+    
+    /// A task id no other call returns, for callers that let the pool
+    /// number their tasks.
+    ///
+    /// Ids count up from 0 and are drawn from the same counter as the pool's
+    /// mailbox keys.
+    #[must_use]
+    pub fn next_task_id(&self) -> TaskId {
+        self.task_id_counter.fetch_add(1, Ordering::Relaxed)
+    }
+    
     /// Submit a task asynchronously.
     ///
+    /// The result is stored under `meta.mailbox` when that names a session,
+    /// as with [`submit_with_key_async`](Self::submit_with_key_async);
+    /// otherwise the pool generates a key.
+    ///
     /// # Returns
     ///
     /// Returns a `MailboxKey` that can be used to retrieve the result.
@@ -225,82 +611,351 @@ where
     /// # Errors
     ///
     /// - `PoolError::QueueFull` if the task queue is full
+    /// - `PoolError::CircuitOpen` if the circuit breaker is shedding load
+    /// - `PoolError::RateLimited` if the task's tenant exceeded its rate limit
+    /// - `PoolError::DependencyFailed` if a task in `meta.depends_on` failed
+    /// - `PoolError::DuplicateTaskId` if `enforce_unique_ids` is set and a
+    ///   task with `meta.id` is still in flight
+    /// - `PoolError::InvalidTask` if `meta.cost.units` is zero
+    /// - `PoolError::InsufficientCapacity` if `meta.cost.units` exceeds
+    ///   `max_units`
+    /// - `PoolError::KeyInUse` if `meta.mailbox` names a session whose result
+    ///   is still pending or unretrieved
     /// - `PoolError::PoolShutdown` if the pool has been shut down
     pub async fn submit_async(
         &self,
         payload: P,
         meta: TaskMetadata,
+    ) -> Result<MailboxKey, PoolError> {
+        let key = meta.mailbox.clone().filter(|key| key.session_id.is_some());
+        self.submit_keyed(payload, meta, key)
+    }
+    
+    /// Submit a task whose result is stored under a caller-chosen `key`.
+    ///
+    /// Results are told apart by tenant and session, so a key's `user_id`
+    /// does not make it distinct.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`submit_async`](Self::submit_async), plus
+    /// `PoolError::KeyInUse` if a result under `key` is still pending or
+    /// unretrieved.
+    pub async fn submit_with_key_async(
+        &self,
+        payload: P,
+        meta: TaskMetadata,
+        key: MailboxKey,
+    ) -> Result<MailboxKey, PoolError> {
+        self.submit_keyed(payload, meta, Some(key))
+    }
+    
+    /// Submit a task under `key`, or a generated key if `None`.
+    fn submit_keyed(
+        &self,
+        payload: P,
+        meta: TaskMetadata,
+        key: Option<MailboxKey>,
     ) -> Result<MailboxKey, PoolError> {
         if self.shutdown.load(Ordering::Acquire) {
             return Err(PoolError::PoolShutdown);
         }
+        if meta.cost.units == 0 {
+            return Err(PoolError::InvalidTask(ZERO_COST_TASK.into()));
+        }
+        // A task larger than the pool would wait for units forever
+        let max_units = self.max_units();
+        if meta.cost.units > max_units {
+            warn!(
+                task_id = meta.id,
+                cost = meta.cost.units,
+                "Task rejected: cost exceeds pool capacity"
+            );
+            return Err(PoolError::InsufficientCapacity {
+                requested: meta.cost.units,
+                available: max_units,
+            });
+        }
+        // Nothing yields between this check and creating the slot below
+        if let Some(key) = key.as_ref().filter(|key| self.results.contains(key)) {
+            warn!(task_id = meta.id, "Task rejected: its mailbox key is already in use");
+            return Err(PoolError::KeyInUse { key: key.clone() });
+        }
+        
+        if let Some(limiter) = &self.rate_limiter {
+            limiter
+                .try_acquire_for(meta.mailbox.as_ref())
+                .map_err(|retry_after_ms| PoolError::RateLimited { retry_after_ms })?;
+        }
         
-        // Check queue depth
-        let current_queued = self.counters.queued_tasks.load(Ordering::Relaxed);
-        if current_queued >= self.config.max_queue_depth as u64 {
+        // Fail fast while the executor is unhealthy
+        let probe = self.circuit.admit()?;
+        
+        // Downgrade the task while the queue is under pressure
+        let mut meta = meta;
+        if let Some(degradation) = &self.degradation {
+            degradation.apply(&mut meta, &self.counters);
+        }
+        
+        // Reserve a queue slot; checking and incrementing in one step keeps
+        // concurrent submissions from overshooting the depth limit
+        let max_queue_depth = self.config.max_queue_depth as u64;
+        let reserved = self.counters.queued_tasks.fetch_update(
+            Ordering::AcqRel,
+            Ordering::Acquire,
+            |queued| (queued < max_queue_depth).then_some(queued + 1),
+        );
+        if reserved.is_err() {
             warn!("Worker pool queue is full");
-            return Err(PoolError::QueueFull);
+            let task_id = meta.id;
+            record_dead_letter(&self.dead_letter, meta, REASON_QUEUE_FULL);
+            return Err(PoolError::QueueFull {
+                task_id: Some(task_id),
+                retry_after_ms: Some(self.counters.throughput.retry_after_ms()),
+            });
         }
+        self.counters.queued_units.fetch_add(u64::from(meta.cost.units), Ordering::Relaxed);
+        
+        // Hold the task back while any of its dependencies is still in flight
+        let (gate_tx, gate_rx) = oneshot::channel();
+        let parked = ParkedGate {
+            id: meta.id,
+            cost: meta.cost.units,
+            open: gate_tx,
+        };
+        let unique = self.config.enforce_unique_ids;
+        let gate = match self.dependencies.submit(meta.id, &meta.depends_on, parked, unique) {
+            Ok(Some(_)) => None,
+            Ok(None) => Some(gate_rx),
+            Err((Refusal::Duplicate, _)) => {
+                self.counters.unqueue_task(meta.cost.units);
+                warn!(task_id = meta.id, "Task rejected: its id is already in flight");
+                return Err(PoolError::DuplicateTaskId { id: meta.id });
+            }
+            Err((Refusal::DependencyFailed(failed), _)) => {
+                self.counters.unqueue_task(meta.cost.units);
+                warn!(task_id = meta.id, dependency = failed, "Task rejected: a dependency failed");
+                record_dead_letter(&self.dead_letter, meta, REASON_DEPENDENCY_FAILED);
+                return Err(PoolError::DependencyFailed { id: failed });
+            }
+        };
         
-        // Generate unique task ID and mailbox key
+        // Generate unique task ID and mailbox key, unless the caller chose one
         let task_id = self.task_id_counter.fetch_add(1, Ordering::Relaxed);
-        let mailbox_key = generate_mailbox_key(task_id);
+        let mailbox_key = key.unwrap_or_else(|| generate_mailbox_key(task_id));
         
-        // Create result slot with notification
-        let _notify_rx = self.results.create_slot(&mailbox_key);
+        // Create result slot and progress channel
+        self.results
+            .create_slot(&mailbox_key, meta.retrieve_timeout_ms.map(Duration::from_millis));
+        let progress = self.progress.open(&mailbox_key);
         
         // Update counters
         self.counters.submitted_tasks.fetch_add(1, Ordering::Relaxed);
-        self.counters.queued_tasks.fetch_add(1, Ordering::Relaxed);
+        self.queued.lock().insert(task_id, (mailbox_key.clone(), meta.created_at_ms));
         
         // Clone refs for the spawned task
         let semaphore = Arc::clone(&self.semaphore);
         let results = Arc::clone(&self.results);
         let counters = Arc::clone(&self.counters);
         let active_units = Arc::clone(&self.active_units);
+        let units_released = Arc::clone(&self.units_released);
+        let pause = Arc::clone(&self.pause);
+        let max_units = Arc::clone(&self.max_units);
         let shutdown = Arc::clone(&self.shutdown);
         let executor = self.executor.clone();
+        let retry = self.config.retry.clone();
+        let per_task_timeout = self.config.per_task_timeout();
+        let slow_task_threshold = self.config.slow_task_threshold();
+        let clone_payload = self.clone_payload;
+        let dead_letter = Arc::clone(&self.dead_letter);
+        let on_complete = Arc::clone(&self.on_complete);
+        let executor_context = self.executor_context.clone();
+        let circuit = Arc::clone(&self.circuit);
+        let dependencies = Arc::clone(&self.dependencies);
+        let queued = Arc::clone(&self.queued);
+        #[cfg(feature = "otel")]
+        let task_cx = otel::start_task_span(&meta);
         let task_cost = meta.cost.units;
+        let task_kind = meta.cost.kind;
         let key_clone = mailbox_key.clone();
+        let enqueued_at_ms = now_ms();
+        let tracked = TrackedTask {
+            handles: Arc::clone(&self.tasks),
+            task_id,
+        };
         
-        // Spawn async task
-        tokio::spawn(async move {
-            // Acquire semaphore permit (efficient async wait, no polling)
-            let _permit = match semaphore.acquire().await {
-                Ok(permit) => permit,
-                Err(_) => {
-                    // Semaphore closed
-                    counters.queued_tasks.fetch_sub(1, Ordering::Relaxed);
+        // Spawn async task; holding the handle map until the handle is in it
+        // keeps a task that finishes at once from leaving a stale entry
+        let mut tasks = self.tasks.lock();
+        let handle = tokio::spawn(async move {
+            let _tracked = tracked;
+            // Wait for dependencies; a failed one, or a lowered max_units,
+            // drops this task (and, through the tracker, its own dependents)
+            if let Some(gate) = gate {
+                if let Err(reason) = gate.await.unwrap_or(Err(REASON_DEPENDENCY_FAILED)) {
+                    counters.unqueue_task(task_cost);
+                    queued.lock().remove(&task_id);
+                    counters.failed_tasks.fetch_add(1, Ordering::Relaxed);
+                    warn!(task_id = task_id, reason = reason, "Dropping parked task");
+                    #[cfg(feature = "otel")]
+                    otel::end_span(&task_cx, "dropped");
+                    record_dead_letter(&dead_letter, meta, reason);
+                    results.remove(&key_clone);
                     return;
                 }
+            }
+            
+            let _permit = loop {
+                pause.wait().await;
+                
+                // Wait until the task's cost fits under max_units
+                let reserved =
+                    reserve_units(&active_units, &units_released, &shutdown, task_cost, &max_units)
+                        .await;
+                match reserved {
+                    Ok(()) => {}
+                    Err(Unreserved::Shutdown) => {
+                        counters.unqueue_task(task_cost);
+                        queued.lock().remove(&task_id);
+                        settle_dependents(&dependencies, meta.id, false);
+                        return;
+                    }
+                    Err(Unreserved::ExceedsMaxUnits) => {
+                        counters.unqueue_task(task_cost);
+                        queued.lock().remove(&task_id);
+                        counters.failed_tasks.fetch_add(1, Ordering::Relaxed);
+                        warn!(
+                            task_id = task_id,
+                            cost = task_cost,
+                            "Queued task no longer fits max_units, dropped"
+                        );
+                        #[cfg(feature = "otel")]
+                        otel::end_span(&task_cx, "dropped");
+                        settle_dependents(&dependencies, meta.id, false);
+                        record_dead_letter(&dead_letter, meta, REASON_EXCEEDS_MAX_UNITS);
+                        results.remove(&key_clone);
+                        return;
+                    }
+                }
+                
+                // Acquire semaphore permit (efficient async wait, no polling)
+                let permit = match semaphore.acquire().await {
+                    Ok(permit) => permit,
+                    Err(_) => {
+                        // Semaphore closed
+                        release_units(&active_units, &units_released, task_cost);
+                        counters.unqueue_task(task_cost);
+                        queued.lock().remove(&task_id);
+                        settle_dependents(&dependencies, meta.id, false);
+                        return;
+                    }
+                };
+                if !pause.is_paused() {
+                    break permit;
+                }
+                // Paused while waiting for capacity: hand it back until resumed
+                drop(permit);
+                release_units(&active_units, &units_released, task_cost);
             };
             
             // Check shutdown
             if shutdown.load(Ordering::Acquire) {
-                counters.queued_tasks.fetch_sub(1, Ordering::Relaxed);
+                release_units(&active_units, &units_released, task_cost);
+                counters.unqueue_task(task_cost);
+                queued.lock().remove(&task_id);
+                settle_dependents(&dependencies, meta.id, false);
+                return;
+            }
+            
+            // Drop tasks whose deadline passed while they waited for units or a permit
+            if is_expired(&meta) {
+                release_units(&active_units, &units_released, task_cost);
+                counters.unqueue_task(task_cost);
+                queued.lock().remove(&task_id);
+                counters.failed_tasks.fetch_add(1, Ordering::Relaxed);
+                warn!(task_id = task_id, "Task deadline expired before execution");
+                #[cfg(feature = "otel")]
+                otel::end_span(&task_cx, "expired");
+                settle_dependents(&dependencies, meta.id, false);
+                record_dead_letter(&dead_letter, meta, REASON_DEADLINE_EXPIRED);
+                results.remove(&key_clone);
                 return;
             }
             
             // Update counters
-            counters.queued_tasks.fetch_sub(1, Ordering::Relaxed);
+            counters.unqueue_task(task_cost);
+            queued.lock().remove(&task_id);
+            counters.wait_ms.record_since(enqueued_at_ms);
             counters.active_tasks.fetch_add(1, Ordering::Relaxed);
-            active_units.fetch_add(task_cost, Ordering::Relaxed);
+            counters.acquire_kind_units(task_kind, task_cost);
             
             debug!(task_id = task_id, "WASM worker executing task");
             
-            // Execute the task
-            let result = executor.execute(payload, meta).await;
+            #[cfg(feature = "otel")]
+            let execute_cx = otel::start_execute_span(&task_cx, &meta);
+            
+            // Execute the task, retrying transient failures; a task past its
+            // timeout is cancelled by dropping it
+            let execution = execute_with_retry(
+                &executor,
+                payload,
+                &meta,
+                &retry,
+                clone_payload,
+                &counters,
+                &progress,
+                &executor_context,
+            );
+            let started_at_ms = now_ms();
+            let executed = match per_task_timeout {
+                Some(limit) => tokio::time::timeout(limit, execution).await.ok(),
+                None => Some(execution.await),
+            };
+            counters.record_execution(&meta, started_at_ms, slow_task_threshold);
+            
+            let Some((result, outcome)) = executed else {
+                #[cfg(feature = "otel")]
+                {
+                    otel::end_span(&execute_cx, "timed_out");
+                    otel::end_span(&task_cx, "timed_out");
+                }
+                warn!(task_id = task_id, "Task exceeded its execution timeout, cancelled");
+                results.time_out(&key_clone);
+                counters.active_tasks.fetch_sub(1, Ordering::Relaxed);
+                release_units(&active_units, &units_released, task_cost);
+                counters.release_kind_units(task_kind, task_cost);
+                counters.record_outcome(ExecutionOutcome::Failed);
+                circuit.record(ExecutionOutcome::Failed, probe);
+                let meta_id = meta.id;
+                record_dead_letter(&dead_letter, meta, REASON_EXECUTION_TIMEOUT);
+                settle_dependents(&dependencies, meta_id, false);
+                return;
+            };
+            
+            #[cfg(feature = "otel")]
+            {
+                otel::end_span(&execute_cx, outcome.as_str());
+                otel::end_span(&task_cx, outcome.as_str());
+            }
             
             debug!(task_id = task_id, "WASM worker completed task");
             
-            // Store result and notify waiters
+            // Run the completion hook, then store the result and notify waiters
+            run_completion_hook(&on_complete, &key_clone, &meta);
             results.store(&key_clone, result);
             
             // Update counters
             counters.active_tasks.fetch_sub(1, Ordering::Relaxed);
-            active_units.fetch_sub(task_cost, Ordering::Relaxed);
-            counters.completed_tasks.fetch_add(1, Ordering::Relaxed);
+            release_units(&active_units, &units_released, task_cost);
+            counters.release_kind_units(task_kind, task_cost);
+            let meta_id = meta.id;
+            finish_task(&counters, &dead_letter, &retry, &circuit, meta, outcome, probe);
+            
+            // Release (or drop) tasks waiting on this one
+            settle_dependents(&dependencies, meta_id, outcome == ExecutionOutcome::Success);
         });
+        tasks.insert(task_id, handle.abort_handle());
+        drop(tasks);
         
         debug!(task_id = task_id, "Task submitted to WASM worker pool");
         Ok(mailbox_key)
@@ -323,6 +978,7 @@ where
         // First, try immediate retrieval (fast path)
         if let Some(result) = self.results.try_retrieve(key) {
             self.results.remove(key);
+            self.progress.close(key);
             return Ok(result);
         }
         
@@ -330,55 +986,256 @@ where
         let notify_rx = self.results.get_notify_rx(key);
         
         let Some(notify_rx) = notify_rx else {
-            // No entry or already ready - try again
-            if let Some(result) = self.results.try_retrieve(key) {
-                self.results.remove(key);
-                return Ok(result);
-            }
-            return Err(PoolError::ResultNotFound);
+            // No entry, already ready, or timed out - take whatever is there
+            self.progress.close(key);
+            return self.results.take(key);
         };
         
         // Wait for notification with timeout (NO POLLING)
         match tokio::time::timeout(timeout, notify_rx).await {
             Ok(Ok(())) => {
                 // Notified - result should be available
-                let result = self.results.remove(key).ok_or(PoolError::ResultNotFound)?;
-                Ok(result)
+                self.progress.close(key);
+                self.results.take(key)
             }
             Ok(Err(_)) => {
                 // Channel closed without result
                 self.results.remove(key);
+                self.progress.close(key);
                 Err(PoolError::Internal("result notification channel closed".into()))
             }
             Err(_) => {
                 // Timeout
                 self.results.remove(key);
-                Err(PoolError::Timeout)
+                self.progress.close(key);
+                Err(PoolError::Timeout { key: key.clone() })
             }
         }
     }
     
+    /// [`retrieve_async`](Self::retrieve_async) with the task's
+    /// `TaskMetadata::retrieve_timeout_ms`, else the pool's `default_timeout_ms`.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`retrieve_async`](Self::retrieve_async).
+    pub async fn retrieve_async_default(&self, key: &MailboxKey) -> Result<R, PoolError> {
+        let timeout = self
+            .results
+            .retrieve_timeout(key)
+            .unwrap_or_else(|| self.config.default_timeout());
+        self.retrieve_async(key, timeout).await
+    }
+    
+    /// [`retrieve_async`](Self::retrieve_async) waiting until `deadline_ms`,
+    /// in milliseconds since the epoch, instead of for a duration.
+    ///
+    /// Same signature as on native, so code that waits up to a request's
+    /// deadline compiles unchanged on both targets. A deadline already passed
+    /// still returns a result that is ready.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`retrieve_async`](Self::retrieve_async).
+    pub async fn retrieve_with_deadline(
+        &self,
+        key: &MailboxKey,
+        deadline_ms: u128,
+    ) -> Result<R, PoolError> {
+        self.retrieve_async(key, time_until(deadline_ms)).await
+    }
+    
+    /// Retrieve a result if it is ready, without waiting.
+    ///
+    /// Returns `Ok(None)` while the task is queued or running; the result can
+    /// then still be retrieved later. Otherwise the entry is released as with
+    /// [`retrieve_async`](Self::retrieve_async).
+    ///
+    /// # Errors
+    ///
+    /// - `PoolError::Timeout` if the task was cancelled by its execution timeout
+    /// - `PoolError::PoolShutdown` if the pool shut down before the task finished
+    /// - `PoolError::ResultNotFound` if the mailbox key is invalid or the
+    ///   result was already retrieved
+    pub fn try_retrieve(&self, key: &MailboxKey) -> Result<Option<R>, PoolError> {
+        if self.results.is_pending(key) {
+            return Ok(None);
+        }
+        self.progress.close(key);
+        self.results.take(key).map(Some)
+    }
+    
+    /// Current state of the pool's circuit breaker.
+    ///
+    /// Always `Closed` when no breaker is configured.
+    #[must_use]
+    pub fn circuit_state(&self) -> CircuitState {
+        self.circuit.state()
+    }
+    
+    /// Subscribe to the progress updates emitted while a task runs.
+    ///
+    /// Updates arrive in the order the executor reported them, and the stream
+    /// disconnects once the task finishes. Subscribe before retrieving the
+    /// result: the channel is released on retrieval, after which (or for an
+    /// unknown key) the returned receiver is already disconnected.
+    #[must_use]
+    pub fn progress_stream(&self, key: &MailboxKey) -> flume::Receiver<Progress> {
+        self.progress.subscribe(key)
+    }
+    
+    /// Mailbox keys of the tasks that have not started yet, in submission order.
+    #[must_use]
+    pub fn queued_keys(&self) -> Vec<MailboxKey> {
+        self.queued.lock().values().map(|(key, _)| key.clone()).collect()
+    }
+    
+    /// Demand on the pool relative to its capacity: running plus queued
+    /// units over `max_units`, capped at 10.0.
+    #[must_use]
+    pub fn load_factor(&self) -> f32 {
+        self.counters.load_factor(self.max_units())
+    }
+    
+    /// Current `max_units`, as configured or set by
+    /// [`set_max_units`](Self::set_max_units).
+    #[must_use]
+    pub fn max_units(&self) -> u32 {
+        self.max_units.load(Ordering::Acquire)
+    }
+    
+    /// Change `max_units` while the pool runs, e.g. after unloading a model
+    /// frees memory.
+    ///
+    /// Lowering it leaves running tasks alone; tasks that haven't claimed
+    /// their units yet wait until usage drops under the new limit, and new
+    /// tasks larger than it are rejected. Waiting tasks larger than the new
+    /// limit, queued or parked behind their dependencies, are dropped and
+    /// dead-lettered, since they could never start. Raising it lets waiting
+    /// tasks that now fit start right away.
+    pub fn set_max_units(&self, new_max: u32) {
+        let old_max = self.max_units.swap(new_max, Ordering::AcqRel);
+        info!(old_max, new_max, "WASM WorkerPool max_units changed");
+        // Waiting tasks recheck the limit: ones that now fit start, and ones
+        // that no longer can drop themselves
+        self.units_released.notify_waiters();
+        while let Some(parked) = self.dependencies.remove_parked(|parked| parked.cost > new_max) {
+            let _ = parked.open.send(Err(REASON_EXCEEDS_MAX_UNITS));
+            settle_dependents(&self.dependencies, parked.id, false);
+        }
+    }
+    
+    /// Whether [`load_factor`](Self::load_factor) exceeds the configured
+    /// `overload_threshold`.
+    #[must_use]
+    pub fn is_overloaded(&self) -> bool {
+        self.load_factor() > self.config.overload_threshold
+    }
+    
     /// Get current pool statistics.
+    ///
+    /// `queued_tasks` and `queue_oldest_age_ms` cover the tasks that have not
+    /// started yet rather than reading the submission counters.
     #[must_use]
     pub fn stats(&self) -> PoolStats {
-        let mut stats = self.counters.snapshot(self.config.worker_count, self.config.max_units);
+        let mut stats = self.counters.snapshot(self.config.worker_count, self.max_units());
+        let queued = self.queued.lock();
+        stats.queued_tasks = u64::try_from(queued.len()).unwrap_or(u64::MAX);
+        stats.queue_oldest_age_ms = queued.values().map(|(_, at)| *at).min().map(age_ms);
+        drop(queued);
         stats.used_units = self.active_units.load(Ordering::Relaxed);
+        stats.paused = self.pause.is_paused();
+        stats.degradation_active = self
+            .degradation
+            .as_ref()
+            .is_some_and(|d| d.is_active(stats.queued_tasks));
         stats
     }
     
+    /// Register this pool's statistics with a Prometheus registry.
+    ///
+    /// Values are read from the pool's counters at scrape time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the metrics are already registered in `registry`.
+    #[cfg(feature = "metrics")]
+    pub fn register_metrics(&self, registry: &prometheus::Registry) -> Result<(), prometheus::Error> {
+        let counters = Arc::clone(&self.counters);
+        let active_units = Arc::clone(&self.active_units);
+        let worker_count = self.config.worker_count;
+        let max_units = Arc::clone(&self.max_units);
+        let exporter = crate::util::telemetry::PrometheusExporter::new(move || {
+            let mut stats = counters.snapshot(worker_count, max_units.load(Ordering::Relaxed));
+            stats.used_units = active_units.load(Ordering::Relaxed);
+            stats
+        })?;
+        registry.register(Box::new(exporter))
+    }
+    
+    /// Stop starting queued tasks, e.g. while a model is reloaded.
+    ///
+    /// Running tasks finish, and submissions keep succeeding up to
+    /// `max_queue_depth`; they wait without claiming units until
+    /// [`resume`](Self::resume). `stats().paused` reports the state.
+    pub fn pause(&self) {
+        self.pause.set(true);
+        info!("WASM worker pool paused");
+    }
+    
+    /// Start queued tasks again after [`pause`](Self::pause).
+    pub fn resume(&self) {
+        self.pause.set(false);
+        info!("WASM worker pool resumed");
+    }
+    
     /// Shut down the pool.
     ///
-    /// This signals all workers to stop. Active tasks will complete,
-    /// but new submissions will be rejected.
+    /// New submissions are rejected, and every task still queued or running
+    /// is aborted: an executor stuck on a response that never arrives would
+    /// otherwise keep its result slot pending. Waiters on the results those
+    /// tasks will never produce get `PoolError::PoolShutdown` right away
+    /// instead of waiting out their timeout.
     pub fn shutdown(&self) {
         if self.shutdown.swap(true, Ordering::AcqRel) {
             return; // Already shut down
         }
         
         info!("Shutting down WASM worker pool");
-        // Close semaphore to prevent new permits
+        // Close semaphore to prevent new permits, and wake tasks waiting for units
         self.semaphore.close();
-        info!("WASM worker pool shut down signaled");
+        self.units_released.notify_waiters();
+        
+        // Aborting drops a task's future, whose tracker then takes the map's lock
+        let handles: Vec<AbortHandle> =
+            self.tasks.lock().drain().map(|(_, handle)| handle).collect();
+        for handle in &handles {
+            handle.abort();
+        }
+        self.results.shut_down_pending();
+        info!(aborted = handles.len(), "WASM worker pool shut down signaled");
+    }
+}
+
+impl<P, R, E> SnapshotSource for WorkerPool<P, R, E>
+where
+    P: Send + 'static,
+    R: Send + 'static,
+    E: WorkerExecutor<P, R> + Clone,
+{
+    fn snapshot_metrics(&self, name: &str) -> PoolSnapshotMetrics {
+        let stats = self.stats();
+        PoolSnapshotMetrics {
+            name: name.to_string(),
+            kind: PoolKind::Worker,
+            used_units: stats.used_units,
+            total_units: stats.total_units,
+            active_tasks: stats.active_tasks,
+            queue_depth: stats.queued_tasks,
+            queue_oldest_age_ms: stats.queue_oldest_age_ms,
+            completed_tasks: stats.completed_tasks,
+            failed_tasks: stats.failed_tasks,
+        }
     }
 }
 
@@ -386,7 +1243,7 @@ impl<P, R, E> Drop for WorkerPool<P, R, E>
 where
     P: Send + 'static,
     R: Send + 'static,
-    E: WorkerExecutor<P, R>,
+    E: WorkerExecutor<P, R> + Clone,
 {
     fn drop(&mut self) {
         self.shutdown();
@@ -426,6 +1283,11 @@ mod tests {
             },
             deadline_ms: None,
             created_at_ms: 0,
+            trace_context: None,
+            degraded: false,
+            depends_on: Vec::new(),
+            idempotency_key: None,
+            retrieve_timeout_ms: None,
         }
     }
     
@@ -452,6 +1314,148 @@ mod tests {
         assert_eq!(executor.execution_count.load(Ordering::Relaxed), 1);
     }
     
+    #[tokio::test]
+    async fn test_wasm_retrieve_with_deadline() {
+        let executor = TestExecutor {
+            execution_count: Arc::new(AtomicUsize::new(0)),
+        };
+        let config = WorkerPoolConfig::new()
+            .with_worker_count(2)
+            .with_max_queue_depth(10);
+        let pool = WorkerPool::new(config, executor).unwrap();
+        
+        let key = pool.submit_async("early".to_string(), make_meta(1)).await.unwrap();
+        assert!(matches!(pool.try_retrieve(&key), Ok(None)));
+        let result = pool.retrieve_with_deadline(&key, now_ms() + 5_000).await.unwrap();
+        assert_eq!(result, "Result: early");
+        
+        // The executor takes 10ms, so a 1ms deadline passes first
+        let key = pool.submit_async("late".to_string(), make_meta(2)).await.unwrap();
+        let timed_out = pool.retrieve_with_deadline(&key, now_ms() + 1).await;
+        assert!(matches!(timed_out, Err(PoolError::Timeout { .. })));
+        assert!(matches!(pool.try_retrieve(&key), Err(PoolError::ResultNotFound { .. })));
+    }
+    
+    /// Executor tracking the highest number of units running at once.
+    #[derive(Clone)]
+    struct UnitTrackingExecutor {
+        running_units: Arc<AtomicU32>,
+        max_running_units: Arc<AtomicU32>,
+    }
+    
+    #[async_trait]
+    impl WorkerExecutor<u32, u32> for UnitTrackingExecutor {
+        async fn execute(&self, units: u32, _meta: TaskMetadata) -> u32 {
+            let running = self.running_units.fetch_add(units, Ordering::SeqCst) + units;
+            self.max_running_units.fetch_max(running, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.running_units.fetch_sub(units, Ordering::SeqCst);
+            units
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_wasm_worker_pool_respects_max_units() {
+        let executor = UnitTrackingExecutor {
+            running_units: Arc::new(AtomicU32::new(0)),
+            max_running_units: Arc::new(AtomicU32::new(0)),
+        };
+        
+        // Plenty of permits, so only the unit budget limits concurrency
+        let config = WorkerPoolConfig::new()
+            .with_worker_count(8)
+            .with_max_units(10)
+            .with_max_queue_depth(100);
+        
+        let pool = WorkerPool::new(config, executor.clone()).unwrap();
+        
+        let mut keys = Vec::new();
+        for (i, units) in [8, 2, 2, 6, 1, 1, 4, 10].into_iter().enumerate() {
+            let mut meta = make_meta(i as u64);
+            meta.cost.units = units;
+            keys.push(pool.submit_async(units, meta).await.unwrap());
+        }
+        for key in &keys {
+            pool.retrieve_async(key, Duration::from_secs(10)).await.unwrap();
+        }
+        
+        assert!(executor.max_running_units.load(Ordering::SeqCst) <= 10);
+        assert_eq!(pool.stats().used_units, 0);
+    }
+    
+    #[tokio::test]
+    async fn test_wasm_set_max_units_wakes_waiting_tasks() {
+        let executor = UnitTrackingExecutor {
+            running_units: Arc::new(AtomicU32::new(0)),
+            max_running_units: Arc::new(AtomicU32::new(0)),
+        };
+        let config = WorkerPoolConfig::new()
+            .with_worker_count(8)
+            .with_max_units(10)
+            .with_max_queue_depth(100);
+        let pool = WorkerPool::new(config, executor.clone()).unwrap();
+        
+        // Under the lowered limit the tasks run one at a time
+        pool.set_max_units(4);
+        let mut keys = Vec::new();
+        for i in 0..3 {
+            let mut meta = make_meta(i);
+            meta.cost.units = 4;
+            keys.push(pool.submit_async(4, meta).await.unwrap());
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert_eq!(pool.stats().used_units, 4);
+        
+        // Raising it lets the waiting ones start alongside the running one
+        pool.set_max_units(12);
+        for key in &keys {
+            pool.retrieve_async(key, Duration::from_secs(10)).await.unwrap();
+        }
+        assert_eq!(executor.max_running_units.load(Ordering::SeqCst), 12);
+    }
+    
+    #[tokio::test]
+    async fn test_wasm_lowered_max_units_drops_oversized_tasks() {
+        let executor = UnitTrackingExecutor {
+            running_units: Arc::new(AtomicU32::new(0)),
+            max_running_units: Arc::new(AtomicU32::new(0)),
+        };
+        let config = WorkerPoolConfig::new()
+            .with_worker_count(8)
+            .with_max_units(10)
+            .with_max_queue_depth(100);
+        let pool = WorkerPool::new(config, executor).unwrap();
+        let submit = |id: u64, units: u32, depends_on: Vec<u64>| {
+            let mut meta = make_meta(id);
+            meta.cost.units = units;
+            meta.depends_on = depends_on;
+            pool.submit_async(units, meta)
+        };
+        
+        // One task holds every unit; one waits for units, one is parked
+        // behind the running task and one behind the parked task
+        let running = submit(1, 10, Vec::new()).await.unwrap();
+        let waiting = submit(2, 8, Vec::new()).await.unwrap();
+        let parked = submit(3, 8, vec![1]).await.unwrap();
+        let dependent = submit(4, 2, vec![3]).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert_eq!(pool.stats().used_units, 10);
+        
+        // Lowering drops the waiting and parked tasks too large for it, and
+        // raising it again afterwards does not bring them back
+        pool.set_max_units(6);
+        tokio::time::sleep(Duration::from_millis(1)).await;
+        pool.set_max_units(10);
+        assert_eq!(pool.retrieve_async(&running, Duration::from_secs(10)).await.unwrap(), 10);
+        for key in [&waiting, &parked, &dependent] {
+            assert!(matches!(pool.try_retrieve(key), Err(PoolError::ResultNotFound { .. })));
+        }
+        let stats = pool.stats();
+        assert_eq!(stats.failed_tasks, 3);
+        assert_eq!(stats.queued_tasks, 0);
+        assert_eq!(stats.used_units, 0);
+    }
+    
     #[tokio::test]
     async fn test_wasm_worker_pool_multiple_tasks() {
         let executor = TestExecutor {
@@ -480,4 +1484,72 @@ mod tests {
         // Check execution count
         assert_eq!(executor.execution_count.load(Ordering::Relaxed), 10);
     }
+    
+    /// Executor whose tasks never finish, like a request that gets no response.
+    #[derive(Clone)]
+    struct HungExecutor;
+    
+    #[async_trait]
+    impl WorkerExecutor<String, String> for HungExecutor {
+        async fn execute(&self, payload: String, _meta: TaskMetadata) -> String {
+            std::future::pending::<()>().await;
+            payload
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_wasm_shutdown_fails_hung_task_waiters() {
+        let config = WorkerPoolConfig::new()
+            .with_worker_count(1)
+            .with_max_queue_depth(10);
+        let pool = Arc::new(WorkerPool::new(config, HungExecutor).unwrap());
+        
+        let running = pool.submit_async("running".to_string(), make_meta(1)).await.unwrap();
+        let queued = pool.submit_async("queued".to_string(), make_meta(2)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        
+        let waiter = {
+            let pool = Arc::clone(&pool);
+            let key = running.clone();
+            tokio::spawn(async move { pool.retrieve_async(&key, Duration::from_secs(30)).await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        
+        let started = std::time::Instant::now();
+        pool.shutdown();
+        let result = tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .expect("waiter still blocked after shutdown")
+            .unwrap();
+        assert!(matches!(result, Err(PoolError::PoolShutdown)));
+        assert!(started.elapsed() < Duration::from_secs(1));
+        
+        // A task that never got to run fails the same way
+        let result = pool.retrieve_async(&queued, Duration::from_secs(30)).await;
+        assert!(matches!(result, Err(PoolError::PoolShutdown)));
+        assert!(pool.tasks.lock().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_wasm_queue_depth_limit_concurrent() {
+        let config = WorkerPoolConfig::new()
+            .with_worker_count(1)
+            .with_max_queue_depth(2);
+        let pool = WorkerPool::new(config, HungExecutor).unwrap();
+        
+        // Race many submissions against the depth check
+        let submissions = (0..32).map(|i| pool.submit_async(format!("task-{i}"), make_meta(i)));
+        let results = futures::future::join_all(submissions).await;
+        
+        let accepted = results.iter().filter(|result| result.is_ok()).count();
+        assert!(results
+            .iter()
+            .all(|result| matches!(result, Ok(_) | Err(PoolError::QueueFull { .. }))));
+        // At most 1 running + 2 queued, however the submissions interleave
+        assert!((2..=3).contains(&accepted), "accepted {accepted} tasks");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(pool.stats().queued_tasks <= 2);
+        
+        pool.shutdown();
+    }
 }
@@ -0,0 +1,523 @@
+//! Bounded priority queue feeding the native `WorkerPool` worker threads.
+//!
+//! Tasks are ordered by priority (highest first) and FIFO within a priority.
+//! Idle workers block on a Condvar until a task arrives. Closing the queue
+//! rejects further pushes; workers keep popping until it is empty and then
+//! exit. With a running limit, items stay queued while that many popped
+//! items are still running. A paused queue hands out nothing until resumed,
+//! unless it is closed. Workers of different sizes pop only the items that
+//! fit them, each item going to the smallest idle worker it fits. With a unit
+//! budget, items are handed out only while the units in use plus theirs stay
+//! within the budget's limit.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicU32, Ordering as AtomicOrdering};
+use std::sync::Arc;
+
+use parking_lot::{Condvar, Mutex};
+
+use crate::util::serde::Priority;
+
+/// Why a push was rejected; the item is handed back.
+pub enum PushError<T> {
+    /// The queue is at capacity.
+    Full(T),
+    /// The queue was closed by shutdown.
+    Closed(T),
+}
+
+/// A queued item with its ordering key.
+struct Entry<T> {
+    priority: Priority,
+    /// Push sequence number, for FIFO within a priority.
+    seq: u64,
+    item: T,
+}
+
+impl<T> PartialEq for Entry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.seq == other.seq
+    }
+}
+
+impl<T> Eq for Entry<T> {}
+
+impl<T> PartialOrd for Entry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Entry<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Higher priority first, then earlier pushes (reversed for the max-heap)
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+/// Mutable queue state, guarded by a single lock.
+struct WorkQueueInner<T> {
+    heap: BinaryHeap<Entry<T>>,
+    next_seq: u64,
+    closed: bool,
+    /// Popped items not yet finished; only counted under a running limit.
+    running: usize,
+    paused: bool,
+    /// Capacities of the workers waiting in `pop_fitting`.
+    idle: Vec<u32>,
+}
+
+impl<T> WorkQueueInner<T> {
+    /// Remove the first item, in pop order, of at most `capacity` units that
+    /// fits in `budget` units and no smaller idle worker could take instead.
+    fn take_best_fit(
+        &mut self,
+        capacity: u32,
+        budget: u32,
+        units: &impl Fn(&T) -> u32,
+    ) -> Option<T> {
+        let idle = &self.idle;
+        let suits = |item: &T| {
+            let need = units(item);
+            need <= capacity.min(budget)
+                && !idle.iter().any(|&other| other < capacity && need <= other)
+        };
+        if suits(&self.heap.peek()?.item) {
+            return self.heap.pop().map(|entry| entry.item);
+        }
+        let mut entries = std::mem::take(&mut self.heap).into_vec();
+        // Descending is pop order
+        entries.sort_unstable_by(|a, b| b.cmp(a));
+        let taken = entries
+            .iter()
+            .position(|entry| suits(&entry.item))
+            .map(|index| entries.remove(index).item);
+        self.heap = entries.into();
+        taken
+    }
+}
+
+/// Units shared with the pool: the units held by popped items and the most
+/// they may hold at once, which can change while the queue runs.
+struct UnitBudget<T> {
+    in_use: Arc<AtomicU32>,
+    limit: Arc<AtomicU32>,
+    units: fn(&T) -> u32,
+}
+
+impl<T> UnitBudget<T> {
+    /// Units still free. While nothing holds units everything fits, so an
+    /// item larger than a lowered limit runs alone instead of blocking the
+    /// queue forever.
+    fn remaining(&self) -> u32 {
+        let in_use = self.in_use.load(AtomicOrdering::Acquire);
+        if in_use == 0 {
+            return u32::MAX;
+        }
+        self.limit.load(AtomicOrdering::Acquire).saturating_sub(in_use)
+    }
+}
+
+/// Bounded, closable priority queue with blocking pop.
+pub struct WorkQueue<T> {
+    capacity: usize,
+    max_running: Option<usize>,
+    budget: Option<UnitBudget<T>>,
+    /// Whether workers pop with `pop_fitting`, so any of them may be the one
+    /// an item needs and every wake goes to all of them.
+    weighted: bool,
+    inner: Mutex<WorkQueueInner<T>>,
+    available: Condvar,
+}
+
+impl<T> WorkQueue<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            max_running: None,
+            budget: None,
+            weighted: false,
+            inner: Mutex::new(WorkQueueInner {
+                heap: BinaryHeap::with_capacity(capacity.min(1024)),
+                next_seq: 0,
+                closed: false,
+                running: 0,
+                paused: false,
+                idle: Vec::new(),
+            }),
+            available: Condvar::new(),
+        }
+    }
+
+    /// Hand out at most `max` items at a time: `pop` waits while `max`
+    /// popped items are unfinished.
+    pub const fn with_max_running(mut self, max: usize) -> Self {
+        self.max_running = Some(max);
+        self
+    }
+
+    /// Hand out items only while `in_use` plus their `units` stay within
+    /// `limit`; popping adds an item's units to `in_use`, and
+    /// [`release_units`](Self::release_units) takes them back.
+    pub fn with_unit_budget(
+        mut self,
+        in_use: Arc<AtomicU32>,
+        limit: Arc<AtomicU32>,
+        units: fn(&T) -> u32,
+    ) -> Self {
+        self.budget = Some(UnitBudget { in_use, limit, units });
+        self
+    }
+
+    /// Units the budget has left, or `u32::MAX` without a budget.
+    fn remaining_units(&self) -> u32 {
+        self.budget.as_ref().map_or(u32::MAX, UnitBudget::remaining)
+    }
+
+    /// Count a popped item against the budget; call with the lock held.
+    fn take_units(&self, item: &T) {
+        if let Some(budget) = &self.budget {
+            budget.in_use.fetch_add((budget.units)(item), AtomicOrdering::AcqRel);
+        }
+    }
+
+    /// Return `units` taken by a popped item to the budget, letting waiting
+    /// workers take items that now fit.
+    pub fn release_units(&self, units: u32) {
+        if let Some(budget) = &self.budget {
+            // Under the lock, so a worker can't miss the release between
+            // checking the budget and waiting
+            let inner = self.inner.lock();
+            budget.in_use.fetch_sub(units, AtomicOrdering::AcqRel);
+            drop(inner);
+            self.available.notify_all();
+        }
+    }
+
+    /// Wake every idle worker to re-check the budget, e.g. after its limit
+    /// was raised.
+    pub fn budget_changed(&self) {
+        drop(self.inner.lock());
+        self.available.notify_all();
+    }
+
+    /// Expect workers to pop with [`pop_fitting`](Self::pop_fitting).
+    pub const fn with_weighted_workers(mut self) -> Self {
+        self.weighted = true;
+        self
+    }
+
+    /// Wake one idle worker, or all of them when they pop different items.
+    fn notify(&self) {
+        if self.weighted {
+            self.available.notify_all();
+        } else {
+            self.available.notify_one();
+        }
+    }
+
+    /// Push an item without blocking, waking an idle worker.
+    pub fn try_push(&self, item: T, priority: Priority) -> Result<(), PushError<T>> {
+        let mut inner = self.inner.lock();
+        if inner.closed {
+            return Err(PushError::Closed(item));
+        }
+        if inner.heap.len() >= self.capacity {
+            return Err(PushError::Full(item));
+        }
+        let seq = inner.next_seq;
+        inner.next_seq += 1;
+        inner.heap.push(Entry { priority, seq, item });
+        drop(inner);
+        self.notify();
+        Ok(())
+    }
+
+    /// Block until an item is available, returning `None` once the queue is
+    /// closed and empty.
+    ///
+    /// Under a running limit the item counts as running until
+    /// [`finish`](Self::finish) is called for it. Under a unit budget the
+    /// next item waits, holding back those behind it, until its units fit.
+    pub fn pop(&self) -> Option<T> {
+        let mut inner = self.inner.lock();
+        loop {
+            let has_slot = self.max_running.is_none_or(|max| inner.running < max);
+            // Closing overrides a pause, so workers can see the shutdown and exit
+            let dispatching = !inner.paused || inner.closed;
+            let fits = inner.heap.peek().is_some_and(|entry| {
+                self.budget
+                    .as_ref()
+                    .is_none_or(|budget| (budget.units)(&entry.item) <= budget.remaining())
+            });
+            if dispatching && has_slot && fits {
+                let entry = inner.heap.pop()?;
+                if self.max_running.is_some() {
+                    inner.running += 1;
+                }
+                self.take_units(&entry.item);
+                drop(inner);
+                return Some(entry.item);
+            }
+            if inner.closed && inner.heap.is_empty() {
+                drop(inner);
+                return None;
+            }
+            self.available.wait(&mut inner);
+        }
+    }
+
+    /// Like [`pop`](Self::pop), for a worker that can run items of at most
+    /// `capacity` units as measured by `units`.
+    ///
+    /// Items that don't fit are left for other workers, and an item that
+    /// also fits a smaller idle worker is left for that one (best fit), so
+    /// small items don't tie up the workers large ones need. Returns `None`
+    /// once the queue is closed and holds nothing this worker can take.
+    pub fn pop_fitting(&self, capacity: u32, units: impl Fn(&T) -> u32) -> Option<T> {
+        let mut inner = self.inner.lock();
+        loop {
+            let has_slot = self.max_running.is_none_or(|max| inner.running < max);
+            let dispatching = !inner.paused || inner.closed;
+            let taken = if dispatching && has_slot {
+                inner.take_best_fit(capacity, self.remaining_units(), &units)
+            } else {
+                None
+            };
+            if let Some(item) = taken {
+                if self.max_running.is_some() {
+                    inner.running += 1;
+                }
+                self.take_units(&item);
+                let more = !inner.heap.is_empty();
+                drop(inner);
+                // Workers that skipped items for this one may take them now
+                if more {
+                    self.available.notify_all();
+                }
+                return Some(item);
+            }
+            if inner.closed {
+                drop(inner);
+                return None;
+            }
+            inner.idle.push(capacity);
+            self.available.wait(&mut inner);
+            if let Some(index) = inner.idle.iter().position(|&other| other == capacity) {
+                inner.idle.swap_remove(index);
+            }
+        }
+    }
+
+    /// Mark a popped item as finished, letting a waiting `pop` take another.
+    pub fn finish(&self) {
+        if self.max_running.is_none() {
+            return;
+        }
+        let mut inner = self.inner.lock();
+        inner.running = inner.running.saturating_sub(1);
+        drop(inner);
+        self.notify();
+    }
+
+    /// Stop or restart handing out items; pushes are accepted either way.
+    pub fn set_paused(&self, paused: bool) {
+        self.inner.lock().paused = paused;
+        if !paused {
+            self.available.notify_all();
+        }
+    }
+
+    /// Whether `pop` is holding items back until the queue is resumed.
+    pub fn is_paused(&self) -> bool {
+        self.inner.lock().paused
+    }
+
+    /// Reject further pushes and wake every idle worker.
+    pub fn close(&self) {
+        self.inner.lock().closed = true;
+        self.available.notify_all();
+    }
+
+    /// Number of queued items.
+    pub fn len(&self) -> usize {
+        self.inner.lock().heap.len()
+    }
+
+    /// Remove every queued item, in the order they would have been popped.
+    pub fn drain(&self) -> Vec<T> {
+        let heap = std::mem::take(&mut self.inner.lock().heap);
+        // Ascending order is lowest first, so reverse it
+        heap.into_sorted_vec().into_iter().rev().map(|entry| entry.item).collect()
+    }
+    
+    /// Remove the first queued item matching `matches`, if any.
+    pub fn remove(&self, matches: impl Fn(&T) -> bool) -> Option<T> {
+        let mut inner = self.inner.lock();
+        let mut entries = std::mem::take(&mut inner.heap).into_vec();
+        let removed = entries
+            .iter()
+            .position(|entry| matches(&entry.item))
+            .map(|index| entries.swap_remove(index).item);
+        inner.heap = entries.into();
+        drop(inner);
+        removed
+    }
+    
+    /// Smallest key `f` maps a queued item to, or `None` when empty.
+    pub fn min_by_key<K: Ord>(&self, f: impl Fn(&T) -> K) -> Option<K> {
+        self.inner.lock().heap.iter().map(|entry| f(&entry.item)).min()
+    }
+    
+    /// Map every queued item with `f`, in the order they will be popped.
+    pub fn snapshot<K>(&self, f: impl Fn(&T) -> K) -> Vec<K> {
+        let inner = self.inner.lock();
+        let mut entries: Vec<(Priority, u64, K)> = inner
+            .heap
+            .iter()
+            .map(|entry| (entry.priority, entry.seq, f(&entry.item)))
+            .collect();
+        drop(inner);
+        // Same order as `Entry::cmp`, highest first
+        entries.sort_unstable_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        entries.into_iter().map(|(_, _, key)| key).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority_then_fifo_order() {
+        let queue = WorkQueue::new(10);
+        assert!(queue.try_push(1, Priority::Normal).is_ok());
+        assert!(queue.try_push(2, Priority::Critical).is_ok());
+        assert!(queue.try_push(3, Priority::Normal).is_ok());
+        assert!(queue.try_push(4, Priority::Low).is_ok());
+
+        assert_eq!(queue.snapshot(|item| *item), vec![2, 1, 3, 4]);
+        assert_eq!(queue.pop(), Some(2));
+        assert_eq!(queue.pop(), Some(1));
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.drain(), vec![3, 4]);
+        assert_eq!(queue.len(), 0);
+    }
+
+    #[test]
+    fn test_full_and_closed() {
+        let queue = WorkQueue::new(1);
+        assert!(queue.try_push(1, Priority::Normal).is_ok());
+        assert!(matches!(queue.try_push(2, Priority::Normal), Err(PushError::Full(2))));
+
+        // Closing keeps queued items poppable, then reports the end
+        queue.close();
+        assert!(matches!(queue.try_push(3, Priority::Normal), Err(PushError::Closed(3))));
+        assert_eq!(queue.pop(), Some(1));
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn test_max_running_holds_items_back() {
+        let queue = WorkQueue::new(10).with_max_running(1);
+        assert!(queue.try_push(1, Priority::Normal).is_ok());
+        assert!(queue.try_push(2, Priority::Normal).is_ok());
+
+        assert_eq!(queue.pop(), Some(1));
+        std::thread::scope(|scope| {
+            let waiter = scope.spawn(|| queue.pop());
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            // The second item stays queued until the first finishes
+            assert_eq!(queue.len(), 1);
+            queue.finish();
+            assert_eq!(waiter.join().unwrap(), Some(2));
+        });
+    }
+
+    #[test]
+    fn test_pause_holds_items_until_resumed() {
+        let queue = WorkQueue::new(10);
+        queue.set_paused(true);
+        assert!(queue.try_push(1, Priority::Normal).is_ok());
+
+        std::thread::scope(|scope| {
+            let waiter = scope.spawn(|| queue.pop());
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            assert_eq!(queue.len(), 1);
+            queue.set_paused(false);
+            assert_eq!(waiter.join().unwrap(), Some(1));
+        });
+    }
+
+    #[test]
+    fn test_pop_fitting_prefers_smallest_idle_worker() {
+        let queue = WorkQueue::new(10).with_weighted_workers();
+        assert!(queue.try_push(20, Priority::High).is_ok());
+        assert!(queue.try_push(5, Priority::Normal).is_ok());
+
+        // A small worker skips the large item for one that fits it
+        assert_eq!(queue.pop_fitting(8, |item| *item), Some(5));
+        assert!(queue.try_push(6, Priority::Normal).is_ok());
+
+        std::thread::scope(|scope| {
+            let small = scope.spawn(|| queue.pop_fitting(8, |item| *item));
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            // With the small worker idle, the large one leaves it the small item
+            assert_eq!(queue.pop_fitting(24, |item| *item), Some(20));
+            assert_eq!(small.join().unwrap(), Some(6));
+        });
+
+        queue.close();
+        assert_eq!(queue.pop_fitting(8, |item| *item), None);
+    }
+
+    #[test]
+    fn test_unit_budget_holds_items_back() {
+        let in_use = Arc::new(AtomicU32::new(0));
+        let limit = Arc::new(AtomicU32::new(10));
+        let queue = WorkQueue::new(10).with_unit_budget(
+            Arc::clone(&in_use),
+            Arc::clone(&limit),
+            |item: &u32| *item,
+        );
+        assert!(queue.try_push(6, Priority::Normal).is_ok());
+        assert!(queue.try_push(6, Priority::Normal).is_ok());
+
+        assert_eq!(queue.pop(), Some(6));
+        assert_eq!(in_use.load(AtomicOrdering::Acquire), 6);
+        std::thread::scope(|scope| {
+            let waiter = scope.spawn(|| queue.pop());
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            // The second item waits for the first one's units
+            assert_eq!(queue.len(), 1);
+            queue.release_units(6);
+            assert_eq!(waiter.join().unwrap(), Some(6));
+        });
+
+        // Lowering the limit holds items back until usage drops under it
+        limit.store(4, AtomicOrdering::Release);
+        assert!(queue.try_push(2, Priority::Normal).is_ok());
+        std::thread::scope(|scope| {
+            let waiter = scope.spawn(|| queue.pop());
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            assert_eq!(queue.len(), 1);
+            queue.release_units(6);
+            assert_eq!(waiter.join().unwrap(), Some(2));
+        });
+    }
+
+    #[test]
+    fn test_remove_keeps_order() {
+        let queue = WorkQueue::new(10);
+        for (item, priority) in [(1, Priority::Low), (2, Priority::High), (3, Priority::Normal), (4, Priority::High)] {
+            assert!(queue.try_push(item, priority).is_ok());
+        }
+
+        assert_eq!(queue.remove(|item| *item == 2), Some(2));
+        assert_eq!(queue.remove(|item| *item == 2), None);
+        assert_eq!(queue.drain(), vec![4, 3, 1]);
+    }
+}
@@ -1,25 +1,37 @@
 //! In-memory mailbox backend.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
 
 use crate::core::{Mailbox, TaskStatus};
 use crate::core::SchedulerError;
+use crate::util::clock::{Clock, SystemClock};
 use crate::util::serde::MailboxKey;
 
-/// Mailbox message container.
-#[derive(Debug, Clone)]
-pub struct MailboxMessage<P> {
-    /// Task status.
-    pub status: TaskStatus,
-    /// Optional payload/result.
-    pub payload: Option<P>,
-    /// Timestamp milliseconds.
-    pub created_at_ms: u128,
+pub use crate::core::MailboxMessage;
+
+/// Which message a full per-key mailbox gives up on delivery.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// Drop the oldest retained message to make room for the new one.
+    #[default]
+    DropOldest,
+    /// Keep the retained messages and drop the new one.
+    RejectNew,
 }
 
 /// Simple in-memory mailbox for development/testing.
+///
+/// Messages are kept per key until the mailbox is dropped. On long-running
+/// servers, cap each key with
+/// [`with_max_messages_per_key`](Self::with_max_messages_per_key) so a busy
+/// tenant's mailbox can't grow without bound.
 pub struct InMemoryMailbox<P> {
-    messages: HashMap<MailboxKey, Vec<MailboxMessage<P>>>,
+    messages: HashMap<MailboxKey, VecDeque<MailboxMessage<P>>>,
+    max_messages_per_key: Option<usize>,
+    eviction: EvictionPolicy,
+    /// Stamps `created_at_ms` of delivered messages.
+    clock: Arc<dyn Clock>,
 }
 
 impl<P> InMemoryMailbox<P> {
@@ -27,11 +39,78 @@ impl<P> InMemoryMailbox<P> {
     pub fn new() -> Self {
         Self {
             messages: HashMap::new(),
+            max_messages_per_key: None,
+            eviction: EvictionPolicy::default(),
+            clock: Arc::new(SystemClock),
         }
     }
 
-    /// Fetch messages for a mailbox key, optionally since a timestamp.
-    pub fn fetch(
+    /// Stamp delivered messages from `clock` instead of the system clock.
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Retain at most `max` messages per key, evicting by `policy` once full.
+    #[must_use]
+    pub const fn with_max_messages_per_key(
+        mut self,
+        max: usize,
+        policy: EvictionPolicy,
+    ) -> Self {
+        self.max_messages_per_key = Some(max);
+        self.eviction = policy;
+        self
+    }
+
+    /// Deliver a message, returning the one evicted to stay within the
+    /// per-key cap, if any.
+    ///
+    /// Under [`EvictionPolicy::DropOldest`] that is the oldest retained
+    /// message; under [`EvictionPolicy::RejectNew`] it is the new one. Hand
+    /// it to a dead-letter sink to keep a record of it.
+    pub fn deliver_evicting(
+        &mut self,
+        key: &MailboxKey,
+        status: TaskStatus,
+        payload: Option<P>,
+    ) -> Option<MailboxMessage<P>> {
+        let message = MailboxMessage {
+            status,
+            payload,
+            created_at_ms: self.clock.now_ms(),
+        };
+        let entry = self.messages.entry(key.clone()).or_default();
+        let full = self.max_messages_per_key.is_some_and(|max| entry.len() >= max);
+        if !full {
+            entry.push_back(message);
+            return None;
+        }
+        match self.eviction {
+            EvictionPolicy::RejectNew => Some(message),
+            EvictionPolicy::DropOldest => {
+                entry.push_back(message);
+                entry.pop_front()
+            }
+        }
+    }
+}
+
+impl<P> Mailbox<P> for InMemoryMailbox<P> {
+    fn deliver(
+        &mut self,
+        key: &MailboxKey,
+        status: TaskStatus,
+        payload: Option<P>,
+    ) -> Result<(), SchedulerError> {
+        if self.deliver_evicting(key, status, payload).is_some() {
+            tracing::debug!("mailbox for tenant {} full, evicted a message", key.tenant);
+        }
+        Ok(())
+    }
+
+    fn fetch(
         &self,
         key: &MailboxKey,
         since_ms: Option<u128>,
@@ -53,19 +132,65 @@ impl<P> InMemoryMailbox<P> {
     }
 }
 
-impl<P> Mailbox<P> for InMemoryMailbox<P> {
-    fn deliver(
-        &mut self,
-        key: &MailboxKey,
-        status: TaskStatus,
-        payload: Option<P>,
-    ) -> Result<(), SchedulerError> {
-        let entry = self.messages.entry(key.clone()).or_default();
-        entry.push(MailboxMessage {
-            status,
-            payload,
-            created_at_ms: crate::util::clock::now_ms(),
-        });
-        Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::clock::ManualClock;
+
+    fn key() -> MailboxKey {
+        MailboxKey {
+            tenant: "tenant".to_string(),
+            user_id: None,
+            session_id: None,
+        }
+    }
+
+    fn payloads(mailbox: &InMemoryMailbox<u32>) -> Vec<u32> {
+        mailbox
+            .fetch(&key(), None, usize::MAX)
+            .into_iter()
+            .filter_map(|m| m.payload)
+            .collect()
+    }
+
+    #[test]
+    fn test_drop_oldest_keeps_latest_window() {
+        let mut mailbox =
+            InMemoryMailbox::new().with_max_messages_per_key(3, EvictionPolicy::DropOldest);
+        for value in 1..=3 {
+            assert!(mailbox.deliver_evicting(&key(), TaskStatus::Completed, Some(value)).is_none());
+        }
+
+        let evicted = mailbox.deliver_evicting(&key(), TaskStatus::Completed, Some(4));
+        assert_eq!(evicted.and_then(|m| m.payload), Some(1));
+        mailbox.deliver(&key(), TaskStatus::Completed, Some(5)).unwrap();
+        assert_eq!(payloads(&mailbox), vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn test_fetch_since_uses_injected_clock() {
+        let clock = ManualClock::new(100);
+        let mut mailbox = InMemoryMailbox::new().with_clock(Arc::new(clock.clone()));
+        mailbox.deliver(&key(), TaskStatus::Completed, Some(1)).unwrap();
+        clock.set(200);
+        mailbox.deliver(&key(), TaskStatus::Completed, Some(2)).unwrap();
+
+        let later = mailbox.fetch(&key(), Some(150), usize::MAX);
+        assert_eq!(later.len(), 1);
+        assert_eq!((later[0].payload, later[0].created_at_ms), (Some(2), 200));
+        // The boundary is inclusive
+        assert_eq!(mailbox.fetch(&key(), Some(100), usize::MAX).len(), 2);
+    }
+
+    #[test]
+    fn test_reject_new_keeps_first_window() {
+        let mut mailbox =
+            InMemoryMailbox::new().with_max_messages_per_key(2, EvictionPolicy::RejectNew);
+        mailbox.deliver(&key(), TaskStatus::Completed, Some(1)).unwrap();
+        mailbox.deliver(&key(), TaskStatus::Completed, Some(2)).unwrap();
+
+        let evicted = mailbox.deliver_evicting(&key(), TaskStatus::Completed, Some(3));
+        assert_eq!(evicted.and_then(|m| m.payload), Some(3));
+        assert_eq!(payloads(&mailbox), vec![1, 2]);
     }
 }
@@ -4,6 +4,6 @@ pub mod memory;
 pub mod postgres;
 pub mod yaque;
 
-pub use memory::InMemoryMailbox;
+pub use memory::{EvictionPolicy, InMemoryMailbox};
 pub use postgres::PostgresMailbox;
 pub use yaque::YaqueMailbox;
@@ -1,32 +1,25 @@
 //! File-backed mailbox adapter inspired by Yaque.
 
 use std::collections::HashMap;
-use std::fs::{create_dir_all, OpenOptions};
-use std::io::{BufRead, BufReader, Write};
+use std::fs::create_dir_all;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Serialize};
 
 use crate::core::{Mailbox, SchedulerError, TaskStatus};
-use crate::util::clock::now_ms;
-use crate::util::serde::MailboxKey;
+use crate::util::clock::{Clock, SystemClock};
+use crate::util::serde::{append_json_line, read_json_lines, MailboxKey};
+
+pub use crate::core::MailboxMessage;
 
 /// File-backed mailbox using JSON lines for durability.
 pub struct YaqueMailbox<P> {
     path: PathBuf,
     stream: String,
     messages: HashMap<MailboxKey, Vec<MailboxMessage<P>>>,
-}
-
-/// Mailbox message container.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MailboxMessage<P> {
-    /// Task status.
-    pub status: TaskStatus,
-    /// Optional payload.
-    pub payload: Option<P>,
-    /// Timestamp milliseconds.
-    pub created_at_ms: u128,
+    /// Stamps `created_at_ms` of delivered messages.
+    clock: Arc<dyn Clock>,
 }
 
 impl<P> YaqueMailbox<P> {
@@ -42,11 +35,19 @@ impl<P> YaqueMailbox<P> {
             path,
             stream,
             messages: HashMap::new(),
+            clock: Arc::new(SystemClock),
         };
         mb.load_from_disk()?;
         Ok(mb)
     }
 
+    /// Stamp delivered messages from `clock` instead of the system clock.
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     fn file_path(&self) -> PathBuf {
         self.path.join(format!("{}_mailbox.jsonl", self.stream))
     }
@@ -55,19 +56,8 @@ impl<P> YaqueMailbox<P> {
     where
         P: DeserializeOwned,
     {
-        let file_path = self.file_path();
-        if !file_path.exists() {
-            return Ok(());
-        }
-        let file = OpenOptions::new()
-            .read(true)
-            .open(&file_path)
-            .map_err(|e| SchedulerError::Backend(e.to_string()))?;
-        let reader = BufReader::new(file);
-        for line in reader.lines() {
-            let line = line.map_err(|e| SchedulerError::Backend(e.to_string()))?;
-            let (key, msg): (MailboxKey, MailboxMessage<P>) =
-                serde_json::from_str(&line).map_err(|e| SchedulerError::Backend(e.to_string()))?;
+        let entries: Vec<(MailboxKey, MailboxMessage<P>)> = read_json_lines(&self.file_path())?;
+        for (key, msg) in entries {
             self.messages.entry(key).or_default().push(msg);
         }
         Ok(())
@@ -77,19 +67,30 @@ impl<P> YaqueMailbox<P> {
     where
         P: Serialize,
     {
-        let file_path = self.file_path();
-        let mut file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&file_path)
-            .map_err(|e| SchedulerError::Backend(e.to_string()))?;
-        let line = serde_json::to_string(&(key, msg))
-            .map_err(|e| SchedulerError::Backend(e.to_string()))?;
-        writeln!(file, "{line}").map_err(|e| SchedulerError::Backend(e.to_string()))
+        append_json_line(&self.file_path(), &(key, msg))
+    }
+}
+
+impl<P> Mailbox<P> for YaqueMailbox<P>
+where
+    P: Serialize + DeserializeOwned + Clone,
+{
+    fn deliver(
+        &mut self,
+        key: &MailboxKey,
+        status: TaskStatus,
+        payload: Option<P>,
+    ) -> Result<(), SchedulerError> {
+        let msg = MailboxMessage {
+            status,
+            payload,
+            created_at_ms: self.clock.now_ms(),
+        };
+        self.messages.entry(key.clone()).or_default().push(msg.clone());
+        self.append_to_disk(key, &msg)
     }
 
-    /// Fetch messages for a mailbox key, optionally since a timestamp.
-    pub fn fetch(
+    fn fetch(
         &self,
         key: &MailboxKey,
         since_ms: Option<u128>,
@@ -111,22 +112,36 @@ impl<P> YaqueMailbox<P> {
     }
 }
 
-impl<P> Mailbox<P> for YaqueMailbox<P>
-where
-    P: Serialize + DeserializeOwned + Clone,
-{
-    fn deliver(
-        &mut self,
-        key: &MailboxKey,
-        status: TaskStatus,
-        payload: Option<P>,
-    ) -> Result<(), SchedulerError> {
-        let msg = MailboxMessage {
-            status,
-            payload,
-            created_at_ms: now_ms(),
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::clock::{now_ms, ManualClock};
+
+    #[test]
+    fn test_fetch_since_uses_injected_clock() {
+        let dir = std::env::temp_dir().join(format!("pl-yaque-clock-{}", now_ms()));
+        let clock = ManualClock::new(100);
+        let mut mailbox = YaqueMailbox::new(&dir, "clock")
+            .unwrap()
+            .with_clock(Arc::new(clock.clone()));
+        let key = MailboxKey {
+            tenant: "tenant".to_string(),
+            user_id: None,
+            session_id: None,
         };
-        self.messages.entry(key.clone()).or_default().push(msg.clone());
-        self.append_to_disk(key, &msg)
+
+        mailbox.deliver(&key, TaskStatus::Completed, Some("early".to_string())).unwrap();
+        clock.set(200);
+        mailbox.deliver(&key, TaskStatus::Completed, Some("late".to_string())).unwrap();
+
+        let later = mailbox.fetch(&key, Some(150), usize::MAX);
+        assert_eq!(later.len(), 1);
+        assert_eq!(later[0].payload.as_deref(), Some("late"));
+        assert_eq!(later[0].created_at_ms, 200);
+
+        // Reloaded messages keep the timestamps they were delivered with
+        let reloaded: YaqueMailbox<String> = YaqueMailbox::new(&dir, "clock").unwrap();
+        assert_eq!(reloaded.fetch(&key, Some(150), usize::MAX).len(), 1);
+        let _ = std::fs::remove_dir_all(&dir);
     }
 }
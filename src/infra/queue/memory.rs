@@ -4,22 +4,33 @@ use std::cmp::Ordering;
 use std::collections::BinaryHeap;
 
 use crate::core::SchedulerError;
-use crate::core::{ScheduledTask, TaskQueue};
+use crate::core::{ScheduledTask, TaskMetadata, TaskQueue};
 use crate::util::serde::Priority;
 
-/// Wrapper to make ScheduledTask orderable by priority (highest first) and FIFO within priority.
+/// Order in which an [`InMemoryQueue`] hands out its tasks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OrderingStrategy {
+    /// Highest priority first, FIFO within a priority.
+    #[default]
+    PriorityFifo,
+    /// Oldest first, ignoring priority.
+    Fifo,
+    /// Newest first, ignoring priority; favors fresh requests under overload.
+    Lifo,
+    /// Earliest `deadline_ms` first, tasks without a deadline last; ties
+    /// fall back to priority, then FIFO.
+    EarliestDeadline,
+}
+
+/// Wrapper to make ScheduledTask orderable by the queue's [`OrderingStrategy`].
 struct PriorityTask<P> {
     task: ScheduledTask<P>,
+    ordering: OrderingStrategy,
 }
 
 impl<P> PriorityTask<P> {
     fn priority_value(p: Priority) -> u8 {
-        match p {
-            Priority::Low => 0,
-            Priority::Normal => 1,
-            Priority::High => 2,
-            Priority::Critical => 3,
-        }
+        p.value()
     }
 }
 
@@ -39,26 +50,36 @@ impl<P> PartialOrd for PriorityTask<P> {
 
 impl<P> Ord for PriorityTask<P> {
     fn cmp(&self, other: &Self) -> Ordering {
+        // Earlier created_at wins (reversed for max-heap)
+        let fifo = other.task.meta.created_at_ms.cmp(&self.task.meta.created_at_ms);
         let self_priority = Self::priority_value(self.task.meta.priority);
         let other_priority = Self::priority_value(other.task.meta.priority);
+        // Higher priority first, FIFO within same priority
+        let priority_fifo = self_priority.cmp(&other_priority).then(fifo);
         
-        // Higher priority first
-        match self_priority.cmp(&other_priority) {
-            Ordering::Equal => {
-                // FIFO within same priority: earlier created_at wins (reversed for max-heap)
-                other.task.meta.created_at_ms.cmp(&self.task.meta.created_at_ms)
+        match self.ordering {
+            OrderingStrategy::PriorityFifo => priority_fifo,
+            OrderingStrategy::Fifo => fifo,
+            OrderingStrategy::Lifo => fifo.reverse(),
+            OrderingStrategy::EarliestDeadline => {
+                // Earlier deadline wins; a missing deadline sorts after any deadline
+                let deadline = |pt: &Self| pt.task.meta.deadline_ms.unwrap_or(u128::MAX);
+                deadline(other).cmp(&deadline(self)).then(priority_fifo)
             }
-            other => other,
         }
     }
 }
 
 /// In-memory queue storing scheduled tasks using a priority heap.
 /// This provides O(log n) enqueue and O(log n) dequeue operations.
+///
+/// Tasks are dequeued by priority, FIFO within a priority, unless another
+/// order is picked with [`with_ordering`](Self::with_ordering).
 pub struct InMemoryQueue<P> {
     max_depth: usize,
     /// Binary heap for O(log n) priority-based operations.
     tasks: BinaryHeap<PriorityTask<P>>,
+    ordering: OrderingStrategy,
 }
 
 impl<P> InMemoryQueue<P> {
@@ -67,17 +88,34 @@ impl<P> InMemoryQueue<P> {
         Self {
             max_depth,
             tasks: BinaryHeap::with_capacity(max_depth.min(1024)),
+            ordering: OrderingStrategy::default(),
         }
     }
+
+    /// Dequeue tasks in the order given by `ordering`.
+    #[must_use]
+    pub fn with_ordering(mut self, ordering: OrderingStrategy) -> Self {
+        self.ordering = ordering;
+        // Re-tag anything already queued so the heap keeps one comparator
+        let tasks: Vec<_> = self.tasks.drain().collect();
+        self.tasks = tasks
+            .into_iter()
+            .map(|pt| PriorityTask { task: pt.task, ordering })
+            .collect();
+        self
+    }
 }
 
 impl<P> TaskQueue<P> for InMemoryQueue<P> {
     fn enqueue(&mut self, task: ScheduledTask<P>) -> Result<(), SchedulerError> {
-        if self.len() >= self.max_depth() {
+        if self.is_full() {
             return Err(SchedulerError::QueueFull("max queue depth reached".into()));
         }
         // O(log n) insertion
-        self.tasks.push(PriorityTask { task });
+        self.tasks.push(PriorityTask {
+            task,
+            ordering: self.ordering,
+        });
         Ok(())
     }
 
@@ -86,18 +124,49 @@ impl<P> TaskQueue<P> for InMemoryQueue<P> {
         Ok(self.tasks.pop().map(|pt| pt.task))
     }
 
+    fn peek(&self) -> Result<Option<TaskMetadata>, SchedulerError> {
+        Ok(self.tasks.peek().map(|pt| pt.task.meta.clone()))
+    }
+
+    fn oldest_created_at_ms(&self) -> Option<u128> {
+        // The head is only the oldest of the highest priority
+        self.tasks.iter().map(|pt| pt.task.meta.created_at_ms).min()
+    }
+
+    fn queued_units(&self) -> u64 {
+        self.tasks.iter().map(|pt| u64::from(pt.task.meta.cost.units)).sum()
+    }
+
     fn prune_expired(&mut self, now_ms: u128) -> Result<usize, SchedulerError> {
         let before = self.tasks.len();
-        // Rebuild heap without expired tasks
-        let tasks: Vec<_> = self.tasks.drain().collect();
-        self.tasks = tasks
-            .into_iter()
-            .filter(|pt| pt.task.meta.deadline_ms.map(|d| d > now_ms).unwrap_or(true))
-            .collect();
+        // Filter in place: the heap is only re-sifted from the first removed
+        // task, so a pass that expires nothing allocates and moves nothing
+        self.tasks
+            .retain(|pt| !pt.task.meta.is_expired_at(now_ms));
         let after = self.tasks.len();
         Ok(before.saturating_sub(after))
     }
 
+    fn take_expired(&mut self, now_ms: u128) -> Result<Vec<ScheduledTask<P>>, SchedulerError> {
+        let (expired, live): (Vec<_>, Vec<_>) = std::mem::take(&mut self.tasks)
+            .into_vec()
+            .into_iter()
+            .partition(|pt| pt.task.meta.is_expired_at(now_ms));
+        self.tasks = live.into();
+        Ok(expired.into_iter().map(|pt| pt.task).collect())
+    }
+
+    fn drain(&mut self) -> Result<Vec<ScheduledTask<P>>, SchedulerError> {
+        // Ascending order puts the next task to dequeue last
+        let mut tasks: Vec<_> = std::mem::take(&mut self.tasks)
+            .into_sorted_vec()
+            .into_iter()
+            .map(|pt| pt.task)
+            .collect();
+        tasks.reverse();
+        Ok(tasks)
+    }
+
     fn max_depth(&self) -> usize {
         self.max_depth
     }
@@ -124,6 +193,11 @@ mod tests {
                 },
                 deadline_ms: None,
                 created_at_ms,
+                trace_context: None,
+                degraded: false,
+                depends_on: Vec::new(),
+                idempotency_key: None,
+                retrieve_timeout_ms: None,
             },
             payload: format!("task-{}", id),
         }
@@ -146,6 +220,37 @@ mod tests {
         assert_eq!(q.dequeue().unwrap().unwrap().meta.id, 1); // Low
     }
 
+    #[test]
+    fn test_custom_priority_interleaves_with_bands() {
+        let mut q = InMemoryQueue::new(100);
+        q.enqueue(make_task(1, Priority::Normal, 100)).unwrap();
+        q.enqueue(make_task(2, Priority::Custom(120), 200)).unwrap();
+        q.enqueue(make_task(3, Priority::High, 300)).unwrap();
+        q.enqueue(make_task(4, Priority::Custom(10), 400)).unwrap();
+        q.enqueue(make_task(5, Priority::Low, 500)).unwrap();
+
+        let ids: Vec<u64> = q.drain().unwrap().into_iter().map(|t| t.meta.id).collect();
+        assert_eq!(ids, vec![3, 2, 1, 5, 4]);
+        assert!(Priority::Normal < Priority::Custom(120));
+        assert!(Priority::Custom(120) < Priority::High);
+    }
+
+    #[test]
+    fn test_oldest_created_at_spans_priorities() {
+        let mut q = InMemoryQueue::new(100);
+        assert_eq!(q.oldest_created_at_ms(), None);
+
+        q.enqueue(make_task(1, Priority::Low, 100)).unwrap();
+        q.enqueue(make_task(2, Priority::Critical, 200)).unwrap();
+        // The head is the critical task, but the low one has waited longest
+        assert_eq!(q.peek().unwrap().unwrap().id, 2);
+        assert_eq!(q.oldest_created_at_ms(), Some(100));
+
+        q.dequeue().unwrap();
+        q.dequeue().unwrap();
+        assert_eq!(q.oldest_created_at_ms(), None);
+    }
+
     #[test]
     fn test_fifo_within_priority() {
         let mut q = InMemoryQueue::new(100);
@@ -161,6 +266,34 @@ mod tests {
         assert_eq!(q.dequeue().unwrap().unwrap().meta.id, 1); // created_at=300
     }
 
+    #[test]
+    fn test_ordering_strategies() {
+        let dequeue_order = |ordering: OrderingStrategy| {
+            let mut q = InMemoryQueue::new(100).with_ordering(ordering);
+            let tasks = [
+                (1, Priority::Low, 100, Some(900)),
+                (2, Priority::High, 200, None),
+                (3, Priority::Normal, 300, Some(500)),
+                (4, Priority::High, 400, Some(500)),
+                (5, Priority::Normal, 500, None),
+            ];
+            for (id, priority, created_at_ms, deadline_ms) in tasks {
+                let mut task = make_task(id, priority, created_at_ms);
+                task.meta.deadline_ms = deadline_ms;
+                q.enqueue(task).unwrap();
+            }
+            std::iter::from_fn(|| q.dequeue().unwrap())
+                .map(|t| t.meta.id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(dequeue_order(OrderingStrategy::default()), vec![2, 4, 3, 5, 1]);
+        assert_eq!(dequeue_order(OrderingStrategy::Fifo), vec![1, 2, 3, 4, 5]);
+        assert_eq!(dequeue_order(OrderingStrategy::Lifo), vec![5, 4, 3, 2, 1]);
+        // Equal deadlines fall back to priority; no deadline goes last
+        assert_eq!(dequeue_order(OrderingStrategy::EarliestDeadline), vec![4, 3, 1, 2, 5]);
+    }
+
     #[test]
     fn test_queue_full() {
         let mut q = InMemoryQueue::new(2);
@@ -171,6 +304,25 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_is_empty_and_is_full() {
+        let mut q = InMemoryQueue::new(2);
+        assert!(q.is_empty());
+        assert!(!q.is_full());
+
+        q.enqueue(make_task(1, Priority::Normal, 100)).unwrap();
+        assert!(!q.is_empty());
+        assert!(!q.is_full());
+
+        q.enqueue(make_task(2, Priority::Normal, 200)).unwrap();
+        assert!(!q.is_empty());
+        assert!(q.is_full());
+
+        q.drain().unwrap();
+        assert!(q.is_empty());
+        assert!(!q.is_full());
+    }
+
     #[test]
     fn test_prune_expired() {
         let mut q = InMemoryQueue::new(100);
@@ -207,6 +359,20 @@ mod tests {
         assert_eq!(q.dequeue().unwrap().unwrap().meta.id, 3);
     }
 
+    #[test]
+    fn test_drain_in_priority_order() {
+        let mut q = InMemoryQueue::new(100);
+        q.enqueue(make_task(1, Priority::Low, 100)).unwrap();
+        q.enqueue(make_task(2, Priority::High, 200)).unwrap();
+        q.enqueue(make_task(3, Priority::High, 150)).unwrap();
+        q.enqueue(make_task(4, Priority::Critical, 300)).unwrap();
+
+        let ids: Vec<u64> = q.drain().unwrap().into_iter().map(|t| t.meta.id).collect();
+        assert_eq!(ids, vec![4, 3, 2, 1]);
+        assert_eq!(q.len(), 0);
+        assert!(q.dequeue().unwrap().is_none());
+    }
+
     #[test]
     fn test_empty_queue() {
         let mut q = InMemoryQueue::<String>::new(100);
@@ -2,8 +2,12 @@
 
 pub mod memory;
 pub mod postgres;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
 pub mod yaque;
 
-pub use memory::InMemoryQueue;
+pub use memory::{InMemoryQueue, OrderingStrategy};
 pub use postgres::PostgresQueue;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteQueue;
 pub use yaque::YaqueQueue;
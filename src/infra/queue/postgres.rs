@@ -1,6 +1,6 @@
 //! Postgres-backed queue adapter (schema and interface stubs).
 
-use crate::core::{ScheduledTask, SchedulerError, TaskQueue};
+use crate::core::{ScheduledTask, SchedulerError, TaskMetadata, TaskQueue};
 
 /// Postgres queue adapter placeholder.
 pub struct PostgresQueue<P> {
@@ -51,10 +51,26 @@ impl<P> TaskQueue<P> for PostgresQueue<P> {
         ))
     }
 
+    fn peek(&self) -> Result<Option<TaskMetadata>, SchedulerError> {
+        Err(SchedulerError::Backend(
+            "postgres queue not wired to database client".into(),
+        ))
+    }
+
     fn prune_expired(&mut self, _now_ms: u128) -> Result<usize, SchedulerError> {
         Ok(0)
     }
 
+    fn take_expired(&mut self, _now_ms: u128) -> Result<Vec<ScheduledTask<P>>, SchedulerError> {
+        Ok(Vec::new())
+    }
+
+    fn drain(&mut self) -> Result<Vec<ScheduledTask<P>>, SchedulerError> {
+        Err(SchedulerError::Backend(
+            "postgres queue not wired to database client".into(),
+        ))
+    }
+
     fn max_depth(&self) -> usize {
         self.max_depth
     }
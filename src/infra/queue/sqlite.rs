@@ -0,0 +1,213 @@
+//! `SQLite`-backed durable queue (requires the `sqlite` feature).
+//!
+//! Tasks live in a single `jobs` table of an embedded database file, so they
+//! survive crashes and restarts without a separate server process. Each row
+//! keeps the ordering columns next to the JSON-serialized task.
+
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::core::{ScheduledTask, SchedulerError, TaskMetadata, TaskQueue};
+
+const SCHEMA: &str = r"
+CREATE TABLE IF NOT EXISTS jobs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    priority INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    deadline_ms INTEGER,
+    payload TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_jobs_order ON jobs (priority DESC, created_at, id);
+";
+
+/// Dequeue order: highest priority first, FIFO within a priority.
+const ORDER_BY: &str = "ORDER BY priority DESC, created_at, id";
+
+fn backend(err: impl std::fmt::Display) -> SchedulerError {
+    SchedulerError::Backend(err.to_string())
+}
+
+/// Clamp a millisecond timestamp into `SQLite`'s signed 64-bit integers.
+fn to_sql_ms(ms: u128) -> i64 {
+    i64::try_from(ms).unwrap_or(i64::MAX)
+}
+
+/// Durable queue stored in an `SQLite` database file.
+pub struct SqliteQueue<P> {
+    conn: Connection,
+    max_depth: usize,
+    /// Row count, kept in step with the table so `len` needs no query.
+    len: usize,
+    _payload: std::marker::PhantomData<fn() -> P>,
+}
+
+impl<P> SqliteQueue<P> {
+    /// Open (or create) the queue database at `path`.
+    ///
+    /// Tasks already stored in the file are kept and dequeued first by
+    /// priority, as if they had been enqueued in this process.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SchedulerError::Backend` if the database cannot be opened or
+    /// its schema created.
+    pub fn new(path: impl AsRef<Path>, max_depth: usize) -> Result<Self, SchedulerError> {
+        let conn = Connection::open(path).map_err(backend)?;
+        conn.execute_batch(SCHEMA).map_err(backend)?;
+        let len: i64 = conn
+            .query_row("SELECT COUNT(*) FROM jobs", [], |row| row.get(0))
+            .map_err(backend)?;
+        Ok(Self {
+            conn,
+            max_depth,
+            len: usize::try_from(len).unwrap_or(0),
+            _payload: std::marker::PhantomData,
+        })
+    }
+}
+
+/// The metadata half of a stored task, skipping its payload.
+#[derive(Deserialize)]
+struct StoredMeta {
+    meta: TaskMetadata,
+}
+
+fn decode<P: DeserializeOwned>(json: &str) -> Result<ScheduledTask<P>, SchedulerError> {
+    serde_json::from_str(json).map_err(backend)
+}
+
+impl<P> TaskQueue<P> for SqliteQueue<P>
+where
+    P: Serialize + DeserializeOwned,
+{
+    fn enqueue(&mut self, task: ScheduledTask<P>) -> Result<(), SchedulerError> {
+        if self.is_full() {
+            return Err(SchedulerError::QueueFull("max queue depth reached".into()));
+        }
+        let payload = serde_json::to_string(&task).map_err(backend)?;
+        self.conn
+            .execute(
+                "INSERT INTO jobs (priority, created_at, deadline_ms, payload) VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    task.meta.priority.value(),
+                    to_sql_ms(task.meta.created_at_ms),
+                    task.meta.deadline_ms.map(to_sql_ms),
+                    payload,
+                ],
+            )
+            .map_err(backend)?;
+        self.len += 1;
+        Ok(())
+    }
+
+    fn dequeue(&mut self) -> Result<Option<ScheduledTask<P>>, SchedulerError> {
+        let tx = self.conn.transaction().map_err(backend)?;
+        let payload: Option<String> = tx
+            .query_row(
+                &format!(
+                    "DELETE FROM jobs WHERE id = (SELECT id FROM jobs {ORDER_BY} LIMIT 1) RETURNING payload"
+                ),
+                [],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(backend)?;
+        tx.commit().map_err(backend)?;
+        let Some(payload) = payload else {
+            return Ok(None);
+        };
+        self.len = self.len.saturating_sub(1);
+        decode(&payload).map(Some)
+    }
+
+    fn peek(&self) -> Result<Option<TaskMetadata>, SchedulerError> {
+        let payload: Option<String> = self
+            .conn
+            .query_row(&format!("SELECT payload FROM jobs {ORDER_BY} LIMIT 1"), [], |row| row.get(0))
+            .optional()
+            .map_err(backend)?;
+        payload
+            .map(|payload| {
+                serde_json::from_str::<StoredMeta>(&payload)
+                    .map(|stored| stored.meta)
+                    .map_err(backend)
+            })
+            .transpose()
+    }
+
+    fn oldest_created_at_ms(&self) -> Option<u128> {
+        let oldest: Option<i64> = self
+            .conn
+            .query_row("SELECT MIN(created_at) FROM jobs", [], |row| row.get(0))
+            .ok()?;
+        oldest.and_then(|ms| u128::try_from(ms).ok())
+    }
+
+    fn queued_units(&self) -> u64 {
+        let units: i64 = self
+            .conn
+            .query_row(
+                "SELECT COALESCE(SUM(json_extract(payload, '$.meta.cost.units')), 0) FROM jobs",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(0);
+        u64::try_from(units).unwrap_or(0)
+    }
+
+    fn prune_expired(&mut self, now_ms: u128) -> Result<usize, SchedulerError> {
+        // `<=` matches `TaskMetadata::is_expired_at`
+        let pruned = self
+            .conn
+            .execute(
+                "DELETE FROM jobs WHERE deadline_ms IS NOT NULL AND deadline_ms <= ?1",
+                params![to_sql_ms(now_ms)],
+            )
+            .map_err(backend)?;
+        self.len = self.len.saturating_sub(pruned);
+        Ok(pruned)
+    }
+
+    fn take_expired(&mut self, now_ms: u128) -> Result<Vec<ScheduledTask<P>>, SchedulerError> {
+        let payloads = {
+            let mut stmt = self
+                .conn
+                .prepare(
+                    "DELETE FROM jobs WHERE deadline_ms IS NOT NULL AND deadline_ms <= ?1 \
+                     RETURNING payload",
+                )
+                .map_err(backend)?;
+            let rows = stmt
+                .query_map(params![to_sql_ms(now_ms)], |row| row.get::<_, String>(0))
+                .map_err(backend)?;
+            rows.collect::<Result<Vec<_>, _>>().map_err(backend)?
+        };
+        self.len = self.len.saturating_sub(payloads.len());
+        payloads.iter().map(|payload| decode(payload)).collect()
+    }
+
+    fn drain(&mut self) -> Result<Vec<ScheduledTask<P>>, SchedulerError> {
+        let tx = self.conn.transaction().map_err(backend)?;
+        let payloads = {
+            let mut stmt = tx
+                .prepare(&format!("SELECT payload FROM jobs {ORDER_BY}"))
+                .map_err(backend)?;
+            let rows = stmt.query_map([], |row| row.get::<_, String>(0)).map_err(backend)?;
+            rows.collect::<Result<Vec<_>, _>>().map_err(backend)?
+        };
+        tx.execute("DELETE FROM jobs", []).map_err(backend)?;
+        tx.commit().map_err(backend)?;
+        self.len = 0;
+        payloads.iter().map(|payload| decode(payload)).collect()
+    }
+
+    fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
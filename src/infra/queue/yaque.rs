@@ -2,15 +2,85 @@
 //!
 //! This is a simplified implementation using JSONL files to persist queued tasks.
 //! It requires payloads to be serializable and deserializable.
+//!
+//! Files start with a header line carrying [`FORMAT_VERSION`]. Version 1
+//! files, written before the header existed, hold task lines only; they still
+//! load, and [`YaqueQueue::repair`] upgrades them in place.
 
 use std::collections::VecDeque;
-use std::fs::{create_dir_all, OpenOptions};
-use std::io::{BufRead, BufReader, Write};
+use std::fs::create_dir_all;
 use std::path::{Path, PathBuf};
 
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::core::{ScheduledTask, SchedulerError, TaskMetadata, TaskQueue};
+use crate::util::serde::append_json_line;
+
+/// On-disk format version written by this build.
+///
+/// Fields added to `TaskMetadata` are `#[serde(default)]`, so task lines from
+/// older versions load with them defaulted. Bump this when a change needs
+/// more than that.
+pub const FORMAT_VERSION: u32 = 2;
+
+/// First line of a queue file since version 2.
+#[derive(Serialize, Deserialize)]
+struct FormatHeader {
+    format_version: u32,
+}
+
+/// Read a queue file's format version and tasks. A missing file is empty and
+/// current.
+fn read_queue_file<P: DeserializeOwned>(
+    file: &Path,
+) -> Result<(u32, Vec<ScheduledTask<P>>), SchedulerError> {
+    if !file.exists() {
+        return Ok((FORMAT_VERSION, Vec::new()));
+    }
+    let contents =
+        std::fs::read_to_string(file).map_err(|e| SchedulerError::Backend(e.to_string()))?;
+    let mut lines = contents.lines().peekable();
+    // A version 1 file has no header; its first line is already a task
+    let header = lines
+        .peek()
+        .and_then(|line| serde_json::from_str::<FormatHeader>(line).ok());
+    if header.is_some() {
+        lines.next();
+    }
+    let version = header.map_or(1, |header| header.format_version);
+    if version > FORMAT_VERSION {
+        return Err(SchedulerError::Backend(format!(
+            "{} has format version {version}, newer than the supported {FORMAT_VERSION}",
+            file.display()
+        )));
+    }
+    let tasks = lines
+        .map(|line| serde_json::from_str(line).map_err(|e| SchedulerError::Backend(e.to_string())))
+        .collect::<Result<_, _>>()?;
+    Ok((version, tasks))
+}
+
+/// The header line of the current format, newline included.
+fn header_line() -> Result<String, SchedulerError> {
+    let header = FormatHeader { format_version: FORMAT_VERSION };
+    let line = serde_json::to_string(&header).map_err(|e| SchedulerError::Backend(e.to_string()))?;
+    Ok(line + "\n")
+}
+
+/// Replace a queue file with the header and one line per task.
+fn write_queue_file<'a, P: Serialize + 'a>(
+    file: &Path,
+    tasks: impl IntoIterator<Item = &'a ScheduledTask<P>>,
+) -> Result<(), SchedulerError> {
+    let mut contents = header_line()?;
+    for task in tasks {
+        let line = serde_json::to_string(task).map_err(|e| SchedulerError::Backend(e.to_string()))?;
+        contents.push_str(&line);
+        contents.push('\n');
+    }
+    std::fs::write(file, contents).map_err(|e| SchedulerError::Backend(e.to_string()))
+}
 
-use crate::core::{ScheduledTask, SchedulerError, TaskQueue};
 /// File-backed queue using JSON lines for durability.
 pub struct YaqueQueue<P> {
     path: PathBuf,
@@ -38,6 +108,34 @@ impl<P> YaqueQueue<P> {
         Ok(queue)
     }
 
+    /// Rewrite the queue file at `file` (a stream's `<stream>.jsonl`) in the
+    /// current format, returning whether it was older.
+    ///
+    /// Older files load without this; repairing them lets tools that expect
+    /// the header read them, and keeps a later format change from having to
+    /// handle every past version.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SchedulerError::Backend` if the file can't be read or
+    /// rewritten, or was written by a newer version.
+    pub fn repair(file: impl AsRef<Path>) -> Result<bool, SchedulerError>
+    where
+        P: Serialize + DeserializeOwned,
+    {
+        let file = file.as_ref();
+        let (version, tasks) = read_queue_file::<P>(file)?;
+        if version == FORMAT_VERSION {
+            return Ok(false);
+        }
+        write_queue_file(file, &tasks)?;
+        tracing::info!(
+            "upgraded {} from format version {version} to {FORMAT_VERSION}",
+            file.display()
+        );
+        Ok(true)
+    }
+
     fn file_path(&self) -> PathBuf {
         self.path.join(format!("{}.jsonl", self.stream))
     }
@@ -46,21 +144,21 @@ impl<P> YaqueQueue<P> {
     where
         P: DeserializeOwned,
     {
-        let file_path = self.file_path();
-        if !file_path.exists() {
-            return Ok(());
+        let file = self.file_path();
+        if !file.exists() {
+            // Start a new file with the header, so appended tasks follow it
+            return std::fs::write(&file, header_line()?)
+                .map_err(|e| SchedulerError::Backend(e.to_string()));
         }
-        let file = OpenOptions::new()
-            .read(true)
-            .open(&file_path)
-            .map_err(|e| SchedulerError::Backend(e.to_string()))?;
-        let reader = BufReader::new(file);
-        for line in reader.lines() {
-            let line = line.map_err(|e| SchedulerError::Backend(e.to_string()))?;
-            let task: ScheduledTask<P> =
-                serde_json::from_str(&line).map_err(|e| SchedulerError::Backend(e.to_string()))?;
-            self.tasks.push_back(task);
+        let (version, tasks) = read_queue_file(&file)?;
+        if version < FORMAT_VERSION {
+            // Appends keep an old file in its format until it's rewritten
+            tracing::warn!(
+                "{} uses format version {version}; YaqueQueue::repair upgrades it",
+                file.display()
+            );
         }
+        self.tasks.extend(tasks);
         Ok(())
     }
 
@@ -68,34 +166,14 @@ impl<P> YaqueQueue<P> {
     where
         P: Serialize,
     {
-        let file_path = self.file_path();
-        let mut file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&file_path)
-            .map_err(|e| SchedulerError::Backend(e.to_string()))?;
-        let line =
-            serde_json::to_string(task).map_err(|e| SchedulerError::Backend(e.to_string()))?;
-        writeln!(file, "{line}").map_err(|e| SchedulerError::Backend(e.to_string()))
+        append_json_line(&self.file_path(), task)
     }
 
     fn rewrite_disk(&self, tasks: &VecDeque<ScheduledTask<P>>) -> Result<(), SchedulerError>
     where
         P: Serialize,
     {
-        let file_path = self.file_path();
-        let mut file = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open(&file_path)
-            .map_err(|e| SchedulerError::Backend(e.to_string()))?;
-        for task in tasks {
-            let line =
-                serde_json::to_string(task).map_err(|e| SchedulerError::Backend(e.to_string()))?;
-            writeln!(file, "{line}").map_err(|e| SchedulerError::Backend(e.to_string()))?;
-        }
-        Ok(())
+        write_queue_file(&self.file_path(), tasks)
     }
 }
 
@@ -104,7 +182,7 @@ where
     P: Serialize + DeserializeOwned + Clone,
 {
     fn enqueue(&mut self, task: ScheduledTask<P>) -> Result<(), SchedulerError> {
-        if self.len() >= self.max_depth() {
+        if self.is_full() {
             return Err(SchedulerError::QueueFull("max queue depth reached".into()));
         }
         self.tasks.push_back(task.clone());
@@ -118,15 +196,41 @@ where
         Ok(item)
     }
 
+    fn peek(&self) -> Result<Option<TaskMetadata>, SchedulerError> {
+        Ok(self.tasks.front().map(|t| t.meta.clone()))
+    }
+
+    fn oldest_created_at_ms(&self) -> Option<u128> {
+        self.tasks.iter().map(|t| t.meta.created_at_ms).min()
+    }
+
+    fn queued_units(&self) -> u64 {
+        self.tasks.iter().map(|t| u64::from(t.meta.cost.units)).sum()
+    }
+
     fn prune_expired(&mut self, now_ms: u128) -> Result<usize, SchedulerError> {
         let before = self.tasks.len();
         self.tasks
-            .retain(|t| t.meta.deadline_ms.map(|d| d > now_ms).unwrap_or(true));
+            .retain(|t| !t.meta.is_expired_at(now_ms));
         let after = self.tasks.len();
         self.rewrite_disk(&self.tasks)?;
         Ok(before.saturating_sub(after))
     }
 
+    fn take_expired(&mut self, now_ms: u128) -> Result<Vec<ScheduledTask<P>>, SchedulerError> {
+        let (expired, live): (VecDeque<_>, VecDeque<_>) = std::mem::take(&mut self.tasks)
+            .into_iter()
+            .partition(|t| t.meta.is_expired_at(now_ms));
+        self.tasks = live;
+        self.rewrite_disk(&self.tasks)?;
+        Ok(expired.into())
+    }
+
+    fn drain(&mut self) -> Result<Vec<ScheduledTask<P>>, SchedulerError> {
+        self.rewrite_disk(&VecDeque::new())?;
+        Ok(std::mem::take(&mut self.tasks).into())
+    }
+
     fn max_depth(&self) -> usize {
         self.max_depth
     }
@@ -1,9 +1,20 @@
 //! API-facing request/response models (skeleton).
 
+use std::collections::{HashMap, VecDeque};
+use std::hash::BuildHasher;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures_util::stream::{self, Stream};
 use serde::{Deserialize, Serialize};
 
-use crate::core::{ResourcePool, ScheduledTask, TaskStatus};
+use crate::core::{MailboxMessage, ResourcePool, ScheduledTask, SchedulerError, TaskStatus};
 use crate::util::serde::{MailboxKey, Priority, ResourceCost, TaskId};
+use crate::util::telemetry::SnapshotSource;
+
+/// Messages read from the mailbox per poll.
+const STREAM_BATCH: usize = 64;
 
 /// Task submission payload.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +27,10 @@ pub struct TaskSubmission<P> {
     pub resource_cost: ResourceCost,
     /// Optional deadline (ms since epoch).
     pub deadline_ms: Option<u128>,
+    /// Optional deadline relative to receipt (ms), immune to client clock
+    /// skew. Ignored when `deadline_ms` is also set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline_in_ms: Option<u64>,
     /// Optional mailbox key.
     pub mailbox_key: Option<MailboxKey>,
     /// Creation time (ms since epoch).
@@ -24,6 +39,24 @@ pub struct TaskSubmission<P> {
     pub payload: P,
 }
 
+impl<P> TaskSubmission<P> {
+    /// Absolute deadline of the submission received at `now_ms`, preferring
+    /// `deadline_ms` over `deadline_in_ms` when both are set.
+    pub fn resolve_deadline(&self, now_ms: u128) -> Option<u128> {
+        match (self.deadline_ms, self.deadline_in_ms) {
+            (Some(deadline), Some(_)) => {
+                tracing::warn!(
+                    "task {} has both deadline_ms and deadline_in_ms; using deadline_ms",
+                    self.task_id
+                );
+                Some(deadline)
+            }
+            (Some(deadline), None) => Some(deadline),
+            (None, relative) => relative.map(|ms| now_ms + u128::from(ms)),
+        }
+    }
+}
+
 /// Task status response.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskStatusResponse {
@@ -36,6 +69,8 @@ pub struct TaskStatusResponse {
 }
 
 /// Pool snapshot data for listing.
+///
+/// The runtime fields are zero for a pool without a live handle.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PoolSnapshot {
     /// Pool identifier.
@@ -44,6 +79,15 @@ pub struct PoolSnapshot {
     pub max_units: u32,
     /// Max queue depth.
     pub max_queue_depth: usize,
+    /// Units held by running tasks.
+    #[serde(default)]
+    pub used_units: u32,
+    /// Tasks accepted but not started yet.
+    #[serde(default)]
+    pub queued_tasks: u64,
+    /// Tasks running now.
+    #[serde(default)]
+    pub active_tasks: u64,
 }
 
 /// Health response.
@@ -54,6 +98,8 @@ pub struct Health {
 }
 
 /// Submit a task to a pool. Placeholder; caller manages pool lookup.
+///
+/// A relative `deadline_in_ms` is made absolute against `now_ms`.
 pub async fn submit_task<P, T, Q, M, E, S>(
     pool: &ResourcePool<P, T, Q, M, E, S>,
     req: TaskSubmission<P>,
@@ -61,10 +107,10 @@ pub async fn submit_task<P, T, Q, M, E, S>(
 ) -> Result<TaskStatus, String>
 where
     P: crate::core::TaskPayload,
-    T: Send + Sync + serde::Serialize + for<'de> serde::Deserialize<'de> + 'static,
+    T: Send + 'static,
     Q: crate::core::TaskQueue<P> + Send + 'static,
     M: crate::core::Mailbox<T> + Send + 'static,
-    E: crate::core::TaskExecutor<P, T>,
+    E: crate::core::TaskExecutor<P, T> + Clone,
     S: crate::core::Spawn + Clone + Send + 'static,
 {
     let meta = crate::core::TaskMetadata {
@@ -72,8 +118,13 @@ where
         mailbox: req.mailbox_key.clone(),
         priority: req.priority,
         cost: req.resource_cost,
-        deadline_ms: req.deadline_ms,
+        deadline_ms: req.resolve_deadline(now_ms),
         created_at_ms: req.created_at_ms,
+        trace_context: None,
+        degraded: false,
+        depends_on: Vec::new(),
+        idempotency_key: None,
+        retrieve_timeout_ms: None,
     };
     let task: ScheduledTask<P> = ScheduledTask {
         meta,
@@ -82,16 +133,135 @@ where
     pool.submit(task, now_ms).await.map_err(|e| e.to_string())
 }
 
-/// Build pool listings from config snapshot.
-pub fn list_pools(
+/// Look up a task's status in a pool, if the pool still tracks it.
+pub fn task_status<P, T, Q, M, E, S>(
+    pool: &ResourcePool<P, T, Q, M, E, S>,
+    task_id: TaskId,
+) -> Option<TaskStatusResponse>
+where
+    P: crate::core::TaskPayload,
+    T: Send + 'static,
+{
+    let status = pool.status(task_id)?;
+    let reason = match &status {
+        TaskStatus::Failed(reason) | TaskStatus::Dropped(reason) => Some(reason.clone()),
+        _ => None,
+    };
+    Some(TaskStatusResponse {
+        task_id,
+        status,
+        reason,
+    })
+}
+
+/// Stream the results delivered to `key` as Server-Sent Events frames.
+///
+/// Each `MailboxMessage` becomes one `data:` frame holding its JSON, in
+/// delivery order, starting with the results the mailbox already keeps for
+/// the key. The mailbox is polled every `poll_interval` while no new results
+/// are waiting, so it must be one that keeps results (see
+/// [`Mailbox::fetch`](crate::core::Mailbox::fetch)). The stream never ends on
+/// its own; drop it when the client disconnects.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// async fn results(State(pool): State<Arc<Pool>>, Path(tenant): Path<String>) -> impl IntoResponse {
+///     let key = MailboxKey { tenant, user_id: None, session_id: None };
+///     let frames = stream_mailbox(pool, key, Duration::from_millis(100));
+///     ([(CONTENT_TYPE, "text/event-stream")], Body::from_stream(frames))
+/// }
+/// ```
+pub fn stream_mailbox<P, T, Q, M, E, S>(
+    pool: Arc<ResourcePool<P, T, Q, M, E, S>>,
+    key: MailboxKey,
+    poll_interval: Duration,
+) -> impl Stream<Item = Result<Bytes, SchedulerError>>
+where
+    P: crate::core::TaskPayload,
+    T: Clone + Send + Sync + serde::Serialize + for<'de> serde::Deserialize<'de> + 'static,
+    M: crate::core::Mailbox<T>,
+{
+    let cursor = MailboxCursor {
+        since_ms: None,
+        seen_at_since: 0,
+        pending: VecDeque::new(),
+    };
+    stream::unfold((pool, key, cursor), move |(pool, key, mut cursor)| async move {
+        loop {
+            if let Some(message) = cursor.pending.pop_front() {
+                let frame = sse_frame(&message);
+                return Some((frame, (pool, key, cursor)));
+            }
+            if !cursor.refill(&pool, &key) {
+                tokio::time::sleep(poll_interval).await;
+            }
+        }
+    })
+}
+
+/// Position of a [`stream_mailbox`] stream in a key's results.
+struct MailboxCursor<T> {
+    /// `created_at_ms` of the last message read.
+    since_ms: Option<u128>,
+    /// Messages already read that were created at `since_ms`, which the
+    /// inclusive `since_ms` filter returns again.
+    seen_at_since: usize,
+    pending: VecDeque<MailboxMessage<T>>,
+}
+
+impl<T: Clone> MailboxCursor<T> {
+    /// Queue the results delivered since the last read, returning whether
+    /// there were any.
+    fn refill<P, Q, M, E, S>(
+        &mut self,
+        pool: &ResourcePool<P, T, Q, M, E, S>,
+        key: &MailboxKey,
+    ) -> bool
+    where
+        P: crate::core::TaskPayload,
+        T: Send + Sync + serde::Serialize + for<'de> serde::Deserialize<'de> + 'static,
+        M: crate::core::Mailbox<T>,
+    {
+        let fetched = pool.fetch_results(key, self.since_ms, self.seen_at_since + STREAM_BATCH);
+        for message in fetched.into_iter().skip(self.seen_at_since) {
+            if Some(message.created_at_ms) == self.since_ms {
+                self.seen_at_since += 1;
+            } else {
+                self.since_ms = Some(message.created_at_ms);
+                self.seen_at_since = 1;
+            }
+            self.pending.push_back(message);
+        }
+        !self.pending.is_empty()
+    }
+}
+
+/// Encode a mailbox message as a single SSE `data:` frame.
+fn sse_frame<T: Serialize>(message: &MailboxMessage<T>) -> Result<Bytes, SchedulerError> {
+    let json =
+        serde_json::to_string(message).map_err(|e| SchedulerError::Backend(e.to_string()))?;
+    Ok(Bytes::from(format!("data: {json}\n\n")))
+}
+
+/// Build pool listings from config snapshot, filling in the runtime fields
+/// of each pool found by name in `live`.
+pub fn list_pools<S: BuildHasher>(
     cfg: &crate::config::SchedulerConfig,
+    live: &HashMap<String, &dyn SnapshotSource, S>,
 ) -> Vec<PoolSnapshot> {
     cfg.pools
         .iter()
-        .map(|(name, pool)| PoolSnapshot {
-            name: name.clone(),
-            max_units: pool.max_units,
-            max_queue_depth: pool.max_queue_depth,
+        .map(|(name, pool)| {
+            let metrics = live.get(name).map(|source| source.snapshot_metrics(name));
+            PoolSnapshot {
+                name: name.clone(),
+                max_units: pool.max_units,
+                max_queue_depth: pool.max_queue_depth,
+                used_units: metrics.as_ref().map_or(0, |m| m.used_units),
+                queued_tasks: metrics.as_ref().map_or(0, |m| m.queue_depth),
+                active_tasks: metrics.as_ref().map_or(0, |m| m.active_tasks),
+            }
         })
         .collect()
 }
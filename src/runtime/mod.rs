@@ -3,5 +3,8 @@
 pub mod api;
 pub mod tokio_spawner;
 
-pub use api::{submit_task, TaskStatusResponse, TaskSubmission};
+pub use api::{
+    list_pools, stream_mailbox, submit_task, task_status, PoolSnapshot, TaskStatusResponse,
+    TaskSubmission,
+};
 pub use tokio_spawner::TokioSpawner;
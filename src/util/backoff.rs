@@ -0,0 +1,158 @@
+//! Exponential backoff with optional jitter.
+//!
+//! Tasks that fail together and retry on the same schedule fail together
+//! again. [`Backoff`] yields the exponentially growing delays of a retry
+//! schedule, randomized by a [`Jitter`] strategy so such retries spread out.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// How a backoff delay is randomized.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Jitter {
+    /// Use the delay as is.
+    #[default]
+    None,
+    /// Pick uniformly between zero and the delay. Spreads retries the most.
+    Full,
+    /// Pick uniformly between half the delay and the delay, keeping a floor
+    /// under each wait.
+    Equal,
+}
+
+impl Jitter {
+    /// Randomize `delay` with `sample`, a value in `0.0..1.0`.
+    #[must_use]
+    pub fn apply(self, delay: Duration, sample: f64) -> Duration {
+        let sample = sample.clamp(0.0, 1.0);
+        match self {
+            Self::None => delay,
+            Self::Full => delay.mul_f64(sample),
+            Self::Equal => delay / 2 + (delay / 2).mul_f64(sample),
+        }
+    }
+}
+
+/// Iterator over the delays of an exponential backoff.
+///
+/// The undelayed schedule starts at `base` and grows by `factor` per step up
+/// to `max`; each yielded delay is that step randomized by the jitter. The
+/// iterator never ends.
+///
+/// ```rust
+/// use prometheus_parking_lot::util::backoff::{Backoff, Jitter};
+/// use std::time::Duration;
+///
+/// let mut backoff = Backoff::new(Duration::from_millis(100), Duration::from_secs(1), 2.0)
+///     .with_jitter(Jitter::Equal);
+/// let first = backoff.next().unwrap();
+/// assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    /// Undelayed delay of the next step.
+    current: Duration,
+    max: Duration,
+    factor: f64,
+    jitter: Jitter,
+    /// State of the splitmix64 generator sampling the jitter.
+    rng: u64,
+}
+
+impl Backoff {
+    /// Backoff starting at `base`, multiplied by `factor` per step and capped
+    /// at `max`, without jitter.
+    #[must_use]
+    pub fn new(base: Duration, max: Duration, factor: f64) -> Self {
+        Self {
+            current: base.min(max),
+            max,
+            factor,
+            jitter: Jitter::None,
+            rng: RandomState::new().build_hasher().finish(),
+        }
+    }
+
+    /// Randomize the delays with `jitter`.
+    #[must_use]
+    pub const fn with_jitter(mut self, jitter: Jitter) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Sample the jitter from a generator seeded with `seed`, for
+    /// reproducible delays.
+    #[must_use]
+    pub const fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = seed;
+        self
+    }
+
+    /// Next sample in `0.0..1.0`.
+    fn sample(&mut self) -> f64 {
+        self.rng = self.rng.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        // The top 53 bits fill an f64 mantissa exactly
+        #[allow(clippy::cast_precision_loss)]
+        let unit = (z >> 11) as f64 / (1_u64 << 53) as f64;
+        unit
+    }
+}
+
+impl Iterator for Backoff {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        let step = self.current;
+        let scaled = step.as_secs_f64() * self.factor;
+        self.current = if scaled.is_finite() && scaled < self.max.as_secs_f64() {
+            Duration::from_secs_f64(scaled)
+        } else {
+            self.max
+        };
+        let sample = self.sample();
+        Some(self.jitter.apply(step, sample))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backoff(jitter: Jitter) -> Backoff {
+        Backoff::new(Duration::from_millis(100), Duration::from_millis(1_000), 2.0)
+            .with_jitter(jitter)
+            .with_seed(7)
+    }
+
+    #[test]
+    fn test_delays_grow_then_cap() {
+        let delays: Vec<u128> = backoff(Jitter::None).take(6).map(|d| d.as_millis()).collect();
+        assert_eq!(delays, vec![100, 200, 400, 800, 1_000, 1_000]);
+    }
+
+    #[test]
+    fn test_jittered_delays_stay_within_bounds() {
+        let ceilings: Vec<Duration> = backoff(Jitter::None).take(50).collect();
+        for jitter in [Jitter::Full, Jitter::Equal] {
+            let delays: Vec<Duration> = backoff(jitter).take(50).collect();
+            for (delay, ceiling) in delays.iter().zip(&ceilings) {
+                let floor = if jitter == Jitter::Equal { *ceiling / 2 } else { Duration::ZERO };
+                assert!(*delay >= floor && delay <= ceiling, "{jitter:?}: {delay:?} vs {ceiling:?}");
+            }
+            // Delays trend upward: the capped tail averages above the first steps
+            let early = delays[..2].iter().sum::<Duration>() / 2;
+            let late = delays[10..].iter().sum::<Duration>() / 40;
+            assert!(late > early, "{jitter:?}: {early:?} then {late:?}");
+            // and are actually spread out
+            assert!(delays[10..].windows(2).any(|pair| pair[0] != pair[1]));
+        }
+    }
+}
@@ -1,7 +1,9 @@
+pub mod backoff;
 pub mod clock;
 pub mod serde;
 pub mod telemetry;
 
+pub use backoff::*;
 pub use clock::*;
 pub use serde::*;
 pub use telemetry::*;
@@ -1,12 +1,23 @@
 //! Serialization-friendly core types and helpers.
 
-use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::core::SchedulerError;
 
 /// Unique task identifier.
 pub type TaskId = u64;
 
 /// Task priority for ordering.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+///
+/// The four bands sit at fixed points of a `u8` scale (see [`Priority::value`]);
+/// `Custom` places a task anywhere on that scale, so it interleaves with them.
+/// Priorities compare and test equal by value alone, so `Custom(100)` ranks
+/// the same as `Normal`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Priority {
     /// Lowest urgency.
@@ -17,10 +28,48 @@ pub enum Priority {
     High,
     /// Highest urgency.
     Critical,
+    /// Explicit position on the priority scale.
+    Custom(u8),
+}
+
+impl Priority {
+    /// Position on the priority scale; higher runs first.
+    ///
+    /// `Low` is 50, `Normal` 100, `High` 150 and `Critical` 200.
+    #[must_use]
+    pub const fn value(self) -> u8 {
+        match self {
+            Self::Low => 50,
+            Self::Normal => 100,
+            Self::High => 150,
+            Self::Critical => 200,
+            Self::Custom(value) => value,
+        }
+    }
+}
+
+impl PartialEq for Priority {
+    fn eq(&self, other: &Self) -> bool {
+        self.value() == other.value()
+    }
+}
+
+impl Eq for Priority {}
+
+impl PartialOrd for Priority {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Priority {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.value().cmp(&other.value())
+    }
 }
 
 /// Resource kind used for capacity accounting.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ResourceKind {
     /// CPU-bound work.
@@ -38,7 +87,7 @@ pub enum ResourceKind {
 pub struct ResourceCost {
     /// Kind of resource being consumed.
     pub kind: ResourceKind,
-    /// Number of units required.
+    /// Number of units required; pools reject tasks costing zero units.
     pub units: u32,
 }
 
@@ -52,3 +101,64 @@ pub struct MailboxKey {
     /// Optional session identifier.
     pub session_id: Option<String>,
 }
+
+/// Append `value` to a JSON-lines file, creating the file if needed.
+///
+/// # Errors
+///
+/// Returns `SchedulerError::Backend` on I/O or serialization failure.
+pub fn append_json_line<T: Serialize>(path: &Path, value: &T) -> Result<(), SchedulerError> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| SchedulerError::Backend(e.to_string()))?;
+    let line = serde_json::to_string(value).map_err(|e| SchedulerError::Backend(e.to_string()))?;
+    writeln!(file, "{line}").map_err(|e| SchedulerError::Backend(e.to_string()))
+}
+
+/// Replace the contents of a JSON-lines file with one line per value.
+///
+/// # Errors
+///
+/// Returns `SchedulerError::Backend` on I/O or serialization failure.
+pub fn write_json_lines<'a, T, I>(path: &Path, values: I) -> Result<(), SchedulerError>
+where
+    T: Serialize + 'a,
+    I: IntoIterator<Item = &'a T>,
+{
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(path)
+        .map_err(|e| SchedulerError::Backend(e.to_string()))?;
+    for value in values {
+        let line =
+            serde_json::to_string(value).map_err(|e| SchedulerError::Backend(e.to_string()))?;
+        writeln!(file, "{line}").map_err(|e| SchedulerError::Backend(e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Read every value from a JSON-lines file. A missing file yields no values.
+///
+/// # Errors
+///
+/// Returns `SchedulerError::Backend` on I/O failure or a malformed line.
+pub fn read_json_lines<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>, SchedulerError> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let file = OpenOptions::new()
+        .read(true)
+        .open(path)
+        .map_err(|e| SchedulerError::Backend(e.to_string()))?;
+    let reader = BufReader::new(file);
+    let mut values = Vec::new();
+    for line in reader.lines() {
+        let line = line.map_err(|e| SchedulerError::Backend(e.to_string()))?;
+        values.push(serde_json::from_str(&line).map_err(|e| SchedulerError::Backend(e.to_string()))?);
+    }
+    Ok(values)
+}
@@ -10,3 +10,264 @@ pub fn init_tracing() {
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
         .try_init();
 }
+
+/// Which pool implementation a [`PoolSnapshotMetrics`] describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PoolKind {
+    /// A `WorkerPool`.
+    Worker,
+    /// A `ResourcePool`.
+    Resource,
+}
+
+/// Point-in-time health of one pool.
+///
+/// Fields mean the same for every pool kind, so a monitoring layer can report
+/// all pools without special-casing either.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct PoolSnapshotMetrics {
+    /// Name the pool was registered under.
+    pub name: String,
+    /// Pool implementation.
+    pub kind: PoolKind,
+    /// Resource units held by running tasks.
+    pub used_units: u32,
+    /// Resource units the pool may hand out (`max_units`).
+    pub total_units: u32,
+    /// Tasks running now.
+    #[serde(default)]
+    pub active_tasks: u64,
+    /// Tasks accepted but not started yet.
+    pub queue_depth: u64,
+    /// Age of the oldest queued task by its `created_at_ms`, or `None` when
+    /// nothing is queued. A growing value means the pool is falling behind.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue_oldest_age_ms: Option<u64>,
+    /// Tasks that finished successfully.
+    pub completed_tasks: u64,
+    /// Tasks that finished unsuccessfully or were dropped after being accepted.
+    pub failed_tasks: u64,
+}
+
+/// A pool that can describe its current health as [`PoolSnapshotMetrics`].
+pub trait SnapshotSource {
+    /// Current metrics of this pool, reported under `name`.
+    fn snapshot_metrics(&self, name: &str) -> PoolSnapshotMetrics;
+}
+
+/// Health of every pool in a scheduler, e.g. for a `/status` endpoint.
+///
+/// # Example
+///
+/// ```rust
+/// use prometheus_parking_lot::util::telemetry::{PoolKind, PoolSnapshotMetrics, SchedulerSnapshot, SnapshotSource};
+///
+/// struct Fixed;
+///
+/// impl SnapshotSource for Fixed {
+///     fn snapshot_metrics(&self, name: &str) -> PoolSnapshotMetrics {
+///         PoolSnapshotMetrics {
+///             name: name.to_string(),
+///             kind: PoolKind::Resource,
+///             used_units: 2,
+///             total_units: 8,
+///             active_tasks: 1,
+///             queue_depth: 0,
+///             queue_oldest_age_ms: None,
+///             completed_tasks: 5,
+///             failed_tasks: 1,
+///         }
+///     }
+/// }
+///
+/// let snapshot = SchedulerSnapshot::collect([("gpu", &Fixed as &dyn SnapshotSource)]);
+/// assert_eq!(snapshot.pools[0].name, "gpu");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SchedulerSnapshot {
+    /// One entry per pool, in collection order.
+    pub pools: Vec<PoolSnapshotMetrics>,
+}
+
+impl SchedulerSnapshot {
+    /// Snapshot each named pool.
+    pub fn collect<'a>(pools: impl IntoIterator<Item = (&'a str, &'a dyn SnapshotSource)>) -> Self {
+        Self {
+            pools: pools
+                .into_iter()
+                .map(|(name, pool)| pool.snapshot_metrics(name))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(feature = "metrics")]
+pub use self::metrics::PrometheusExporter;
+
+#[cfg(feature = "metrics")]
+mod metrics {
+    use prometheus::core::{Collector, Desc, Describer};
+    use prometheus::proto::MetricFamily;
+    use prometheus::{IntCounter, IntGauge, Opts};
+
+    use crate::core::PoolStats;
+
+    /// How a pool statistic is exposed to Prometheus.
+    #[derive(Clone, Copy)]
+    enum Kind {
+        Counter,
+        Gauge,
+    }
+
+    /// A single exported statistic: name, help text, kind, and how to read it.
+    type Spec = (&'static str, &'static str, Kind, fn(&PoolStats) -> u64);
+
+    const SPECS: [Spec; 7] = [
+        ("submitted_tasks_total", "Total tasks submitted", Kind::Counter, |s| s.submitted_tasks),
+        ("completed_tasks_total", "Total tasks completed successfully", Kind::Counter, |s| s.completed_tasks),
+        ("failed_tasks_total", "Total tasks that failed", Kind::Counter, |s| s.failed_tasks),
+        ("active_tasks", "Currently executing tasks", Kind::Gauge, |s| s.active_tasks),
+        ("queued_tasks", "Tasks waiting in the queue", Kind::Gauge, |s| s.queued_tasks),
+        ("used_units", "Resource units currently in use", Kind::Gauge, |s| u64::from(s.used_units)),
+        ("total_units", "Total resource units available", Kind::Gauge, |s| u64::from(s.total_units)),
+    ];
+
+    /// Prometheus collector exposing `PoolStats` as counters and gauges.
+    ///
+    /// Values are read from the pool's atomics at scrape time, so registering
+    /// the exporter once is enough; there is nothing to update by hand.
+    pub struct PrometheusExporter {
+        source: Box<dyn Fn() -> PoolStats + Send + Sync>,
+        opts: Vec<Opts>,
+        descs: Vec<Desc>,
+    }
+
+    impl PrometheusExporter {
+        /// Metric name prefix shared by every exported series.
+        pub const NAMESPACE: &'static str = "parking_lot";
+
+        /// Create an exporter reading statistics from `source` on each scrape.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if a metric descriptor is invalid.
+        pub fn new(
+            source: impl Fn() -> PoolStats + Send + Sync + 'static,
+        ) -> Result<Self, prometheus::Error> {
+            let opts: Vec<Opts> = SPECS
+                .iter()
+                .map(|(name, help, _, _)| Opts::new(*name, *help).namespace(Self::NAMESPACE))
+                .collect();
+            let descs = opts
+                .iter()
+                .map(Describer::describe)
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Self {
+                source: Box::new(source),
+                opts,
+                descs,
+            })
+        }
+    }
+
+    impl Collector for PrometheusExporter {
+        fn desc(&self) -> Vec<&Desc> {
+            self.descs.iter().collect()
+        }
+
+        fn collect(&self) -> Vec<MetricFamily> {
+            let stats = (self.source)();
+            let mut families = Vec::with_capacity(SPECS.len());
+            for ((_, _, kind, read), opts) in SPECS.iter().zip(&self.opts) {
+                let value = read(&stats);
+                let collected = match kind {
+                    Kind::Counter => IntCounter::with_opts(opts.clone()).map(|counter| {
+                        counter.inc_by(value);
+                        counter.collect()
+                    }),
+                    Kind::Gauge => IntGauge::with_opts(opts.clone()).map(|gauge| {
+                        gauge.set(i64::try_from(value).unwrap_or(i64::MAX));
+                        gauge.collect()
+                    }),
+                };
+                match collected {
+                    Ok(family) => families.extend(family),
+                    Err(e) => tracing::error!("failed to collect pool metric: {}", e),
+                }
+            }
+            families
+        }
+    }
+}
+
+#[cfg(feature = "otel")]
+pub use self::otel::current_trace_context;
+
+/// OpenTelemetry spans around task queueing and execution.
+///
+/// Each task gets a `parking_lot.task` span from submission to completion and a
+/// child `parking_lot.execute` span around the executor call. Spans use the
+/// global tracer provider, so installing one is all a caller has to do.
+#[cfg(feature = "otel")]
+pub(crate) mod otel {
+    use std::collections::HashMap;
+
+    use opentelemetry::propagation::TextMapPropagator;
+    use opentelemetry::trace::{SpanKind, TraceContextExt, Tracer};
+    use opentelemetry::{global, Context, KeyValue};
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+
+    use crate::core::TaskMetadata;
+
+    /// Instrumentation scope name for spans emitted by the crate.
+    const TRACER_NAME: &str = "prometheus_parking_lot";
+
+    /// Capture the current trace context as a W3C carrier for `TaskMetadata::trace_context`.
+    #[must_use]
+    pub fn current_trace_context() -> HashMap<String, String> {
+        let mut carrier = HashMap::new();
+        TraceContextPropagator::new().inject_context(&Context::current(), &mut carrier);
+        carrier
+    }
+
+    fn task_attributes(meta: &TaskMetadata) -> Vec<KeyValue> {
+        vec![
+            KeyValue::new("task_id", i64::try_from(meta.id).unwrap_or(i64::MAX)),
+            KeyValue::new("priority", format!("{:?}", meta.priority).to_lowercase()),
+            KeyValue::new("cost", i64::from(meta.cost.units)),
+        ]
+    }
+
+    /// Start the task-lifetime span, parented to the caller's trace if one was carried.
+    pub fn start_task_span(meta: &TaskMetadata) -> Context {
+        let parent = meta.trace_context.as_ref().map_or_else(Context::current, |carrier| {
+            TraceContextPropagator::new().extract(carrier)
+        });
+        let tracer = global::tracer(TRACER_NAME);
+        let span = tracer
+            .span_builder("parking_lot.task")
+            .with_kind(SpanKind::Internal)
+            .with_attributes(task_attributes(meta))
+            .start_with_context(&tracer, &parent);
+        parent.with_span(span)
+    }
+
+    /// Start the execution span as a child of the task span.
+    pub fn start_execute_span(task_cx: &Context, meta: &TaskMetadata) -> Context {
+        let tracer = global::tracer(TRACER_NAME);
+        let span = tracer
+            .span_builder("parking_lot.execute")
+            .with_kind(SpanKind::Internal)
+            .with_attributes(task_attributes(meta))
+            .start_with_context(&tracer, task_cx);
+        task_cx.with_span(span)
+    }
+
+    /// Record the final status on a span and end it.
+    pub fn end_span(cx: &Context, status: &'static str) {
+        let span = cx.span();
+        span.set_attribute(KeyValue::new("status", status));
+        span.end();
+    }
+}
@@ -0,0 +1,256 @@
+//! Integration tests for audit sinks.
+
+mod common;
+
+use async_trait::async_trait;
+use common::SharedSink;
+use prometheus_parking_lot::config::WorkerPoolConfig;
+use prometheus_parking_lot::core::{
+    build_audit_event, AuditEvent, AuditSink, FileAuditSink, FilteringAuditSink, InMemoryAuditSink,
+    PoolError, PoolLimits, ResourcePool, ScheduledTask, Spawn, TaskExecutor, TaskMetadata,
+    TeeAuditSink, TracingAuditSink, WorkerExecutor, WorkerPool,
+};
+use prometheus_parking_lot::infra::mailbox::memory::InMemoryMailbox;
+use prometheus_parking_lot::infra::queue::memory::InMemoryQueue;
+use prometheus_parking_lot::util::clock::now_ms;
+use prometheus_parking_lot::util::serde::{Priority, ResourceKind};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing_test::traced_test;
+
+/// Sink that only counts the events it receives.
+#[derive(Clone, Default)]
+struct CountingAuditSink(Arc<AtomicUsize>);
+
+impl AuditSink for CountingAuditSink {
+    fn record(&mut self, _event: AuditEvent) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Clone)]
+struct EchoExecutor;
+
+#[async_trait]
+impl TaskExecutor<u64, u64> for EchoExecutor {
+    async fn execute(&self, payload: u64, _meta: TaskMetadata) -> u64 {
+        payload
+    }
+}
+
+/// Worker executor that panics on every task.
+#[derive(Clone)]
+struct PanickingExecutor;
+
+#[async_trait]
+impl WorkerExecutor<u64, u64> for PanickingExecutor {
+    async fn execute(&self, payload: u64, _meta: TaskMetadata) -> u64 {
+        panic!("model crashed on input {payload}");
+    }
+}
+
+#[derive(Clone)]
+struct TestSpawner;
+
+impl Spawn for TestSpawner {
+    fn spawn<F>(&self, fut: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        tokio::spawn(fut);
+    }
+}
+
+fn event(id: &str, task_id: &str, tenant: &str, action: &str, created_at_ms: u128) -> AuditEvent {
+    AuditEvent {
+        event_id: id.into(),
+        task_id: task_id.into(),
+        pool: "pool".into(),
+        tenant: tenant.into(),
+        action: action.into(),
+        created_at_ms,
+        payload: None,
+    }
+}
+
+#[test]
+fn test_in_memory_audit_sink_queries() {
+    let mut sink = InMemoryAuditSink::new(16);
+    sink.record(event("e1", "1", "acme", "enqueue", 100));
+    sink.record(event("e2", "2", "globex", "enqueue", 110));
+    sink.record(event("e3", "1", "acme", "wake", 120));
+    sink.record(event("e4", "2", "globex", "reject", 130));
+    sink.record(event("e5", "1", "acme", "complete", 140));
+
+    let ids = |events: Vec<AuditEvent>| -> Vec<String> {
+        events.into_iter().map(|e| e.event_id).collect()
+    };
+
+    assert_eq!(ids(sink.events_for_task("1")), ["e1", "e3", "e5"]);
+    assert_eq!(ids(sink.events_for_task("3")), Vec::<String>::new());
+    assert_eq!(ids(sink.events_for_tenant("globex")), ["e2", "e4"]);
+    assert_eq!(ids(sink.events_since(120)), ["e3", "e4", "e5"]);
+
+    let counts = sink.count_by_action();
+    assert_eq!(counts.len(), 4);
+    assert_eq!(counts["enqueue"], 2);
+    assert_eq!(counts["wake"], 1);
+    assert_eq!(counts["reject"], 1);
+    assert_eq!(counts["complete"], 1);
+}
+
+#[test]
+fn test_file_audit_sink_survives_reopen() {
+    let dir = std::env::temp_dir().join(format!("pl-audit-{}", now_ms()));
+    let path = dir.join("audit.jsonl");
+
+    let first = build_audit_event("e1", "1", "pool", "tenant-a", "enqueue", None);
+    let second = build_audit_event("e2", "1", "pool", "tenant-a", "complete", Some(serde_json::json!({"cost": 3})));
+
+    {
+        let mut sink = FileAuditSink::new(&path).unwrap();
+        sink.record(first.clone());
+    }
+
+    // Re-open the same file and keep appending
+    let mut sink = FileAuditSink::new(&path).unwrap();
+    sink.record(second.clone());
+
+    let events = FileAuditSink::read_all(sink.path()).unwrap();
+    assert_eq!(events, vec![first, second]);
+    assert!(events[0].created_at_ms > 0);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+#[traced_test]
+fn test_tracing_audit_sink_emits_fields() {
+    let mut sink = TracingAuditSink::new();
+    sink.record(AuditEvent {
+        event_id: "evt-42".into(),
+        task_id: "42".into(),
+        pool: "gpu".into(),
+        tenant: "acme".into(),
+        action: "complete".into(),
+        created_at_ms: 1_700_000_000_000,
+        payload: None,
+    });
+
+    assert!(logs_contain("audit event"));
+    assert!(logs_contain("event_id=evt-42"));
+    assert!(logs_contain("task_id=42"));
+    assert!(logs_contain("pool=gpu"));
+    assert!(logs_contain("tenant=acme"));
+    assert!(logs_contain("action=complete"));
+    assert!(logs_contain("created_at_ms=1700000000000"));
+}
+
+#[tokio::test]
+async fn test_complete_event_carries_task_context() {
+    let events = Arc::new(Mutex::new(InMemoryAuditSink::new(16)));
+    let limits = PoolLimits {
+        max_units: 10,
+        max_queue_depth: 10,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+    let pool = ResourcePool::new(
+        limits,
+        InMemoryQueue::new(10),
+        InMemoryMailbox::new(),
+        EchoExecutor,
+        TestSpawner,
+    )
+    .with_audit(Box::new(SharedSink(Arc::clone(&events))));
+
+    let meta = TaskMetadata::builder(9)
+        .priority(Priority::High)
+        .cost(ResourceKind::Cpu, 4)
+        .build();
+    pool.submit(ScheduledTask { meta, payload: 1 }, now_ms()).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let complete = events.lock().unwrap().events_for_task("9");
+    let complete = complete
+        .iter()
+        .find(|e| e.action == "complete")
+        .expect("complete event recorded");
+    let payload = complete.payload.as_ref().expect("structured payload");
+    assert_eq!(payload["cost"], 4);
+    assert_eq!(payload["priority"], "high");
+    assert_eq!(payload["queue_len"], 0);
+}
+
+#[tokio::test]
+async fn test_worker_panic_is_audited() {
+    let events = Arc::new(Mutex::new(InMemoryAuditSink::new(16)));
+    let pool = WorkerPool::new(WorkerPoolConfig::new().with_worker_count(1), PanickingExecutor)
+        .expect("Failed to create pool")
+        .with_audit(Box::new(SharedSink(Arc::clone(&events))));
+
+    let meta = TaskMetadata::builder(42).build();
+    let key = pool.submit(7, meta).unwrap();
+
+    let mut panics = Vec::new();
+    for _ in 0..200 {
+        panics = events.lock().unwrap().events_for_task("42");
+        if !panics.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    let [event] = panics.as_slice() else {
+        panic!("expected one panic event, got {panics:?}");
+    };
+    assert_eq!(event.action, "panic");
+    let payload = event.payload.as_ref().expect("structured payload");
+    assert_eq!(payload["message"], "model crashed on input 7");
+    assert_eq!(payload["worker_id"], 0);
+
+    // The panicked task gives back its capacity and fails its waiters at once
+    let stats = pool.stats();
+    assert_eq!(stats.active_tasks, 0);
+    assert_eq!(stats.used_units, 0);
+    assert_eq!(stats.failed_tasks, 1);
+    assert!(matches!(pool.try_retrieve(&key), Err(PoolError::ResultNotFound { .. })));
+
+    pool.shutdown();
+}
+
+#[test]
+fn test_tee_audit_sink_reaches_every_sink() {
+    let memory = Arc::new(Mutex::new(InMemoryAuditSink::new(10)));
+    let counter = CountingAuditSink::default();
+    let mut tee = TeeAuditSink::new(vec![Box::new(SharedSink(Arc::clone(&memory)))])
+        .with_sink(Box::new(counter.clone()));
+    assert_eq!(tee.len(), 2);
+
+    tee.record(event("e1", "1", "acme", "submit", 10));
+    tee.record(event("e2", "1", "acme", "start", 20));
+
+    let events = memory.lock().unwrap().events();
+    let ids: Vec<&str> = events.iter().map(|e| e.event_id.as_str()).collect();
+    assert_eq!(ids, ["e1", "e2"]);
+    assert_eq!(counter.0.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_filtering_audit_sink_drops_other_actions() {
+    let memory = Arc::new(Mutex::new(InMemoryAuditSink::new(10)));
+    let mut sink =
+        FilteringAuditSink::new(Box::new(SharedSink(Arc::clone(&memory))), ["expire"]);
+    assert!(sink.allows("expire"));
+    assert!(!sink.allows("start"));
+
+    sink.record(event("e1", "1", "acme", "start", 10));
+    sink.record(event("e2", "1", "acme", "complete", 20));
+    sink.record(event("e3", "2", "acme", "expire", 30));
+
+    let events = memory.lock().unwrap().events();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_id, "e3");
+    assert_eq!(events[0].action, "expire");
+}
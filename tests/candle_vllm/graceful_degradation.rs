@@ -19,6 +19,11 @@ fn make_meta(task_id: u64) -> TaskMetadata {
         cost: ResourceCost { kind: ResourceKind::Cpu, units: 10 },
         deadline_ms: None,
         created_at_ms: now_ms(),
+        trace_context: None,
+        degraded: false,
+        depends_on: Vec::new(),
+        idempotency_key: None,
+        retrieve_timeout_ms: None,
     }
 }
 
@@ -53,7 +58,8 @@ async fn test_queue_full_rejection() {
     let result = pool.submit_async((), make_meta(4)).await;
     
     match result {
-        Err(PoolError::QueueFull) => {
+        Err(PoolError::QueueFull { task_id, .. }) => {
+            assert_eq!(task_id, Some(4));
             println!("Correctly rejected with QueueFull");
         }
         Ok(_) => {
@@ -85,7 +91,10 @@ async fn test_backpressure_handling() {
     for i in 0..20 {
         match pool.submit_async((), make_meta(i)).await {
             Ok(_) => accepted += 1,
-            Err(PoolError::QueueFull) => rejected += 1,
+            Err(PoolError::QueueFull { task_id, .. }) => {
+                assert_eq!(task_id, Some(i));
+                rejected += 1;
+            }
             Err(e) => panic!("Unexpected error: {:?}", e),
         }
     }
@@ -96,3 +105,54 @@ async fn test_backpressure_handling() {
     
     println!("=== test_backpressure_handling PASSED ===\n");
 }
+
+/// Reports the cost and degradation flag the executor was handed
+#[derive(Clone)]
+struct CostReportingExecutor;
+
+#[async_trait]
+impl WorkerExecutor<(), (u32, bool)> for CostReportingExecutor {
+    async fn execute(&self, _: (), meta: TaskMetadata) -> (u32, bool) {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        (meta.cost.units, meta.degraded)
+    }
+}
+
+#[tokio::test]
+async fn test_degradation_rewrites_cost_above_watermark() {
+    println!("\n=== test_degradation_rewrites_cost_above_watermark ===");
+    
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(1)
+        .with_max_units(10)
+        .with_max_queue_depth(10);
+    
+    // Serve a quantized (2-unit) path once two tasks are waiting
+    let pool = WorkerPool::new(config, CostReportingExecutor)
+        .expect("Failed to create pool")
+        .with_degradation(2, |meta| meta.cost.units = 2);
+    
+    // Occupy the only worker, then queue up to the watermark
+    let running = pool.submit_async((), make_meta(1)).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    let queued_a = pool.submit_async((), make_meta(2)).await.unwrap();
+    let queued_b = pool.submit_async((), make_meta(3)).await.unwrap();
+    assert!(pool.stats().degradation_active);
+    
+    // Submitted above the watermark: downgraded
+    let degraded = pool.submit_async((), make_meta(4)).await.unwrap();
+    assert_eq!(pool.stats().degraded_tasks, 1);
+    
+    let timeout = Duration::from_secs(5);
+    assert_eq!(pool.retrieve_async(&running, timeout).await.unwrap(), (10, false));
+    assert_eq!(pool.retrieve_async(&queued_a, timeout).await.unwrap(), (10, false));
+    assert_eq!(pool.retrieve_async(&queued_b, timeout).await.unwrap(), (10, false));
+    assert_eq!(pool.retrieve_async(&degraded, timeout).await.unwrap(), (2, true));
+    
+    // Pressure is gone once the queue drains
+    let stats = pool.stats();
+    assert!(!stats.degradation_active);
+    assert_eq!(stats.degraded_tasks, 1);
+    
+    println!("=== test_degradation_rewrites_cost_above_watermark PASSED ===\n");
+}
@@ -20,6 +20,11 @@ fn make_meta(task_id: u64, priority: Priority) -> TaskMetadata {
         cost: ResourceCost { kind: ResourceKind::Cpu, units: 10 },
         deadline_ms: None,
         created_at_ms: now_ms(),
+        trace_context: None,
+        degraded: false,
+        depends_on: Vec::new(),
+        idempotency_key: None,
+        retrieve_timeout_ms: None,
     }
 }
 
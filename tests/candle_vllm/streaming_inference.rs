@@ -18,6 +18,11 @@ fn make_gpu_meta(task_id: u64, units: u32) -> TaskMetadata {
         cost: ResourceCost { kind: ResourceKind::GpuVram, units },
         deadline_ms: None,
         created_at_ms: now_ms(),
+        trace_context: None,
+        degraded: false,
+        depends_on: Vec::new(),
+        idempotency_key: None,
+        retrieve_timeout_ms: None,
     }
 }
 
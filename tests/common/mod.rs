@@ -0,0 +1,43 @@
+//! Helpers shared by the integration tests.
+
+// Each test crate compiles its own copy and uses only part of it
+#![allow(dead_code)]
+
+use prometheus_parking_lot::core::{
+    AuditEvent, AuditSink, DeadLetter, DeadLetterSink, InMemoryAuditSink, InMemoryDeadLetter,
+    TaskMetadata,
+};
+use std::sync::{Arc, Mutex};
+
+/// Sink that shares an in-memory buffer with the test.
+pub struct SharedSink<S>(pub Arc<Mutex<S>>);
+
+impl<S> Clone for SharedSink<S> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<S> SharedSink<S> {
+    pub fn new(sink: S) -> Self {
+        Self(Arc::new(Mutex::new(sink)))
+    }
+}
+
+impl SharedSink<InMemoryDeadLetter> {
+    pub fn entries(&self) -> Vec<DeadLetter> {
+        self.0.lock().unwrap().entries()
+    }
+}
+
+impl DeadLetterSink for SharedSink<InMemoryDeadLetter> {
+    fn record(&mut self, task_meta: TaskMetadata, reason: String) {
+        self.0.lock().unwrap().record(task_meta, reason);
+    }
+}
+
+impl AuditSink for SharedSink<InMemoryAuditSink> {
+    fn record(&mut self, event: AuditEvent) {
+        self.0.lock().unwrap().record(event);
+    }
+}
@@ -0,0 +1,264 @@
+//! Integration tests for dead-letter sinks.
+//!
+//! Validates that tasks dropped without running are handed to the sink:
+//! - Queue-full rejections (`ResourcePool` and `WorkerPool`)
+//! - Deadline expiry before execution
+//! - Expired tasks pruned from a `ResourcePool` queue
+//! - Expired and over-waited tasks skipped by a `ResourcePool` wake pass
+//! - File-backed sink survives re-reading from disk
+
+mod common;
+
+use async_trait::async_trait;
+use common::SharedSink;
+use prometheus_parking_lot::config::WorkerPoolConfig;
+use prometheus_parking_lot::core::{
+    DeadLetterSink, FileDeadLetter, InMemoryDeadLetter, PoolLimits, ResourcePool, ScheduledTask,
+    SchedulerError, Spawn, TaskExecutor, TaskMetadata, WorkerExecutor, WorkerPool,
+    REASON_DEADLINE_EXPIRED, REASON_QUEUE_FULL, REASON_QUEUE_WAIT_EXCEEDED,
+};
+use prometheus_parking_lot::infra::mailbox::memory::InMemoryMailbox;
+use prometheus_parking_lot::infra::queue::memory::InMemoryQueue;
+use prometheus_parking_lot::util::clock::now_ms;
+use prometheus_parking_lot::util::serde::ResourceKind;
+use std::future::Future;
+use std::time::Duration;
+
+#[derive(Clone)]
+struct SleepExecutor {
+    delay_ms: u64,
+}
+
+#[async_trait]
+impl TaskExecutor<u64, u64> for SleepExecutor {
+    async fn execute(&self, payload: u64, _meta: TaskMetadata) -> u64 {
+        tokio::time::sleep(Duration::from_millis(self.delay_ms)).await;
+        payload
+    }
+}
+
+#[async_trait]
+impl WorkerExecutor<u64, u64> for SleepExecutor {
+    async fn execute(&self, payload: u64, _meta: TaskMetadata) -> u64 {
+        tokio::time::sleep(Duration::from_millis(self.delay_ms)).await;
+        payload
+    }
+}
+
+#[derive(Clone)]
+struct TestSpawner;
+
+impl Spawn for TestSpawner {
+    fn spawn<F>(&self, fut: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        tokio::spawn(fut);
+    }
+}
+
+fn make_meta(id: u64, units: u32, deadline_ms: Option<u128>) -> TaskMetadata {
+    let mut meta = TaskMetadata::builder(id).cost(ResourceKind::Cpu, units).build();
+    meta.deadline_ms = deadline_ms;
+    meta
+}
+
+#[tokio::test]
+async fn test_resource_pool_dead_letters_rejected_tasks() {
+    let limits = PoolLimits {
+        max_units: 10,
+        max_queue_depth: 1,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+    let sink = SharedSink::new(InMemoryDeadLetter::new(16));
+    let pool = ResourcePool::new(
+        limits,
+        InMemoryQueue::new(1),
+        InMemoryMailbox::new(),
+        SleepExecutor { delay_ms: 200 },
+        TestSpawner,
+    )
+    .with_dead_letter(Box::new(sink.clone()));
+
+    let now = now_ms();
+
+    // Occupies all capacity, then fills the queue
+    pool.submit(ScheduledTask { meta: make_meta(1, 10, None), payload: 1 }, now)
+        .await
+        .unwrap();
+    pool.submit(ScheduledTask { meta: make_meta(2, 10, None), payload: 2 }, now)
+        .await
+        .unwrap();
+
+    // Rejected: queue full
+    let err = pool
+        .submit(ScheduledTask { meta: make_meta(3, 10, None), payload: 3 }, now)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, SchedulerError::QueueFull(_)));
+
+    // Rejected: already past its deadline
+    let err = pool
+        .submit(ScheduledTask { meta: make_meta(4, 1, Some(now - 1)), payload: 4 }, now)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, SchedulerError::DeadlineExpired));
+
+    let entries = sink.entries();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].meta.id, 3);
+    assert_eq!(entries[0].reason, REASON_QUEUE_FULL);
+    assert_eq!(entries[1].meta.id, 4);
+    assert_eq!(entries[1].reason, REASON_DEADLINE_EXPIRED);
+
+    pool.shutdown();
+}
+
+#[tokio::test]
+async fn test_resource_pool_dead_letters_pruned_tasks() {
+    let limits = PoolLimits {
+        max_units: 10,
+        max_queue_depth: 10,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+    let sink = SharedSink::new(InMemoryDeadLetter::new(16));
+    let pool = ResourcePool::new(
+        limits,
+        InMemoryQueue::new(10),
+        InMemoryMailbox::new(),
+        SleepExecutor { delay_ms: 500 },
+        TestSpawner,
+    )
+    .with_dead_letter(Box::new(sink.clone()));
+
+    let now = now_ms();
+    pool.submit(ScheduledTask { meta: make_meta(1, 10, None), payload: 1 }, now)
+        .await
+        .unwrap();
+    pool.submit(ScheduledTask { meta: make_meta(2, 5, Some(now + 20)), payload: 2 }, now)
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    assert_eq!(pool.prune_expired(now_ms()).await.unwrap(), 1);
+
+    let entries = sink.entries();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].meta.id, 2);
+    assert_eq!(entries[0].reason, REASON_DEADLINE_EXPIRED);
+
+    pool.shutdown();
+}
+
+#[tokio::test]
+async fn test_resource_pool_dead_letters_tasks_skipped_on_wake() {
+    let limits = PoolLimits {
+        max_units: 10,
+        max_queue_depth: 10,
+        default_timeout: Duration::from_secs(60),
+        max_queue_wait: Some(Duration::from_millis(30)),
+        ..PoolLimits::default()
+    };
+    let sink = SharedSink::new(InMemoryDeadLetter::new(16));
+    let pool = ResourcePool::new(
+        limits,
+        InMemoryQueue::new(10),
+        InMemoryMailbox::new(),
+        SleepExecutor { delay_ms: 100 },
+        TestSpawner,
+    )
+    .with_dead_letter(Box::new(sink.clone()));
+
+    // Both wait behind the running task: one past its deadline, the other
+    // past max_queue_wait by the time the wake pass reaches them
+    let now = now_ms();
+    pool.submit(ScheduledTask { meta: make_meta(1, 10, None), payload: 1 }, now)
+        .await
+        .unwrap();
+    pool.submit(ScheduledTask { meta: make_meta(2, 5, Some(now + 20)), payload: 2 }, now)
+        .await
+        .unwrap();
+    pool.submit(ScheduledTask { meta: make_meta(3, 5, None), payload: 3 }, now)
+        .await
+        .unwrap();
+
+    let started = std::time::Instant::now();
+    while sink.entries().len() < 2 {
+        assert!(started.elapsed() < Duration::from_secs(2), "skipped tasks never dead-lettered");
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let entries = sink.entries();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].meta.id, 2);
+    assert_eq!(entries[0].reason, REASON_DEADLINE_EXPIRED);
+    assert_eq!(entries[1].meta.id, 3);
+    assert_eq!(entries[1].reason, REASON_QUEUE_WAIT_EXCEEDED);
+
+    pool.shutdown();
+}
+
+#[tokio::test]
+async fn test_worker_pool_dead_letters_rejected_and_expired_tasks() {
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(1)
+        .with_max_units(100)
+        .with_max_queue_depth(1);
+    let sink = SharedSink::new(InMemoryDeadLetter::new(16));
+    let pool = WorkerPool::new(config, SleepExecutor { delay_ms: 200 })
+        .expect("Failed to create pool")
+        .with_dead_letter(Box::new(sink.clone()));
+
+    // Keep the only worker busy
+    let busy = pool.submit_async(1, make_meta(1, 10, None)).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // Queued behind the busy worker; expires before it can start
+    let expired = pool
+        .submit_async(2, make_meta(2, 10, Some(now_ms() + 20)))
+        .await
+        .unwrap();
+
+    // Queue is full
+    let err = pool.submit_async(3, make_meta(3, 10, None)).await.unwrap_err();
+    assert!(matches!(
+        err,
+        prometheus_parking_lot::core::PoolError::QueueFull { task_id: Some(3), .. }
+    ));
+
+    assert_eq!(pool.retrieve_async(&busy, Duration::from_secs(5)).await.unwrap(), 1);
+    assert!(pool.retrieve(&expired, Duration::from_millis(200)).is_err());
+
+    let entries = sink.entries();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].meta.id, 3);
+    assert_eq!(entries[0].reason, REASON_QUEUE_FULL);
+    assert_eq!(entries[1].meta.id, 2);
+    assert_eq!(entries[1].reason, REASON_DEADLINE_EXPIRED);
+
+    let stats = pool.stats();
+    assert_eq!(stats.completed_tasks, 1);
+    assert_eq!(stats.failed_tasks, 1);
+
+    pool.shutdown();
+}
+
+#[test]
+fn test_file_dead_letter_round_trip() {
+    let dir = std::env::temp_dir().join(format!("pl-dead-letter-{}", now_ms()));
+    let mut sink = FileDeadLetter::new(&dir, "jobs").unwrap();
+    sink.record(make_meta(7, 3, Some(42)), REASON_DEADLINE_EXPIRED.to_string());
+    sink.record(make_meta(8, 1, None), REASON_QUEUE_FULL.to_string());
+
+    let entries = FileDeadLetter::read_all(sink.path()).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].meta.id, 7);
+    assert_eq!(entries[0].meta.deadline_ms, Some(42));
+    assert_eq!(entries[0].reason, REASON_DEADLINE_EXPIRED);
+    assert_eq!(entries[1].meta.id, 8);
+    assert_eq!(entries[1].reason, REASON_QUEUE_FULL);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,127 @@
+//! Integration tests for task dependency ordering in `WorkerPool`.
+//!
+//! A task whose `TaskMetadata::depends_on` names in-flight tasks is held back
+//! until they all succeed; if one fails, the task and its own dependents are
+//! dropped and dead-lettered instead of running.
+
+mod common;
+
+use async_trait::async_trait;
+use common::SharedSink;
+use prometheus_parking_lot::config::WorkerPoolConfig;
+use prometheus_parking_lot::core::{
+    ExecutionOutcome, InMemoryDeadLetter, PoolError, TaskMetadata, WorkerExecutor, WorkerPool,
+    REASON_DEPENDENCY_FAILED,
+};
+use prometheus_parking_lot::util::serde::TaskId;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Executor that records the order tasks run in; payload `(id, fail)`.
+#[derive(Clone)]
+struct OrderExecutor {
+    order: Arc<Mutex<Vec<TaskId>>>,
+}
+
+#[async_trait]
+impl WorkerExecutor<(TaskId, bool), Result<TaskId, String>> for OrderExecutor {
+    async fn execute(&self, payload: (TaskId, bool), _meta: TaskMetadata) -> Result<TaskId, String> {
+        let (id, fail) = payload;
+        // Long enough that dependents are submitted while this task is in flight
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        self.order.lock().unwrap().push(id);
+        if fail {
+            Err(format!("task {} failed", id))
+        } else {
+            Ok(id)
+        }
+    }
+
+    fn classify(&self, result: &Result<TaskId, String>) -> ExecutionOutcome {
+        match result {
+            Ok(_) => ExecutionOutcome::Success,
+            Err(_) => ExecutionOutcome::Failed,
+        }
+    }
+}
+
+fn make_meta(id: TaskId, depends_on: Vec<TaskId>) -> TaskMetadata {
+    TaskMetadata::builder(id).depends_on(depends_on).build()
+}
+
+type OrderPool = WorkerPool<(TaskId, bool), Result<TaskId, String>, OrderExecutor>;
+
+fn make_pool() -> (OrderPool, Arc<Mutex<Vec<TaskId>>>) {
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(3)
+        .with_max_units(10)
+        .with_max_queue_depth(10);
+    let pool = WorkerPool::new(config, OrderExecutor { order: Arc::clone(&order) })
+        .expect("Failed to create pool");
+    (pool, order)
+}
+
+#[tokio::test]
+async fn test_dependency_chain_runs_in_order() {
+    let (pool, order) = make_pool();
+
+    // With three idle workers, only the dependency edges keep B and C waiting
+    let a = pool.submit((1, false), make_meta(1, Vec::new())).unwrap();
+    let b = pool.submit((2, false), make_meta(2, vec![1])).unwrap();
+    let c = pool.submit((3, false), make_meta(3, vec![2])).unwrap();
+
+    let timeout = Duration::from_secs(5);
+    assert_eq!(pool.retrieve_async(&c, timeout).await.unwrap(), Ok(3));
+    assert_eq!(pool.retrieve_async(&b, timeout).await.unwrap(), Ok(2));
+    assert_eq!(pool.retrieve_async(&a, timeout).await.unwrap(), Ok(1));
+    assert_eq!(*order.lock().unwrap(), vec![1, 2, 3]);
+
+    pool.shutdown();
+}
+
+#[tokio::test]
+async fn test_failed_dependency_drops_dependents() {
+    let (pool, order) = make_pool();
+    let sink = SharedSink::new(InMemoryDeadLetter::new(16));
+    let pool = pool.with_dead_letter(Box::new(sink.clone()));
+
+    let a = pool.submit((1, true), make_meta(1, Vec::new())).unwrap();
+    let b = pool.submit((2, false), make_meta(2, vec![1])).unwrap();
+    let c = pool.submit((3, false), make_meta(3, vec![2])).unwrap();
+
+    let timeout = Duration::from_secs(5);
+    assert!(pool.retrieve_async(&a, timeout).await.unwrap().is_err());
+    assert!(pool.retrieve_async(&b, timeout).await.is_err());
+    assert!(pool.retrieve_async(&c, timeout).await.is_err());
+
+    // Neither dependent ever reached the executor
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(*order.lock().unwrap(), vec![1]);
+
+    let mut dropped: Vec<(TaskId, String)> = sink
+        .entries()
+        .into_iter()
+        .map(|letter| (letter.meta.id, letter.reason))
+        .collect();
+    dropped.sort();
+    assert_eq!(
+        dropped,
+        vec![
+            (2, REASON_DEPENDENCY_FAILED.to_string()),
+            (3, REASON_DEPENDENCY_FAILED.to_string()),
+        ]
+    );
+
+    // Later dependents of the failed task are rejected at submission
+    assert!(matches!(
+        pool.submit((4, false), make_meta(4, vec![1])),
+        Err(PoolError::DependencyFailed { id: 1 })
+    ));
+
+    let stats = pool.stats();
+    assert_eq!(stats.failed_tasks, 3);
+    assert_eq!(stats.queued_tasks, 0);
+
+    pool.shutdown();
+}
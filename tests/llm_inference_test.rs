@@ -73,6 +73,7 @@ async fn test_llm_inference_with_parking() {
         max_units: 3,
         max_queue_depth: 50,
         default_timeout: Duration::from_secs(120),
+        ..PoolLimits::default()
     };
 
     let queue = InMemoryQueue::new(50);
@@ -132,6 +133,11 @@ async fn test_llm_inference_with_parking() {
                 },
                 deadline_ms: None,
                 created_at_ms: now_ms(),
+                trace_context: None,
+                degraded: false,
+                depends_on: Vec::new(),
+                idempotency_key: None,
+                retrieve_timeout_ms: None,
             },
             payload: LLMTaskPayload {
                 prompt: prompts[i % prompts.len()].to_string(),
@@ -0,0 +1,71 @@
+//! Integration tests for the Prometheus exporter (requires the `metrics` feature).
+
+#![cfg(feature = "metrics")]
+
+use async_trait::async_trait;
+use prometheus::{Registry, TextEncoder};
+use prometheus_parking_lot::config::WorkerPoolConfig;
+use prometheus_parking_lot::core::{TaskMetadata, WorkerExecutor, WorkerPool};
+use prometheus_parking_lot::util::serde::ResourceKind;
+use std::collections::HashMap;
+use std::time::Duration;
+
+#[derive(Clone)]
+struct DoubleExecutor;
+
+#[async_trait]
+impl WorkerExecutor<u64, u64> for DoubleExecutor {
+    async fn execute(&self, payload: u64, _meta: TaskMetadata) -> u64 {
+        payload * 2
+    }
+}
+
+fn make_meta(id: u64, units: u32) -> TaskMetadata {
+    TaskMetadata::builder(id).cost(ResourceKind::Cpu, units).build()
+}
+
+/// Scrape `registry` in the text exposition format as `name -> value`.
+fn scrape(registry: &Registry) -> HashMap<String, f64> {
+    let text = TextEncoder::new()
+        .encode_to_string(&registry.gather())
+        .expect("Failed to encode metrics");
+    text.lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once(' '))
+        .map(|(name, value)| (name.to_string(), value.parse().unwrap()))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_register_metrics_reports_pool_counters() {
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(2)
+        .with_max_units(50)
+        .with_max_queue_depth(10);
+    let pool = WorkerPool::new(config, DoubleExecutor).expect("Failed to create pool");
+
+    let registry = Registry::new();
+    pool.register_metrics(&registry).expect("Failed to register metrics");
+
+    for id in 0..3 {
+        let key = pool.submit_async(id, make_meta(id, 5)).await.unwrap();
+        let result = pool.retrieve_async(&key, Duration::from_secs(5)).await.unwrap();
+        assert_eq!(result, id * 2);
+    }
+    // Counters are updated just after the result is stored
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let metrics = scrape(&registry);
+    assert_eq!(metrics["parking_lot_submitted_tasks_total"], 3.0);
+    assert_eq!(metrics["parking_lot_completed_tasks_total"], 3.0);
+    assert_eq!(metrics["parking_lot_failed_tasks_total"], 0.0);
+    assert_eq!(metrics["parking_lot_active_tasks"], 0.0);
+    assert_eq!(metrics["parking_lot_queued_tasks"], 0.0);
+    assert_eq!(metrics["parking_lot_used_units"], 0.0);
+    assert_eq!(metrics["parking_lot_total_units"], 50.0);
+
+    // Registering the same pool twice is rejected by the registry
+    assert!(pool.register_metrics(&registry).is_err());
+
+    pool.shutdown();
+}
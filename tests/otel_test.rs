@@ -0,0 +1,98 @@
+//! Integration tests for OpenTelemetry spans (requires the `otel` feature).
+
+#![cfg(feature = "otel")]
+
+use async_trait::async_trait;
+use opentelemetry::trace::{TraceContextExt, Tracer};
+use opentelemetry::{global, Context, KeyValue, Value};
+use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider, SpanData};
+use prometheus_parking_lot::config::WorkerPoolConfig;
+use prometheus_parking_lot::core::{TaskMetadata, WorkerExecutor, WorkerPool};
+use prometheus_parking_lot::util::serde::{Priority, ResourceKind};
+use prometheus_parking_lot::util::telemetry::current_trace_context;
+use std::time::Duration;
+
+#[derive(Clone)]
+struct EchoExecutor;
+
+#[async_trait]
+impl WorkerExecutor<u64, u64> for EchoExecutor {
+    async fn execute(&self, payload: u64, _meta: TaskMetadata) -> u64 {
+        payload
+    }
+}
+
+fn make_meta(id: u64) -> TaskMetadata {
+    TaskMetadata::builder(id)
+        .priority(Priority::High)
+        .cost(ResourceKind::Cpu, 3)
+        .build()
+}
+
+fn attribute<'a>(span: &'a SpanData, key: &str) -> Option<&'a Value> {
+    span.attributes
+        .iter()
+        .find(|kv: &&KeyValue| kv.key.as_str() == key)
+        .map(|kv| &kv.value)
+}
+
+#[tokio::test]
+async fn test_task_and_execute_spans_per_task() {
+    let exporter = InMemorySpanExporter::default();
+    let provider = SdkTracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .build();
+    global::set_tracer_provider(provider.clone());
+
+    let pool = WorkerPool::new(
+        WorkerPoolConfig::new().with_worker_count(1).with_max_units(10),
+        EchoExecutor,
+    )
+    .expect("Failed to create pool");
+
+    // Task 1 carries the caller's trace context; task 2 starts a fresh trace
+    let tracer = global::tracer("caller");
+    let caller_cx = Context::current_with_span(tracer.start("caller"));
+    let mut linked = make_meta(1);
+    {
+        let _guard = caller_cx.clone().attach();
+        linked.trace_context = Some(current_trace_context());
+    }
+
+    for (id, meta) in [(1, linked), (2, make_meta(2))] {
+        let key = pool.submit_async(id, meta).await.unwrap();
+        assert_eq!(pool.retrieve_async(&key, Duration::from_secs(5)).await.unwrap(), id);
+    }
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    pool.shutdown();
+    provider.force_flush().unwrap();
+
+    let spans = exporter.get_finished_spans().unwrap();
+    for id in [1_i64, 2] {
+        let of_task = |name: &str| {
+            spans
+                .iter()
+                .find(|s| s.name == name && attribute(s, "task_id") == Some(&Value::I64(id)))
+                .unwrap_or_else(|| panic!("missing {name} span for task {id}"))
+        };
+        let task = of_task("parking_lot.task");
+        let execute = of_task("parking_lot.execute");
+
+        assert_eq!(execute.parent_span_id, task.span_context.span_id());
+        assert_eq!(execute.span_context.trace_id(), task.span_context.trace_id());
+        for span in [task, execute] {
+            assert_eq!(attribute(span, "priority"), Some(&Value::from("high")));
+            assert_eq!(attribute(span, "cost"), Some(&Value::I64(3)));
+            assert_eq!(attribute(span, "status"), Some(&Value::from("success")));
+        }
+    }
+
+    // The carried trace context links task 1 to the caller's span
+    let caller_span = caller_cx.span().span_context().clone();
+    let linked_task = spans
+        .iter()
+        .find(|s| s.name == "parking_lot.task" && attribute(s, "task_id") == Some(&Value::I64(1)))
+        .unwrap();
+    assert_eq!(linked_task.span_context.trace_id(), caller_span.trace_id());
+    assert_eq!(linked_task.parent_span_id, caller_span.span_id());
+}
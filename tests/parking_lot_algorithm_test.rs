@@ -7,16 +7,57 @@
 //! 4. Tasks wake up when capacity becomes available
 //! 5. Results are delivered to mailbox
 //! 6. Priority ordering is respected
+//! 7. Tasks that expire while queued are skipped on wake
+//! 8. Per-kind capacity floors are honored
+//! 9. Tasks can be submitted from non-async callers
+//! 10. The queue can be drained and its tasks re-admitted
+//! 11. Queued tasks survive a snapshot and restore into a fresh pool
+//! 12. Tasks that wait in the queue too long are dropped on wake
+//! 13. The next task to start can be inspected without dequeuing it
+//! 14. Zero-cost tasks are rejected instead of bypassing capacity
+//! 15. Small tasks don't overtake a queued higher-priority task
+//! 16. Task metadata can be built without spelling out every field
+//! 17. API submissions may give a deadline relative to receipt
+//! 18. Results persisted by the mailbox can be fetched back through the pool
+//! 19. Tasks are rejected once the queued cost would exceed the pool's unit cap
+//! 20. Mailbox results can be streamed as Server-Sent Events frames
+//! 21. Larger queued tasks still start when wakes wait for enough free units
+//! 22. Pool listings report the live usage of each running pool
+//! 23. A reserved share of capacity keeps urgent tasks startable
+//! 24. Executor errors mark tasks failed in the mailbox
+//! 25. Queue-always dispatch starts every task from the wake pass in priority order
+//! 26. A dedicated sync wake worker runs queued tasks in priority order
+//! 27. Capacity reserved outside any task holds off admission until released
+//! 28. Non-serializable results reach an in-memory mailbox
+//! 29. A task cap limits concurrency even when units are plentiful
+//! 30. Pruning hands back the expired tasks and notifies their mailboxes
+//! 31. Preferring queued work keeps newcomers behind equal-priority tasks
+//! 32. One large release lets the sync wake worker start several queued tasks
+//! 33. Jobs submitted with just a payload and mailbox key deliver to that key
+//! 34. Tasks larger than the pool can ever hold are rejected instead of queued
+//! 35. Changing max_units at runtime holds back, drops or starts queued tasks
+//! 36. A deadline equal to the current time counts as expired on every path
+//! 37. Racing submissions and snapshot restores both respect max_queued_units
+//! 38. Dependent tasks wait for their dependencies and are dropped when one fails
 
 use async_trait::async_trait;
+use prometheus_parking_lot::config::{DispatchMode, KindFloors, SchedulerConfig};
 use prometheus_parking_lot::core::{
-    PoolLimits, ResourcePool, ScheduledTask, Spawn, TaskExecutor, TaskMetadata, TaskStatus,
+    ExecError, FnExecutor, Mailbox, PoolLimits, PoolSnapshotState, ResourcePool, ScheduledTask,
+    SchedulerError, Spawn, TaskExecutor, TaskMetadata, TaskStatus, REASON_QUEUE_WAIT_EXCEEDED,
 };
 use prometheus_parking_lot::infra::mailbox::memory::InMemoryMailbox;
+use prometheus_parking_lot::infra::mailbox::yaque::YaqueMailbox;
 use prometheus_parking_lot::infra::queue::memory::InMemoryQueue;
+use prometheus_parking_lot::runtime::{
+    list_pools, stream_mailbox, submit_task, TaskSubmission, TokioSpawner,
+};
 use prometheus_parking_lot::util::clock::now_ms;
 use prometheus_parking_lot::util::serde::{MailboxKey, Priority, ResourceCost, ResourceKind};
+use prometheus_parking_lot::util::telemetry::SnapshotSource;
+use std::collections::HashMap;
 use std::future::Future;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::Mutex;
@@ -61,6 +102,88 @@ impl TaskExecutor<TestJob, String> for TestExecutor {
     }
 }
 
+// Executor that counts how often each task runs and the peak concurrency
+#[derive(Clone)]
+struct CountingExecutor {
+    runs: Arc<std::sync::Mutex<HashMap<u64, u32>>>,
+    running: Arc<AtomicU32>,
+    peak: Arc<AtomicU32>,
+}
+
+impl CountingExecutor {
+    fn new() -> Self {
+        Self {
+            runs: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            running: Arc::new(AtomicU32::new(0)),
+            peak: Arc::new(AtomicU32::new(0)),
+        }
+    }
+}
+
+#[async_trait]
+impl TaskExecutor<TestJob, String> for CountingExecutor {
+    async fn execute(&self, payload: TestJob, meta: TaskMetadata) -> String {
+        let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(running, Ordering::SeqCst);
+        *self.runs.lock().unwrap().entry(meta.id).or_insert(0) += 1;
+
+        tokio::time::sleep(Duration::from_millis(1)).await;
+
+        self.running.fetch_sub(1, Ordering::SeqCst);
+        payload.name
+    }
+}
+
+// Executor that holds each task until the test hands out a permit
+#[derive(Clone)]
+struct GatedExecutor {
+    gate: Arc<tokio::sync::Semaphore>,
+}
+
+#[async_trait]
+impl TaskExecutor<TestJob, String> for GatedExecutor {
+    async fn execute(&self, payload: TestJob, _meta: TaskMetadata) -> String {
+        self.gate.acquire().await.unwrap().forget();
+        payload.name
+    }
+}
+
+// Executor that fails every task with a zero value
+#[derive(Clone)]
+struct FallibleExecutor;
+
+#[async_trait]
+impl TaskExecutor<TestJob, String> for FallibleExecutor {
+    async fn execute(&self, payload: TestJob, _meta: TaskMetadata) -> String {
+        payload.name
+    }
+
+    async fn try_execute(&self, payload: TestJob, meta: TaskMetadata) -> Result<String, ExecError> {
+        if payload.value == 0 {
+            return Err(ExecError::new(format!("{} has no value", payload.name)));
+        }
+        Ok(self.execute(payload, meta).await)
+    }
+}
+
+// Mailbox that records every delivered status, shared with the test
+#[derive(Clone, Default)]
+struct RecordingMailbox {
+    delivered: Arc<std::sync::Mutex<Vec<(MailboxKey, TaskStatus)>>>,
+}
+
+impl Mailbox<String> for RecordingMailbox {
+    fn deliver(
+        &mut self,
+        key: &MailboxKey,
+        status: TaskStatus,
+        _payload: Option<String>,
+    ) -> Result<(), SchedulerError> {
+        self.delivered.lock().unwrap().push((key.clone(), status));
+        Ok(())
+    }
+}
+
 // Simple tokio spawner for tests
 #[derive(Clone)]
 struct TestSpawner;
@@ -81,6 +204,7 @@ async fn test_immediate_execution() {
         max_units: 10,
         max_queue_depth: 100,
         default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
     };
 
     let queue = InMemoryQueue::new(100);
@@ -100,6 +224,11 @@ async fn test_immediate_execution() {
         created_at_ms: now_ms(),
         deadline_ms: None,
         mailbox: None,
+        trace_context: None,
+        degraded: false,
+        depends_on: Vec::new(),
+        idempotency_key: None,
+        retrieve_timeout_ms: None,
     };
 
     let job = TestJob {
@@ -128,6 +257,7 @@ async fn test_capacity_enforcement_and_queueing() {
         max_units: 10,
         max_queue_depth: 100,
         default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
     };
 
     let queue = InMemoryQueue::new(100);
@@ -148,6 +278,11 @@ async fn test_capacity_enforcement_and_queueing() {
         created_at_ms: now_ms(),
         deadline_ms: None,
         mailbox: None,
+        trace_context: None,
+        degraded: false,
+        depends_on: Vec::new(),
+        idempotency_key: None,
+        retrieve_timeout_ms: None,
     };
 
     let job1 = TestJob {
@@ -169,6 +304,11 @@ async fn test_capacity_enforcement_and_queueing() {
         created_at_ms: now_ms(),
         deadline_ms: None,
         mailbox: None,
+        trace_context: None,
+        degraded: false,
+        depends_on: Vec::new(),
+        idempotency_key: None,
+        retrieve_timeout_ms: None,
     };
 
     let job2 = TestJob {
@@ -194,6 +334,7 @@ async fn test_wake_up_mechanism() {
         max_units: 10,
         max_queue_depth: 100,
         default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
     };
 
     let queue = InMemoryQueue::new(100);
@@ -214,6 +355,11 @@ async fn test_wake_up_mechanism() {
         created_at_ms: now_ms(),
         deadline_ms: None,
         mailbox: None,
+        trace_context: None,
+        degraded: false,
+        depends_on: Vec::new(),
+        idempotency_key: None,
+        retrieve_timeout_ms: None,
     };
 
     pool.submit(ScheduledTask { 
@@ -233,6 +379,11 @@ async fn test_wake_up_mechanism() {
             created_at_ms: now_ms(),
             deadline_ms: None,
             mailbox: None,
+            trace_context: None,
+            degraded: false,
+            depends_on: Vec::new(),
+            idempotency_key: None,
+            retrieve_timeout_ms: None,
         };
 
         let status = pool.submit(ScheduledTask { 
@@ -258,6 +409,7 @@ async fn test_mailbox_delivery() {
         max_units: 10,
         max_queue_depth: 100,
         default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
     };
 
     let queue = InMemoryQueue::new(100);
@@ -289,6 +441,11 @@ async fn test_mailbox_delivery() {
         created_at_ms: now_ms(),
         deadline_ms: None,
         mailbox: Some(mailbox_key.clone()),
+        trace_context: None,
+        degraded: false,
+        depends_on: Vec::new(),
+        idempotency_key: None,
+        retrieve_timeout_ms: None,
     };
 
     let job = TestJob {
@@ -316,6 +473,7 @@ async fn test_priority_ordering() {
         max_units: 10,
         max_queue_depth: 100,
         default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
     };
 
     let queue = InMemoryQueue::new(100);
@@ -334,6 +492,11 @@ async fn test_priority_ordering() {
             created_at_ms: now_ms(),
             deadline_ms: None,
             mailbox: None,
+            trace_context: None,
+            degraded: false,
+            depends_on: Vec::new(),
+            idempotency_key: None,
+            retrieve_timeout_ms: None,
         },
         payload: TestJob { name: "blocker".to_string(), value: 0 },
     }, now_ms()).await.unwrap();
@@ -355,6 +518,11 @@ async fn test_priority_ordering() {
                 created_at_ms: now_ms(),
                 deadline_ms: None,
                 mailbox: None,
+                trace_context: None,
+                degraded: false,
+                depends_on: Vec::new(),
+                idempotency_key: None,
+                retrieve_timeout_ms: None,
             },
             payload: TestJob { name: format!("task_{:?}", priority), value: id as u32 },
         }, now_ms()).await.unwrap();
@@ -370,6 +538,193 @@ async fn test_priority_ordering() {
     assert!(results[1].contains("Critical"));
 }
 
+#[tokio::test]
+async fn test_critical_not_overtaken_by_low_tasks() {
+    // A queued Critical task gets freed capacity before Low tasks that would fit now
+    let limits = PoolLimits {
+        max_units: 10,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+
+    let queue = InMemoryQueue::new(100);
+    let mailbox = InMemoryMailbox::new();
+    let executor = TestExecutor::new();
+    let spawner = TestSpawner;
+
+    let pool = ResourcePool::new(limits, queue, mailbox, executor.clone(), spawner);
+
+    let make_task = |id: u64, priority: Priority, units: u32| ScheduledTask {
+        meta: TaskMetadata {
+            id,
+            priority,
+            cost: ResourceCost { kind: ResourceKind::Cpu, units },
+            created_at_ms: now_ms(),
+            deadline_ms: None,
+            mailbox: None,
+            trace_context: None,
+            degraded: false,
+            depends_on: Vec::new(),
+            idempotency_key: None,
+            retrieve_timeout_ms: None,
+        },
+        payload: TestJob { name: format!("task_{:?}", priority), value: id as u32 },
+    };
+
+    // The blocker leaves 4 units free, too few for the Critical task
+    let status = pool.submit(make_task(1, Priority::Normal, 6), now_ms()).await.unwrap();
+    assert!(matches!(status, TaskStatus::Running));
+    let status = pool.submit(make_task(2, Priority::Critical, 8), now_ms()).await.unwrap();
+    assert!(matches!(status, TaskStatus::Queued));
+
+    // Low tasks fit in the free units but queue behind the Critical one
+    for id in 3..=6 {
+        let status = pool.submit(make_task(id, Priority::Low, 3), now_ms()).await.unwrap();
+        assert!(matches!(status, TaskStatus::Queued));
+    }
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    let results = executor.get_results().await;
+    assert_eq!(results.len(), 6);
+    assert!(results[0].contains("Normal"));
+    assert!(results[1].contains("Critical"));
+    assert!(results[2..].iter().all(|r| r.contains("Low")));
+}
+
+#[test]
+fn test_metadata_builder_matches_manual_construction() {
+    let key = MailboxKey {
+        tenant: "acme".into(),
+        user_id: None,
+        session_id: None,
+    };
+    let built = TaskMetadata::builder(7)
+        .priority(Priority::High)
+        .cost(ResourceKind::GpuVram, 4)
+        .mailbox(key.clone())
+        .deadline_in(Duration::from_secs(5))
+        .build();
+    let manual = TaskMetadata {
+        id: 7,
+        priority: Priority::High,
+        cost: ResourceCost { kind: ResourceKind::GpuVram, units: 4 },
+        created_at_ms: built.created_at_ms,
+        deadline_ms: Some(built.created_at_ms + 5_000),
+        mailbox: Some(key),
+        trace_context: None,
+        degraded: false,
+        depends_on: Vec::new(),
+        idempotency_key: None,
+        retrieve_timeout_ms: None,
+    };
+    assert_eq!(
+        serde_json::to_value(&built).unwrap(),
+        serde_json::to_value(&manual).unwrap()
+    );
+    assert!(built.created_at_ms.abs_diff(now_ms()) < 1_000);
+
+    // Defaults: Normal priority, one CPU unit, no deadline or mailbox
+    let defaults = TaskMetadata::builder(8).build();
+    assert_eq!(defaults.priority, Priority::Normal);
+    assert_eq!(defaults.cost.units, 1);
+    assert!(matches!(defaults.cost.kind, ResourceKind::Cpu));
+    assert!(defaults.deadline_ms.is_none());
+    assert!(defaults.mailbox.is_none());
+}
+
+#[tokio::test]
+async fn test_relative_deadline_submission() {
+    let limits = PoolLimits {
+        max_units: 10,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+
+    let queue = InMemoryQueue::new(100);
+    let mailbox = InMemoryMailbox::new();
+    let executor = TestExecutor::new();
+    let spawner = TestSpawner;
+
+    let pool = ResourcePool::new(limits, queue, mailbox, executor.clone(), spawner);
+
+    // With the pool's capacity held, it stays queued for inspection
+    let _held = pool.reserve(10).unwrap();
+    let submission = |deadline_ms: Option<u128>, deadline_in_ms: Option<u64>| TaskSubmission {
+        task_id: 1,
+        priority: Priority::Normal,
+        resource_cost: ResourceCost { kind: ResourceKind::Cpu, units: 10 },
+        deadline_ms,
+        deadline_in_ms,
+        mailbox_key: None,
+        // A client clock far behind the server's doesn't matter
+        created_at_ms: 0,
+        payload: TestJob { name: "relative".to_string(), value: 1 },
+    };
+
+    let now = now_ms();
+    let status = submit_task(&pool, submission(None, Some(5_000)), now).await.unwrap();
+    assert!(matches!(status, TaskStatus::Queued));
+    let deadline = pool.peek_next().unwrap().unwrap().deadline_ms.unwrap();
+    assert_eq!(deadline, now + 5_000);
+
+    // An absolute deadline wins over a relative one
+    let both = submission(Some(now + 60_000), Some(5_000));
+    assert_eq!(both.resolve_deadline(now), Some(now + 60_000));
+    assert_eq!(submission(None, None).resolve_deadline(now), None);
+}
+
+#[tokio::test]
+async fn test_fetch_results_after_completion() {
+    let limits = PoolLimits {
+        max_units: 10,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+
+    let dir = std::env::temp_dir().join(format!("pl-fetch-results-{}", now_ms()));
+    let queue = InMemoryQueue::new(100);
+    let mailbox = YaqueMailbox::new(&dir, "results").unwrap();
+    let executor = TestExecutor::new();
+    let spawner = TestSpawner;
+
+    let pool = ResourcePool::new(limits, queue, mailbox, executor.clone(), spawner);
+
+    let key = MailboxKey {
+        tenant: "acme".into(),
+        user_id: Some("alice".into()),
+        session_id: None,
+    };
+    let since = now_ms();
+    let meta = TaskMetadata::builder(42).mailbox(key.clone()).build();
+    let task = ScheduledTask {
+        meta,
+        payload: TestJob { name: "fetch".to_string(), value: 21 },
+    };
+    pool.submit(task, now_ms()).await.unwrap();
+
+    let mut messages = Vec::new();
+    for _ in 0..100 {
+        messages = pool.fetch_results(&key, Some(since), 10);
+        if !messages.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(messages.len(), 1);
+    assert!(matches!(messages[0].status, TaskStatus::Completed));
+    assert_eq!(messages[0].payload.as_deref(), Some("Task 42: fetch = 42"));
+
+    // Other mailboxes see nothing
+    let other = MailboxKey { tenant: "other".into(), user_id: None, session_id: None };
+    assert!(pool.fetch_results(&other, None, 10).is_empty());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
 #[tokio::test]
 async fn test_deadline_rejection() {
     // Test that expired tasks are rejected
@@ -377,6 +732,7 @@ async fn test_deadline_rejection() {
         max_units: 10,
         max_queue_depth: 100,
         default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
     };
 
     let queue = InMemoryQueue::new(100);
@@ -398,6 +754,11 @@ async fn test_deadline_rejection() {
         created_at_ms: now_ms(),
         deadline_ms: Some(past_time),
         mailbox: None,
+        trace_context: None,
+        degraded: false,
+        depends_on: Vec::new(),
+        idempotency_key: None,
+        retrieve_timeout_ms: None,
     };
 
     let result = pool.submit(ScheduledTask {
@@ -415,6 +776,7 @@ async fn test_concurrent_submissions() {
         max_units: 100,
         max_queue_depth: 1000,
         default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
     };
 
     let queue = InMemoryQueue::new(1000);
@@ -441,6 +803,11 @@ async fn test_concurrent_submissions() {
                 created_at_ms: now_ms(),
                 deadline_ms: None,
                 mailbox: None,
+                trace_context: None,
+                degraded: false,
+                depends_on: Vec::new(),
+                idempotency_key: None,
+                retrieve_timeout_ms: None,
             };
 
             let job = TestJob {
@@ -474,6 +841,7 @@ async fn test_shutdown() {
         max_units: 10,
         max_queue_depth: 100,
         default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
     };
 
     let queue = InMemoryQueue::new(100);
@@ -494,6 +862,11 @@ async fn test_shutdown() {
         created_at_ms: now_ms(),
         deadline_ms: None,
         mailbox: None,
+        trace_context: None,
+        degraded: false,
+        depends_on: Vec::new(),
+        idempotency_key: None,
+        retrieve_timeout_ms: None,
     };
 
     let job = TestJob {
@@ -513,3 +886,1644 @@ async fn test_shutdown() {
     let results = executor.get_results().await;
     assert_eq!(results.len(), 1);
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_wake_stress_no_double_execution() {
+    // Many equal-cost tasks finishing close together must each run exactly once
+    let limits = PoolLimits {
+        max_units: 4,
+        max_queue_depth: 1000,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+
+    let queue = InMemoryQueue::new(1000);
+    let mailbox = InMemoryMailbox::new();
+    let executor = CountingExecutor::new();
+    let spawner = TestSpawner;
+
+    let pool = Arc::new(ResourcePool::new(limits, queue, mailbox, executor.clone(), spawner));
+
+    let num_tasks = 300u64;
+    let mut handles = Vec::new();
+    for i in 0..num_tasks {
+        let pool = Arc::clone(&pool);
+        handles.push(tokio::spawn(async move {
+            let meta = TaskMetadata {
+                id: i,
+                priority: Priority::Normal,
+                cost: ResourceCost {
+                    kind: ResourceKind::Cpu,
+                    units: 1,
+                },
+                created_at_ms: now_ms(),
+                deadline_ms: None,
+                mailbox: None,
+                trace_context: None,
+                degraded: false,
+                depends_on: Vec::new(),
+                idempotency_key: None,
+                retrieve_timeout_ms: None,
+            };
+            let job = TestJob {
+                name: format!("stress_task_{}", i),
+                value: 1,
+            };
+            pool.submit(ScheduledTask { meta, payload: job }, now_ms()).await
+        }));
+    }
+    for handle in handles {
+        assert!(handle.await.unwrap().is_ok());
+    }
+
+    // Wait until every task has run
+    let started = std::time::Instant::now();
+    while executor.runs.lock().unwrap().len() < num_tasks as usize {
+        assert!(started.elapsed() < Duration::from_secs(10), "tasks stalled in the queue");
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let runs = executor.runs.lock().unwrap().clone();
+    assert_eq!(runs.len(), num_tasks as usize);
+    assert!(runs.values().all(|&count| count == 1), "a task executed more than once");
+    assert!(executor.peak.load(Ordering::SeqCst) <= 4);
+}
+
+#[tokio::test]
+async fn test_expired_task_skipped_on_wake() {
+    // A queued task whose deadline passes before capacity frees up must not run
+    let limits = PoolLimits {
+        max_units: 10,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+
+    let queue = InMemoryQueue::new(100);
+    let mailbox = RecordingMailbox::default();
+    let executor = TestExecutor::new();
+    let spawner = TestSpawner;
+
+    let pool = ResourcePool::new(limits, queue, mailbox.clone(), executor.clone(), spawner);
+
+    let make_meta = |id: u64, units: u32, deadline_ms: Option<u128>, mailbox: Option<MailboxKey>| {
+        TaskMetadata {
+            id,
+            priority: Priority::Normal,
+            cost: ResourceCost { kind: ResourceKind::Cpu, units },
+            created_at_ms: now_ms(),
+            deadline_ms,
+            mailbox,
+            trace_context: None,
+            degraded: false,
+            depends_on: Vec::new(),
+            idempotency_key: None,
+            retrieve_timeout_ms: None,
+        }
+    };
+    let expired_key = MailboxKey {
+        tenant: "expired-tenant".to_string(),
+        user_id: None,
+        session_id: None,
+    };
+
+    // Fill capacity so the next tasks have to queue
+    pool.submit(ScheduledTask {
+        meta: make_meta(1, 10, None, None),
+        payload: TestJob { name: "blocker".to_string(), value: 0 },
+    }, now_ms()).await.unwrap();
+
+    // Expires while the blocker is still running
+    let status = pool.submit(ScheduledTask {
+        meta: make_meta(2, 3, Some(now_ms() + 2), Some(expired_key.clone())),
+        payload: TestJob { name: "expires".to_string(), value: 2 },
+    }, now_ms()).await.unwrap();
+    assert!(matches!(status, TaskStatus::Queued));
+
+    // Queued behind the expired task; must still be woken
+    pool.submit(ScheduledTask {
+        meta: make_meta(3, 3, None, None),
+        payload: TestJob { name: "survivor".to_string(), value: 3 },
+    }, now_ms()).await.unwrap();
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let results = executor.get_results().await;
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|r| !r.contains("expires")));
+    assert!(results[1].contains("survivor"));
+
+    let delivered = mailbox.delivered.lock().unwrap().clone();
+    assert_eq!(delivered.len(), 1);
+    assert_eq!(delivered[0].0, expired_key);
+    assert!(matches!(delivered[0].1, TaskStatus::Expired));
+}
+
+#[tokio::test]
+async fn test_task_status_transitions() {
+    // Status lookup follows an immediate task and a queued-then-woken task
+    let limits = PoolLimits {
+        max_units: 10,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+
+    let queue = InMemoryQueue::new(100);
+    let mailbox = InMemoryMailbox::new();
+    let executor = TestExecutor::new();
+    let spawner = TestSpawner;
+
+    let pool = ResourcePool::new(limits, queue, mailbox, executor, spawner);
+
+    let make_task = |id: u64, units: u32| ScheduledTask {
+        meta: TaskMetadata {
+            id,
+            priority: Priority::Normal,
+            cost: ResourceCost { kind: ResourceKind::Cpu, units },
+            created_at_ms: now_ms(),
+            deadline_ms: None,
+            mailbox: None,
+            trace_context: None,
+            degraded: false,
+            depends_on: Vec::new(),
+            idempotency_key: None,
+            retrieve_timeout_ms: None,
+        },
+        payload: TestJob { name: format!("status_{}", id), value: 1 },
+    };
+
+    assert!(pool.status(1).is_none());
+
+    pool.submit(make_task(1, 10), now_ms()).await.unwrap();
+    pool.submit(make_task(2, 5), now_ms()).await.unwrap();
+    assert!(matches!(pool.status(1), Some(TaskStatus::Running)));
+    assert!(matches!(pool.status(2), Some(TaskStatus::Queued)));
+
+    // Task 2 is woken once task 1 releases its capacity
+    let started = std::time::Instant::now();
+    while !matches!(pool.status(2), Some(TaskStatus::Running)) {
+        assert!(started.elapsed() < Duration::from_secs(5), "task 2 was never woken");
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    assert!(matches!(pool.status(1), Some(TaskStatus::Completed)));
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let response = prometheus_parking_lot::runtime::task_status(&pool, 2).unwrap();
+    assert_eq!(response.task_id, 2);
+    assert!(matches!(response.status, TaskStatus::Completed));
+    assert!(response.reason.is_none());
+}
+
+#[tokio::test]
+async fn test_kind_floor_admits_gpu_under_cpu_flood() {
+    // A GPU floor keeps part of the budget free while CPU work saturates the pool
+    let limits = PoolLimits {
+        max_units: 10,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+
+    let queue = InMemoryQueue::new(100);
+    let mailbox = InMemoryMailbox::new();
+    let executor = TestExecutor::new();
+    let spawner = TestSpawner;
+
+    let floors = KindFloors::default().with_floor(ResourceKind::GpuVram, 0.4);
+    let pool = ResourcePool::new(limits, queue, mailbox, executor.clone(), spawner)
+        .with_kind_floors(&floors);
+
+    let make_task = |id: u64, kind: ResourceKind, units: u32| ScheduledTask {
+        meta: TaskMetadata {
+            id,
+            priority: Priority::Normal,
+            cost: ResourceCost { kind, units },
+            created_at_ms: now_ms(),
+            deadline_ms: None,
+            mailbox: None,
+            trace_context: None,
+            degraded: false,
+            depends_on: Vec::new(),
+            idempotency_key: None,
+            retrieve_timeout_ms: None,
+        },
+        payload: TestJob { name: format!("{:?}_{}", kind, id), value: 1 },
+    };
+
+    // CPU may only take the 6 units outside the GPU floor
+    let mut cpu_statuses = Vec::new();
+    for id in 0..10 {
+        let status = pool.submit(make_task(id, ResourceKind::Cpu, 1), now_ms()).await.unwrap();
+        cpu_statuses.push(status);
+    }
+    let running = cpu_statuses.iter().filter(|s| matches!(s, TaskStatus::Running)).count();
+    assert_eq!(running, 6);
+
+    // The GPU task is admitted into its floor despite the CPU backlog
+    let status = pool.submit(make_task(100, ResourceKind::GpuVram, 4), now_ms()).await.unwrap();
+    assert!(matches!(status, TaskStatus::Running));
+
+    // The budget is now exhausted for every kind
+    let status = pool.submit(make_task(101, ResourceKind::GpuVram, 1), now_ms()).await.unwrap();
+    assert!(matches!(status, TaskStatus::Queued));
+
+    // Everything drains once capacity is released
+    let started = std::time::Instant::now();
+    while executor.get_results().await.len() < 12 {
+        assert!(started.elapsed() < Duration::from_secs(5), "queued tasks were never woken");
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+#[test]
+fn test_submit_blocking_from_sync_caller() {
+    // A plain thread with no runtime of its own submits to a Tokio-backed pool
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap();
+    let limits = PoolLimits {
+        max_units: 10,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+
+    let queue = InMemoryQueue::new(100);
+    let mailbox = InMemoryMailbox::new();
+    let executor = TestExecutor::new();
+    let spawner = TokioSpawner::new(runtime.handle().clone());
+
+    let pool = ResourcePool::new(limits, queue, mailbox, executor.clone(), spawner);
+
+    let make_task = |id: u64, units: u32| ScheduledTask {
+        meta: TaskMetadata {
+            id,
+            priority: Priority::Normal,
+            cost: ResourceCost { kind: ResourceKind::Cpu, units },
+            created_at_ms: now_ms(),
+            deadline_ms: None,
+            mailbox: None,
+            trace_context: None,
+            degraded: false,
+            depends_on: Vec::new(),
+            idempotency_key: None,
+            retrieve_timeout_ms: None,
+        },
+        payload: TestJob { name: format!("sync_{}", id), value: 1 },
+    };
+
+    let first = pool.submit_blocking(make_task(1, 10), now_ms()).unwrap();
+    let second = pool.submit_blocking(make_task(2, 5), now_ms()).unwrap();
+    assert!(matches!(first, TaskStatus::Running));
+    assert!(matches!(second, TaskStatus::Queued));
+
+    // Both run to completion on the spawner's runtime
+    let started = std::time::Instant::now();
+    while !matches!(pool.status(2), Some(TaskStatus::Completed)) {
+        assert!(started.elapsed() < Duration::from_secs(5), "task 2 never completed");
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    assert!(matches!(pool.status(1), Some(TaskStatus::Completed)));
+    assert_eq!(runtime.block_on(executor.get_results()).len(), 2);
+}
+
+#[tokio::test]
+async fn test_drain_queue_and_enqueue_all() {
+    // With the pool's capacity held, tasks stay queued until drained
+    let limits = PoolLimits {
+        max_units: 10,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+
+    let queue = InMemoryQueue::new(100);
+    let mailbox = InMemoryMailbox::new();
+    let executor = TestExecutor::new();
+    let spawner = TestSpawner;
+
+    let pool = ResourcePool::new(limits, queue, mailbox, executor.clone(), spawner);
+
+    let make_task = |id: u64, priority: Priority, units: u32| ScheduledTask {
+        meta: TaskMetadata {
+            id,
+            priority,
+            cost: ResourceCost { kind: ResourceKind::Cpu, units },
+            created_at_ms: now_ms(),
+            deadline_ms: None,
+            mailbox: None,
+            trace_context: None,
+            degraded: false,
+            depends_on: Vec::new(),
+            idempotency_key: None,
+            retrieve_timeout_ms: None,
+        },
+        payload: TestJob { name: format!("drain_{}", id), value: 1 },
+    };
+    let _held = pool.reserve(10).unwrap();
+
+    let submitted = [
+        (1, Priority::Low),
+        (2, Priority::Critical),
+        (3, Priority::Normal),
+        (4, Priority::High),
+    ];
+    for (id, priority) in submitted {
+        let status = pool.submit(make_task(id, priority, 10), now_ms()).await.unwrap();
+        assert!(matches!(status, TaskStatus::Queued));
+    }
+
+    let drained = pool.drain_queue().unwrap();
+    let ids: Vec<u64> = drained.iter().map(|task| task.meta.id).collect();
+    assert_eq!(ids, vec![2, 4, 3, 1]);
+    assert!(pool.status(2).is_none());
+    assert!(pool.drain_queue().unwrap().is_empty());
+
+    // Re-admitted tasks are queued again, in the same order
+    let outcomes = pool.enqueue_all(drained, now_ms());
+    assert_eq!(outcomes.len(), 4);
+    assert!(outcomes.iter().all(|outcome| matches!(outcome, Ok(TaskStatus::Queued))));
+    assert!(matches!(pool.status(1), Some(TaskStatus::Queued)));
+    let ids: Vec<u64> = pool.drain_queue().unwrap().iter().map(|task| task.meta.id).collect();
+    assert_eq!(ids, vec![2, 4, 3, 1]);
+    assert!(executor.get_results().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_zero_cost_task_rejected() {
+    // A zero-cost task never counts against max_units, so it is refused
+    let limits = PoolLimits {
+        max_units: 1,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+    let executor = CountingExecutor::new();
+    let pool = ResourcePool::new(
+        limits,
+        InMemoryQueue::new(100),
+        InMemoryMailbox::new(),
+        executor.clone(),
+        TestSpawner,
+    );
+
+    for id in 1..=5 {
+        let task = ScheduledTask {
+            meta: TaskMetadata {
+                id,
+                priority: Priority::Normal,
+                cost: ResourceCost { kind: ResourceKind::Cpu, units: 0 },
+                created_at_ms: now_ms(),
+                deadline_ms: None,
+                mailbox: None,
+                trace_context: None,
+                degraded: false,
+                depends_on: Vec::new(),
+                idempotency_key: None,
+                retrieve_timeout_ms: None,
+            },
+            payload: TestJob { name: format!("free_{}", id), value: 1 },
+        };
+        let result = pool.submit(task, now_ms()).await;
+        assert!(matches!(result, Err(SchedulerError::InvalidTask(_))));
+        assert!(pool.status(id).is_none());
+    }
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(executor.runs.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_peek_next_reports_highest_priority() {
+    // With the pool's capacity held, tasks stay queued behind each other
+    let limits = PoolLimits {
+        max_units: 30,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+
+    let queue = InMemoryQueue::new(100);
+    let mailbox = InMemoryMailbox::new();
+    let executor = TestExecutor::new();
+    let spawner = TestSpawner;
+
+    let pool = ResourcePool::new(limits, queue, mailbox, executor.clone(), spawner);
+    assert!(pool.peek_next().unwrap().is_none());
+    let _held = pool.reserve(30).unwrap();
+
+    let make_task = |id: u64, priority: Priority| ScheduledTask {
+        meta: TaskMetadata {
+            id,
+            priority,
+            cost: ResourceCost { kind: ResourceKind::GpuVram, units: 20 + id as u32 },
+            created_at_ms: now_ms(),
+            deadline_ms: None,
+            mailbox: None,
+            trace_context: None,
+            degraded: false,
+            depends_on: Vec::new(),
+            idempotency_key: None,
+            retrieve_timeout_ms: None,
+        },
+        payload: TestJob { name: format!("peek_{}", id), value: 1 },
+    };
+
+    for (id, priority) in [(1, Priority::Low), (2, Priority::High), (3, Priority::Normal)] {
+        let status = pool.submit(make_task(id, priority), now_ms()).await.unwrap();
+        assert!(matches!(status, TaskStatus::Queued));
+    }
+
+    let next = pool.peek_next().unwrap().expect("a task is queued");
+    assert_eq!(next.id, 2);
+    assert_eq!(next.cost.units, 22);
+
+    // Peeking leaves the task queued
+    let ids: Vec<u64> = pool.drain_queue().unwrap().iter().map(|task| task.meta.id).collect();
+    assert_eq!(ids, vec![2, 3, 1]);
+    assert!(executor.get_results().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_snapshot_and_restore_queue() {
+    // With each pool's capacity held, tasks stay queued
+    let limits = PoolLimits {
+        max_units: 10,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+    let make_pool = || {
+        ResourcePool::new(
+            limits.clone(),
+            InMemoryQueue::new(100),
+            InMemoryMailbox::new(),
+            TestExecutor::new(),
+            TestSpawner,
+        )
+    };
+
+    let make_task = |id: u64, priority: Priority| ScheduledTask {
+        meta: TaskMetadata {
+            id,
+            priority,
+            cost: ResourceCost { kind: ResourceKind::Cpu, units: 10 },
+            created_at_ms: 1_000 + u128::from(id),
+            deadline_ms: None,
+            mailbox: None,
+            trace_context: None,
+            degraded: false,
+            depends_on: Vec::new(),
+            idempotency_key: None,
+            retrieve_timeout_ms: None,
+        },
+        payload: TestJob { name: format!("snapshot_{}", id), value: 1 },
+    };
+
+    let pool = make_pool();
+    let held = pool.reserve(10).unwrap();
+    let submitted = [
+        (1, Priority::Normal),
+        (2, Priority::High),
+        (3, Priority::Normal),
+        (4, Priority::Low),
+        (5, Priority::High),
+    ];
+    for (id, priority) in submitted {
+        let status = pool.submit(make_task(id, priority), now_ms()).await.unwrap();
+        assert!(matches!(status, TaskStatus::Queued));
+    }
+
+    let snapshot = pool.snapshot().unwrap();
+    assert_eq!(snapshot.queued.len(), 5);
+    assert_eq!(snapshot.active_units, 10);
+    // Taking a snapshot leaves the queue in place
+    assert!(matches!(pool.status(3), Some(TaskStatus::Queued)));
+    let original: Vec<u64> = pool.drain_queue().unwrap().iter().map(|task| task.meta.id).collect();
+    assert_eq!(original, vec![2, 5, 1, 3, 4]);
+
+    // Persist across the "restart"
+    let bytes = serde_json::to_vec(&snapshot).unwrap();
+    let snapshot: PoolSnapshotState<TestJob> = serde_json::from_slice(&bytes).unwrap();
+
+    drop(held);
+    let restarted = make_pool();
+    let _held = restarted.reserve(10).unwrap();
+    assert_eq!(restarted.restore(snapshot, now_ms()), 5);
+    assert!(matches!(restarted.status(1), Some(TaskStatus::Queued)));
+    let restored: Vec<u64> =
+        restarted.drain_queue().unwrap().iter().map(|task| task.meta.id).collect();
+    assert_eq!(restored, original);
+}
+
+#[tokio::test]
+async fn test_queue_wait_exceeded_dropped_on_wake() {
+    // A queued task without a deadline still gives up after the pool's max wait
+    let limits = PoolLimits {
+        max_units: 10,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        max_queue_wait: Some(Duration::from_millis(5)),
+        ..PoolLimits::default()
+    };
+
+    let queue = InMemoryQueue::new(100);
+    let mailbox = RecordingMailbox::default();
+    let executor = TestExecutor::new();
+    let spawner = TestSpawner;
+
+    let pool = ResourcePool::new(limits, queue, mailbox.clone(), executor.clone(), spawner);
+
+    let make_task = |id: u64, name: &str, mailbox: Option<MailboxKey>| ScheduledTask {
+        meta: TaskMetadata {
+            id,
+            priority: Priority::Normal,
+            cost: ResourceCost { kind: ResourceKind::Cpu, units: 10 },
+            created_at_ms: now_ms(),
+            deadline_ms: None,
+            mailbox,
+            trace_context: None,
+            degraded: false,
+            depends_on: Vec::new(),
+            idempotency_key: None,
+            retrieve_timeout_ms: None,
+        },
+        payload: TestJob { name: name.to_string(), value: 1 },
+    };
+    let waiting_key = MailboxKey {
+        tenant: "waiting-tenant".to_string(),
+        user_id: None,
+        session_id: None,
+    };
+
+    // The blocker holds all capacity for longer than the allowed wait
+    let status = pool.submit(make_task(1, "blocker", None), now_ms()).await.unwrap();
+    assert!(matches!(status, TaskStatus::Running));
+    let status = pool
+        .submit(make_task(2, "waiter", Some(waiting_key.clone())), now_ms())
+        .await
+        .unwrap();
+    assert!(matches!(status, TaskStatus::Queued));
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let results = executor.get_results().await;
+    assert_eq!(results.len(), 1);
+    assert!(results[0].contains("blocker"));
+    assert!(matches!(
+        pool.status(2),
+        Some(TaskStatus::Dropped(reason)) if reason == REASON_QUEUE_WAIT_EXCEEDED
+    ));
+    let delivered = mailbox.delivered.lock().unwrap().clone();
+    assert!(delivered.iter().any(|(key, status)| {
+        *key == waiting_key && matches!(status, TaskStatus::Dropped(_))
+    }));
+
+    // Capacity is free again, so a new task starts right away
+    let status = pool.submit(make_task(3, "next", None), now_ms()).await.unwrap();
+    assert!(matches!(status, TaskStatus::Running));
+}
+
+#[tokio::test]
+async fn test_queued_units_limit_rejects_overflow() {
+    // With the pool's capacity held, tasks stay queued, and the queue holds
+    // at most 50 units however many tasks that is
+    let limits = PoolLimits {
+        max_units: 20,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        max_queued_units: Some(50),
+        ..PoolLimits::default()
+    };
+    let executor = TestExecutor::new();
+    let pool = ResourcePool::new(
+        limits,
+        InMemoryQueue::new(100),
+        InMemoryMailbox::new(),
+        executor.clone(),
+        TestSpawner,
+    );
+
+    let make_task = |id: u64, units: u32| ScheduledTask {
+        meta: TaskMetadata {
+            id,
+            priority: Priority::Normal,
+            cost: ResourceCost { kind: ResourceKind::GpuVram, units },
+            created_at_ms: now_ms(),
+            deadline_ms: None,
+            mailbox: None,
+            trace_context: None,
+            degraded: false,
+            depends_on: Vec::new(),
+            idempotency_key: None,
+            retrieve_timeout_ms: None,
+        },
+        payload: TestJob { name: format!("units_{}", id), value: 1 },
+    };
+    let _held = pool.reserve(20).unwrap();
+
+    for id in 1..=2 {
+        let status = pool.submit(make_task(id, 20), now_ms()).await.unwrap();
+        assert!(matches!(status, TaskStatus::Queued));
+    }
+
+    // Two tasks are far below max_queue_depth, but 40 + 20 units is too many
+    let result = pool.submit(make_task(3, 20), now_ms()).await;
+    assert!(matches!(
+        result,
+        Err(SchedulerError::QueueFull(reason)) if reason.contains("max_queued_units")
+    ));
+    assert!(pool.status(3).is_none());
+
+    // A task filling the cap exactly is still accepted
+    assert!(pool.submit(make_task(4, 10), now_ms()).await.is_ok());
+
+    // It waits behind the queued tasks rather than overtaking them
+    let mut ids: Vec<u64> = pool.drain_queue().unwrap().iter().map(|task| task.meta.id).collect();
+    ids.sort_unstable();
+    assert_eq!(ids, vec![1, 2, 4]);
+}
+
+#[tokio::test]
+async fn test_queued_units_limit_holds_under_concurrency() {
+    let limits = PoolLimits {
+        max_units: 20,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        max_queued_units: Some(10),
+        ..PoolLimits::default()
+    };
+    let make_pool = || {
+        ResourcePool::new(
+            limits.clone(),
+            InMemoryQueue::new(100),
+            InMemoryMailbox::new(),
+            TestExecutor::new(),
+            TestSpawner,
+        )
+    };
+    let make_task = |id: u64| ScheduledTask {
+        meta: TaskMetadata::builder(id).cost(ResourceKind::GpuVram, 3).build(),
+        payload: TestJob { name: format!("units_{id}"), value: 1 },
+    };
+
+    // Racing submissions all queue behind held capacity, yet only three
+    // 3-unit tasks fit under the 10-unit cap
+    let pool = make_pool();
+    let _held = pool.reserve(20).unwrap();
+    let accepted = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..32)
+            .map(|id| {
+                let pool = &pool;
+                scope.spawn(move || pool.submit_blocking(make_task(id), now_ms()).is_ok())
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .filter(|accepted| *accepted)
+            .count()
+    });
+    assert_eq!(accepted, 3);
+    let queued = pool.drain_queue().unwrap();
+    assert_eq!(queued.iter().map(|task| task.meta.cost.units).sum::<u32>(), 9);
+
+    // Restoring a snapshot into the same cap leaves the overflow out
+    let pool = make_pool();
+    let _held = pool.reserve(20).unwrap();
+    let snapshot = PoolSnapshotState {
+        queued: (1..=5).map(make_task).collect(),
+        active_units: 0,
+        taken_at_ms: now_ms(),
+    };
+    assert_eq!(pool.restore(snapshot, now_ms()), 3);
+    assert!(matches!(pool.status(3), Some(TaskStatus::Queued)));
+    assert!(pool.status(4).is_none());
+}
+
+#[tokio::test]
+async fn test_stream_mailbox_sse_frames() {
+    use futures::StreamExt;
+
+    let limits = PoolLimits {
+        max_units: 10,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+    let pool = Arc::new(ResourcePool::new(
+        limits,
+        InMemoryQueue::new(100),
+        InMemoryMailbox::new(),
+        TestExecutor::new(),
+        TestSpawner,
+    ));
+
+    let key = MailboxKey {
+        tenant: "acme".into(),
+        user_id: None,
+        session_id: Some("sse".into()),
+    };
+    let frames = stream_mailbox(Arc::clone(&pool), key.clone(), Duration::from_millis(5));
+    let mut frames = Box::pin(frames);
+
+    for id in 1..=3 {
+        let task = ScheduledTask {
+            meta: TaskMetadata::builder(id).mailbox(key.clone()).build(),
+            payload: TestJob { name: format!("sse_{}", id), value: 1 },
+        };
+        pool.submit(task, now_ms()).await.unwrap();
+    }
+
+    let mut received = Vec::new();
+    for _ in 0..3 {
+        let frame = tokio::time::timeout(Duration::from_secs(5), frames.next())
+            .await
+            .expect("a frame arrives for each delivered result")
+            .expect("the stream stays open")
+            .unwrap();
+        received.push(String::from_utf8(frame.to_vec()).unwrap());
+    }
+
+    for frame in &received {
+        assert!(frame.starts_with("data: {"));
+        assert!(frame.ends_with("\n\n"));
+        assert_eq!(frame.matches('\n').count(), 2);
+        assert!(frame.contains("\"Completed\""));
+    }
+    for id in 1..=3 {
+        let name = format!("sse_{}", id);
+        assert_eq!(received.iter().filter(|frame| frame.contains(&name)).count(), 1);
+    }
+
+    // Nothing else was delivered, so the stream waits for more
+    let more = tokio::time::timeout(Duration::from_millis(50), frames.next()).await;
+    assert!(more.is_err());
+}
+
+#[tokio::test]
+async fn test_wake_hysteresis_starts_larger_tasks() {
+    // Cheap tasks fill the pool; the queued tasks need several of their units
+    for hysteresis in [true, false] {
+        let limits = PoolLimits {
+            max_units: 4,
+            max_queue_depth: 100,
+            default_timeout: Duration::from_secs(60),
+            ..PoolLimits::default()
+        };
+        let executor = TestExecutor::new();
+        let pool = ResourcePool::new(
+            limits,
+            InMemoryQueue::new(100),
+            InMemoryMailbox::new(),
+            executor.clone(),
+            TestSpawner,
+        )
+        .with_wake_hysteresis(hysteresis);
+
+        let make_task = |id: u64, units: u32| ScheduledTask {
+            meta: TaskMetadata::builder(id)
+                .cost(ResourceKind::Cpu, units)
+                .build(),
+            payload: TestJob { name: format!("hysteresis_{}", id), value: 1 },
+        };
+
+        for id in 1..=4 {
+            let status = pool.submit(make_task(id, 1), now_ms()).await.unwrap();
+            assert!(matches!(status, TaskStatus::Running));
+        }
+        for (id, units) in [(5, 3), (6, 4), (7, 1)] {
+            let status = pool.submit(make_task(id, units), now_ms()).await.unwrap();
+            assert!(matches!(status, TaskStatus::Queued));
+        }
+
+        // Single freed units don't start the 3-unit head, but the last one does
+        let started = std::time::Instant::now();
+        while executor.get_results().await.len() < 7 {
+            assert!(
+                started.elapsed() < Duration::from_secs(2),
+                "queued tasks stalled (hysteresis: {hysteresis})"
+            );
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        for id in 5..=7 {
+            assert!(matches!(pool.status(id), Some(TaskStatus::Completed)));
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_list_pools_reports_live_usage() {
+    let config = SchedulerConfig::from_json_str(
+        r#"{
+            "pools": {
+                "gpu": {
+                    "max_units": 10,
+                    "max_queue_depth": 100,
+                    "default_timeout_secs": 60,
+                    "queue": "in_memory",
+                    "mailbox": "in_memory",
+                    "runtime": "native"
+                },
+                "cpu": {
+                    "max_units": 4,
+                    "max_queue_depth": 20,
+                    "default_timeout_secs": 60,
+                    "queue": "in_memory",
+                    "mailbox": "in_memory",
+                    "runtime": "native"
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+
+    let limits = PoolLimits {
+        max_units: 10,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+    let gate = Arc::new(tokio::sync::Semaphore::new(0));
+    let pool = ResourcePool::new(
+        limits,
+        InMemoryQueue::new(100),
+        InMemoryMailbox::new(),
+        GatedExecutor { gate: Arc::clone(&gate) },
+        TestSpawner,
+    );
+
+    for id in 1..=3 {
+        let task = ScheduledTask {
+            meta: TaskMetadata::builder(id).cost(ResourceKind::GpuVram, 5).build(),
+            payload: TestJob { name: format!("listed_{}", id), value: 1 },
+        };
+        pool.submit(task, now_ms()).await.unwrap();
+    }
+
+    let live = HashMap::from([("gpu".to_string(), &pool as &dyn SnapshotSource)]);
+    let mut listed = list_pools(&config, &live);
+    listed.sort_by(|a, b| a.name.cmp(&b.name));
+
+    // The pool without a live handle reports only its config
+    assert_eq!(listed[0].name, "cpu");
+    assert_eq!(listed[0].max_units, 4);
+    assert_eq!((listed[0].used_units, listed[0].queued_tasks, listed[0].active_tasks), (0, 0, 0));
+
+    assert_eq!(listed[1].name, "gpu");
+    assert_eq!(listed[1].max_units, 10);
+    assert_eq!(listed[1].used_units, 10);
+    assert_eq!(listed[1].active_tasks, 2);
+    assert_eq!(listed[1].queued_tasks, 1);
+
+    gate.add_permits(3);
+}
+
+#[tokio::test]
+async fn test_high_priority_reserve_admits_critical_tasks() {
+    let limits = PoolLimits {
+        max_units: 10,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        high_priority_reserve: Some(0.3),
+        ..PoolLimits::default()
+    };
+    assert_eq!(limits.unit_limit(Priority::Normal), 7);
+    assert_eq!(limits.unit_limit(Priority::Critical), 10);
+
+    let gate = Arc::new(tokio::sync::Semaphore::new(0));
+    let pool = ResourcePool::new(
+        limits,
+        InMemoryQueue::new(100),
+        InMemoryMailbox::new(),
+        GatedExecutor { gate: Arc::clone(&gate) },
+        TestSpawner,
+    );
+    let make_task = |id: u64, priority: Priority, units: u32| ScheduledTask {
+        meta: TaskMetadata::builder(id)
+            .priority(priority)
+            .cost(ResourceKind::GpuVram, units)
+            .build(),
+        payload: TestJob { name: format!("reserve_{}", id), value: 1 },
+    };
+
+    // Normal tasks fill the unreserved 7 units, then have to wait
+    for id in 1..=7 {
+        let status = pool.submit(make_task(id, Priority::Normal, 1), now_ms()).await.unwrap();
+        assert!(matches!(status, TaskStatus::Running));
+    }
+    let status = pool.submit(make_task(8, Priority::Normal, 1), now_ms()).await.unwrap();
+    assert!(matches!(status, TaskStatus::Queued));
+
+    // The reserve still has room for urgent work
+    let status = pool.submit(make_task(9, Priority::Critical, 2), now_ms()).await.unwrap();
+    assert!(matches!(status, TaskStatus::Running));
+    let status = pool.submit(make_task(10, Priority::High, 1), now_ms()).await.unwrap();
+    assert!(matches!(status, TaskStatus::Running));
+
+    gate.add_permits(10);
+    let started = std::time::Instant::now();
+    while !matches!(pool.status(8), Some(TaskStatus::Completed)) {
+        assert!(started.elapsed() < Duration::from_secs(2), "queued task never started");
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+}
+
+#[tokio::test]
+async fn test_executor_error_marks_task_failed() {
+    let limits = PoolLimits {
+        max_units: 10,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+    let mailbox = RecordingMailbox::default();
+    let pool = ResourcePool::new(
+        limits,
+        InMemoryQueue::new(100),
+        mailbox.clone(),
+        FallibleExecutor,
+        TestSpawner,
+    );
+    let key = |tenant: &str| MailboxKey {
+        tenant: tenant.to_string(),
+        user_id: None,
+        session_id: None,
+    };
+    let make_task = |id: u64, name: &str, value: u32| ScheduledTask {
+        meta: TaskMetadata::builder(id)
+            .cost(ResourceKind::Cpu, 1)
+            .mailbox(key(name))
+            .build(),
+        payload: TestJob { name: name.to_string(), value },
+    };
+
+    pool.submit(make_task(1, "broken", 0), now_ms()).await.unwrap();
+    pool.submit(make_task(2, "working", 1), now_ms()).await.unwrap();
+
+    let started = std::time::Instant::now();
+    while mailbox.delivered.lock().unwrap().len() < 2 {
+        assert!(started.elapsed() < Duration::from_secs(2), "tasks never finished");
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    let delivered = mailbox.delivered.lock().unwrap().clone();
+    let status_of = |tenant: &str| {
+        delivered
+            .iter()
+            .find(|(k, _)| k.tenant == tenant)
+            .map(|(_, status)| status.clone())
+            .unwrap()
+    };
+    assert!(matches!(status_of("broken"), TaskStatus::Failed(reason) if reason == "broken has no value"));
+    assert!(matches!(status_of("working"), TaskStatus::Completed));
+    assert!(matches!(pool.status(1), Some(TaskStatus::Failed(_))));
+
+    let metrics = pool.snapshot_metrics("fallible");
+    assert_eq!((metrics.completed_tasks, metrics.failed_tasks), (1, 1));
+}
+
+#[tokio::test]
+async fn test_queue_always_dispatch_orders_by_priority() {
+    let limits = PoolLimits {
+        max_units: 1,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        dispatch_mode: DispatchMode::QueueAlways,
+        ..PoolLimits::default()
+    };
+    let executor = TestExecutor::new();
+    let pool = ResourcePool::new(
+        limits,
+        InMemoryQueue::new(100),
+        InMemoryMailbox::new(),
+        executor.clone(),
+        TestSpawner,
+    );
+    let make_task = |id: u64, priority: Priority| ScheduledTask {
+        meta: TaskMetadata::builder(id)
+            .priority(priority)
+            .cost(ResourceKind::Cpu, 1)
+            .build(),
+        payload: TestJob { name: format!("{:?}", priority), value: 1 },
+    };
+
+    // Even an empty pool queues the task; the wake kick starts it
+    let status = pool.submit(make_task(1, Priority::Low), now_ms()).await.unwrap();
+    assert!(matches!(status, TaskStatus::Queued));
+    let started = std::time::Instant::now();
+    while !matches!(pool.status(1), Some(TaskStatus::Completed)) {
+        assert!(started.elapsed() < Duration::from_secs(2), "queued task never started");
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    // A burst submitted before the wake pass runs starts strictly by priority
+    let burst = [
+        (2, Priority::Low),
+        (3, Priority::Normal),
+        (4, Priority::Critical),
+        (5, Priority::High),
+    ];
+    for (id, priority) in burst {
+        let status = pool.submit(make_task(id, priority), now_ms()).await.unwrap();
+        assert!(matches!(status, TaskStatus::Queued));
+    }
+    let started = std::time::Instant::now();
+    while executor.get_results().await.len() < 5 {
+        assert!(started.elapsed() < Duration::from_secs(2), "burst never finished");
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    let order: Vec<String> = executor
+        .get_results()
+        .await
+        .iter()
+        .map(|r| r.split(": ").nth(1).unwrap().split(" =").next().unwrap().to_string())
+        .collect();
+    assert_eq!(order, vec!["Low", "Critical", "High", "Normal", "Low"]);
+}
+
+#[tokio::test]
+async fn test_sync_wake_worker_runs_queued_tasks() {
+    let limits = PoolLimits {
+        max_units: 1,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+    let executor = TestExecutor::new();
+    // The wake thread has no runtime of its own, so spawn through a handle
+    let pool = ResourcePool::new(
+        limits,
+        InMemoryQueue::new(100),
+        InMemoryMailbox::new(),
+        executor.clone(),
+        TokioSpawner::new(tokio::runtime::Handle::current()),
+    );
+    let worker = pool.spawn_sync_wake_worker().unwrap();
+    let make_task = |id: u64, priority: Priority| ScheduledTask {
+        meta: TaskMetadata::builder(id)
+            .priority(priority)
+            .cost(ResourceKind::Cpu, 1)
+            .build(),
+        payload: TestJob { name: format!("{:?}", priority), value: 1 },
+    };
+
+    // The first task takes the only unit; the rest wait for the worker
+    let status = pool.submit(make_task(1, Priority::Normal), now_ms()).await.unwrap();
+    assert!(matches!(status, TaskStatus::Running));
+    for (id, priority) in [
+        (2, Priority::Low),
+        (3, Priority::High),
+        (4, Priority::Normal),
+        (5, Priority::Critical),
+    ] {
+        let status = pool.submit(make_task(id, priority), now_ms()).await.unwrap();
+        assert!(matches!(status, TaskStatus::Queued));
+    }
+
+    let started = std::time::Instant::now();
+    while executor.get_results().await.len() < 5 {
+        assert!(started.elapsed() < Duration::from_secs(2), "queued tasks never ran");
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    let order: Vec<String> = executor
+        .get_results()
+        .await
+        .iter()
+        .map(|r| r.split(": ").nth(1).unwrap().split(" =").next().unwrap().to_string())
+        .collect();
+    assert_eq!(order, vec!["Normal", "Critical", "High", "Normal", "Low"]);
+    assert!(matches!(pool.status(2), Some(TaskStatus::Completed)));
+
+    pool.shutdown();
+    worker.join().unwrap();
+}
+
+#[tokio::test]
+async fn test_capacity_guard_blocks_then_wakes() {
+    let limits = PoolLimits {
+        max_units: 4,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+    let pool = ResourcePool::new(
+        limits,
+        InMemoryQueue::new(100),
+        InMemoryMailbox::new(),
+        TestExecutor::new(),
+        TestSpawner,
+    );
+
+    assert!(pool.reserve(0).is_none());
+    assert!(pool.reserve(u32::MAX).is_none(), "more than max_units can never fit");
+
+    let guard = pool.reserve(4).expect("an idle pool has room for its full capacity");
+    assert_eq!(guard.units(), 4);
+    assert!(pool.reserve(1).is_none());
+
+    let task = ScheduledTask {
+        meta: TaskMetadata::builder(1).cost(ResourceKind::Cpu, 2).build(),
+        payload: TestJob { name: "after_guard".to_string(), value: 1 },
+    };
+    let status = pool.submit(task, now_ms()).await.unwrap();
+    assert!(matches!(status, TaskStatus::Queued));
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(matches!(pool.status(1), Some(TaskStatus::Queued)));
+
+    // Releasing the units wakes the queued task like a completion would
+    drop(guard);
+    let started = std::time::Instant::now();
+    while !matches!(pool.status(1), Some(TaskStatus::Completed)) {
+        assert!(started.elapsed() < Duration::from_secs(2), "queued task never woke");
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    assert_eq!(pool.snapshot_metrics("guarded").used_units, 0);
+}
+
+#[tokio::test]
+async fn test_non_serializable_results_in_memory_mailbox() {
+    let limits = PoolLimits {
+        max_units: 4,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+    // A channel receiver can't be serialized; only a persistent mailbox would need that
+    let executor = FnExecutor::new(|job: TestJob, _meta| async move {
+        let (tx, rx) = flume::unbounded();
+        for i in 0..job.value {
+            tx.send(format!("{}-{}", job.name, i)).unwrap();
+        }
+        rx
+    });
+    let pool = ResourcePool::new(
+        limits,
+        InMemoryQueue::new(100),
+        InMemoryMailbox::<flume::Receiver<String>>::new(),
+        executor,
+        TestSpawner,
+    );
+
+    let key = MailboxKey { tenant: "acme".into(), user_id: None, session_id: None };
+    let task = ScheduledTask {
+        meta: TaskMetadata::builder(1).mailbox(key.clone()).build(),
+        payload: TestJob { name: "token".to_string(), value: 3 },
+    };
+    pool.submit(task, now_ms()).await.unwrap();
+
+    let started = std::time::Instant::now();
+    let messages = loop {
+        let messages = pool.fetch_results(&key, None, 10);
+        if !messages.is_empty() {
+            break messages;
+        }
+        assert!(started.elapsed() < Duration::from_secs(2), "result never delivered");
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    };
+    assert!(matches!(messages[0].status, TaskStatus::Completed));
+    let stream = messages[0].payload.as_ref().expect("completed tasks carry their result");
+    let tokens: Vec<String> = stream.drain().collect();
+    assert_eq!(tokens, vec!["token-0", "token-1", "token-2"]);
+}
+
+#[tokio::test]
+async fn test_max_concurrent_tasks_caps_running_tasks() {
+    let limits = PoolLimits {
+        max_units: 100,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        max_concurrent_tasks: Some(2),
+        ..PoolLimits::default()
+    };
+    let executor = CountingExecutor::new();
+    let pool = ResourcePool::new(
+        limits,
+        InMemoryQueue::new(100),
+        InMemoryMailbox::new(),
+        executor.clone(),
+        TestSpawner,
+    );
+
+    // Units alone would let all ten run at once
+    for id in 1..=10 {
+        let task = ScheduledTask {
+            meta: TaskMetadata::builder(id).cost(ResourceKind::Cpu, 1).build(),
+            payload: TestJob { name: format!("task_{id}"), value: 1 },
+        };
+        pool.submit(task, now_ms()).await.unwrap();
+    }
+
+    let started = std::time::Instant::now();
+    while (1..=10).any(|id| !matches!(pool.status(id), Some(TaskStatus::Completed))) {
+        assert!(started.elapsed() < Duration::from_secs(5), "queued tasks never finished");
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    assert_eq!(executor.runs.lock().unwrap().len(), 10);
+    assert_eq!(executor.peak.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_prune_expired_collect_returns_expired_tasks() {
+    let limits = PoolLimits {
+        max_units: 4,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+    let pool = ResourcePool::new(
+        limits,
+        InMemoryQueue::new(100),
+        InMemoryMailbox::new(),
+        TestExecutor::new(),
+        TestSpawner,
+    );
+    // Hold the full capacity so every task stays queued
+    let _guard = pool.reserve(4).unwrap();
+
+    let key = MailboxKey { tenant: "acme".into(), user_id: None, session_id: Some("s1".into()) };
+    for (id, deadline) in [(1, Some(1_000)), (2, None), (3, Some(2_000))] {
+        let mut builder = TaskMetadata::builder(id)
+            .cost(ResourceKind::GpuVram, 2)
+            .priority(Priority::High)
+            .mailbox(key.clone());
+        if let Some(ms) = deadline {
+            builder = builder.deadline_in(Duration::from_millis(ms));
+        }
+        let task = ScheduledTask {
+            meta: builder.build(),
+            payload: TestJob { name: format!("job_{id}"), value: id as u32 },
+        };
+        pool.submit(task, now_ms()).await.unwrap();
+    }
+
+    let mut expired = pool.prune_expired_collect(now_ms() + 5_000).unwrap();
+    expired.sort_by_key(|task| task.meta.id);
+    assert_eq!(expired.len(), 2);
+    for (task, id) in expired.iter().zip([1, 3]) {
+        assert_eq!(task.meta.id, id);
+        assert_eq!(task.meta.cost.units, 2);
+        assert_eq!(task.meta.priority, Priority::High);
+        assert_eq!(task.meta.mailbox.as_ref(), Some(&key));
+        assert_eq!(task.payload.name, format!("job_{id}"));
+        assert!(matches!(pool.status(id), Some(TaskStatus::Expired)));
+    }
+    assert!(matches!(pool.status(2), Some(TaskStatus::Queued)));
+
+    let delivered = pool.fetch_results(&key, None, 10);
+    assert_eq!(delivered.len(), 2);
+    assert!(delivered.iter().all(|m| matches!(m.status, TaskStatus::Expired)));
+}
+
+#[tokio::test]
+async fn test_prefer_queued_on_contention() {
+    // A small task fits while a larger one of equal priority waits
+    for prefer_queued in [false, true] {
+        let limits = PoolLimits {
+            max_units: 10,
+            max_queue_depth: 100,
+            default_timeout: Duration::from_secs(60),
+            prefer_queued_on_contention: prefer_queued,
+            ..PoolLimits::default()
+        };
+        let gate = Arc::new(tokio::sync::Semaphore::new(0));
+        let pool = ResourcePool::new(
+            limits,
+            InMemoryQueue::new(100),
+            InMemoryMailbox::new(),
+            GatedExecutor { gate: Arc::clone(&gate) },
+            TestSpawner,
+        );
+
+        let submit = |id: u64, units: u32| {
+            let task = ScheduledTask {
+                meta: TaskMetadata::builder(id).cost(ResourceKind::Cpu, units).build(),
+                payload: TestJob { name: format!("task_{id}"), value: 1 },
+            };
+            pool.submit_blocking(task, now_ms()).unwrap()
+        };
+
+        // The running task leaves 4 units free, too few for the next one
+        assert!(matches!(submit(1, 6), TaskStatus::Running));
+        assert!(matches!(submit(2, 8), TaskStatus::Queued));
+
+        // By default the small task starts in the free units; preferring
+        // queued work keeps it behind the waiting task
+        let status = submit(3, 2);
+        if prefer_queued {
+            assert!(matches!(status, TaskStatus::Queued));
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            assert!(matches!(pool.status(3), Some(TaskStatus::Queued)));
+        } else {
+            assert!(matches!(status, TaskStatus::Running));
+        }
+
+        // Everything drains in order once the running tasks finish
+        gate.add_permits(3);
+        let started = std::time::Instant::now();
+        while !matches!(pool.status(3), Some(TaskStatus::Completed)) {
+            assert!(started.elapsed() < Duration::from_secs(5), "queued tasks were never woken");
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_sync_wake_worker_fills_large_release() {
+    let limits = PoolLimits {
+        max_units: 50,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+    let gate = Arc::new(tokio::sync::Semaphore::new(0));
+    let pool = ResourcePool::new(
+        limits,
+        InMemoryQueue::new(100),
+        InMemoryMailbox::new(),
+        GatedExecutor { gate: Arc::clone(&gate) },
+        TokioSpawner::new(tokio::runtime::Handle::current()),
+    );
+    let worker = pool.spawn_sync_wake_worker().unwrap();
+    let make_task = |id: u64, units: u32| ScheduledTask {
+        meta: TaskMetadata::builder(id).cost(ResourceKind::Cpu, units).build(),
+        payload: TestJob { name: format!("task_{id}"), value: 1 },
+    };
+
+    // The large task takes every unit; the small ones queue behind it
+    let status = pool.submit(make_task(1, 50), now_ms()).await.unwrap();
+    assert!(matches!(status, TaskStatus::Running));
+    for id in 2..=6 {
+        let status = pool.submit(make_task(id, 10), now_ms()).await.unwrap();
+        assert!(matches!(status, TaskStatus::Queued));
+    }
+
+    // A single permit finishes only the large task; its release starts all five
+    gate.add_permits(1);
+    let started = std::time::Instant::now();
+    while !(2..=6).all(|id| matches!(pool.status(id), Some(TaskStatus::Running))) {
+        assert!(started.elapsed() < Duration::from_secs(2), "queued tasks weren't all started");
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    assert!(matches!(pool.status(1), Some(TaskStatus::Completed)));
+
+    gate.add_permits(5);
+    let started = std::time::Instant::now();
+    while !(2..=6).all(|id| matches!(pool.status(id), Some(TaskStatus::Completed))) {
+        assert!(started.elapsed() < Duration::from_secs(2), "started tasks never finished");
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    pool.shutdown();
+    worker.join().unwrap();
+}
+
+#[tokio::test]
+async fn test_submit_job_delivers_to_key() {
+    let limits = PoolLimits {
+        max_units: 10,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+    let pool = ResourcePool::new(
+        limits,
+        InMemoryQueue::new(100),
+        InMemoryMailbox::new(),
+        TestExecutor::new(),
+        TestSpawner,
+    );
+    let key = MailboxKey { tenant: "acme".into(), user_id: Some("bob".into()), session_id: None };
+    let cost = ResourceCost { kind: ResourceKind::Cpu, units: 4 };
+    let job = TestJob { name: "job".to_string(), value: 5 };
+
+    let returned = pool.submit_job(job, cost, Priority::High, key.clone(), now_ms()).unwrap();
+    assert_eq!(returned, key);
+
+    let mut messages = Vec::new();
+    for _ in 0..100 {
+        messages = pool.fetch_results(&key, None, 10);
+        if !messages.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(messages.len(), 1);
+    assert!(matches!(messages[0].status, TaskStatus::Completed));
+    // The first generated id is 0, and the next job gets a fresh one
+    assert_eq!(messages[0].payload.as_deref(), Some("Task 0: job = 10"));
+    assert_eq!(pool.next_task_id(), 1);
+}
+
+#[tokio::test]
+async fn test_oversized_task_rejected() {
+    let limits = PoolLimits {
+        max_units: 100,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        high_priority_reserve: Some(0.2),
+        ..PoolLimits::default()
+    };
+    let executor = CountingExecutor::new();
+    let pool = ResourcePool::new(
+        limits,
+        InMemoryQueue::new(100),
+        InMemoryMailbox::new(),
+        executor.clone(),
+        TestSpawner,
+    );
+    let make_task = |id: u64, priority: Priority, units: u32| ScheduledTask {
+        meta: TaskMetadata::builder(id).priority(priority).cost(ResourceKind::Cpu, units).build(),
+        payload: TestJob { name: format!("big_{id}"), value: 1 },
+    };
+
+    let result = pool.submit(make_task(1, Priority::Critical, 200), now_ms()).await;
+    assert!(matches!(result, Err(SchedulerError::CapacityExceeded)));
+    assert!(pool.status(1).is_none());
+    assert!(pool.peek_next().unwrap().is_none());
+
+    // The high-priority reserve is out of reach of lower priorities for good
+    let result = pool.submit(make_task(2, Priority::Normal, 90), now_ms()).await;
+    assert!(matches!(result, Err(SchedulerError::CapacityExceeded)));
+    let status = pool.submit(make_task(3, Priority::High, 90), now_ms()).await.unwrap();
+    assert!(matches!(status, TaskStatus::Running));
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(executor.runs.lock().unwrap().keys().copied().collect::<Vec<_>>(), vec![3]);
+}
+
+#[tokio::test]
+async fn test_set_max_units_at_runtime() {
+    let limits = PoolLimits {
+        max_units: 20,
+        max_queue_depth: 100,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+    let gate = Arc::new(tokio::sync::Semaphore::new(0));
+    let pool = ResourcePool::new(
+        limits,
+        InMemoryQueue::new(100),
+        InMemoryMailbox::new(),
+        GatedExecutor { gate: Arc::clone(&gate) },
+        TokioSpawner::new(tokio::runtime::Handle::current()),
+    );
+    let make_task = |id: u64, units: u32| ScheduledTask {
+        meta: TaskMetadata::builder(id).cost(ResourceKind::Cpu, units).build(),
+        payload: TestJob { name: format!("task_{id}"), value: 1 },
+    };
+    let wait_for = |ids: &[u64], expected: fn(Option<TaskStatus>) -> bool| {
+        let started = std::time::Instant::now();
+        let pool = &pool;
+        let ids = ids.to_vec();
+        async move {
+            while !ids.iter().all(|&id| expected(pool.status(id))) {
+                assert!(started.elapsed() < Duration::from_secs(2), "tasks {ids:?} never settled");
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }
+    };
+
+    for id in 1..=2 {
+        let status = pool.submit(make_task(id, 10), now_ms()).await.unwrap();
+        assert!(matches!(status, TaskStatus::Running));
+    }
+    let status = pool.submit(make_task(7, 15), now_ms()).await.unwrap();
+    assert!(matches!(status, TaskStatus::Queued));
+
+    // Lowering the limit leaves running tasks alone but holds new ones back,
+    // and drops queued ones that could never start
+    pool.set_max_units(10);
+    assert_eq!(pool.max_units(), 10);
+    assert!(matches!(pool.status(7), Some(TaskStatus::Dropped(_))));
+    let status = pool.submit(make_task(3, 5), now_ms()).await.unwrap();
+    assert!(matches!(status, TaskStatus::Queued));
+    let result = pool.submit(make_task(4, 15), now_ms()).await;
+    assert!(matches!(result, Err(SchedulerError::CapacityExceeded)));
+
+    // One finished task still leaves usage at the new limit
+    gate.add_permits(1);
+    let finished = std::time::Instant::now();
+    while ![1, 2].iter().any(|&id| matches!(pool.status(id), Some(TaskStatus::Completed))) {
+        assert!(finished.elapsed() < Duration::from_secs(2), "no task finished");
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(matches!(pool.status(3), Some(TaskStatus::Queued)));
+
+    // Once usage drops under it, the queued task starts
+    gate.add_permits(1);
+    wait_for(&[3], |status| matches!(status, Some(TaskStatus::Running))).await;
+
+    // Raising the limit starts queued tasks that now fit without a completion
+    for id in 5..=6 {
+        let status = pool.submit(make_task(id, 10), now_ms()).await.unwrap();
+        assert!(matches!(status, TaskStatus::Queued));
+    }
+    pool.set_max_units(30);
+    wait_for(&[5, 6], |status| matches!(status, Some(TaskStatus::Running))).await;
+
+    gate.add_permits(3);
+    wait_for(&[3, 5, 6], |status| matches!(status, Some(TaskStatus::Completed))).await;
+}
+
+#[tokio::test]
+async fn test_deadline_boundary_is_expired() {
+    let at = now_ms();
+    let mut meta = TaskMetadata::builder(1).cost(ResourceKind::Cpu, 2).build();
+    meta.deadline_ms = Some(at);
+    assert!(meta.is_expired_at(at));
+    assert!(!meta.is_expired_at(at - 1));
+
+    let limits = PoolLimits {
+        max_units: 4,
+        max_queue_depth: 10,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+    let pool = ResourcePool::new(
+        limits,
+        InMemoryQueue::new(10),
+        InMemoryMailbox::new(),
+        TestExecutor::new(),
+        TestSpawner,
+    );
+    let job = |id: u64| TestJob { name: format!("job_{id}"), value: id as u32 };
+
+    // Submitting exactly at the deadline is too late
+    let result = pool.submit(ScheduledTask { meta: meta.clone(), payload: job(1) }, at).await;
+    assert!(matches!(result, Err(SchedulerError::DeadlineExpired)));
+
+    // A queued task is pruned once the clock reaches its deadline
+    let _guard = pool.reserve(4).unwrap();
+    meta.id = 2;
+    meta.deadline_ms = Some(at + 100);
+    let status = pool.submit(ScheduledTask { meta, payload: job(2) }, at).await.unwrap();
+    assert!(matches!(status, TaskStatus::Queued));
+    assert!(pool.prune_expired_collect(at + 99).unwrap().is_empty());
+    let expired = pool.prune_expired_collect(at + 100).unwrap();
+    assert_eq!(expired.len(), 1);
+    assert!(matches!(pool.status(2), Some(TaskStatus::Expired)));
+}
+
+#[tokio::test]
+async fn test_dependency_chain_runs_in_order() {
+    let limits = PoolLimits {
+        max_units: 10,
+        max_queue_depth: 10,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+    let task = |id: u64, depends_on: Vec<u64>, value: u32| ScheduledTask {
+        meta: TaskMetadata::builder(id).depends_on(depends_on).build(),
+        payload: TestJob { name: format!("step_{id}"), value },
+    };
+
+    // A -> B -> C: each waits for the one before, though all fit at once
+    let executor = TestExecutor::new();
+    let pool = ResourcePool::new(
+        limits.clone(),
+        InMemoryQueue::new(10),
+        InMemoryMailbox::new(),
+        executor.clone(),
+        TestSpawner,
+    );
+    let status = pool.submit(task(1, Vec::new(), 1), now_ms()).await.unwrap();
+    assert!(matches!(status, TaskStatus::Running));
+    for (id, dependency) in [(2, 1), (3, 2)] {
+        let status = pool.submit(task(id, vec![dependency], 1), now_ms()).await.unwrap();
+        assert!(matches!(status, TaskStatus::Queued));
+    }
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert_eq!(
+        executor.get_results().await,
+        vec!["Task 1: step_1 = 2", "Task 2: step_2 = 2", "Task 3: step_3 = 2"]
+    );
+    assert!(matches!(pool.status(3), Some(TaskStatus::Completed)));
+
+    // When A fails, B and C are dropped without running, and later
+    // dependents of A are refused
+    let pool = ResourcePool::new(
+        limits,
+        InMemoryQueue::new(10),
+        InMemoryMailbox::new(),
+        FallibleExecutor,
+        TestSpawner,
+    );
+    pool.submit(task(1, Vec::new(), 0), now_ms()).await.unwrap();
+    pool.submit(task(2, vec![1], 1), now_ms()).await.unwrap();
+    pool.submit(task(3, vec![2], 1), now_ms()).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(matches!(pool.status(1), Some(TaskStatus::Failed(_))));
+    for id in [2, 3] {
+        let status = pool.status(id);
+        assert!(matches!(status, Some(TaskStatus::Dropped(reason)) if reason == "dependency failed"));
+    }
+    let result = pool.submit(task(4, vec![1], 1), now_ms()).await;
+    assert!(matches!(result, Err(SchedulerError::DependencyFailed { id: 1 })));
+}
@@ -0,0 +1,127 @@
+//! Integration tests for per-tenant rate limiting.
+//!
+//! Both pools check a tenant's token bucket before admitting a task. The
+//! limiter runs on a `ManualClock`, so refills happen only when the test
+//! advances time.
+
+use async_trait::async_trait;
+use prometheus_parking_lot::config::{RateLimitConfig, TokenBucketConfig, WorkerPoolConfig};
+use prometheus_parking_lot::core::{
+    PoolError, PoolLimits, RateLimiter, ResourcePool, ScheduledTask, SchedulerError, TaskExecutor,
+    TaskMetadata, TaskStatus, WorkerExecutor, WorkerPool,
+};
+use prometheus_parking_lot::infra::mailbox::memory::InMemoryMailbox;
+use prometheus_parking_lot::infra::queue::memory::InMemoryQueue;
+use prometheus_parking_lot::runtime::TokioSpawner;
+use prometheus_parking_lot::util::clock::{now_ms, ManualClock};
+use prometheus_parking_lot::util::serde::{MailboxKey, TaskId};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Clone)]
+struct EchoExecutor;
+
+#[async_trait]
+impl WorkerExecutor<u32, u32> for EchoExecutor {
+    async fn execute(&self, payload: u32, _meta: TaskMetadata) -> u32 {
+        payload
+    }
+}
+
+#[async_trait]
+impl TaskExecutor<u32, u32> for EchoExecutor {
+    async fn execute(&self, payload: u32, _meta: TaskMetadata) -> u32 {
+        payload
+    }
+}
+
+fn make_meta(id: TaskId, tenant: &str) -> TaskMetadata {
+    TaskMetadata::builder(id)
+        .mailbox(MailboxKey {
+            tenant: tenant.to_string(),
+            user_id: None,
+            session_id: None,
+        })
+        .build()
+}
+
+/// Bursts of three per tenant, refilling at 10 tokens per second.
+fn make_limiter(clock: &ManualClock) -> RateLimiter {
+    let config = RateLimitConfig::default()
+        .with_default(TokenBucketConfig::new(3, 10.0))
+        .with_tenant("bulk", TokenBucketConfig::new(1, 1.0));
+    RateLimiter::with_clock(config, Arc::new(clock.clone()))
+}
+
+#[tokio::test]
+async fn test_worker_pool_burst_then_refill() {
+    let clock = ManualClock::new(0);
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(2)
+        .with_max_units(10)
+        .with_max_queue_depth(10);
+    let pool = WorkerPool::new(config, EchoExecutor)
+        .expect("Failed to create pool")
+        .with_rate_limiter(make_limiter(&clock));
+
+    for id in 1..=3 {
+        assert!(pool.submit(id as u32, make_meta(id, "acme")).is_ok());
+    }
+    let retry_after_ms = match pool.submit(4, make_meta(4, "acme")) {
+        Err(PoolError::RateLimited { retry_after_ms }) => retry_after_ms,
+        other => panic!("expected RateLimited, got {:?}", other.map(|_| ())),
+    };
+    assert_eq!(retry_after_ms, 100);
+
+    // Another tenant has its own bucket
+    assert!(pool.submit(5, make_meta(5, "bulk")).is_ok());
+    assert!(pool.submit(6, make_meta(6, "bulk")).is_err());
+
+    // One refill interval later the tenant may submit again
+    clock.advance(Duration::from_millis(retry_after_ms));
+    let key = pool.submit(7, make_meta(7, "acme")).unwrap();
+    assert_eq!(pool.retrieve_async(&key, Duration::from_secs(5)).await.unwrap(), 7);
+    assert!(pool.submit(8, make_meta(8, "acme")).is_err());
+
+    pool.shutdown();
+}
+
+#[tokio::test]
+async fn test_resource_pool_burst_then_refill() {
+    let clock = ManualClock::new(0);
+    let limits = PoolLimits {
+        max_units: 10,
+        max_queue_depth: 10,
+        default_timeout: Duration::from_secs(60),
+        ..PoolLimits::default()
+    };
+    let pool = ResourcePool::new(
+        limits,
+        InMemoryQueue::new(10),
+        InMemoryMailbox::new(),
+        EchoExecutor,
+        TokioSpawner::new(tokio::runtime::Handle::current()),
+    )
+    .with_rate_limiter(make_limiter(&clock));
+
+    let make_task = |id: TaskId| ScheduledTask {
+        meta: make_meta(id, "acme"),
+        payload: id as u32,
+    };
+
+    for id in 1..=3 {
+        assert!(matches!(
+            pool.submit(make_task(id), now_ms()).await,
+            Ok(TaskStatus::Running)
+        ));
+    }
+    assert!(matches!(
+        pool.submit(make_task(4), now_ms()).await,
+        Err(SchedulerError::RateLimited { retry_after_ms: 100 })
+    ));
+
+    clock.advance(Duration::from_millis(250));
+    assert!(pool.submit(make_task(5), now_ms()).await.is_ok());
+    assert!(pool.submit(make_task(6), now_ms()).await.is_ok());
+    assert!(pool.submit(make_task(7), now_ms()).await.is_err());
+}
@@ -0,0 +1,101 @@
+//! Integration tests for the SQLite queue (requires the `sqlite` feature).
+
+#![cfg(feature = "sqlite")]
+
+use prometheus_parking_lot::core::{ScheduledTask, TaskMetadata, TaskQueue};
+use prometheus_parking_lot::infra::queue::SqliteQueue;
+use prometheus_parking_lot::util::clock::now_ms;
+use prometheus_parking_lot::util::serde::{Priority, ResourceCost, ResourceKind, TaskId};
+
+fn make_task(id: TaskId, priority: Priority, created_at_ms: u128) -> ScheduledTask<String> {
+    ScheduledTask {
+        meta: TaskMetadata {
+            id,
+            mailbox: None,
+            priority,
+            cost: ResourceCost {
+                kind: ResourceKind::Cpu,
+                units: 1,
+            },
+            deadline_ms: None,
+            created_at_ms,
+            trace_context: None,
+            degraded: false,
+            depends_on: Vec::new(),
+            idempotency_key: None,
+            retrieve_timeout_ms: None,
+        },
+        payload: format!("task-{id}"),
+    }
+}
+
+#[test]
+fn test_priority_order_survives_reopen() {
+    let path = std::env::temp_dir().join(format!("pl-sqlite-{}.db", now_ms()));
+
+    {
+        let mut queue = SqliteQueue::new(&path, 10).unwrap();
+        queue.enqueue(make_task(1, Priority::Low, 100)).unwrap();
+        queue.enqueue(make_task(2, Priority::High, 200)).unwrap();
+        queue.enqueue(make_task(3, Priority::Normal, 300)).unwrap();
+        queue.enqueue(make_task(4, Priority::High, 150)).unwrap();
+        let mut expiring = make_task(5, Priority::Critical, 400);
+        expiring.meta.deadline_ms = Some(1_000);
+        queue.enqueue(expiring).unwrap();
+        assert_eq!(queue.len(), 5);
+
+        let first = queue.dequeue().unwrap().unwrap();
+        assert_eq!(first.meta.id, 5);
+        assert_eq!(first.payload, "task-5");
+    }
+
+    // Reopening the file sees the remaining tasks in the same order
+    let mut queue = SqliteQueue::<String>::new(&path, 10).unwrap();
+    assert_eq!(queue.len(), 4);
+    assert_eq!(queue.queued_units(), 4);
+    let mut expiring = make_task(6, Priority::Critical, 500);
+    expiring.meta.deadline_ms = Some(1_000);
+    queue.enqueue(expiring).unwrap();
+    assert_eq!(queue.prune_expired(2_000).unwrap(), 1);
+
+    assert_eq!(queue.peek().unwrap().map(|meta| meta.id), Some(4));
+    assert_eq!(queue.dequeue().unwrap().unwrap().meta.id, 4);
+    let ids: Vec<TaskId> = queue.drain().unwrap().into_iter().map(|t| t.meta.id).collect();
+    assert_eq!(ids, vec![2, 3, 1]);
+    assert_eq!(queue.len(), 0);
+    assert!(queue.dequeue().unwrap().is_none());
+
+    drop(queue);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_max_depth() {
+    let path = std::env::temp_dir().join(format!("pl-sqlite-depth-{}.db", now_ms()));
+    let mut queue = SqliteQueue::new(&path, 1).unwrap();
+    queue.enqueue(make_task(1, Priority::Normal, 100)).unwrap();
+    assert!(queue.enqueue(make_task(2, Priority::Normal, 200)).is_err());
+
+    drop(queue);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_take_expired_returns_tasks() {
+    let path = std::env::temp_dir().join(format!("pl-sqlite-expired-{}.db", now_ms()));
+    let mut queue = SqliteQueue::new(&path, 10).unwrap();
+    for (id, deadline_ms) in [(1, Some(1_000)), (2, None), (3, Some(1_500)), (4, Some(5_000))] {
+        let mut task = make_task(id, Priority::Normal, 100 * u128::from(id));
+        task.meta.deadline_ms = deadline_ms;
+        queue.enqueue(task).unwrap();
+    }
+
+    let mut expired = queue.take_expired(2_000).unwrap();
+    expired.sort_by_key(|task| task.meta.id);
+    let taken: Vec<_> = expired.iter().map(|t| (t.meta.id, t.payload.as_str())).collect();
+    assert_eq!(taken, vec![(1, "task-1"), (3, "task-3")]);
+    assert_eq!(queue.len(), 2);
+
+    drop(queue);
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,197 @@
+//! Integration tests for `SchedulerSnapshot`.
+//!
+//! `WorkerPool` and `ResourcePool` both implement `SnapshotSource`; the same
+//! workload must produce the same metrics from either pool kind, including
+//! the age of the oldest queued task.
+
+use async_trait::async_trait;
+use prometheus_parking_lot::config::WorkerPoolConfig;
+use prometheus_parking_lot::core::{
+    PoolLimits, ResourcePool, ScheduledTask, TaskExecutor, TaskMetadata, TaskQueue,
+    TaskStatus, WorkerExecutor, WorkerPool,
+};
+use prometheus_parking_lot::infra::mailbox::memory::InMemoryMailbox;
+use prometheus_parking_lot::infra::queue::memory::InMemoryQueue;
+use prometheus_parking_lot::infra::queue::YaqueQueue;
+use prometheus_parking_lot::runtime::TokioSpawner;
+use prometheus_parking_lot::util::clock::now_ms;
+use prometheus_parking_lot::util::serde::{Priority, ResourceKind, TaskId};
+use prometheus_parking_lot::util::telemetry::{
+    PoolKind, PoolSnapshotMetrics, SchedulerSnapshot, SnapshotSource,
+};
+use std::time::Duration;
+
+/// Executor that sleeps for the number of milliseconds in its payload.
+#[derive(Clone)]
+struct SleepExecutor;
+
+#[async_trait]
+impl WorkerExecutor<u64, u64> for SleepExecutor {
+    async fn execute(&self, delay_ms: u64, _meta: TaskMetadata) -> u64 {
+        tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+        delay_ms
+    }
+}
+
+#[async_trait]
+impl TaskExecutor<u64, u64> for SleepExecutor {
+    async fn execute(&self, delay_ms: u64, _meta: TaskMetadata) -> u64 {
+        tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+        delay_ms
+    }
+}
+
+fn make_meta(id: TaskId, units: u32) -> TaskMetadata {
+    TaskMetadata::builder(id).cost(ResourceKind::Cpu, units).build()
+}
+
+/// Poll `source` until its snapshot satisfies `done`.
+async fn wait_for(
+    source: &dyn SnapshotSource,
+    done: impl Fn(&PoolSnapshotMetrics) -> bool,
+) -> PoolSnapshotMetrics {
+    for _ in 0..500 {
+        let metrics = source.snapshot_metrics("pool");
+        if done(&metrics) {
+            return metrics;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("pool never reached the expected state");
+}
+
+#[tokio::test]
+async fn test_both_pool_kinds_report_same_metrics() {
+    // One task fills the pool's 4 units, a second one has to queue
+    let worker_pool = WorkerPool::new(
+        WorkerPoolConfig::new()
+            .with_worker_count(1)
+            .with_max_units(4),
+        SleepExecutor,
+    )
+    .expect("Failed to create pool");
+    let resource_pool = ResourcePool::new(
+        PoolLimits {
+            max_units: 4,
+            max_queue_depth: 10,
+            default_timeout: Duration::from_secs(60),
+            ..PoolLimits::default()
+        },
+        InMemoryQueue::new(10),
+        InMemoryMailbox::new(),
+        SleepExecutor,
+        TokioSpawner::new(tokio::runtime::Handle::current()),
+    );
+
+    worker_pool
+        .submit_async(200, make_meta(1, 4))
+        .await
+        .unwrap();
+    worker_pool.submit_async(10, make_meta(2, 4)).await.unwrap();
+    let task = |id: TaskId, delay_ms: u64| ScheduledTask {
+        meta: make_meta(id, 4),
+        payload: delay_ms,
+    };
+    assert!(matches!(
+        resource_pool.submit(task(1, 200), now_ms()).await,
+        Ok(TaskStatus::Running)
+    ));
+    assert!(matches!(
+        resource_pool.submit(task(2, 10), now_ms()).await,
+        Ok(TaskStatus::Queued)
+    ));
+
+    let busy = |m: &PoolSnapshotMetrics| m.used_units == 4 && m.queue_depth == 1;
+    let worker_busy = wait_for(&worker_pool, busy).await;
+    let resource_busy = wait_for(&resource_pool, busy).await;
+    assert_eq!(worker_busy.kind, PoolKind::Worker);
+    assert_eq!(resource_busy.kind, PoolKind::Resource);
+    for metrics in [&worker_busy, &resource_busy] {
+        assert_eq!(metrics.total_units, 4);
+        assert_eq!(metrics.completed_tasks, 0);
+        assert_eq!(metrics.failed_tasks, 0);
+    }
+
+    let idle = |m: &PoolSnapshotMetrics| m.completed_tasks == 2;
+    wait_for(&worker_pool, idle).await;
+    wait_for(&resource_pool, idle).await;
+
+    let snapshot = SchedulerSnapshot::collect([
+        ("workers", &worker_pool as &dyn SnapshotSource),
+        ("resources", &resource_pool as &dyn SnapshotSource),
+    ]);
+    let names: Vec<&str> = snapshot.pools.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, ["workers", "resources"]);
+    for metrics in &snapshot.pools {
+        assert_eq!(metrics.used_units, 0);
+        assert_eq!(metrics.total_units, 4);
+        assert_eq!(metrics.queue_depth, 0);
+        assert_eq!(metrics.completed_tasks, 2);
+        assert_eq!(metrics.failed_tasks, 0);
+    }
+
+    worker_pool.shutdown();
+}
+
+#[tokio::test]
+async fn test_queue_oldest_age() {
+    // Queues report the earliest-created task, whatever its priority
+    let dir = std::env::temp_dir().join(format!("pl-oldest-age-{}", now_ms()));
+    let mut queue = YaqueQueue::<u64>::new(&dir, "jobs", 10).expect("Failed to open queue");
+    assert_eq!(queue.oldest_created_at_ms(), None);
+    let mut urgent = make_meta(2, 1);
+    urgent.priority = Priority::Critical;
+    let mut early = make_meta(1, 1);
+    early.created_at_ms -= 500;
+    let early_at = early.created_at_ms;
+    queue.enqueue(ScheduledTask { meta: urgent, payload: 0 }).unwrap();
+    queue.enqueue(ScheduledTask { meta: early, payload: 0 }).unwrap();
+    assert_eq!(queue.oldest_created_at_ms(), Some(early_at));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    // Both pool kinds surface the age of their oldest waiting task
+    let worker_pool = WorkerPool::new(
+        WorkerPoolConfig::new()
+            .with_worker_count(1)
+            .with_max_units(4),
+        SleepExecutor,
+    )
+    .expect("Failed to create pool");
+    let resource_pool = ResourcePool::new(
+        PoolLimits {
+            max_units: 4,
+            max_queue_depth: 10,
+            default_timeout: Duration::from_secs(60),
+            ..PoolLimits::default()
+        },
+        InMemoryQueue::new(10),
+        InMemoryMailbox::new(),
+        SleepExecutor,
+        TokioSpawner::new(tokio::runtime::Handle::current()),
+    );
+    let waiting = || {
+        let mut meta = make_meta(2, 4);
+        meta.created_at_ms -= 300;
+        meta
+    };
+
+    worker_pool.submit_async(200, make_meta(1, 4)).await.unwrap();
+    worker_pool.submit_async(10, waiting()).await.unwrap();
+    let task = |meta: TaskMetadata, delay_ms: u64| ScheduledTask { meta, payload: delay_ms };
+    resource_pool.submit(task(make_meta(1, 4), 200), now_ms()).await.unwrap();
+    resource_pool.submit(task(waiting(), 10), now_ms()).await.unwrap();
+
+    let busy = |m: &PoolSnapshotMetrics| m.used_units == 4 && m.queue_depth == 1;
+    for source in [&worker_pool as &dyn SnapshotSource, &resource_pool] {
+        let age = wait_for(source, busy).await.queue_oldest_age_ms.expect("no queue age");
+        assert!((300..5_000).contains(&age), "unexpected age {age}");
+    }
+    assert!(worker_pool.stats().queue_oldest_age_ms.is_some());
+
+    let idle = |m: &PoolSnapshotMetrics| m.completed_tasks == 2;
+    for source in [&worker_pool as &dyn SnapshotSource, &resource_pool] {
+        assert_eq!(wait_for(source, idle).await.queue_oldest_age_ms, None);
+    }
+
+    worker_pool.shutdown();
+}
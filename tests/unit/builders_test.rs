@@ -13,6 +13,7 @@ fn test_pool_builder_defaults() {
         queue: QueueBackendConfig::InMemory,
         mailbox: MailboxBackendConfig::InMemory,
         runtime: RuntimeConfig::Native,
+        ..PoolConfig::default()
     };
 
     let builder = PoolBuilder::new("pool1", config.clone());
@@ -11,8 +11,10 @@ fn test_pool_config_validation() {
         queue: QueueBackendConfig::InMemory,
         mailbox: MailboxBackendConfig::InMemory,
         runtime: RuntimeConfig::Native,
+        ..PoolConfig::default()
     };
     assert!(valid.validate().is_ok());
+    assert!(PoolConfig::default().validate().is_ok());
 }
 
 #[test]
@@ -24,6 +26,7 @@ fn test_pool_config_invalid_max_units() {
         queue: QueueBackendConfig::InMemory,
         mailbox: MailboxBackendConfig::InMemory,
         runtime: RuntimeConfig::Native,
+        ..PoolConfig::default()
     };
     assert!(invalid.validate().is_err());
 }
@@ -37,6 +40,7 @@ fn test_pool_config_invalid_queue_depth() {
         queue: QueueBackendConfig::InMemory,
         mailbox: MailboxBackendConfig::InMemory,
         runtime: RuntimeConfig::Native,
+        ..PoolConfig::default()
     };
     assert!(invalid.validate().is_err());
 }
@@ -50,6 +54,7 @@ fn test_pool_config_invalid_timeout() {
         queue: QueueBackendConfig::InMemory,
         mailbox: MailboxBackendConfig::InMemory,
         runtime: RuntimeConfig::Native,
+        ..PoolConfig::default()
     };
     assert!(invalid.validate().is_err());
 }
@@ -64,6 +69,7 @@ fn test_scheduler_config_validation() {
         queue: QueueBackendConfig::InMemory,
         mailbox: MailboxBackendConfig::InMemory,
         runtime: RuntimeConfig::Native,
+        ..PoolConfig::default()
     });
     
     let config = SchedulerConfig { pools };
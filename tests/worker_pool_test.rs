@@ -2,20 +2,55 @@
 //!
 //! These tests validate real-world functionality including:
 //! - Basic task execution with real executors
-//! - Blocking and async APIs
+//! - Blocking and async APIs, and typed task handles
 //! - Concurrent task submission
-//! - Resource limits and queueing
+//! - Resource limits and queueing, with usage broken down by resource kind
+//!   and summarized as a load factor
+//! - A cap on concurrently running tasks independent of units and workers
+//! - Pausing dispatch while submissions keep queueing
+//! - Queue wait time reported separately from execution time
+//! - Warnings and a counter for tasks running past a slow-task threshold
 //! - Non-serializable streaming results (candle-vllm pattern)
-//! - Timeout handling
-//! - Graceful shutdown
+//! - Timeout handling, including per-task execution timeouts and retrieval
+//!   with the pool's default timeout
+//! - Detection of running tasks that stopped sending progress heartbeats
+//! - Per-worker view of the task each worker is executing
+//! - Workers of different unit capacities, each running only tasks that fit
+//! - Lowered worker thread priority
+//! - Worker runtimes built before the pool is returned
+//! - Non-Clone executors shared through an `Arc`
+//! - Executors built from async closures
+//! - A per-pool context shared with every executor invocation
+//! - Routing tasks to per-model executors
+//! - Graceful shutdown, including saving queued tasks for the next boot,
+//!   the per-worker join timeout, and awaiting the workers from async code
+//! - Unretrieved results persisted and restored after a restart
+//! - Idempotent submission
+//! - A completion hook run once per finished task
+//! - Rejection of task ids already in flight, and pool-assigned ids
+//! - Results shared by several consumers until they expire
+//! - Queue depth limit under concurrent submission
+//! - Retry-after hints on queue-full rejections
+//! - Rejection of zero-cost tasks and of tasks larger than the pool
+//! - Changing the pool's max units at runtime
+//! - Cancelling every queued task of a tenant
+//! - Results stored under caller-chosen mailbox keys
 
 use async_trait::async_trait;
-use prometheus_parking_lot::config::WorkerPoolConfig;
-use prometheus_parking_lot::core::{PoolError, TaskMetadata, WorkerExecutor, WorkerPool};
-use prometheus_parking_lot::util::{Priority, ResourceCost, ResourceKind};
-use std::sync::atomic::{AtomicU64, Ordering};
+use prometheus_parking_lot::config::{
+    CircuitBreakerConfig, RetryPolicy, WorkerPoolConfig, WorkerRuntimeKind,
+};
+use prometheus_parking_lot::core::{
+    CircuitState, ExecutionOutcome, ExecutorContext, FnExecutor, PoolError, Progress, ProgressReporter,
+    RoutingExecutor, RuntimeBuilderFn, ScheduledTask, TaskMetadata, WorkerExecutor, WorkerPool,
+};
+use prometheus_parking_lot::util::{
+    read_json_lines, write_json_lines, MailboxKey, Priority, ResourceCost, ResourceKind,
+};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing_test::traced_test;
 
 // Test wrapper with explicit timeout enforcement
 // If the test doesn't complete in the specified time, we panic immediately
@@ -65,6 +100,11 @@ fn make_meta(task_id: u64, units: u32) -> TaskMetadata {
         },
         deadline_ms: None,
         created_at_ms: now_ms(),
+        trace_context: None,
+        degraded: false,
+        depends_on: Vec::new(),
+        idempotency_key: None,
+        retrieve_timeout_ms: None,
     }
 }
 
@@ -79,6 +119,11 @@ fn make_gpu_meta(task_id: u64, units: u32) -> TaskMetadata {
         },
         deadline_ms: None,
         created_at_ms: now_ms(),
+        trace_context: None,
+        degraded: false,
+        depends_on: Vec::new(),
+        idempotency_key: None,
+        retrieve_timeout_ms: None,
     }
 }
 
@@ -241,6 +286,226 @@ impl WorkerExecutor<(), String> for SlowExecutor {
     }
 }
 
+/// Executor that sleeps for the number of milliseconds in its payload
+#[derive(Clone)]
+struct SleepExecutor;
+
+#[async_trait]
+impl WorkerExecutor<u64, u64> for SleepExecutor {
+    async fn execute(&self, delay_ms: u64, _meta: TaskMetadata) -> u64 {
+        tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+        delay_ms
+    }
+}
+
+/// Executor that fails a fixed number of times before succeeding
+#[derive(Clone)]
+struct FlakyExecutor {
+    failures_before_success: u64,
+    attempts: Arc<AtomicU64>,
+}
+
+impl FlakyExecutor {
+    fn new(failures_before_success: u64) -> Self {
+        Self {
+            failures_before_success,
+            attempts: Arc::new(AtomicU64::new(0)),
+        }
+    }
+}
+
+#[async_trait]
+impl WorkerExecutor<String, Result<String, String>> for FlakyExecutor {
+    async fn execute(&self, payload: String, _meta: TaskMetadata) -> Result<String, String> {
+        let attempt = self.attempts.fetch_add(1, Ordering::SeqCst) + 1;
+        if attempt <= self.failures_before_success {
+            Err(format!("transient failure #{}", attempt))
+        } else {
+            Ok(format!("{} ok after {} attempts", payload, attempt))
+        }
+    }
+
+    fn classify(&self, result: &Result<String, String>) -> ExecutionOutcome {
+        match result {
+            Ok(_) => ExecutionOutcome::Success,
+            Err(_) => ExecutionOutcome::Retryable,
+        }
+    }
+}
+
+/// Executor that fails permanently until it is marked healthy
+#[derive(Clone)]
+struct SwitchableExecutor {
+    healthy: Arc<AtomicBool>,
+}
+
+#[async_trait]
+impl WorkerExecutor<u64, Result<u64, String>> for SwitchableExecutor {
+    async fn execute(&self, payload: u64, _meta: TaskMetadata) -> Result<u64, String> {
+        if self.healthy.load(Ordering::SeqCst) {
+            Ok(payload)
+        } else {
+            Err("model server unavailable".to_string())
+        }
+    }
+
+    fn classify(&self, result: &Result<u64, String>) -> ExecutionOutcome {
+        match result {
+            Ok(_) => ExecutionOutcome::Success,
+            Err(_) => ExecutionOutcome::Failed,
+        }
+    }
+}
+
+/// Executor that reports progress in thirds before returning its result
+#[derive(Clone)]
+struct ProgressExecutor;
+
+#[async_trait]
+impl WorkerExecutor<String, String> for ProgressExecutor {
+    async fn execute(&self, payload: String, meta: TaskMetadata) -> String {
+        self.execute_with_progress(payload, meta, ProgressReporter::noop()).await
+    }
+
+    async fn execute_with_progress(
+        &self,
+        payload: String,
+        _meta: TaskMetadata,
+        progress: ProgressReporter,
+    ) -> String {
+        for step in 1..=3u8 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            progress.report(f32::from(step) / 3.0, Some(format!("step {}", step)));
+        }
+        format!("{} done", payload)
+    }
+}
+
+/// Executor that adds its payload to the counter in the pool's context
+#[derive(Clone)]
+struct ContextExecutor;
+
+#[async_trait]
+impl WorkerExecutor<u64, u64> for ContextExecutor {
+    async fn execute(&self, _payload: u64, _meta: TaskMetadata) -> u64 {
+        panic!("the pool should call execute_with_context")
+    }
+
+    async fn execute_with_context(
+        &self,
+        payload: u64,
+        _meta: TaskMetadata,
+        _progress: ProgressReporter,
+        context: &ExecutorContext,
+    ) -> u64 {
+        let counter = context.get::<AtomicU64>().expect("context should be an AtomicU64");
+        counter.fetch_add(payload, Ordering::SeqCst) + payload
+    }
+}
+
+/// Executor that streams a few progress updates, then goes silent for
+/// the number of milliseconds in its payload
+#[derive(Clone)]
+struct StallingExecutor;
+
+#[async_trait]
+impl WorkerExecutor<u64, u64> for StallingExecutor {
+    async fn execute(&self, payload: u64, meta: TaskMetadata) -> u64 {
+        self.execute_with_progress(payload, meta, ProgressReporter::noop()).await
+    }
+
+    async fn execute_with_progress(
+        &self,
+        stall_ms: u64,
+        _meta: TaskMetadata,
+        progress: ProgressReporter,
+    ) -> u64 {
+        for step in 1..=3u8 {
+            progress.report(f32::from(step) / 10.0, None);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(stall_ms)).await;
+        stall_ms
+    }
+}
+
+/// Executor that fans out blocking sub-tasks on the worker's runtime
+#[derive(Clone)]
+struct FanOutExecutor;
+
+#[async_trait]
+impl WorkerExecutor<u64, u64> for FanOutExecutor {
+    async fn execute(&self, payload: u64, _meta: TaskMetadata) -> u64 {
+        let handles: Vec<_> = (0..payload)
+            .map(|i| {
+                tokio::spawn(async move {
+                    // Stands in for a sub-task that keeps its runtime thread busy
+                    std::thread::sleep(Duration::from_millis(50));
+                    i
+                })
+            })
+            .collect();
+        let mut sum = 0;
+        for handle in handles {
+            sum += handle.await.unwrap();
+        }
+        sum
+    }
+}
+
+thread_local! {
+    /// Device id bound by the runtime builder's `on_thread_start` hook
+    static BOUND_DEVICE: std::cell::Cell<Option<u32>> = const { std::cell::Cell::new(None) };
+}
+
+/// Executor that reports the device bound to the runtime thread its sub-task runs on
+#[derive(Clone)]
+struct DeviceExecutor;
+
+#[async_trait]
+impl WorkerExecutor<(), Option<u32>> for DeviceExecutor {
+    async fn execute(&self, _payload: (), _meta: TaskMetadata) -> Option<u32> {
+        tokio::spawn(async { BOUND_DEVICE.with(std::cell::Cell::get) })
+            .await
+            .unwrap()
+    }
+}
+
+/// Executor owning a large resource that is deliberately not `Clone`
+struct ModelExecutor {
+    weights: Vec<u8>,
+    loads: AtomicU64,
+}
+
+#[async_trait]
+impl WorkerExecutor<usize, u8> for ModelExecutor {
+    async fn execute(&self, index: usize, _meta: TaskMetadata) -> u8 {
+        self.loads.fetch_add(1, Ordering::SeqCst);
+        self.weights[index]
+    }
+}
+
+/// Model backend that tags its output with the model's name
+struct BackendExecutor {
+    model: &'static str,
+    calls: Arc<AtomicU64>,
+}
+
+impl BackendExecutor {
+    fn new(model: &'static str) -> (Self, Arc<AtomicU64>) {
+        let calls = Arc::new(AtomicU64::new(0));
+        (Self { model, calls: Arc::clone(&calls) }, calls)
+    }
+}
+
+#[async_trait]
+impl WorkerExecutor<(String, u64), String> for BackendExecutor {
+    async fn execute(&self, (_, prompt): (String, u64), _meta: TaskMetadata) -> String {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        format!("{}:{}", self.model, prompt)
+    }
+}
+
 // ============================================================================
 // TESTS
 // ============================================================================
@@ -465,6 +730,83 @@ async fn test_resource_limits_queueing() {
     }).await;
 }
 
+/// Test max_concurrent_tasks caps running tasks below the worker count
+#[tokio::test]
+async fn test_max_concurrent_tasks() {
+    with_timeout("test_max_concurrent_tasks", 15, async {
+    println!("\n=== test_max_concurrent_tasks ===");
+
+    let executor = CountingExecutor::new();
+
+    // Units and workers would allow four at once; the task cap allows two
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(4)
+        .with_max_units(1000)
+        .with_max_concurrent_tasks(2)
+        .with_max_queue_depth(50);
+    let pool = WorkerPool::new(config, executor.clone()).expect("Failed to create pool");
+
+    let mut keys = Vec::new();
+    for i in 0..8 {
+        keys.push(pool.submit(i, make_meta(i, 10)).expect("Failed to submit"));
+    }
+    for key in keys {
+        pool.retrieve_async(&key, Duration::from_secs(10))
+            .await
+            .expect("Failed to retrieve");
+    }
+
+    println!("Max concurrent executions observed: {}", executor.max_concurrent());
+    assert_eq!(executor.execution_count(), 8);
+    assert_eq!(executor.max_concurrent(), 2);
+
+    pool.shutdown();
+    println!("=== test_max_concurrent_tasks PASSED ===\n");
+    }).await;
+}
+
+/// Test that a paused pool queues submissions and runs them once resumed
+#[tokio::test]
+async fn test_pause_resume() {
+    with_timeout("test_pause_resume", 15, async {
+    println!("\n=== test_pause_resume ===");
+
+    let executor = CountingExecutor::new();
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(2)
+        .with_max_units(1000)
+        .with_max_queue_depth(50);
+    let pool = WorkerPool::new(config, executor.clone()).expect("Failed to create pool");
+
+    pool.pause();
+    let mut keys = Vec::new();
+    for i in 0..5 {
+        keys.push(pool.submit(i, make_meta(i, 10)).expect("Failed to submit"));
+    }
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let stats = pool.stats();
+    println!("Paused stats: {stats:?}");
+    assert!(stats.paused);
+    assert_eq!(stats.queued_tasks, 5);
+    assert_eq!(executor.execution_count(), 0);
+
+    pool.resume();
+    for (i, key) in keys.iter().enumerate() {
+        let result = pool
+            .retrieve_async(key, Duration::from_secs(10))
+            .await
+            .expect("Failed to retrieve");
+        assert_eq!(result, i as u64 * 2);
+    }
+    assert_eq!(executor.execution_count(), 5);
+    assert!(!pool.stats().paused);
+
+    pool.shutdown();
+    println!("=== test_pause_resume PASSED ===\n");
+    }).await;
+}
+
 /// Test non-serializable streaming results (THE KEY CANDLE-VLLM PATTERN)
 #[tokio::test]
 async fn test_streaming_non_serializable_results() {
@@ -553,7 +895,8 @@ async fn test_timeout_handling() {
     println!("Retrieve returned after {:?}", elapsed);
 
     match result {
-        Err(PoolError::Timeout) => {
+        Err(PoolError::Timeout { key: timed_out }) => {
+            assert_eq!(timed_out, key);
             println!("Correctly got Timeout error");
         }
         other => {
@@ -577,6 +920,59 @@ async fn test_timeout_handling() {
     }).await;
 }
 
+/// Test that retrieve_async times out and frees its blocking thread when the
+/// result slot is removed concurrently
+#[test]
+fn test_retrieve_async_concurrent_remove() {
+    // A single blocking thread: if the first wait leaked it, the second
+    // retrieve_async could never run its wait and would time out too
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .max_blocking_threads(1)
+        .enable_all()
+        .build()
+        .unwrap();
+
+    rt.block_on(with_timeout("test_retrieve_async_concurrent_remove", 10, async {
+    println!("\n=== test_retrieve_async_concurrent_remove ===");
+
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(1)
+        .with_max_units(100)
+        .with_max_queue_depth(10);
+    let pool = Arc::new(WorkerPool::new(config, SlowExecutor::new(300)).expect("Failed to create pool"));
+
+    let key = pool.submit_async((), make_meta(1, 10)).await.expect("Failed to submit");
+    let waiter = {
+        let pool = Arc::clone(&pool);
+        let key = key.clone();
+        tokio::spawn(async move { pool.retrieve_async(&key, Duration::from_millis(200)).await })
+    };
+
+    // Drop the result slot while retrieve_async is waiting on it
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(matches!(
+        pool.retrieve(&key, Duration::from_millis(1)),
+        Err(PoolError::Timeout { key: ref timed_out }) if *timed_out == key
+    ));
+
+    let result = waiter.await.unwrap();
+    assert!(
+        matches!(result, Err(PoolError::Timeout { key: ref timed_out }) if *timed_out == key),
+        "got: {:?}",
+        result
+    );
+
+    // The blocking thread was released, so another wait can still run
+    let key = pool.submit_async((), make_meta(2, 10)).await.expect("Failed to submit");
+    let result = pool.retrieve_async(&key, Duration::from_secs(5)).await;
+    assert_eq!(result.expect("Failed to retrieve"), "completed");
+
+    pool.shutdown();
+    println!("=== test_retrieve_async_concurrent_remove PASSED ===\n");
+    }));
+}
+
 /// Test graceful shutdown
 #[tokio::test]
 async fn test_graceful_shutdown() {
@@ -674,6 +1070,167 @@ async fn test_submit_after_shutdown() {
     }).await;
 }
 
+/// Test that shutdown_drain saves queued tasks and load_pending resubmits them
+#[tokio::test]
+async fn test_shutdown_drain_and_reload() {
+    with_timeout("test_shutdown_drain_and_reload", 15, async {
+    println!("\n=== test_shutdown_drain_and_reload ===");
+
+    let path = std::env::temp_dir().join(format!("pl-pending-{}.jsonl", now_ms()));
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(1)
+        .with_max_units(10);
+
+    let pool = WorkerPool::new(config.clone(), SleepExecutor)
+        .expect("Failed to create pool")
+        .with_persistence(&path);
+
+    // Keep the only worker busy so the rest stay queued
+    let running = pool.submit_async(300, make_meta(1, 10)).await.unwrap();
+    while pool.stats().active_tasks == 0 {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    let mut queued = Vec::new();
+    for (id, delay_ms) in [(2, 10), (3, 20), (4, 30)] {
+        queued.push(pool.submit_async(delay_ms, make_meta(id, 10)).await.unwrap());
+    }
+
+    let saved = tokio::task::spawn_blocking({
+        let pool = Arc::new(pool);
+        move || {
+            let saved = pool.shutdown_drain();
+            (pool, saved)
+        }
+    });
+    let (pool, saved) = saved.await.unwrap();
+    assert_eq!(saved.unwrap(), 3);
+    assert_eq!(pool.retrieve_async(&running, Duration::from_secs(5)).await.unwrap(), 300);
+    assert!(matches!(
+        pool.retrieve_async(&queued[0], Duration::from_secs(1)).await,
+        Err(PoolError::ResultNotFound { .. })
+    ));
+    println!("Saved 3 queued tasks to {}", path.display());
+
+    // The next boot picks them up in their original order
+    let pool = WorkerPool::new(config, SleepExecutor).expect("Failed to create pool");
+    let keys = pool.load_pending(&path).unwrap();
+    assert_eq!(keys.len(), 3);
+    let mut results = Vec::new();
+    for key in &keys {
+        results.push(pool.retrieve_async(key, Duration::from_secs(5)).await.unwrap());
+    }
+    assert_eq!(results, vec![10, 20, 30]);
+    assert!(!path.exists());
+    assert!(pool.load_pending(&path).unwrap().is_empty());
+
+    pool.shutdown();
+    println!("=== test_shutdown_drain_and_reload PASSED ===\n");
+    }).await;
+}
+
+/// Test that load_pending keeps the tasks a too-small pool rejects
+#[tokio::test]
+async fn test_load_pending_keeps_rejected_tasks() {
+    with_timeout("test_load_pending_keeps_rejected_tasks", 15, async {
+    println!("\n=== test_load_pending_keeps_rejected_tasks ===");
+
+    let path = std::env::temp_dir().join(format!("pl-pending-rejected-{}.jsonl", now_ms()));
+    let saved: Vec<ScheduledTask<u64>> = [(2, 10), (3, 20), (4, 30)]
+        .into_iter()
+        .map(|(id, delay_ms)| ScheduledTask { meta: make_meta(id, 10), payload: delay_ms })
+        .collect();
+    write_json_lines(&path, &saved).unwrap();
+
+    // One busy worker and room for a single queued task
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(1)
+        .with_max_units(10)
+        .with_max_queue_depth(1);
+    let pool = WorkerPool::new(config, SleepExecutor).expect("Failed to create pool");
+    let running = pool.submit_async(300, make_meta(1, 10)).await.unwrap();
+    while pool.stats().active_tasks == 0 {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    let keys = pool.load_pending(&path).unwrap();
+    assert_eq!(keys.len(), 1);
+    let kept: Vec<ScheduledTask<u64>> = read_json_lines(&path).unwrap();
+    assert_eq!(kept.iter().map(|task| task.meta.id).collect::<Vec<_>>(), vec![3, 4]);
+    assert_eq!(pool.retrieve_async(&running, Duration::from_secs(5)).await.unwrap(), 300);
+    assert_eq!(pool.retrieve_async(&keys[0], Duration::from_secs(5)).await.unwrap(), 10);
+    pool.shutdown();
+
+    // A pool with room takes the rest and removes the file
+    let pool = WorkerPool::new(WorkerPoolConfig::new().with_worker_count(1), SleepExecutor)
+        .expect("Failed to create pool");
+    let keys = pool.load_pending(&path).unwrap();
+    let mut results = Vec::new();
+    for key in &keys {
+        results.push(pool.retrieve_async(key, Duration::from_secs(5)).await.unwrap());
+    }
+    assert_eq!(results, vec![20, 30]);
+    assert!(!path.exists());
+
+    pool.shutdown();
+    println!("=== test_load_pending_keeps_rejected_tasks PASSED ===\n");
+    }).await;
+}
+
+/// Test that finished but unretrieved results survive a restart
+#[tokio::test]
+async fn test_result_persistence_and_reload() {
+    with_timeout("test_result_persistence_and_reload", 15, async {
+    println!("\n=== test_result_persistence_and_reload ===");
+
+    let path = std::env::temp_dir().join(format!("pl-results-{}.jsonl", now_ms()));
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(2)
+        .with_max_units(100);
+
+    let pool = WorkerPool::new(config.clone(), SleepExecutor)
+        .expect("Failed to create pool")
+        .with_result_persistence(&path);
+    let taken = pool.submit_async(10, make_meta(1, 10)).await.unwrap();
+    let kept = pool.submit_async(20, make_meta(2, 10)).await.unwrap();
+    assert_eq!(pool.retrieve_async(&taken, Duration::from_secs(5)).await.unwrap(), 10);
+    while pool.stats().completed_tasks < 2 {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    pool.shutdown();
+    drop(pool);
+
+    // The restarted pool hands out only the result nobody retrieved
+    let pool = WorkerPool::new(config.clone(), SleepExecutor)
+        .expect("Failed to create pool")
+        .with_result_persistence(&path);
+    assert_eq!(pool.load_results(&path).unwrap(), 1);
+    assert!(matches!(
+        pool.try_retrieve(&taken),
+        Err(PoolError::ResultNotFound { .. })
+    ));
+
+    // New tasks don't reuse the restored keys
+    let mut fresh = Vec::new();
+    for (id, delay_ms) in [(3, 30), (4, 40)] {
+        fresh.push(pool.submit_async(delay_ms, make_meta(id, 10)).await.unwrap());
+    }
+    assert!(!fresh.contains(&kept));
+    assert_eq!(pool.retrieve_async(&kept, Duration::from_secs(1)).await.unwrap(), 20);
+    for (key, expected) in fresh.iter().zip([30, 40]) {
+        assert_eq!(pool.retrieve_async(key, Duration::from_secs(5)).await.unwrap(), expected);
+    }
+    pool.shutdown();
+    drop(pool);
+
+    // Everything has been handed out, so a third boot restores nothing
+    let pool = WorkerPool::new(config, SleepExecutor).expect("Failed to create pool");
+    assert_eq!(pool.load_results(&path).unwrap(), 0);
+    pool.shutdown();
+    std::fs::remove_file(&path).unwrap();
+    println!("=== test_result_persistence_and_reload PASSED ===\n");
+    }).await;
+}
+
 /// Test CPU-bound work doesn't block the async runtime
 #[tokio::test]
 async fn test_cpu_work_isolation() {
@@ -755,7 +1312,8 @@ async fn test_queue_depth_limit() {
                 println!("Task {} accepted", i);
                 keys.push(key);
             }
-            Err(PoolError::QueueFull) => {
+            Err(PoolError::QueueFull { task_id, .. }) => {
+                assert_eq!(task_id, Some(i as u64));
                 println!("Task {} rejected (queue full)", i);
                 rejected += 1;
             }
@@ -786,52 +1344,1545 @@ async fn test_queue_depth_limit() {
     }).await;
 }
 
-/// Test multiple result retrievals for same key
+/// Test queue-full rejections carry a retry-after hint that tracks the drain rate
 #[tokio::test]
-async fn test_result_consumed_once() {
-    with_timeout("test_result_consumed_once", 10, async {
-    println!("\n=== test_result_consumed_once ===");
+async fn test_queue_full_retry_after() {
+    with_timeout("test_queue_full_retry_after", 15, async {
+    println!("\n=== test_queue_full_retry_after ===");
 
     let config = WorkerPoolConfig::new()
         .with_worker_count(1)
-        .with_max_units(100)
-        .with_max_queue_depth(10);
+        .with_max_units(10)
+        .with_max_queue_depth(2);
+    let pool = WorkerPool::new(config, SleepExecutor).expect("Failed to create pool");
+    let retry_after = |err: PoolError| match err {
+        PoolError::QueueFull { retry_after_ms: Some(ms), .. } => {
+            assert_eq!(err.retry_after(), Some(Duration::from_millis(ms)));
+            ms
+        }
+        other => panic!("Expected QueueFull with a hint, got {:?}", other),
+    };
 
-    let pool = WorkerPool::new(config, AddExecutor).expect("Failed to create pool");
+    let mut keys = vec![pool.submit(20, make_meta(0, 10)).unwrap()];
+    while pool.stats().active_tasks == 0 {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    keys.push(pool.submit(20, make_meta(1, 10)).unwrap());
+    keys.push(pool.submit(20, make_meta(2, 10)).unwrap());
+    // Nothing has finished yet, so the hint is the most conservative one
+    let before = retry_after(pool.submit(20, make_meta(3, 10)).unwrap_err());
+    assert!(before > 0);
+
+    // Keep the queue topped up while tasks drain
+    let mut id = 4;
+    while pool.stats().completed_tasks < 10 {
+        if let Ok(key) = pool.submit(20, make_meta(id, 10)) {
+            keys.push(key);
+        }
+        id += 1;
+        tokio::time::sleep(Duration::from_millis(2)).await;
+    }
+    let mut rejection = pool.submit(20, make_meta(id, 10));
+    while let Ok(key) = rejection {
+        keys.push(key);
+        id += 1;
+        rejection = pool.submit(20, make_meta(id, 10));
+    }
+    let after = retry_after(rejection.unwrap_err());
+    println!("retry after {} ms before any completion, {} ms once draining", before, after);
+    assert!(after > 0 && after < before);
 
-    // Submit and retrieve
-    let meta = make_meta(1, 10);
-    let key = pool
-        .submit_async((1, 2), meta)
-        .await
-        .expect("Failed to submit");
+    for key in keys {
+        pool.retrieve_async(&key, Duration::from_secs(5)).await.unwrap();
+    }
+    pool.shutdown();
+    println!("=== test_queue_full_retry_after PASSED ===\n");
+    }).await;
+}
 
-    let result = pool
-        .retrieve_async(&key, Duration::from_secs(5))
-        .await
-        .expect("Failed to retrieve");
-    assert_eq!(result, 3);
+/// Test a hung task is cancelled by the per-task timeout and frees its worker
+#[tokio::test]
+async fn test_per_task_timeout() {
+    with_timeout("test_per_task_timeout", 15, async {
+    println!("\n=== test_per_task_timeout ===");
 
-    println!("First retrieval succeeded: {}", result);
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(1)
+        .with_max_units(10)
+        .with_per_task_timeout_ms(200);
 
-    // Second retrieval should fail (result already consumed)
-    let result2 = pool.retrieve_async(&key, Duration::from_millis(100)).await;
+    let pool = WorkerPool::new(config, SleepExecutor).expect("Failed to create pool");
 
-    match result2 {
-        Err(PoolError::Timeout) | Err(PoolError::ResultNotFound) => {
-            println!("Second retrieval correctly failed");
-        }
-        Ok(v) => {
-            panic!("Should not get result twice, got: {}", v);
-        }
-        Err(e) => {
-            println!("Got error (acceptable): {:?}", e);
-        }
+    // The only worker picks up a task that would run for a minute
+    let start = Instant::now();
+    let hung = pool.submit_async(60_000, make_meta(1, 10)).await.unwrap();
+    let next = pool.submit_async(10, make_meta(2, 10)).await.unwrap();
+
+    match pool.retrieve_async(&hung, Duration::from_secs(5)).await {
+        Err(PoolError::Timeout { key }) => assert_eq!(key, hung),
+        other => panic!("Expected Timeout, got {:?}", other),
     }
 
-    eprintln!("[CLEANUP] test_result_consumed_once shutting down pool");
+    // The worker moved on to the next task
+    let result = pool.retrieve_async(&next, Duration::from_secs(5)).await;
+    assert_eq!(result.unwrap(), 10);
+    println!("Recovered after {:?}", start.elapsed());
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    let stats = pool.stats();
+    assert_eq!(stats.used_units, 0);
+    assert_eq!(stats.failed_tasks, 1);
+    assert_eq!(stats.completed_tasks, 1);
+
     pool.shutdown();
-    eprintln!("[CLEANUP] test_result_consumed_once shutdown complete");
-    println!("=== test_result_consumed_once PASSED ===\n");
+    println!("=== test_per_task_timeout PASSED ===\n");
+    }).await;
+}
+
+/// Test a task handle behaves like submit + retrieve, and can cancel a queued task
+#[tokio::test]
+async fn test_submit_handle() {
+    with_timeout("test_submit_handle", 10, async {
+    println!("\n=== test_submit_handle ===");
+
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(1)
+        .with_max_units(10);
+    let pool = WorkerPool::new(config, SleepExecutor).expect("Failed to create pool");
+
+    let key = pool.submit(20, make_meta(1, 10)).unwrap();
+    let expected = pool.retrieve_async(&key, Duration::from_secs(5)).await.unwrap();
+    let handle = pool.submit_handle(20, make_meta(2, 10)).unwrap();
+    assert_eq!(handle.await_result(Duration::from_secs(5)).await.unwrap(), expected);
+
+    // The only worker is busy, so the next task waits in the queue
+    let running = pool.submit_handle(300, make_meta(3, 10)).unwrap();
+    while pool.stats().active_tasks == 0 {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    let queued = pool.submit_handle(10, make_meta(4, 10)).unwrap();
+    assert!(matches!(running.try_result(), Ok(None)));
+    let queued_key = queued.key().clone();
+    assert!(queued.cancel());
+    assert!(pool.queued_keys().is_empty());
+    match pool.retrieve_async(&queued_key, Duration::from_millis(100)).await {
+        Err(PoolError::ResultNotFound { .. }) => {}
+        other => panic!("Expected ResultNotFound, got {:?}", other),
+    }
+
+    // A running task can't be cancelled
+    assert!(!pool.cancel(running.key()));
+    assert_eq!(running.await_result(Duration::from_secs(5)).await.unwrap(), 300);
+    assert_eq!(pool.stats().queued_tasks, 0);
+
+    pool.shutdown();
+    println!("=== test_submit_handle PASSED ===\n");
+    }).await;
+}
+
+/// Test cancelling a tenant removes only its queued tasks
+#[tokio::test]
+async fn test_cancel_tenant() {
+    with_timeout("test_cancel_tenant", 10, async {
+    println!("\n=== test_cancel_tenant ===");
+
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(1)
+        .with_max_units(10);
+    let pool = WorkerPool::new(config, SleepExecutor).expect("Failed to create pool");
+    let tenant_meta = |id: u64, tenant: &str| TaskMetadata {
+        mailbox: Some(MailboxKey {
+            tenant: tenant.to_string(),
+            user_id: None,
+            session_id: None,
+        }),
+        ..make_meta(id, 10)
+    };
+
+    // Occupy the only worker with a tenant "a" task, then queue behind it
+    let running = pool.submit(200, tenant_meta(1, "a")).unwrap();
+    while pool.stats().active_tasks == 0 {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    let a_queued: Vec<_> = (2..5)
+        .map(|id| pool.submit(10, tenant_meta(id, "a")).unwrap())
+        .collect();
+    let b_queued: Vec<_> = (5..7)
+        .map(|id| pool.submit(20, tenant_meta(id, "b")).unwrap())
+        .collect();
+    let dependent = pool
+        .submit(10, TaskMetadata { depends_on: vec![2], ..tenant_meta(7, "a") })
+        .unwrap();
+    assert_eq!(pool.queued_keys().len(), 6);
+
+    assert_eq!(pool.cancel_tenant("a"), 4);
+    assert_eq!(pool.cancel_tenant("unknown"), 0);
+    assert_eq!(pool.queued_keys(), b_queued);
+    for key in a_queued.iter().chain([&dependent]) {
+        match pool.try_retrieve(key) {
+            Err(PoolError::ResultNotFound { .. }) => {}
+            other => panic!("Expected ResultNotFound, got {:?}", other),
+        }
+    }
+
+    // The running task and the other tenant's tasks still complete
+    assert_eq!(pool.retrieve_async(&running, Duration::from_secs(5)).await.unwrap(), 200);
+    for key in &b_queued {
+        assert_eq!(pool.retrieve_async(key, Duration::from_secs(5)).await.unwrap(), 20);
+    }
+    assert_eq!(pool.stats().queued_tasks, 0);
+
+    pool.shutdown();
+    println!("=== test_cancel_tenant PASSED ===\n");
+    }).await;
+}
+
+/// Test a result submitted under a chosen key is retrievable by exactly that key
+#[tokio::test]
+async fn test_submit_with_key() {
+    with_timeout("test_submit_with_key", 10, async {
+    println!("\n=== test_submit_with_key ===");
+
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(1)
+        .with_max_units(10);
+    let pool = WorkerPool::new(config, SleepExecutor).expect("Failed to create pool");
+    let session_key = |tenant: &str, session: &str| MailboxKey {
+        tenant: tenant.to_string(),
+        user_id: Some("user-1".to_string()),
+        session_id: Some(session.to_string()),
+    };
+
+    let key = session_key("acme", "chat-1");
+    assert_eq!(pool.submit_with_key(50, make_meta(1, 10), key.clone()).unwrap(), key);
+    // The key stays taken until its result is retrieved
+    match pool.submit_with_key(60, make_meta(2, 10), key.clone()) {
+        Err(PoolError::KeyInUse { key: in_use }) => assert_eq!(in_use, key),
+        other => panic!("Expected KeyInUse, got {:?}", other),
+    }
+    match pool.try_retrieve(&session_key("acme", "chat-2")) {
+        Err(PoolError::ResultNotFound { .. }) => {}
+        other => panic!("Expected ResultNotFound, got {:?}", other),
+    }
+    assert_eq!(pool.retrieve_async(&key, Duration::from_secs(5)).await.unwrap(), 50);
+
+    // A session named in the task's metadata is used as its key too
+    let meta = TaskMetadata {
+        mailbox: Some(key.clone()),
+        ..make_meta(3, 10)
+    };
+    assert_eq!(pool.submit_async(70, meta).await.unwrap(), key);
+    assert_eq!(pool.retrieve_async(&key, Duration::from_secs(5)).await.unwrap(), 70);
+
+    pool.shutdown();
+    println!("=== test_submit_with_key PASSED ===\n");
+    }).await;
+}
+
+/// Test zero-cost tasks are rejected rather than bypassing the unit budget
+#[tokio::test]
+async fn test_zero_cost_task_rejected() {
+    with_timeout("test_zero_cost_task_rejected", 10, async {
+    println!("\n=== test_zero_cost_task_rejected ===");
+
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(1)
+        .with_max_units(10);
+    let pool = WorkerPool::new(config, SleepExecutor).expect("Failed to create pool");
+
+    for id in 0..5 {
+        match pool.submit_async(10, make_meta(id, 0)).await {
+            Err(PoolError::InvalidTask(msg)) => assert!(msg.contains("at least 1 unit")),
+            other => panic!("Expected InvalidTask, got {:?}", other),
+        }
+    }
+    let stats = pool.stats();
+    assert_eq!(stats.submitted_tasks, 0);
+    assert_eq!(stats.queued_tasks, 0);
+
+    pool.shutdown();
+    println!("=== test_zero_cost_task_rejected PASSED ===\n");
+    }).await;
+}
+
+/// Test that tasks costing more than the pool's units are rejected, not queued
+#[tokio::test]
+async fn test_oversized_task_rejected() {
+    with_timeout("test_oversized_task_rejected", 10, async {
+    println!("\n=== test_oversized_task_rejected ===");
+
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(1)
+        .with_max_units(100);
+    let pool = WorkerPool::new(config, SleepExecutor).expect("Failed to create pool");
+
+    match pool.submit_async(10, make_meta(1, 200)).await {
+        Err(PoolError::InsufficientCapacity { requested, available }) => {
+            assert_eq!((requested, available), (200, 100));
+        }
+        other => panic!("Expected InsufficientCapacity, got {:?}", other),
+    }
+    let stats = pool.stats();
+    assert_eq!(stats.submitted_tasks, 0);
+    assert_eq!(stats.queued_tasks, 0);
+
+    // A task using the whole pool still runs
+    let key = pool.submit_async(10, make_meta(2, 100)).await.unwrap();
+    assert_eq!(pool.retrieve_async(&key, Duration::from_secs(5)).await.unwrap(), 10);
+
+    pool.shutdown();
+    println!("=== test_oversized_task_rejected PASSED ===\n");
+    }).await;
+}
+
+/// Test that max_units can be lowered and raised while the pool runs
+#[tokio::test]
+async fn test_set_max_units() {
+    with_timeout("test_set_max_units", 10, async {
+    println!("\n=== test_set_max_units ===");
+
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(3)
+        .with_max_units(20);
+    let pool = WorkerPool::new(config, SleepExecutor).expect("Failed to create pool");
+    let running = pool.submit_async(200, make_meta(1, 10)).await.unwrap();
+    let also_running = pool.submit_async(200, make_meta(2, 10)).await.unwrap();
+    // Waits for units even though a worker is free
+    let oversized = pool.submit_async(10, make_meta(3, 15)).await.unwrap();
+    // Parked until the first task finishes
+    let mut parked_meta = make_meta(7, 15);
+    parked_meta.depends_on = vec![1];
+    let parked = pool.submit_async(10, parked_meta).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(pool.stats().used_units, 20);
+    assert_eq!(pool.stats().queued_tasks, 2);
+
+    // Lowering keeps running work, drops queued and parked tasks that can
+    // never start and refuses new ones that no longer fit
+    pool.set_max_units(5);
+    assert_eq!(pool.max_units(), 5);
+    assert_eq!(pool.stats().total_units, 5);
+    assert_eq!(pool.stats().queued_tasks, 0);
+    assert!(pool.retrieve(&oversized, Duration::from_millis(50)).is_err());
+    assert!(pool.retrieve(&parked, Duration::from_millis(50)).is_err());
+    assert!(matches!(
+        pool.submit_async(10, make_meta(4, 10)).await,
+        Err(PoolError::InsufficientCapacity { requested: 10, available: 5 })
+    ));
+
+    // Tasks that fit wait until usage drops under the new limit
+    let held = pool.submit_async(10, make_meta(5, 5)).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(pool.stats().queued_tasks, 1);
+    let timeout = Duration::from_secs(5);
+    assert_eq!(pool.retrieve_async(&running, timeout).await.unwrap(), 200);
+    assert_eq!(pool.retrieve_async(&also_running, timeout).await.unwrap(), 200);
+    assert_eq!(pool.retrieve_async(&held, timeout).await.unwrap(), 10);
+
+    // Raising it admits them again
+    pool.set_max_units(20);
+    let key = pool.submit_async(10, make_meta(6, 10)).await.unwrap();
+    assert_eq!(pool.retrieve_async(&key, timeout).await.unwrap(), 10);
+    assert_eq!(pool.stats().total_units, 20);
+
+    pool.shutdown();
+    println!("=== test_set_max_units PASSED ===\n");
+    }).await;
+}
+
+/// Test the load factor tracks running and queued units
+#[tokio::test]
+async fn test_load_factor() {
+    with_timeout("test_load_factor", 10, async {
+    println!("\n=== test_load_factor ===");
+
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(1)
+        .with_max_units(10)
+        .with_overload_threshold(1.2);
+    let pool = WorkerPool::new(config, SleepExecutor).expect("Failed to create pool");
+    assert_eq!(pool.load_factor(), 0.0);
+
+    let running = pool.submit_async(300, make_meta(1, 10)).await.unwrap();
+    while pool.stats().active_tasks == 0 {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    assert_eq!(pool.load_factor(), 1.0);
+    assert!(!pool.is_overloaded());
+
+    let queued = pool.submit_async(10, make_meta(2, 5)).await.unwrap();
+    println!("Load with a task queued: {}", pool.load_factor());
+    assert_eq!(pool.load_factor(), 1.5);
+    assert!(pool.is_overloaded());
+
+    pool.retrieve_async(&running, Duration::from_secs(5)).await.unwrap();
+    pool.retrieve_async(&queued, Duration::from_secs(5)).await.unwrap();
+    while pool.stats().completed_tasks < 2 {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    assert_eq!(pool.load_factor(), 0.0);
+    assert!(!pool.is_overloaded());
+
+    pool.shutdown();
+    println!("=== test_load_factor PASSED ===\n");
+    }).await;
+}
+
+/// Test used units are broken down by resource kind
+#[tokio::test]
+async fn test_used_units_by_kind() {
+    with_timeout("test_used_units_by_kind", 10, async {
+    println!("\n=== test_used_units_by_kind ===");
+
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(2)
+        .with_max_units(100);
+    let pool = WorkerPool::new(config, SleepExecutor).expect("Failed to create pool");
+
+    let cpu = pool.submit_async(300, make_meta(1, 10)).await.unwrap();
+    let gpu = pool.submit_async(300, make_gpu_meta(2, 30)).await.unwrap();
+    while pool.stats().used_units < 40 {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let by_kind = pool.stats().used_units_by_kind;
+    println!("Units by kind while running: {:?}", by_kind);
+    assert_eq!(by_kind[&ResourceKind::Cpu], 10);
+    assert_eq!(by_kind[&ResourceKind::GpuVram], 30);
+    assert_eq!(by_kind[&ResourceKind::Io], 0);
+
+    pool.retrieve_async(&cpu, Duration::from_secs(5)).await.unwrap();
+    pool.retrieve_async(&gpu, Duration::from_secs(5)).await.unwrap();
+    while pool.stats().completed_tasks < 2 {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    let by_kind = pool.stats().used_units_by_kind;
+    assert!(by_kind.values().all(|&units| units == 0));
+
+    pool.shutdown();
+    println!("=== test_used_units_by_kind PASSED ===\n");
+    }).await;
+}
+
+/// Test the shutdown join timeout detaches a busy worker or waits for it
+#[tokio::test]
+async fn test_shutdown_timeout() {
+    with_timeout("test_shutdown_timeout", 15, async {
+    println!("\n=== test_shutdown_timeout ===");
+
+    // A short timeout detaches a worker stuck in a long task
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(1)
+        .with_max_units(10)
+        .with_shutdown_timeout_ms(100);
+    let pool = WorkerPool::new(config, SleepExecutor).expect("Failed to create pool");
+    pool.submit_async(5_000, make_meta(1, 10)).await.unwrap();
+    while pool.stats().used_units == 0 {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let start = Instant::now();
+    pool.shutdown();
+    println!("Detached after {:?}", start.elapsed());
+    assert!(start.elapsed() < Duration::from_secs(2));
+
+    // A generous timeout lets the running task finish
+    let pool = WorkerPool::new(
+        WorkerPoolConfig::new().with_worker_count(1).with_max_units(10),
+        SleepExecutor,
+    )
+    .expect("Failed to create pool");
+    let key = pool.submit_async(300, make_meta(2, 10)).await.unwrap();
+    while pool.stats().used_units == 0 {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    pool.shutdown_with_timeout(Duration::from_secs(5));
+    assert_eq!(pool.stats().completed_tasks, 1);
+    let result = pool.retrieve_async(&key, Duration::from_secs(1)).await;
+    assert_eq!(result.unwrap(), 300);
+
+    println!("=== test_shutdown_timeout PASSED ===\n");
+    }).await;
+}
+
+/// Test the async close awaits the workers and respects its timeout
+#[tokio::test]
+async fn test_async_close() {
+    with_timeout("test_async_close", 15, async {
+    println!("\n=== test_async_close ===");
+
+    // Running tasks finish before the workers exit
+    let config = WorkerPoolConfig::new().with_worker_count(2).with_max_units(10);
+    let pool = WorkerPool::new(config, SleepExecutor).expect("Failed to create pool");
+    for i in 0..2 {
+        pool.submit_async(200, make_meta(i, 5)).await.unwrap();
+    }
+    while pool.stats().used_units < 10 {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(pool.close(Duration::from_secs(5)).await);
+    assert_eq!(pool.stats().completed_tasks, 2);
+    // Closing again is a no-op
+    assert!(pool.close(Duration::from_secs(5)).await);
+
+    // A worker stuck in a long task is detached once the timeout passes
+    let config = WorkerPoolConfig::new().with_worker_count(1).with_max_units(10);
+    let pool = WorkerPool::new(config, SleepExecutor).expect("Failed to create pool");
+    pool.submit_async(5_000, make_meta(5, 10)).await.unwrap();
+    while pool.stats().used_units == 0 {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let start = Instant::now();
+    assert!(!pool.close(Duration::from_millis(100)).await);
+    println!("Detached after {:?}", start.elapsed());
+    assert!(start.elapsed() < Duration::from_secs(2));
+
+    println!("=== test_async_close PASSED ===\n");
+    }).await;
+}
+
+/// Test retained results can be retrieved by several consumers until they expire
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_result_retention() {
+    with_timeout("test_result_retention", 10, async {
+    println!("\n=== test_result_retention ===");
+
+    let config = WorkerPoolConfig::new().with_worker_count(2).with_max_units(10);
+    let pool = WorkerPool::new(config, SleepExecutor)
+        .expect("Failed to create pool")
+        .with_result_retention(Duration::from_millis(300));
+
+    // Two consumers wait on the same key concurrently
+    let key = pool.submit_async(50, make_meta(1, 1)).await.unwrap();
+    let (first, second) = tokio::join!(
+        pool.retrieve_async(&key, Duration::from_secs(2)),
+        pool.retrieve_async(&key, Duration::from_secs(2)),
+    );
+    assert_eq!(first.unwrap(), 50);
+    assert_eq!(second.unwrap(), 50);
+    assert_eq!(pool.retrieve(&key, Duration::from_millis(100)).unwrap(), 50);
+    assert_eq!(pool.try_retrieve(&key).unwrap(), Some(50));
+
+    // Once the retention expires the result is gone
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    assert!(matches!(pool.try_retrieve(&key), Err(PoolError::ResultNotFound { .. })));
+
+    // Without retention the first retrieval takes the result
+    let pool = WorkerPool::new(
+        WorkerPoolConfig::new().with_worker_count(1).with_max_units(10),
+        SleepExecutor,
+    )
+    .expect("Failed to create pool");
+    let key = pool.submit_async(10, make_meta(2, 1)).await.unwrap();
+    assert_eq!(pool.retrieve_async(&key, Duration::from_secs(2)).await.unwrap(), 10);
+    assert!(matches!(
+        pool.retrieve_async(&key, Duration::from_millis(100)).await,
+        Err(PoolError::ResultNotFound { .. })
+    ));
+
+    println!("=== test_result_retention PASSED ===\n");
+    }).await;
+}
+
+/// Test the completion hook fires once per task with its key and metadata
+#[tokio::test]
+async fn test_on_complete_hook() {
+    with_timeout("test_on_complete_hook", 10, async {
+    println!("\n=== test_on_complete_hook ===");
+
+    let completed = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let recorder = Arc::clone(&completed);
+    let config = WorkerPoolConfig::new().with_worker_count(2).with_max_units(100);
+    let pool = WorkerPool::new(config, CountingExecutor::new())
+        .expect("Failed to create pool")
+        .with_on_complete(move |key: &MailboxKey, meta: &TaskMetadata| {
+            recorder.lock().unwrap().push((key.clone(), meta.id, meta.cost.units));
+        });
+
+    let mut expected = Vec::new();
+    for id in 0..5 {
+        let key = pool.submit(id, make_meta(id, 3)).expect("Failed to submit");
+        expected.push((key, id, 3));
+    }
+    // The hook runs before the result is published, so it has fired by now
+    for (key, id, _) in &expected {
+        assert_eq!(pool.retrieve(key, Duration::from_secs(5)).unwrap(), id * 2);
+    }
+
+    let mut fired = completed.lock().unwrap().clone();
+    fired.sort_by_key(|(_, id, _)| *id);
+    assert_eq!(fired, expected);
+
+    pool.shutdown();
+    println!("=== test_on_complete_hook PASSED ===\n");
+    }).await;
+}
+
+/// Test a non-Clone executor shared by all workers through an Arc
+#[tokio::test]
+async fn test_arc_shared_executor() {
+    with_timeout("test_arc_shared_executor", 10, async {
+    println!("\n=== test_arc_shared_executor ===");
+
+    let executor = Arc::new(ModelExecutor {
+        weights: (0..=255).cycle().take(1 << 20).collect(),
+        loads: AtomicU64::new(0),
+    });
+    let config = WorkerPoolConfig::new().with_worker_count(4).with_max_units(10);
+    let pool = WorkerPool::new(config, Arc::clone(&executor)).expect("Failed to create pool");
+
+    let mut keys = Vec::new();
+    for id in 0..8 {
+        keys.push(pool.submit_async(id as usize * 1000, make_meta(id, 1)).await.unwrap());
+    }
+    for (id, key) in keys.iter().enumerate() {
+        let result = pool.retrieve_async(key, Duration::from_secs(5)).await.unwrap();
+        assert_eq!(result, ((id * 1000) % 256) as u8);
+    }
+
+    // Every worker ran against the same instance
+    assert_eq!(executor.loads.load(Ordering::SeqCst), 8);
+
+    pool.shutdown();
+    println!("=== test_arc_shared_executor PASSED ===\n");
+    }).await;
+}
+
+/// Test a pool built around an async closure instead of an executor type
+#[tokio::test]
+async fn test_fn_executor() {
+    with_timeout("test_fn_executor", 10, async {
+    println!("\n=== test_fn_executor ===");
+
+    let calls = Arc::new(AtomicU64::new(0));
+    let counter = Arc::clone(&calls);
+    let executor = FnExecutor::new(move |word: String, meta| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            format!("{}:{}", meta.id, word.to_uppercase())
+        }
+    });
+    let config = WorkerPoolConfig::new().with_worker_count(2).with_max_units(10);
+    let pool = WorkerPool::new(config, executor).expect("Failed to create pool");
+
+    let words = ["park", "lot", "queue"];
+    let mut keys = Vec::new();
+    for (id, word) in words.iter().enumerate() {
+        keys.push(pool.submit_async(word.to_string(), make_meta(id as u64, 1)).await.unwrap());
+    }
+    for (id, key) in keys.iter().enumerate() {
+        let result = pool.retrieve_async(key, Duration::from_secs(5)).await.unwrap();
+        assert_eq!(result, format!("{}:{}", id, words[id].to_uppercase()));
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    pool.shutdown();
+    println!("=== test_fn_executor PASSED ===\n");
+    }).await;
+}
+
+/// Test that a routing executor sends each task to its model's executor
+#[tokio::test]
+async fn test_routing_executor() {
+    with_timeout("test_routing_executor", 10, async {
+    println!("\n=== test_routing_executor ===");
+
+    let (model_a, a_calls) = BackendExecutor::new("A");
+    let (model_b, b_calls) = BackendExecutor::new("B");
+    let (fallback, fallback_calls) = BackendExecutor::new("default");
+    let router = RoutingExecutor::new(|(model, _): &(String, u64)| model.clone())
+        .register("A".to_string(), model_a)
+        .register("B".to_string(), model_b)
+        .with_fallback(fallback);
+    assert!(router.routes_to(&"A".to_string()));
+    assert!(!router.routes_to(&"C".to_string()));
+
+    let config = WorkerPoolConfig::new().with_worker_count(2).with_max_units(10);
+    let pool = WorkerPool::new(config, router).expect("Failed to create pool");
+
+    let jobs = [("A", 1), ("B", 2), ("A", 3), ("C", 4)];
+    let mut keys = Vec::new();
+    for (id, (model, prompt)) in jobs.iter().enumerate() {
+        let payload = (model.to_string(), *prompt);
+        keys.push(pool.submit_async(payload, make_meta(id as u64, 1)).await.unwrap());
+    }
+    let mut results = Vec::new();
+    for key in &keys {
+        results.push(pool.retrieve_async(key, Duration::from_secs(5)).await.unwrap());
+    }
+    assert_eq!(results, ["A:1", "B:2", "A:3", "default:4"]);
+    assert_eq!(a_calls.load(Ordering::SeqCst), 2);
+    assert_eq!(b_calls.load(Ordering::SeqCst), 1);
+    assert_eq!(fallback_calls.load(Ordering::SeqCst), 1);
+
+    pool.shutdown();
+    println!("=== test_routing_executor PASSED ===\n");
+    }).await;
+}
+
+/// Test a pool with low-priority worker threads still runs tasks
+#[tokio::test]
+async fn test_low_thread_priority() {
+    with_timeout("test_low_thread_priority", 10, async {
+    println!("\n=== test_low_thread_priority ===");
+
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(2)
+        .with_max_units(10)
+        .with_thread_priority(19);
+    assert!(config.validate().is_ok());
+    assert!(WorkerPoolConfig::new().with_thread_priority(20).validate().is_err());
+
+    let pool = WorkerPool::new(config, SleepExecutor).expect("Failed to create pool");
+    let mut keys = Vec::new();
+    for id in 0..4 {
+        keys.push(pool.submit_async(10, make_meta(id, 1)).await.unwrap());
+    }
+    for key in &keys {
+        let result = pool.retrieve_async(key, Duration::from_secs(5)).await;
+        assert_eq!(result.unwrap(), 10);
+    }
+
+    pool.shutdown();
+    println!("=== test_low_thread_priority PASSED ===\n");
+    }).await;
+}
+
+/// Test the queue depth limit holds under concurrent submissions
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_queue_depth_limit_concurrent() {
+    with_timeout("test_queue_depth_limit_concurrent", 15, async {
+    println!("\n=== test_queue_depth_limit_concurrent ===");
+
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(1)
+        .with_max_units(10) // Only 1 task at a time
+        .with_max_queue_depth(2);
+
+    let pool = Arc::new(
+        WorkerPool::new(config, SlowExecutor::new(500)).expect("Failed to create pool"),
+    );
+
+    // Race many submissions against the depth check
+    let handles: Vec<_> = (0..64u64)
+        .map(|i| {
+            let pool = Arc::clone(&pool);
+            tokio::spawn(async move { pool.submit_async((), make_meta(i, 10)).await })
+        })
+        .collect();
+
+    let mut keys = Vec::new();
+    let mut rejected = 0;
+    for handle in handles {
+        match handle.await.expect("submit task panicked") {
+            Ok(key) => keys.push(key),
+            Err(PoolError::QueueFull { .. }) => rejected += 1,
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    println!("Accepted {} tasks, rejected {}", keys.len(), rejected);
+
+    // At most 1 running + 2 queued, however the submissions interleave
+    assert!(keys.len() <= 3, "Too many tasks accepted: {}", keys.len());
+    assert_eq!(keys.len() + rejected, 64);
+
+    for key in keys {
+        let _ = pool.retrieve_async(&key, Duration::from_secs(5)).await;
+    }
+
+    pool.shutdown();
+    println!("=== test_queue_depth_limit_concurrent PASSED ===\n");
+    }).await;
+}
+
+/// Test multiple result retrievals for same key
+#[tokio::test]
+async fn test_result_consumed_once() {
+    with_timeout("test_result_consumed_once", 10, async {
+    println!("\n=== test_result_consumed_once ===");
+
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(1)
+        .with_max_units(100)
+        .with_max_queue_depth(10);
+
+    let pool = WorkerPool::new(config, AddExecutor).expect("Failed to create pool");
+
+    // Submit and retrieve
+    let meta = make_meta(1, 10);
+    let key = pool
+        .submit_async((1, 2), meta)
+        .await
+        .expect("Failed to submit");
+
+    let result = pool
+        .retrieve_async(&key, Duration::from_secs(5))
+        .await
+        .expect("Failed to retrieve");
+    assert_eq!(result, 3);
+
+    println!("First retrieval succeeded: {}", result);
+
+    // Second retrieval should fail (result already consumed)
+    let result2 = pool.retrieve_async(&key, Duration::from_millis(100)).await;
+
+    match result2 {
+        Err(PoolError::Timeout { key: failed }) | Err(PoolError::ResultNotFound { key: failed }) => {
+            assert_eq!(failed, key);
+            println!("Second retrieval correctly failed");
+        }
+        Ok(v) => {
+            panic!("Should not get result twice, got: {}", v);
+        }
+        Err(e) => {
+            println!("Got error (acceptable): {:?}", e);
+        }
+    }
+
+    eprintln!("[CLEANUP] test_result_consumed_once shutting down pool");
+    pool.shutdown();
+    eprintln!("[CLEANUP] test_result_consumed_once shutdown complete");
+    println!("=== test_result_consumed_once PASSED ===\n");
+    }).await;
+}
+
+/// Test that retryable failures are retried with backoff until success
+#[tokio::test]
+async fn test_retry_with_backoff() {
+    with_timeout("test_retry_with_backoff", 10, async {
+    println!("\n=== test_retry_with_backoff ===");
+
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(1)
+        .with_max_units(100)
+        .with_max_queue_depth(10)
+        .with_retry_policy(RetryPolicy {
+            max_attempts: 3,
+            base_delay_ms: 10,
+            max_delay_ms: 100,
+            backoff_factor: 2.0,
+            ..RetryPolicy::default()
+        });
+
+    let executor = FlakyExecutor::new(2);
+    let pool = WorkerPool::new_retryable(config, executor.clone())
+        .expect("Failed to create pool");
+
+    let start = Instant::now();
+    let key = pool
+        .submit_async("job".to_string(), make_meta(1, 10))
+        .await
+        .expect("Failed to submit");
+
+    let result = pool
+        .retrieve_async(&key, Duration::from_secs(5))
+        .await
+        .expect("Failed to retrieve");
+    let elapsed = start.elapsed();
+
+    println!("Result: {:?} after {:?}", result, elapsed);
+    assert_eq!(result, Ok("job ok after 3 attempts".to_string()));
+    // Backoff of 10ms then 20ms between the three attempts
+    assert!(elapsed >= Duration::from_millis(30));
+
+    let stats = pool.stats();
+    println!("Final stats: {:?}", stats);
+    assert_eq!(stats.total_attempts, 3);
+    assert_eq!(stats.retried_tasks, 1);
+    assert_eq!(stats.completed_tasks, 1);
+    assert_eq!(stats.failed_tasks, 0);
+
+    pool.shutdown();
+    println!("=== test_retry_with_backoff PASSED ===\n");
+    }).await;
+}
+
+/// Test that exhausting the retry budget records a failure with the last result
+#[tokio::test]
+async fn test_retry_exhausted() {
+    with_timeout("test_retry_exhausted", 10, async {
+    println!("\n=== test_retry_exhausted ===");
+
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(1)
+        .with_max_units(100)
+        .with_max_queue_depth(10)
+        .with_retry_policy(RetryPolicy {
+            max_attempts: 2,
+            base_delay_ms: 1,
+            max_delay_ms: 10,
+            backoff_factor: 2.0,
+            ..RetryPolicy::default()
+        });
+
+    let pool = WorkerPool::new_retryable(config, FlakyExecutor::new(5))
+        .expect("Failed to create pool");
+
+    let key = pool
+        .submit_async("job".to_string(), make_meta(1, 10))
+        .await
+        .expect("Failed to submit");
+
+    let result = pool
+        .retrieve_async(&key, Duration::from_secs(5))
+        .await
+        .expect("Failed to retrieve");
+
+    assert_eq!(result, Err("transient failure #2".to_string()));
+
+    let stats = pool.stats();
+    assert_eq!(stats.total_attempts, 2);
+    assert_eq!(stats.retried_tasks, 1);
+    assert_eq!(stats.completed_tasks, 0);
+    assert_eq!(stats.failed_tasks, 1);
+
+    // A retry policy is rejected when the payload cannot be cloned
+    let config = WorkerPoolConfig::new().with_retry_policy(RetryPolicy {
+        max_attempts: 2,
+        ..RetryPolicy::default()
+    });
+    assert!(matches!(
+        WorkerPool::new(config, FlakyExecutor::new(0)),
+        Err(PoolError::InvalidConfig(_))
+    ));
+
+    pool.shutdown();
+    println!("=== test_retry_exhausted PASSED ===\n");
+    }).await;
+}
+
+/// Test that a task queued behind a blocker reports the blocker's run as its wait
+#[tokio::test]
+async fn test_wait_and_exec_times() {
+    with_timeout("test_wait_and_exec_times", 10, async {
+    println!("\n=== test_wait_and_exec_times ===");
+
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(1)
+        .with_max_units(100)
+        .with_max_queue_depth(10);
+    let pool = WorkerPool::new(config, SleepExecutor).expect("Failed to create pool");
+    let stats = pool.stats();
+    assert_eq!((stats.p99_wait_ms, stats.p99_exec_ms), (0, 0));
+
+    let blocker = pool.submit(300, make_meta(1, 10)).expect("Failed to submit");
+    let queued = pool.submit(10, make_meta(2, 10)).expect("Failed to submit");
+    let timeout = Duration::from_secs(5);
+    assert_eq!(pool.retrieve_async(&blocker, timeout).await.unwrap(), 300);
+    assert_eq!(pool.retrieve_async(&queued, timeout).await.unwrap(), 10);
+
+    // The blocker started at once; the queued task waited out its run
+    let stats = pool.stats();
+    println!("wait avg/p99: {}/{}, exec avg/p99: {}/{}",
+        stats.avg_wait_ms, stats.p99_wait_ms, stats.avg_exec_ms, stats.p99_exec_ms);
+    assert!((280..1000).contains(&stats.p99_wait_ms), "wait {}", stats.p99_wait_ms);
+    assert!((140.0..500.0).contains(&stats.avg_wait_ms));
+    assert!((300..1000).contains(&stats.p99_exec_ms), "exec {}", stats.p99_exec_ms);
+    assert!(stats.avg_exec_ms >= 155.0);
+
+    pool.shutdown();
+    println!("=== test_wait_and_exec_times PASSED ===\n");
+    }).await;
+}
+
+/// Test that tasks running past the slow-task threshold are logged and counted
+#[tokio::test]
+#[traced_test]
+async fn test_slow_task_threshold() {
+    with_timeout("test_slow_task_threshold", 10, async {
+    println!("\n=== test_slow_task_threshold ===");
+
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(1)
+        .with_max_units(100)
+        .with_slow_task_threshold_ms(100);
+    let pool = WorkerPool::new(config, SleepExecutor).expect("Failed to create pool");
+
+    let fast = pool.submit_async(10, make_meta(1, 10)).await.unwrap();
+    let slow = pool.submit_async(250, make_meta(2, 7)).await.unwrap();
+    let timeout = Duration::from_secs(5);
+    assert_eq!(pool.retrieve_async(&fast, timeout).await.unwrap(), 10);
+    assert_eq!(pool.retrieve_async(&slow, timeout).await.unwrap(), 250);
+    assert_eq!(pool.stats().slow_tasks, 1);
+
+    // The worker thread logs outside this test's span, so match on the
+    // pool's target rather than with `logs_contain`
+    tracing_test::internal::logs_assert("prometheus_parking_lot::core::worker_pool", |lines| {
+        let slow: Vec<_> = lines.iter().filter(|line| line.contains("Slow task")).collect();
+        match slow.as_slice() {
+            [line] if line.contains("task_id=2") && line.contains("cost=7") => Ok(()),
+            _ => Err(format!("unexpected slow task warnings: {slow:?}")),
+        }
+    })
+    .unwrap();
+
+    pool.shutdown();
+    println!("=== test_slow_task_threshold PASSED ===\n");
+    }).await;
+}
+
+/// Test that a task that stops reporting progress is listed as stale
+#[tokio::test]
+async fn test_stale_tasks() {
+    with_timeout("test_stale_tasks", 10, async {
+    println!("\n=== test_stale_tasks ===");
+
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(1)
+        .with_max_units(100)
+        .with_max_queue_depth(10);
+    let pool = WorkerPool::new(config, StallingExecutor).expect("Failed to create pool");
+    let window = Duration::from_millis(150);
+
+    let key = pool.submit(1000, make_meta(1, 10)).expect("Failed to submit");
+    // Queued and freshly started tasks are not stale
+    assert!(pool.stale_tasks(window).is_empty());
+
+    // Once the executor goes silent the task shows up after the window
+    let started = Instant::now();
+    while pool.stale_tasks(window).is_empty() {
+        assert!(started.elapsed() < Duration::from_secs(1), "task never became stale");
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(started.elapsed() >= window);
+    assert_eq!(pool.stale_tasks(window), vec![key.clone()]);
+    // A wider window still covers the last heartbeat
+    assert!(pool.stale_tasks(Duration::from_secs(5)).is_empty());
+
+    // Listing is read-only: the task still completes normally
+    let result = pool
+        .retrieve_async(&key, Duration::from_secs(5))
+        .await
+        .expect("Failed to retrieve");
+    assert_eq!(result, 1000);
+    assert!(pool.stale_tasks(window).is_empty());
+
+    pool.shutdown();
+    println!("=== test_stale_tasks PASSED ===\n");
+    }).await;
+}
+
+/// Test that `new` returns only once every worker runtime is built
+#[tokio::test]
+async fn test_workers_ready_after_new() {
+    with_timeout("test_workers_ready_after_new", 10, async {
+    println!("\n=== test_workers_ready_after_new ===");
+
+    // Each runtime takes a while to build, as when a hook binds a device
+    let builder_fn: RuntimeBuilderFn = Arc::new(|| {
+        std::thread::sleep(Duration::from_millis(300));
+        tokio::runtime::Builder::new_current_thread()
+    });
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(2)
+        .with_max_units(100)
+        .with_max_queue_depth(10);
+    let started = Instant::now();
+    let pool = WorkerPool::with_runtime_builder(config, SleepExecutor, builder_fn)
+        .expect("Failed to create pool");
+    assert!(started.elapsed() >= Duration::from_millis(300));
+
+    // The first task runs on a ready worker
+    let submitted = Instant::now();
+    let key = pool.submit(1, make_meta(1, 10)).expect("Failed to submit");
+    let result = pool
+        .retrieve_async(&key, Duration::from_secs(5))
+        .await
+        .expect("Failed to retrieve");
+    assert_eq!(result, 1);
+    assert!(submitted.elapsed() < Duration::from_millis(200));
+    pool.shutdown();
+
+    // A worker that can't start fails construction instead of the first task
+    let builder_fn: RuntimeBuilderFn = Arc::new(|| panic!("no device available"));
+    let config = WorkerPoolConfig::new().with_worker_count(1);
+    let err = WorkerPool::with_runtime_builder(config, SleepExecutor, builder_fn)
+        .err()
+        .expect("pool creation should fail");
+    assert!(matches!(err, PoolError::Internal(_)));
+
+    println!("=== test_workers_ready_after_new PASSED ===\n");
+    }).await;
+}
+
+/// Test that a strict pool rejects an id that is still in flight
+#[tokio::test]
+async fn test_duplicate_task_id_rejected() {
+    with_timeout("test_duplicate_task_id_rejected", 10, async {
+    println!("\n=== test_duplicate_task_id_rejected ===");
+
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(1)
+        .with_max_units(100)
+        .with_max_queue_depth(10)
+        .with_enforce_unique_ids(true);
+    let pool = WorkerPool::new(config, SleepExecutor).expect("Failed to create pool");
+
+    // Pool-assigned ids never repeat
+    let id = pool.next_task_id();
+    assert!(pool.next_task_id() > id);
+
+    let key = pool.submit(200, make_meta(id, 10)).expect("Failed to submit");
+    let err = pool.submit(200, make_meta(id, 10)).unwrap_err();
+    assert!(matches!(err, PoolError::DuplicateTaskId { id: dup } if dup == id));
+
+    // Other ids are unaffected
+    let other = pool.submit(1, make_meta(pool.next_task_id(), 10)).expect("Failed to submit");
+
+    let timeout = Duration::from_secs(5);
+    assert_eq!(pool.retrieve_async(&key, timeout).await.unwrap(), 200);
+    assert_eq!(pool.retrieve_async(&other, timeout).await.unwrap(), 1);
+
+    // Once the task has finished its id may be used again
+    let started = Instant::now();
+    let reused = loop {
+        match pool.submit(1, make_meta(id, 10)) {
+            Ok(key) => break key,
+            Err(PoolError::DuplicateTaskId { .. }) => {
+                assert!(started.elapsed() < Duration::from_secs(1), "id never released");
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+            Err(err) => panic!("unexpected error: {err}"),
+        }
+    };
+    assert_eq!(pool.retrieve_async(&reused, timeout).await.unwrap(), 1);
+
+    pool.shutdown();
+    println!("=== test_duplicate_task_id_rejected PASSED ===\n");
+    }).await;
+}
+
+/// Test that the default retrievals wait for the configured timeout, or the
+/// task's own retrieval timeout when it sets one
+#[tokio::test]
+async fn test_retrieve_default_timeout() {
+    with_timeout("test_retrieve_default_timeout", 10, async {
+    println!("\n=== test_retrieve_default_timeout ===");
+
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(2)
+        .with_max_units(100)
+        .with_max_queue_depth(10)
+        .with_timeout_ms(100);
+    let pool = WorkerPool::new(config, SleepExecutor).expect("Failed to create pool");
+
+    let slow = pool.submit_async(2000, make_meta(1, 10)).await.expect("Failed to submit");
+    let started = Instant::now();
+    let err = pool.retrieve_async_default(&slow).await.unwrap_err();
+    let waited = started.elapsed();
+    assert!(matches!(err, PoolError::Timeout { .. }));
+    assert!(waited >= Duration::from_millis(100), "gave up after {:?}", waited);
+    assert!(waited < Duration::from_millis(1000), "waited {:?}", waited);
+
+    // A task's own retrieval timeout takes precedence over the pool's
+    let mut meta = make_meta(2, 10);
+    meta.retrieve_timeout_ms = Some(3000);
+    let patient = pool.submit_async(300, meta).await.expect("Failed to submit");
+    assert_eq!(pool.retrieve_async_default(&patient).await.unwrap(), 300);
+
+    let blocking = pool.submit_async(1, make_meta(3, 10)).await.expect("Failed to submit");
+    assert_eq!(pool.retrieve_default(&blocking).unwrap(), 1);
+
+    pool.shutdown();
+    println!("=== test_retrieve_default_timeout PASSED ===\n");
+    }).await;
+}
+
+/// Test that worker states show which worker holds a running task
+#[tokio::test]
+async fn test_worker_states_show_running_task() {
+    with_timeout("test_worker_states_show_running_task", 10, async {
+    println!("\n=== test_worker_states_show_running_task ===");
+
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(2)
+        .with_max_units(100)
+        .with_max_queue_depth(10);
+    let pool = WorkerPool::new(config, SleepExecutor).expect("Failed to create pool");
+    assert!(pool.worker_states().iter().all(|w| w.current_task.is_none()));
+
+    let before = now_ms();
+    let key = pool.submit_async(400, make_meta(42, 10)).await.expect("Failed to submit");
+    let busy = loop {
+        let states = pool.worker_states();
+        if let Some(busy) = states.iter().find(|w| w.current_task.is_some()) {
+            break busy.clone();
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    };
+    println!("worker {} holds task {:?}", busy.id, busy.current_task);
+    assert_eq!(busy.current_task, Some(42));
+    assert!(busy.started_at_ms.is_some_and(|at| at >= before));
+
+    let states = pool.worker_states();
+    assert_eq!(states.len(), 2);
+    assert_eq!(states.iter().filter(|w| w.current_task.is_some()).count(), 1);
+    assert_eq!(states[busy.id], busy);
+
+    // The slot is cleared once the task finishes
+    assert_eq!(pool.retrieve_async(&key, Duration::from_secs(5)).await.unwrap(), 400);
+    assert!(pool
+        .worker_states()
+        .iter()
+        .all(|w| w.current_task.is_none() && w.started_at_ms.is_none()));
+
+    pool.shutdown();
+    println!("=== test_worker_states_show_running_task PASSED ===\n");
+    }).await;
+}
+
+/// Test that weighted workers only run tasks that fit their capacity
+#[tokio::test]
+async fn test_weighted_workers() {
+    with_timeout("test_weighted_workers", 10, async {
+    println!("\n=== test_weighted_workers ===");
+
+    let config = WorkerPoolConfig::new()
+        .with_worker_units(vec![24, 8])
+        .with_max_units(32)
+        .with_max_queue_depth(10);
+    let pool = WorkerPool::new(config, SleepExecutor).expect("Failed to create pool");
+    let capacities: Vec<_> = pool.worker_states().iter().map(|w| w.capacity_units).collect();
+    assert_eq!(capacities, vec![Some(24), Some(8)]);
+
+    // A 20-unit task lands on the 24-unit worker
+    let large = pool.submit_async(300, make_meta(1, 20)).await.unwrap();
+    let holder = loop {
+        let states = pool.worker_states();
+        if let Some(busy) = states.iter().find(|w| w.current_task.is_some()) {
+            break busy.id;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    };
+    assert_eq!(holder, 0);
+
+    // Another one waits for it even though the 8-unit worker is idle
+    let queued = pool.submit_async(50, make_meta(2, 20)).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(pool.worker_states()[1].current_task, None);
+
+    // while a small task runs on the 8-unit worker
+    let small = pool.submit_async(50, make_meta(3, 5)).await.unwrap();
+    assert_eq!(pool.retrieve_async(&small, Duration::from_secs(5)).await.unwrap(), 50);
+    assert_eq!(pool.worker_states()[0].current_task, Some(1));
+
+    // A task larger than every worker is rejected up front
+    let too_large = pool.submit_async(50, make_meta(4, 30)).await;
+    assert!(matches!(
+        too_large,
+        Err(PoolError::InsufficientCapacity { requested: 30, available: 24 })
+    ));
+
+    assert_eq!(pool.retrieve_async(&large, Duration::from_secs(5)).await.unwrap(), 300);
+    assert_eq!(pool.retrieve_async(&queued, Duration::from_secs(5)).await.unwrap(), 50);
+    pool.shutdown();
+
+    // With only the 8-unit worker, a 20-unit task has nowhere to run
+    let config = WorkerPoolConfig::new().with_worker_units(vec![8]).with_max_units(8);
+    let pool = WorkerPool::new(config, SleepExecutor).expect("Failed to create pool");
+    assert!(matches!(
+        pool.submit_async(50, make_meta(5, 20)).await,
+        Err(PoolError::InsufficientCapacity { requested: 20, available: 8 })
+    ));
+    pool.shutdown();
+
+    println!("=== test_weighted_workers PASSED ===\n");
+    }).await;
+}
+
+/// Test that every task sees the one context set on the pool
+#[tokio::test]
+async fn test_executor_reads_shared_context() {
+    with_timeout("test_executor_reads_shared_context", 10, async {
+    println!("\n=== test_executor_reads_shared_context ===");
+
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(2)
+        .with_max_units(100)
+        .with_max_queue_depth(10);
+    let pool = WorkerPool::new(config, ContextExecutor)
+        .expect("Failed to create pool")
+        .with_context(AtomicU64::new(100));
+
+    let mut keys = Vec::new();
+    for id in 1..=4 {
+        keys.push(pool.submit_async(id, make_meta(id, 10)).await.expect("Failed to submit"));
+    }
+    let timeout = Duration::from_secs(5);
+    let mut totals = Vec::new();
+    for key in &keys {
+        totals.push(pool.retrieve_async(key, timeout).await.expect("Failed to retrieve"));
+    }
+
+    // Each task added to the same counter, so the largest total saw them all
+    assert_eq!(totals.iter().max(), Some(&110));
+    assert!(totals.iter().all(|total| *total > 100));
+
+    pool.shutdown();
+    println!("=== test_executor_reads_shared_context PASSED ===\n");
+    }).await;
+}
+
+/// Test that progress updates are streamed in order before the result
+#[tokio::test]
+async fn test_progress_stream() {
+    with_timeout("test_progress_stream", 10, async {
+    println!("\n=== test_progress_stream ===");
+
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(1)
+        .with_max_units(100)
+        .with_max_queue_depth(10);
+    let pool = WorkerPool::new(config, ProgressExecutor).expect("Failed to create pool");
+
+    let key = pool
+        .submit_async("job".to_string(), make_meta(1, 10))
+        .await
+        .expect("Failed to submit");
+    let progress = pool.progress_stream(&key);
+
+    // All updates arrive before the stream disconnects at task completion
+    let mut updates: Vec<Progress> = Vec::new();
+    while let Ok(update) = progress.recv_async().await {
+        updates.push(update);
+    }
+    let messages: Vec<_> = updates.iter().map(|p| p.message.clone().unwrap()).collect();
+    assert_eq!(messages, vec!["step 1", "step 2", "step 3"]);
+    assert!(updates.windows(2).all(|w| w[0].fraction < w[1].fraction));
+    assert!((updates[2].fraction - 1.0).abs() < f32::EPSILON);
+
+    let result = pool
+        .retrieve_async(&key, Duration::from_secs(5))
+        .await
+        .expect("Failed to retrieve");
+    assert_eq!(result, "job done");
+
+    // The channel is released once the result has been retrieved
+    assert!(pool.progress_stream(&key).recv_async().await.is_err());
+
+    pool.shutdown();
+    println!("=== test_progress_stream PASSED ===\n");
+    }).await;
+}
+
+/// Test the circuit breaker open -> half-open -> closed cycle
+#[tokio::test]
+async fn test_circuit_breaker_transitions() {
+    with_timeout("test_circuit_breaker_transitions", 10, async {
+    println!("\n=== test_circuit_breaker_transitions ===");
+
+    let healthy = Arc::new(AtomicBool::new(false));
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(1)
+        .with_max_units(100)
+        .with_max_queue_depth(10)
+        .with_circuit_breaker(CircuitBreakerConfig {
+            window_size: 3,
+            failure_threshold: 0.5,
+            cooldown_ms: 100,
+        });
+    let pool = WorkerPool::new(config, SwitchableExecutor { healthy: Arc::clone(&healthy) })
+        .expect("Failed to create pool");
+
+    // Runs one task to completion and lets the worker record its outcome
+    let run = |id: u64| {
+        let pool = &pool;
+        async move {
+            let key = pool.submit_async(id, make_meta(id, 10)).await?;
+            let result = pool.retrieve_async(&key, Duration::from_secs(5)).await;
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            result
+        }
+    };
+
+    // A full window of failures trips the breaker
+    for id in 1..=3 {
+        assert_eq!(pool.circuit_state(), CircuitState::Closed);
+        assert!(run(id).await.unwrap().is_err());
+    }
+    assert_eq!(pool.circuit_state(), CircuitState::Open);
+    assert!(matches!(run(4).await, Err(PoolError::CircuitOpen)));
+    assert!(matches!(pool.submit(5, make_meta(5, 10)), Err(PoolError::CircuitOpen)));
+
+    // After the cooldown a failing probe re-opens the circuit
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert_eq!(pool.circuit_state(), CircuitState::HalfOpen);
+    assert!(run(6).await.unwrap().is_err());
+    assert_eq!(pool.circuit_state(), CircuitState::Open);
+    assert!(matches!(run(7).await, Err(PoolError::CircuitOpen)));
+
+    // Once the executor recovers, a successful probe closes it again
+    healthy.store(true, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert_eq!(pool.circuit_state(), CircuitState::HalfOpen);
+    assert_eq!(run(8).await.unwrap(), Ok(8));
+    assert_eq!(pool.circuit_state(), CircuitState::Closed);
+    assert_eq!(run(9).await.unwrap(), Ok(9));
+
+    let stats = pool.stats();
+    assert_eq!(stats.failed_tasks, 4);
+    assert_eq!(stats.completed_tasks, 2);
+
+    pool.shutdown();
+    println!("=== test_circuit_breaker_transitions PASSED ===\n");
+    }).await;
+}
+
+/// Test that a multi-thread worker runtime runs an executor's sub-tasks in parallel
+#[tokio::test]
+async fn test_multi_thread_worker_runtime() {
+    with_timeout("test_multi_thread_worker_runtime", 10, async {
+    println!("\n=== test_multi_thread_worker_runtime ===");
+
+    // Runs one fan-out task of four sub-tasks and reports how long it took
+    let run = |runtime_kind: WorkerRuntimeKind| async move {
+        let config = WorkerPoolConfig::new()
+            .with_worker_count(1)
+            .with_max_units(100)
+            .with_max_queue_depth(10)
+            .with_runtime_kind(runtime_kind);
+        let pool = WorkerPool::new(config, FanOutExecutor).expect("Failed to create pool");
+        let start = Instant::now();
+        let key = pool.submit_async(4, make_meta(1, 10)).await.expect("Failed to submit");
+        let result = pool
+            .retrieve_async(&key, Duration::from_secs(5))
+            .await
+            .expect("Failed to retrieve");
+        let elapsed = start.elapsed();
+        pool.shutdown();
+        assert_eq!(result, 6);
+        elapsed
+    };
+
+    let current = run(WorkerRuntimeKind::CurrentThread).await;
+    let multi = run(WorkerRuntimeKind::MultiThread { threads: 4 }).await;
+    println!("current_thread: {:?}, multi_thread: {:?}", current, multi);
+
+    // A single runtime thread runs the sub-tasks back to back
+    assert!(current >= Duration::from_millis(200));
+    assert!(multi < current);
+
+    assert!(WorkerPoolConfig::new()
+        .with_runtime_kind(WorkerRuntimeKind::MultiThread { threads: 0 })
+        .validate()
+        .is_err());
+
+    println!("=== test_multi_thread_worker_runtime PASSED ===\n");
+    }).await;
+}
+
+/// Test that a caller-supplied runtime builder is used for worker runtimes
+#[tokio::test]
+async fn test_custom_runtime_builder() {
+    with_timeout("test_custom_runtime_builder", 10, async {
+    println!("\n=== test_custom_runtime_builder ===");
+
+    let builder_fn: RuntimeBuilderFn = Arc::new(|| {
+        let mut builder = tokio::runtime::Builder::new_multi_thread();
+        builder
+            .worker_threads(1)
+            .on_thread_start(|| BOUND_DEVICE.with(|device| device.set(Some(7))));
+        builder
+    });
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(2)
+        .with_max_units(100)
+        .with_max_queue_depth(10);
+    let pool = WorkerPool::with_runtime_builder(config, DeviceExecutor, builder_fn)
+        .expect("Failed to create pool");
+
+    let key = pool.submit_async((), make_meta(1, 10)).await.expect("Failed to submit");
+    let device = pool
+        .retrieve_async(&key, Duration::from_secs(5))
+        .await
+        .expect("Failed to retrieve");
+    assert_eq!(device, Some(7));
+
+    // The default runtime never runs the hook
+    let config = WorkerPoolConfig::new().with_worker_count(1);
+    let pool = WorkerPool::new(config, DeviceExecutor).expect("Failed to create pool");
+    let key = pool.submit_async((), make_meta(2, 10)).await.expect("Failed to submit");
+    assert_eq!(pool.retrieve_async(&key, Duration::from_secs(5)).await.unwrap(), None);
+
+    pool.shutdown();
+    println!("=== test_custom_runtime_builder PASSED ===\n");
+    }).await;
+}
+
+/// Test that queued_keys reports the tasks still waiting, in run order
+#[tokio::test]
+async fn test_queued_keys() {
+    with_timeout("test_queued_keys", 10, async {
+    println!("\n=== test_queued_keys ===");
+
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(1)
+        .with_max_units(100)
+        .with_max_queue_depth(10);
+    let pool = WorkerPool::new(config, SlowExecutor::new(200)).expect("Failed to create pool");
+
+    let first = pool.submit((), make_meta(1, 10)).expect("Failed to submit");
+    // Let the single worker pick up the first task
+    while pool.stats().active_tasks == 0 {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    let second = pool.submit((), make_meta(2, 10)).expect("Failed to submit");
+    let third = pool.submit((), make_meta(3, 10)).expect("Failed to submit");
+
+    assert_eq!(pool.queued_keys(), vec![second.clone(), third.clone()]);
+    assert_eq!(pool.stats().queued_tasks, 2);
+
+    // A higher-priority task jumps ahead of the waiting ones
+    let mut urgent_meta = make_meta(4, 10);
+    urgent_meta.priority = Priority::High;
+    let urgent = pool.submit((), urgent_meta).expect("Failed to submit");
+    assert_eq!(pool.queued_keys(), vec![urgent, second, third]);
+    assert!(!pool.queued_keys().contains(&first));
+
+    pool.shutdown();
+    println!("=== test_queued_keys PASSED ===\n");
+    }).await;
+}
+
+#[tokio::test]
+async fn test_idempotent_submission() {
+    with_timeout("test_idempotent_submission", 10, async {
+    println!("\n=== test_idempotent_submission ===");
+
+    let config = WorkerPoolConfig::new()
+        .with_worker_count(2)
+        .with_max_units(100)
+        .with_max_queue_depth(10);
+    let executor = CountingExecutor::new();
+    let pool = WorkerPool::new(config, executor.clone())
+        .expect("Failed to create pool")
+        .with_idempotency(Duration::from_secs(60));
+
+    // A client retrying after a network failure resubmits the same job
+    let mut meta = make_meta(1, 10);
+    meta.idempotency_key = Some("job-42".to_string());
+    let first = pool.submit(21, meta.clone()).expect("Failed to submit");
+    let retried = pool.submit(21, meta).expect("Failed to resubmit");
+    assert_eq!(first, retried);
+
+    let timeout = Duration::from_secs(5);
+    assert_eq!(pool.retrieve_async(&first, timeout).await.unwrap(), 42);
+    assert_eq!(pool.retrieve_async(&retried, timeout).await.unwrap(), 42);
+    assert_eq!(executor.execution_count(), 1);
+
+    // Other keys still run
+    let mut other = make_meta(2, 10);
+    other.idempotency_key = Some("job-43".to_string());
+    let key = pool.submit(5, other).expect("Failed to submit");
+    assert_ne!(key, first);
+    assert_eq!(pool.retrieve(&key, timeout).unwrap(), 10);
+    assert_eq!(executor.execution_count(), 2);
+
+    pool.shutdown();
+    println!("=== test_idempotent_submission PASSED ===\n");
     }).await;
 }
@@ -0,0 +1,68 @@
+//! Integration tests for the file-backed Yaque queue's on-disk format.
+
+use prometheus_parking_lot::core::{ScheduledTask, TaskMetadata, TaskQueue};
+use prometheus_parking_lot::infra::queue::yaque::FORMAT_VERSION;
+use prometheus_parking_lot::infra::queue::YaqueQueue;
+use prometheus_parking_lot::util::clock::now_ms;
+use prometheus_parking_lot::util::serde::{Priority, ResourceKind};
+
+#[test]
+fn test_v1_file_loads_with_new_fields_defaulted() {
+    let dir = std::env::temp_dir().join(format!("pl-yaque-v1-{}", now_ms()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("jobs.jsonl");
+
+    // A version 1 file: no header, and metadata from before `depends_on`,
+    // `idempotency_key` and `retrieve_timeout_ms` existed
+    let v1_line = concat!(
+        r#"{"meta":{"id":7,"mailbox":null,"priority":"high","cost":{"kind":"cpu","units":3},"#,
+        r#""deadline_ms":null,"created_at_ms":100},"payload":"job-7"}"#,
+    );
+    std::fs::write(&file, format!("{v1_line}\n")).unwrap();
+
+    {
+        let mut queue = YaqueQueue::<String>::new(&dir, "jobs", 10).unwrap();
+        assert_eq!(queue.len(), 1);
+        let head = queue.peek().unwrap().unwrap();
+        assert_eq!(head.id, 7);
+        assert_eq!(head.priority, Priority::High);
+        assert_eq!(head.cost.kind, ResourceKind::Cpu);
+        assert!(head.depends_on.is_empty());
+        assert!(head.idempotency_key.is_none());
+        assert!(head.retrieve_timeout_ms.is_none());
+        assert!(!head.degraded);
+
+        // Appending keeps the old file readable
+        let task = ScheduledTask {
+            meta: TaskMetadata::builder(8).cost(ResourceKind::Cpu, 1).build(),
+            payload: "job-8".to_string(),
+        };
+        queue.enqueue(task).unwrap();
+    }
+
+    assert!(YaqueQueue::<String>::repair(&file).unwrap());
+    let contents = std::fs::read_to_string(&file).unwrap();
+    let header = contents.lines().next().unwrap();
+    assert_eq!(header, format!(r#"{{"format_version":{FORMAT_VERSION}}}"#));
+    // Already current, so a second repair leaves the file alone
+    assert!(!YaqueQueue::<String>::repair(&file).unwrap());
+
+    let mut queue = YaqueQueue::<String>::new(&dir, "jobs", 10).unwrap();
+    assert_eq!(queue.len(), 2);
+    assert_eq!(queue.dequeue().unwrap().unwrap().payload, "job-7");
+    assert_eq!(queue.dequeue().unwrap().unwrap().payload, "job-8");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_newer_format_is_rejected() {
+    let dir = std::env::temp_dir().join(format!("pl-yaque-future-{}", now_ms()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let header = format!("{{\"format_version\":{}}}\n", FORMAT_VERSION + 1);
+    std::fs::write(dir.join("jobs.jsonl"), header).unwrap();
+
+    assert!(YaqueQueue::<String>::new(&dir, "jobs", 10).is_err());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}